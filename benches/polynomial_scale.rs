@@ -0,0 +1,22 @@
+//! Times the polynomial-heavy provers with both expansion strategies.
+//!
+//! Run with `cargo bench --features bench-internals --bench polynomial_scale [SIZE...]`. Without
+//! arguments it measures the default sizes, up to the maximum degree of the public parameters;
+//! larger sizes are rejected.
+
+use esa_rust::acc::bench_internals::{run, RootsStrategy, DEFAULT_SIZES};
+
+fn main() {
+    // Cargo passes `--bench` to harness-less benches; only numeric arguments are sizes.
+    let mut sizes: Vec<usize> = std::env::args()
+        .skip(1)
+        .filter_map(|arg| arg.parse().ok())
+        .collect();
+    if sizes.is_empty() {
+        sizes = DEFAULT_SIZES.to_vec();
+    }
+    let strategies = [RootsStrategy::Naive, RootsStrategy::SubproductTree];
+    for measurement in run(&sizes, &strategies).expect("benchmark failed") {
+        println!("{}", measurement);
+    }
+}
@@ -0,0 +1,10 @@
+//! Generates the OpenCL multiexp kernels of the `gpu` feature, see `acc::offload`.
+
+fn main() {
+    #[cfg(feature = "gpu")]
+    ec_gpu_gen::generate(
+        &ec_gpu_gen::SourceBuilder::new()
+            .add_multiexp::<blstrs::G1Affine, blstrs::Fp>()
+            .add_multiexp::<blstrs::G2Affine, blstrs::Fp2>(),
+    );
+}
@@ -24,7 +24,7 @@ fn main() {
 
     // 3. 查询元素 200 (应存在)
     println!("查询元素 200...");
-    match acc.query(&200) {
+    match acc.query(&200).unwrap() {
         QueryResult::Membership(proof) => {
             println!("找到元素 200，正在验证成员资格证明...");
             assert!(acc.verify_membership(&proof));
@@ -35,7 +35,7 @@ fn main() {
 
     // 4. 查询元素 999 (应不存在)
     println!("查询元素 999...");
-    match acc.query(&999) {
+    match acc.query(&999).unwrap() {
         QueryResult::NonMembership(proof) => {
             println!("元素 999 不存在，正在验证非成员资格证明...");
             assert!(acc.verify_non_membership(&proof));
@@ -50,7 +50,7 @@ fn main() {
     println!("验证交集中的具体元素...");
     
     // 验证 200 在交集中
-    match intersection_acc.query(&200) {
+    match intersection_acc.query(&200).unwrap() {
         esa_rust::acc::dynamic_accumulator::QueryResult::Membership(proof) => {
             if intersection_acc.verify_membership(&proof) {
                 println!("元素 200 在交集中，成员资格证明有效");
@@ -60,7 +60,7 @@ fn main() {
     }
 
     // 验证 300 在交集中
-    match intersection_acc.query(&300) {
+    match intersection_acc.query(&300).unwrap() {
         esa_rust::acc::dynamic_accumulator::QueryResult::Membership(proof) => {
             if intersection_acc.verify_membership(&proof) {
                 println!("元素 300 在交集中，成员资格证明有效");
@@ -70,7 +70,7 @@ fn main() {
     }
 
     // 验证 100 不在交集中
-    match intersection_acc.query(&100) {
+    match intersection_acc.query(&100).unwrap() {
         esa_rust::acc::dynamic_accumulator::QueryResult::NonMembership(proof) => {
             if intersection_acc.verify_non_membership(&proof) {
                 println!("元素 100 不在交集中，非成员资格证明有效");
@@ -80,7 +80,7 @@ fn main() {
     }
 
     // 验证 400 不在交集中
-    match intersection_acc.query(&400) {
+    match intersection_acc.query(&400).unwrap() {
         esa_rust::acc::dynamic_accumulator::QueryResult::NonMembership(proof) => {
             if intersection_acc.verify_non_membership(&proof) {
                 println!("元素 400 不在交集中，非成员资格证明有效");
@@ -0,0 +1,148 @@
+//! Deliberately broken proofs for testing verifiers.
+//!
+//! Only compiled with the `adversarial` feature. Every [`Corruption`] turns a valid proof into
+//! one an honest verifier must reject, so downstream integration tests can check their
+//! verification paths without hand-crafting bad inputs.
+
+use super::{
+    dynamic_accumulator::{AddProof, DeleteProof, MembershipProof, NonMembershipProof},
+    Fr, G1Affine,
+};
+use ark_bls12_381::Fq;
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::One;
+
+/// The ways a proof can be corrupted.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Corruption {
+    /// The witness (or, for update proofs, the new accumulator value) is shifted by g1.
+    WrongWitness,
+    /// The proof claims a different element.
+    WrongElement,
+    /// The proof claims the previous epoch.
+    StaleEpoch,
+    /// The witness is replaced by a point on the curve outside the prime-order subgroup.
+    NonSubgroupPoint,
+}
+
+impl Corruption {
+    pub const ALL: [Corruption; 4] = [
+        Corruption::WrongWitness,
+        Corruption::WrongElement,
+        Corruption::StaleEpoch,
+        Corruption::NonSubgroupPoint,
+    ];
+}
+
+/// Returns a point on the G1 curve that is not in the prime-order subgroup.
+pub fn non_subgroup_g1() -> G1Affine {
+    let mut x = Fq::one();
+    loop {
+        if let Some(p) = G1Affine::get_point_from_x(x, false) {
+            if !p.is_in_correct_subgroup_assuming_on_curve() {
+                return p;
+            }
+        }
+        x += Fq::one();
+    }
+}
+
+fn shift(p: G1Affine) -> G1Affine {
+    (p.into_projective() + G1Affine::prime_subgroup_generator().into_projective()).into_affine()
+}
+
+/// Proofs that can be corrupted.
+pub trait Corrupt: Sized {
+    /// Returns a copy of the proof with the given corruption applied.
+    fn corrupt(&self, corruption: Corruption) -> Self;
+}
+
+impl Corrupt for MembershipProof {
+    fn corrupt(&self, corruption: Corruption) -> Self {
+        let mut proof = self.clone();
+        match corruption {
+            Corruption::WrongWitness => proof.witness = shift(proof.witness),
+            Corruption::WrongElement => proof.element.0 += Fr::one(),
+            Corruption::StaleEpoch => proof.epoch = proof.epoch.wrapping_sub(1),
+            Corruption::NonSubgroupPoint => proof.witness = non_subgroup_g1(),
+        }
+        proof
+    }
+}
+
+impl Corrupt for NonMembershipProof {
+    fn corrupt(&self, corruption: Corruption) -> Self {
+        let mut proof = self.clone();
+        match corruption {
+            Corruption::WrongWitness => proof.g1_a = shift(proof.g1_a),
+            Corruption::WrongElement => proof.element.0 += Fr::one(),
+            Corruption::StaleEpoch => proof.epoch = proof.epoch.wrapping_sub(1),
+            Corruption::NonSubgroupPoint => proof.g1_a = non_subgroup_g1(),
+        }
+        proof
+    }
+}
+
+impl Corrupt for AddProof {
+    fn corrupt(&self, corruption: Corruption) -> Self {
+        let mut proof = self.clone();
+        match corruption {
+            Corruption::WrongWitness => proof.new_acc_value = shift(proof.new_acc_value),
+            Corruption::WrongElement => proof.element.0 += Fr::one(),
+            Corruption::StaleEpoch => proof.epoch = proof.epoch.wrapping_sub(1),
+            Corruption::NonSubgroupPoint => proof.new_acc_value = non_subgroup_g1(),
+        }
+        proof
+    }
+}
+
+impl Corrupt for DeleteProof {
+    fn corrupt(&self, corruption: Corruption) -> Self {
+        let mut proof = self.clone();
+        match corruption {
+            Corruption::WrongWitness => proof.new_acc_value = shift(proof.new_acc_value),
+            Corruption::WrongElement => proof.element.0 += Fr::one(),
+            Corruption::StaleEpoch => proof.epoch = proof.epoch.wrapping_sub(1),
+            Corruption::NonSubgroupPoint => proof.new_acc_value = non_subgroup_g1(),
+        }
+        proof
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::dynamic_accumulator::DynamicAccumulator;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_corruptions_are_rejected() {
+        init_logger();
+        assert!(non_subgroup_g1().is_on_curve());
+
+        let mut acc = DynamicAccumulator::new();
+        acc.add_batch(&[1, 2]).unwrap();
+        let membership = acc.prove_membership(&1).unwrap();
+        let non_membership = acc.prove_non_membership(&3).unwrap();
+        let mut next = acc.clone();
+        let add = next.add(&3).unwrap();
+        let delete = next.delete(&2).unwrap();
+
+        for corruption in Corruption::ALL {
+            assert!(!acc.verify_membership(&membership.corrupt(corruption)));
+            assert!(!acc.verify_non_membership(&non_membership.corrupt(corruption)));
+            assert!(acc
+                .clone()
+                .apply_add_proof(&add.corrupt(corruption))
+                .is_err());
+            let mut after_add = acc.clone();
+            after_add.apply_add_proof(&add).unwrap();
+            assert!(after_add
+                .apply_delete_proof(&delete.corrupt(corruption))
+                .is_err());
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! Aggregation of membership proofs across accumulators.
+//!
+//! Proofs that x_1 is in A_1, x_2 in A_2, ... are checked together with one multi-pairing:
+//! Π e(r_i * w_i, g2^(s-x_i)) * e(-Σ r_i * A_i, g2) == 1, where the r_i are Fiat-Shamir
+//! coefficients over all proofs and accumulator values. The random linear combination keeps a
+//! bad witness from being cancelled out by another one.
+
+use super::{
+    dynamic_accumulator::MembershipProof, g2_s_minus, prepared::PreparedPublicParams,
+    update::fr_to_digest, utils::digest_to_prime_field, Curve, Fr, G1Affine, G1Projective,
+};
+use crate::digest::{concat_digest, Digestible};
+use anyhow::{bail, Result};
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{One, PrimeField, Zero};
+
+type G1Prepared = <Curve as PairingEngine>::G1Prepared;
+type G2Prepared = <Curve as PairingEngine>::G2Prepared;
+
+/// Membership proofs for several accumulators, verified with one multi-pairing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AggregateMembershipProof {
+    /// One proof per accumulator, in the order the accumulator values are passed to
+    /// [`AggregateMembershipProof::verify`].
+    pub proofs: Vec<MembershipProof>,
+}
+
+impl AggregateMembershipProof {
+    /// Aggregates proofs, each for its own accumulator. Returns an error if there are none.
+    pub fn new(proofs: Vec<MembershipProof>) -> Result<Self> {
+        if proofs.is_empty() {
+            bail!("No proofs to aggregate");
+        }
+        Ok(Self { proofs })
+    }
+
+    /// Verifies the i-th proof against `accumulators[i]` for every i.
+    pub fn verify(&self, accumulators: &[G1Affine]) -> bool {
+        if self.proofs.is_empty() || self.proofs.len() != accumulators.len() {
+            return false;
+        }
+        if self
+            .proofs
+            .iter()
+            .zip(accumulators)
+            .any(|(proof, acc)| proof.acc_digest != acc.to_digest())
+        {
+            return false;
+        }
+
+        let transcript = concat_digest(self.proofs.iter().zip(accumulators).flat_map(
+            |(proof, acc)| {
+                [
+                    acc.to_digest(),
+                    proof.witness.to_digest(),
+                    fr_to_digest(&proof.element.0),
+                ]
+            },
+        ));
+        let mut pairs: Vec<(G1Prepared, G2Prepared)> = Vec::with_capacity(self.proofs.len() + 1);
+        let mut acc_sum = G1Projective::zero();
+        for (i, (proof, acc)) in self.proofs.iter().zip(accumulators).enumerate() {
+            // The first coefficient can be one without loss of soundness.
+            let r: Fr = if i == 0 {
+                One::one()
+            } else {
+                digest_to_prime_field(&concat_digest(
+                    [transcript, (i as u64).to_digest()].iter().copied(),
+                ))
+            };
+            let g2_s_minus_elem = g2_s_minus(proof.element.0);
+            pairs.push((
+                proof.witness.mul(r.into_repr()).into_affine().into(),
+                g2_s_minus_elem.into(),
+            ));
+            acc_sum += &acc.mul(r.into_repr());
+        }
+        pairs.push((
+            (-acc_sum).into_affine().into(),
+            PreparedPublicParams::global().g2().clone(),
+        ));
+        Curve::product_of_pairings(&pairs).is_one()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::dynamic_accumulator::DynamicAccumulator;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_aggregate_membership() {
+        init_logger();
+        let shards: Vec<DynamicAccumulator> = [[1, 2], [3, 4], [5, 6]]
+            .iter()
+            .map(|elements| {
+                let mut acc = DynamicAccumulator::new();
+                acc.add_batch(elements).unwrap();
+                acc
+            })
+            .collect();
+        let values: Vec<G1Affine> = shards.iter().map(|acc| acc.acc_value).collect();
+        let proof = AggregateMembershipProof::new(vec![
+            shards[0].prove_membership(&1).unwrap(),
+            shards[1].prove_membership(&4).unwrap(),
+            shards[2].prove_membership(&5).unwrap(),
+        ])
+        .unwrap();
+        assert!(proof.verify(&values));
+
+        let mut swapped = values.clone();
+        swapped.swap(0, 1);
+        assert!(!proof.verify(&swapped));
+        assert!(!proof.verify(&values[..2]));
+
+        // Witnesses that cancel out in a plain product are still rejected.
+        let mut forged = proof.clone();
+        let delta = G1Affine::prime_subgroup_generator();
+        forged.proofs[0].witness =
+            (forged.proofs[0].witness.into_projective() + delta.into_projective()).into_affine();
+        forged.proofs[1].witness =
+            (forged.proofs[1].witness.into_projective() - delta.into_projective()).into_affine();
+        assert!(!forged.verify(&values));
+
+        assert!(AggregateMembershipProof::new(Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,160 @@
+//! Low-level accumulator arithmetic for prototyping new proofs.
+//!
+//! Each helper raises a raw accumulator value to a polynomial in the secret s (or its
+//! inverse) and returns an [`ArithProof`] that the result is correct, so experimental
+//! constructions can be checked without touching the accumulator's internals. `apply_linear(x)`
+//! is what adding x does; `remove_linear(x)` is what deleting it does.
+//!
+//! Raising the value goes through the installed [`TrapdoorSigner`](super::trapdoor::TrapdoorSigner),
+//! which may not support polynomials other than linear factors. The proof commits to f(s) with
+//! the published powers, so f has degree at most [`MAX_DEGREE`](super::MAX_DEGREE).
+
+use super::{
+    commit_g2, commitment::params_id, dynamic_accumulator::ark_serde, metrics, polymath, trapdoor,
+    Curve, Fr, G1Affine, G2Affine,
+};
+use crate::digest::Digest;
+use anyhow::{bail, Result};
+use ark_ec::{AffineCurve, PairingEngine};
+use ark_ff::Zero;
+use ark_poly::univariate::DensePolynomial;
+use serde::{Deserialize, Serialize};
+
+/// A proof that `new_acc_value` is `old_acc_value` raised to f(s), or to 1/f(s) if `inverse`
+/// is set, for the f(s) committed in `exponent_g2`: e(new, g2) == e(old, g2^f(s)), or
+/// e(old, g2) == e(new, g2^f(s)) for the inverse.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArithProof {
+    #[serde(with = "ark_serde")]
+    pub old_acc_value: G1Affine,
+    #[serde(with = "ark_serde")]
+    pub new_acc_value: G1Affine,
+    /// g2^f(s).
+    #[serde(with = "ark_serde")]
+    pub exponent_g2: G2Affine,
+    /// Whether the value was raised to 1/f(s) rather than f(s).
+    pub inverse: bool,
+    /// The identifier of the public parameters the proof was generated under, see
+    /// [`params_id`].
+    pub params_id: Digest,
+}
+
+impl ArithProof {
+    pub fn verify(&self) -> bool {
+        if self.params_id != params_id() {
+            return false;
+        }
+        let (raised, base) = if self.inverse {
+            (self.old_acc_value, self.new_acc_value)
+        } else {
+            (self.new_acc_value, self.old_acc_value)
+        };
+        let lhs = Curve::pairing(raised, G2Affine::prime_subgroup_generator());
+        let rhs = Curve::pairing(base, self.exponent_g2);
+        metrics::record_pairings(2);
+        lhs == rhs
+    }
+
+    /// Verifies the proof for the given exponent polynomial, applied directly or inverted.
+    pub fn verify_for(&self, poly: &DensePolynomial<Fr>, inverse: bool) -> bool {
+        self.inverse == inverse
+            && commit_g2(poly.clone()).is_ok_and(|g2| g2 == self.exponent_g2)
+            && self.verify()
+    }
+}
+
+fn proof(
+    old_acc_value: G1Affine,
+    new_acc_value: G1Affine,
+    poly: DensePolynomial<Fr>,
+    inverse: bool,
+) -> Result<ArithProof> {
+    Ok(ArithProof {
+        old_acc_value,
+        new_acc_value,
+        exponent_g2: commit_g2(poly)?,
+        inverse,
+        params_id: params_id(),
+    })
+}
+
+/// Raises `acc_value` to (s - x).
+pub fn apply_linear(acc_value: G1Affine, x: Fr) -> Result<ArithProof> {
+    let new_acc_value = trapdoor::signer().mul_s_minus(acc_value, &[x])?;
+    proof(acc_value, new_acc_value, polymath::linear(x), false)
+}
+
+/// Raises `acc_value` to 1/(s - x).
+pub fn remove_linear(acc_value: G1Affine, x: Fr) -> Result<ArithProof> {
+    let new_acc_value = trapdoor::signer().div_s_minus(acc_value, x)?;
+    proof(acc_value, new_acc_value, polymath::linear(x), true)
+}
+
+/// Raises `acc_value` to f(s). Returns an error for the zero polynomial.
+pub fn apply_poly(acc_value: G1Affine, poly: &DensePolynomial<Fr>) -> Result<ArithProof> {
+    if poly.coeffs.iter().all(|c| c.is_zero()) {
+        bail!("Cannot apply the zero polynomial");
+    }
+    let new_acc_value = trapdoor::signer().mul_poly(acc_value, poly)?;
+    proof(acc_value, new_acc_value, poly.clone(), false)
+}
+
+/// Raises `acc_value` to 1/f(s). Returns an error if f(s) is zero.
+pub fn remove_poly(acc_value: G1Affine, poly: &DensePolynomial<Fr>) -> Result<ArithProof> {
+    let new_acc_value = trapdoor::signer().div_poly(acc_value, poly)?;
+    proof(acc_value, new_acc_value, poly.clone(), true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::dynamic_accumulator::DynamicAccumulator;
+    use crate::acc::polymath::linear;
+    use crate::acc::utils::digest_to_prime_field;
+    use crate::acc::PRI_S;
+    use crate::digest::Digestible;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_arith_matches_accumulator() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        let start = acc.acc_value;
+        acc.add_batch(&[1, 2]).unwrap();
+        let (x1, x2): (Fr, Fr) = (
+            digest_to_prime_field(&1i64.to_digest()),
+            digest_to_prime_field(&2i64.to_digest()),
+        );
+
+        let step = apply_linear(start, x1).unwrap();
+        let step = apply_linear(step.new_acc_value, x2).unwrap();
+        assert!(step.verify());
+        assert_eq!(step.new_acc_value, acc.acc_value);
+
+        let poly = &linear(x1) * &linear(x2);
+        let batch = apply_poly(start, &poly).unwrap();
+        assert!(batch.verify_for(&poly, false));
+        assert!(!batch.verify_for(&poly, true));
+        assert_eq!(batch.new_acc_value, acc.acc_value);
+
+        let back = remove_poly(acc.acc_value, &poly).unwrap();
+        assert!(back.verify_for(&poly, true));
+        assert_eq!(back.new_acc_value, start);
+        assert_eq!(
+            remove_linear(acc.acc_value, x2).unwrap().new_acc_value,
+            apply_linear(start, x1).unwrap().new_acc_value
+        );
+
+        assert!(apply_poly(
+            start,
+            &DensePolynomial {
+                coeffs: vec![Fr::zero()]
+            }
+        )
+        .is_err());
+        assert!(remove_poly(start, &linear(*PRI_S)).is_err());
+    }
+}
@@ -0,0 +1,149 @@
+//! Conversions to and from the arkworks 0.4 types, with the `ark04` feature.
+//!
+//! The crate is built on arkworks 0.2 and its `PairingEngine`/`AffineCurve` traits. Downstream
+//! code on arkworks 0.4 links both versions side by side, and [`Ark04`] moves scalars and points
+//! between them, e.g. to check a proof with the 0.4 `Pairing` trait or to hand an accumulator
+//! value to 0.4 code. Conversions go through coordinates, not bytes: arkworks 0.4 encodes
+//! BLS12-381 points in the zcash format, so the encodings of the two versions are not
+//! interchangeable.
+
+use super::{Fr, G1Affine, G2Affine};
+use ark_bls12_381::{g1, g2, Fq, Fq2};
+use ark_bls12_381_04 as bls04;
+use ark_ec::short_weierstrass_jacobian::GroupAffine;
+use ark_ff::{
+    biginteger::{BigInteger256, BigInteger384},
+    PrimeField, Zero,
+};
+use ark_ff_04::{BigInt, PrimeField as PrimeField04};
+
+/// A type with an arkworks 0.4 counterpart.
+pub trait Ark04: Sized {
+    type Repr;
+
+    fn to_ark04(&self) -> Self::Repr;
+
+    fn from_ark04(repr: &Self::Repr) -> Self;
+}
+
+impl Ark04 for Fr {
+    type Repr = bls04::Fr;
+
+    fn to_ark04(&self) -> bls04::Fr {
+        bls04::Fr::from_bigint(BigInt(self.into_repr().0)).expect("a reduced scalar")
+    }
+
+    fn from_ark04(repr: &bls04::Fr) -> Self {
+        Fr::from_repr(BigInteger256(repr.into_bigint().0)).expect("a reduced scalar")
+    }
+}
+
+impl Ark04 for Fq {
+    type Repr = bls04::Fq;
+
+    fn to_ark04(&self) -> bls04::Fq {
+        bls04::Fq::from_bigint(BigInt(self.into_repr().0)).expect("a reduced field element")
+    }
+
+    fn from_ark04(repr: &bls04::Fq) -> Self {
+        Fq::from_repr(BigInteger384(repr.into_bigint().0)).expect("a reduced field element")
+    }
+}
+
+impl Ark04 for Fq2 {
+    type Repr = bls04::Fq2;
+
+    fn to_ark04(&self) -> bls04::Fq2 {
+        bls04::Fq2::new(self.c0.to_ark04(), self.c1.to_ark04())
+    }
+
+    fn from_ark04(repr: &bls04::Fq2) -> Self {
+        Fq2::new(Fq::from_ark04(&repr.c0), Fq::from_ark04(&repr.c1))
+    }
+}
+
+// The impls name the curve parameters, as coherence cannot tell `G1Affine` and `G2Affine` apart
+// through the projections they are defined with.
+impl Ark04 for GroupAffine<g1::Parameters> {
+    type Repr = bls04::G1Affine;
+
+    fn to_ark04(&self) -> bls04::G1Affine {
+        if self.infinity {
+            return bls04::G1Affine::identity();
+        }
+        bls04::G1Affine::new_unchecked(self.x.to_ark04(), self.y.to_ark04())
+    }
+
+    fn from_ark04(repr: &bls04::G1Affine) -> Self {
+        if repr.infinity {
+            return G1Affine::zero();
+        }
+        G1Affine::new(Fq::from_ark04(&repr.x), Fq::from_ark04(&repr.y), false)
+    }
+}
+
+impl Ark04 for GroupAffine<g2::Parameters> {
+    type Repr = bls04::G2Affine;
+
+    fn to_ark04(&self) -> bls04::G2Affine {
+        if self.infinity {
+            return bls04::G2Affine::identity();
+        }
+        bls04::G2Affine::new_unchecked(self.x.to_ark04(), self.y.to_ark04())
+    }
+
+    fn from_ark04(repr: &bls04::G2Affine) -> Self {
+        if repr.infinity {
+            return G2Affine::zero();
+        }
+        G2Affine::new(Fq2::from_ark04(&repr.x), Fq2::from_ark04(&repr.y), false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::{G2_POWER, PRI_S};
+    use crate::DynamicAccumulator;
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_ec_04::{pairing::Pairing, AffineRepr, CurveGroup};
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_ark04() {
+        init_logger();
+        let g1 = G1Affine::prime_subgroup_generator();
+        let g2 = G2Affine::prime_subgroup_generator();
+        assert_eq!(g1.to_ark04(), bls04::G1Affine::generator());
+        assert_eq!(g2.to_ark04(), bls04::G2Affine::generator());
+        assert_eq!(
+            G1Affine::from_ark04(&G1Affine::zero().to_ark04()),
+            G1Affine::zero()
+        );
+
+        let mut acc = DynamicAccumulator::new();
+        acc.add(&1).unwrap();
+        acc.add(&2).unwrap();
+        let proof = acc.prove_membership(&1).unwrap();
+        assert_eq!(Fr::from_ark04(&proof.element.0.to_ark04()), proof.element.0);
+        assert_eq!(
+            G1Affine::from_ark04(&proof.witness.to_ark04()),
+            proof.witness
+        );
+
+        // A verifier on arkworks 0.4 checks e(witness, g2^(s-x)) == e(acc, g2).
+        let g2_s_minus = G2_POWER.apply(&(*PRI_S - proof.element.0)).into_affine();
+        assert_eq!(G2Affine::from_ark04(&g2_s_minus.to_ark04()), g2_s_minus);
+        let lhs = bls04::Bls12_381::multi_pairing(
+            [
+                proof.witness.to_ark04(),
+                (-acc.acc_value.to_ark04().into_group()).into_affine(),
+            ],
+            [g2_s_minus.to_ark04(), g2.to_ark04()],
+        );
+        assert!(lhs.is_zero());
+    }
+}
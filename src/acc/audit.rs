@@ -0,0 +1,412 @@
+//! Self-verifying audit exports.
+//!
+//! An [`AuditTrail`] records what a manager publishes: signed checkpoints, the add and delete
+//! proofs of every operation and the membership and non-membership proofs it hands out.
+//! [`AuditTrail::export_audit`] packs the part of the record within an epoch range, together
+//! with the public parameters and the manager's verifying key, into an [`AuditBundle`]. A third
+//! party checks the bundle offline with [`verify_audit_bundle`], which needs nothing but its bytes
+//! and the key it expects:
+//!
+//! - the bytes match their SHA-256 and the parameters are the ones proofs were made under,
+//! - every checkpoint is signed and the first one is at the start of the range,
+//! - the operations form an unbroken chain of valid proofs from the first checkpoint, and every
+//!   later checkpoint publishes the value the chain reaches at its epoch,
+//! - every issued proof verifies against the value at its epoch.
+//!
+//! The layout is the magic `ESAAUDT1`, the SHA-256 of everything after it, the verifying key, the
+//! parameters file of [`PublicParams::to_bytes`] with its length, then the checkpoints, the
+//! operations and the issued proofs, each as a `u64` count followed by the items. Points are
+//! compressed, integers little-endian, and operations and proofs start with a tag byte.
+
+use super::{
+    dynamic_accumulator::{AddProof, DeleteProof, MembershipProof, NonMembershipProof},
+    params::PublicParams,
+    signing::{Signature, SignedState, VerifyingKey},
+    update::{Update, UpdateLog},
+    wire::{read_u64, write_u64, EncodingOptions, WireEncode},
+    G1Affine,
+};
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read};
+use sha2::{Digest as _, Sha256};
+use std::{collections::BTreeMap, ops::RangeInclusive};
+
+const MAGIC: &[u8; 8] = b"ESAAUDT1";
+
+/// A membership or non-membership proof handed out by the manager.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IssuedProof {
+    Membership(MembershipProof),
+    NonMembership(NonMembershipProof),
+}
+
+impl IssuedProof {
+    pub fn epoch(&self) -> u64 {
+        match self {
+            IssuedProof::Membership(proof) => proof.epoch,
+            IssuedProof::NonMembership(proof) => proof.epoch,
+        }
+    }
+
+    pub fn verify_at(&self, accumulator: G1Affine, epoch: u64) -> bool {
+        match self {
+            IssuedProof::Membership(proof) => proof.verify_at(accumulator, epoch),
+            IssuedProof::NonMembership(proof) => proof.verify_at(accumulator, epoch),
+        }
+    }
+}
+
+impl From<MembershipProof> for IssuedProof {
+    fn from(proof: MembershipProof) -> Self {
+        IssuedProof::Membership(proof)
+    }
+}
+
+impl From<NonMembershipProof> for IssuedProof {
+    fn from(proof: NonMembershipProof) -> Self {
+        IssuedProof::NonMembership(proof)
+    }
+}
+
+/// The published record of a manager, see the [module docs](self).
+#[derive(Debug, Clone)]
+pub struct AuditTrail {
+    vk: VerifyingKey,
+    checkpoints: BTreeMap<u64, SignedState>,
+    /// Operations by the epoch they end in.
+    updates: BTreeMap<u64, Update>,
+    proofs: Vec<IssuedProof>,
+}
+
+impl AuditTrail {
+    /// Creates an empty trail of the manager with key `vk`.
+    pub fn new(vk: VerifyingKey) -> Self {
+        Self {
+            vk,
+            checkpoints: BTreeMap::new(),
+            updates: BTreeMap::new(),
+            proofs: Vec::new(),
+        }
+    }
+
+    /// Records a published checkpoint. Fails unless it is signed by the manager.
+    pub fn record_checkpoint(&mut self, state: SignedState) -> Result<()> {
+        ensure!(
+            state.verify(&self.vk),
+            "The checkpoint is not signed by the manager"
+        );
+        self.checkpoints.insert(state.epoch, state);
+        Ok(())
+    }
+
+    /// Records an operation.
+    pub fn record_update(&mut self, update: impl Into<Update>) {
+        let update = update.into();
+        self.updates.insert(update.epoch(), update);
+    }
+
+    /// Records a proof handed out.
+    pub fn record_proof(&mut self, proof: impl Into<IssuedProof>) {
+        self.proofs.push(proof.into());
+    }
+
+    /// Packs the record of the epochs in `range`. Fails unless a checkpoint was recorded at the
+    /// start of the range and every operation after it up to the end of the range.
+    pub fn export_audit(&self, range: RangeInclusive<u64>) -> Result<AuditBundle> {
+        let (start, end) = (*range.start(), *range.end());
+        ensure!(start <= end, "Empty audit range {}..={}", start, end);
+        if !self.checkpoints.contains_key(&start) {
+            bail!("No checkpoint at the start epoch {}", start);
+        }
+        let mut log = UpdateLog::new(start);
+        for epoch in start + 1..=end {
+            let update = self
+                .updates
+                .get(&epoch)
+                .ok_or_else(|| anyhow!("No operation recorded for epoch {}", epoch))?;
+            log.push(update.clone());
+        }
+        Ok(AuditBundle {
+            vk: self.vk,
+            params: PublicParams::generate(1),
+            checkpoints: self
+                .checkpoints
+                .range(range.clone())
+                .map(|(_, s)| s.clone())
+                .collect(),
+            log,
+            proofs: self
+                .proofs
+                .iter()
+                .filter(|p| range.contains(&p.epoch()))
+                .cloned()
+                .collect(),
+        })
+    }
+}
+
+/// The record of a range of epochs with everything needed to check it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditBundle {
+    pub vk: VerifyingKey,
+    pub params: PublicParams,
+    /// The checkpoints in the range, by epoch. The first is at the start of the range.
+    pub checkpoints: Vec<SignedState>,
+    /// The operations after the first checkpoint.
+    pub log: UpdateLog,
+    pub proofs: Vec<IssuedProof>,
+}
+
+/// The outcome of a successful audit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditReport {
+    pub start_epoch: u64,
+    pub end_epoch: u64,
+    /// The accumulator value at the end of the range.
+    pub end_value: G1Affine,
+    pub checkpoints: usize,
+    pub operations: usize,
+    pub proofs: usize,
+}
+
+/// Decodes and checks an exported bundle of the manager with key `vk`.
+pub fn verify_audit_bundle(bytes: &[u8], vk: &VerifyingKey) -> Result<AuditReport> {
+    AuditBundle::from_bytes(bytes)?.verify(vk)
+}
+
+impl AuditBundle {
+    /// Checks the bundle, see the [module docs](self).
+    pub fn verify(&self, vk: &VerifyingKey) -> Result<AuditReport> {
+        ensure!(self.vk == *vk, "The bundle is for another manager key");
+        self.params.ensure_active()?;
+        let start = self
+            .checkpoints
+            .first()
+            .ok_or_else(|| anyhow!("The bundle has no checkpoint"))?;
+        ensure!(
+            start.epoch == self.log.start_epoch,
+            "The operations do not start at the first checkpoint"
+        );
+
+        let mut values = vec![start.acc_value];
+        for (i, update) in self.log.updates.iter().enumerate() {
+            let value = *values.last().expect("the start value");
+            let single = UpdateLog {
+                start_epoch: self.log.start_epoch + i as u64,
+                updates: vec![update.clone()],
+            };
+            values.push(
+                single
+                    .verify_chain(value)
+                    .with_context(|| format!("Operation {}", i))?,
+            );
+        }
+        let value_at = |epoch: u64| {
+            epoch
+                .checked_sub(self.log.start_epoch)
+                .and_then(|i| values.get(i as usize))
+                .copied()
+        };
+
+        for (i, state) in self.checkpoints.iter().enumerate() {
+            ensure!(
+                state.verify(vk),
+                "Checkpoint {} has an invalid signature",
+                i
+            );
+            ensure!(
+                value_at(state.epoch) == Some(state.acc_value),
+                "Checkpoint {} at epoch {} does not match the operations",
+                i,
+                state.epoch
+            );
+        }
+        for (i, proof) in self.proofs.iter().enumerate() {
+            let valid =
+                value_at(proof.epoch()).is_some_and(|value| proof.verify_at(value, proof.epoch()));
+            ensure!(valid, "Issued proof {} does not verify", i);
+        }
+        Ok(AuditReport {
+            start_epoch: self.log.start_epoch,
+            end_epoch: self.log.end_epoch(),
+            end_value: *values.last().expect("the start value"),
+            checkpoints: self.checkpoints.len(),
+            operations: self.log.updates.len(),
+            proofs: self.proofs.len(),
+        })
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let options = EncodingOptions::compressed();
+        let mut body = Vec::new();
+        self.vk.0.serialize(&mut body)?;
+        let params = self.params.to_bytes()?;
+        write_u64(params.len() as u64, &mut body);
+        body.extend_from_slice(&params);
+
+        write_u64(self.checkpoints.len() as u64, &mut body);
+        for state in &self.checkpoints {
+            write_u64(state.epoch, &mut body);
+            state.acc_value.serialize(&mut body)?;
+            state.signature.0.serialize(&mut body)?;
+        }
+        write_u64(self.log.start_epoch, &mut body);
+        write_u64(self.log.updates.len() as u64, &mut body);
+        for update in &self.log.updates {
+            match update {
+                Update::Add(proof) => {
+                    body.push(0);
+                    proof.encode_into(&options, &mut body)?;
+                }
+                Update::Delete(proof) => {
+                    body.push(1);
+                    proof.encode_into(&options, &mut body)?;
+                }
+            }
+        }
+        write_u64(self.proofs.len() as u64, &mut body);
+        for proof in &self.proofs {
+            match proof {
+                IssuedProof::Membership(proof) => {
+                    body.push(0);
+                    proof.encode_into(&options, &mut body)?;
+                }
+                IssuedProof::NonMembership(proof) => {
+                    body.push(1);
+                    proof.encode_into(&options, &mut body)?;
+                }
+            }
+        }
+
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&Sha256::digest(&body));
+        bytes.extend_from_slice(&body);
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < MAGIC.len() + 32 || !bytes.starts_with(MAGIC) {
+            bail!("Not an audit bundle");
+        }
+        let (hash, body) = bytes[MAGIC.len()..].split_at(32);
+        if Sha256::digest(body)[..] != hash[..] {
+            bail!("The audit bundle does not match its SHA-256");
+        }
+        let options = EncodingOptions::compressed();
+        let reader = &mut &body[..];
+        let vk = VerifyingKey(CanonicalDeserialize::deserialize(&mut *reader)?);
+        let params_len = read_len(reader)?;
+        let params = PublicParams::from_bytes(&reader[..params_len])?;
+        *reader = &reader[params_len..];
+
+        let checkpoints = (0..read_len(reader)?)
+            .map(|_| {
+                Ok(SignedState {
+                    epoch: read_u64(reader)?,
+                    acc_value: CanonicalDeserialize::deserialize(&mut *reader)?,
+                    signature: Signature(CanonicalDeserialize::deserialize(&mut *reader)?),
+                })
+            })
+            .collect::<Result<_>>()?;
+        let mut log = UpdateLog::new(read_u64(reader)?);
+        for _ in 0..read_len(reader)? {
+            log.push(match read_tag(reader)? {
+                0 => Update::Add(AddProof::decode_from(reader, &options)?),
+                1 => Update::Delete(DeleteProof::decode_from(reader, &options)?),
+                tag => bail!("Invalid operation tag {}", tag),
+            });
+        }
+        let proofs = (0..read_len(reader)?)
+            .map(|_| {
+                Ok(match read_tag(reader)? {
+                    0 => IssuedProof::Membership(MembershipProof::decode_from(reader, &options)?),
+                    1 => IssuedProof::NonMembership(NonMembershipProof::decode_from(
+                        reader, &options,
+                    )?),
+                    tag => bail!("Invalid proof tag {}", tag),
+                })
+            })
+            .collect::<Result<_>>()?;
+        if !reader.is_empty() {
+            bail!("{} trailing bytes after the audit bundle", reader.len());
+        }
+        Ok(Self {
+            vk,
+            params,
+            checkpoints,
+            log,
+            proofs,
+        })
+    }
+}
+
+/// Reads a count or a length, which cannot exceed the remaining bytes.
+fn read_len(reader: &mut &[u8]) -> Result<usize> {
+    let len = read_u64(reader)?;
+    if len > reader.len() as u64 {
+        bail!("The length {} exceeds the remaining bytes", len);
+    }
+    Ok(len as usize)
+}
+
+fn read_tag(reader: &mut &[u8]) -> Result<u8> {
+    let mut tag = [0u8; 1];
+    reader.read_exact(&mut tag)?;
+    Ok(tag[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::signing::SigningKey;
+    use crate::DynamicAccumulator;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_audit_bundle() {
+        init_logger();
+        let key = SigningKey::from_seed(b"audit");
+        let vk = key.verifying_key();
+        let mut trail = AuditTrail::new(vk);
+        let mut acc = DynamicAccumulator::new();
+        trail.record_checkpoint(acc.sign_state(&key)).unwrap();
+        trail.record_update(acc.add(&1).unwrap());
+        trail.record_update(acc.add(&2).unwrap());
+        trail.record_checkpoint(acc.sign_state(&key)).unwrap();
+        trail.record_proof(acc.prove_membership(&1).unwrap());
+        trail.record_update(acc.delete(&1).unwrap());
+        trail.record_proof(acc.prove_non_membership(&1).unwrap());
+        trail.record_update(acc.add(&3).unwrap());
+        trail.record_checkpoint(acc.sign_state(&key)).unwrap();
+
+        let bytes = trail.export_audit(0..=4).unwrap().to_bytes().unwrap();
+        let report = verify_audit_bundle(&bytes, &vk).unwrap();
+        assert_eq!((report.start_epoch, report.end_epoch), (0, 4));
+        assert_eq!(report.end_value, acc.acc_value);
+        assert_eq!(
+            (report.checkpoints, report.operations, report.proofs),
+            (3, 4, 2)
+        );
+
+        let part = trail.export_audit(2..=3).unwrap();
+        assert_eq!((part.checkpoints.len(), part.proofs.len()), (1, 2));
+        assert!(part.verify(&vk).is_ok());
+        assert!(trail.export_audit(1..=4).is_err());
+        assert!(
+            verify_audit_bundle(&bytes, &SigningKey::from_seed(b"other").verifying_key()).is_err()
+        );
+
+        let mut corrupted = bytes.clone();
+        let last = corrupted.len() - 1;
+        corrupted[last] ^= 1;
+        assert!(verify_audit_bundle(&corrupted, &vk).is_err());
+
+        // A checkpoint that does not match the operations fails the audit.
+        let mut forged = trail.export_audit(0..=4).unwrap();
+        forged.checkpoints[1] = SignedState::new(2, acc.acc_value, &key);
+        assert!(verify_audit_bundle(&forged.to_bytes().unwrap(), &vk).is_err());
+    }
+}
@@ -0,0 +1,228 @@
+//! An append-only authenticated log backed by the dynamic accumulator.
+//!
+//! Each entry is accumulated together with its position, so inclusion proofs attest both the
+//! entry and its index. The log keeps the accumulator value ("head") after every append, which
+//! allows inclusion proofs against older heads and consistency proofs between two lengths in the
+//! style of a transparency log. A consistency proof is a single G2 element committing to the
+//! entries appended in between, plus degree proofs binding both heads to their lengths.
+//! An inclusion proof is checked against a head and the length the verifier knows it has.
+
+use super::{
+    cardinality::DegreeProof,
+    commit_g2,
+    commitment::params_id,
+    dynamic_accumulator::{AddProof, DynamicAccumulator, MembershipProof},
+    handle::ElementHandle,
+    polymath, trapdoor,
+    utils::digest_to_prime_field,
+    Curve, Fr, G1Affine, G2Affine,
+};
+use crate::digest::{concat_digest, Digest, Digestible};
+use anyhow::{bail, Result};
+use ark_ec::{AffineCurve, PairingEngine};
+
+/// Maps an entry at a position to the field element that is accumulated.
+pub fn log_entry_to_fr(index: u64, entry: &Digest) -> Fr {
+    digest_to_prime_field(&concat_digest([index.to_digest(), *entry].iter().copied()))
+}
+
+/// A proof that the entry at `index` is part of the log of length `log_len`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogInclusionProof {
+    pub index: u64,
+    /// The length the prover claims. Only trusted once it matches the verifier's own.
+    pub log_len: u64,
+    pub membership: MembershipProof,
+}
+
+impl LogInclusionProof {
+    /// Verifies that `entry` sits at `self.index` in the log of length `log_len` whose head is
+    /// `head`. The length comes from the verifier, e.g. from the signed head or a
+    /// [`LogConsistencyProof`], as the head alone does not reveal it.
+    pub fn verify(&self, head: G1Affine, log_len: u64, entry: &(impl Digestible + ?Sized)) -> bool {
+        self.log_len == log_len
+            && self.index < log_len
+            && self.membership.element
+                == ElementHandle(log_entry_to_fr(self.index, &entry.to_digest()))
+            && self.membership.verify_at(head, self.log_len)
+    }
+}
+
+/// A proof that the log of length `new_len` extends the log of length `old_len`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogConsistencyProof {
+    pub old_len: u64,
+    pub new_len: u64,
+    /// g2^Q(s), where Q(X) is the characteristic polynomial of the appended entries.
+    pub appended: G2Affine,
+    pub old_degree: DegreeProof,
+    pub new_degree: DegreeProof,
+}
+
+impl LogConsistencyProof {
+    /// Verifies that `new_head` commits to every entry of `old_head` plus
+    /// `new_len - old_len` more. It checks if e(new_head, g2) == e(old_head, g2^Q(s)).
+    pub fn verify(&self, old_head: G1Affine, new_head: G1Affine) -> bool {
+        self.old_len <= self.new_len
+            && self.old_degree.n as u64 == self.old_len
+            && self.new_degree.n as u64 == self.new_len
+            && self.old_degree.verify(old_head)
+            && self.new_degree.verify(new_head)
+            && Curve::pairing(new_head, G2Affine::prime_subgroup_generator())
+                == Curve::pairing(old_head, self.appended)
+    }
+}
+
+/// An append-only log of entry digests with accumulator-based proofs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthenticatedLog {
+    acc: DynamicAccumulator,
+    /// Accumulated field element of every entry, by index.
+    entries: Vec<Fr>,
+    /// `heads[n]` is the accumulator value of the log of length n.
+    heads: Vec<G1Affine>,
+}
+
+impl AuthenticatedLog {
+    pub fn new() -> Self {
+        let acc = DynamicAccumulator::new();
+        let heads = vec![acc.acc_value];
+        Self {
+            acc,
+            entries: Vec::new(),
+            heads,
+        }
+    }
+
+    /// Appends an entry and returns its index together with the accumulator's add proof.
+    pub fn append(&mut self, entry: &(impl Digestible + ?Sized)) -> Result<(u64, AddProof)> {
+        let index = self.len();
+        let fr_entry = log_entry_to_fr(index, &entry.to_digest());
+        let proof = self.acc.add_fr(fr_entry)?;
+        self.entries.push(fr_entry);
+        self.heads.push(self.acc.acc_value);
+        Ok((index, proof))
+    }
+
+    pub fn len(&self) -> u64 {
+        self.entries.len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The current head, i.e. the accumulator value of the whole log.
+    pub fn head(&self) -> G1Affine {
+        self.acc.acc_value
+    }
+
+    /// The head of the log when it had `len` entries.
+    pub fn head_at(&self, len: u64) -> Option<G1Affine> {
+        self.heads.get(len as usize).copied()
+    }
+
+    /// Proves that the entry at `index` is part of the current log.
+    pub fn prove_inclusion(&self, index: u64) -> Result<LogInclusionProof> {
+        self.prove_inclusion_at(index, self.len())
+    }
+
+    /// Proves that the entry at `index` is part of the log of length `log_len`.
+    pub fn prove_inclusion_at(&self, index: u64, log_len: u64) -> Result<LogInclusionProof> {
+        if index >= log_len || log_len > self.len() {
+            bail!("index {} is not within a log of length {}", index, log_len);
+        }
+        let head = self.heads[log_len as usize];
+        let element = self.entries[index as usize];
+        // witness = head^((s-element)^-1)
+        let witness = trapdoor::signer().div_s_minus(head, element)?;
+        Ok(LogInclusionProof {
+            index,
+            log_len,
+            membership: MembershipProof {
+                witness,
+                element: ElementHandle(element),
+                epoch: log_len,
+                acc_digest: head.to_digest(),
+                params_id: params_id(),
+            },
+        })
+    }
+
+    /// Proves that the log of length `new_len` is an extension of the log of length `old_len`.
+    pub fn prove_consistency(&self, old_len: u64, new_len: u64) -> Result<LogConsistencyProof> {
+        if old_len > new_len || new_len > self.len() {
+            bail!("invalid log lengths {} and {}", old_len, new_len);
+        }
+        let (old, appended) = self.entries[..new_len as usize].split_at(old_len as usize);
+        Ok(LogConsistencyProof {
+            old_len,
+            new_len,
+            appended: commit_g2(polymath::from_roots(appended))?,
+            old_degree: DegreeProof::from_elements(old.iter())?,
+            new_degree: DegreeProof::from_elements(self.entries[..new_len as usize].iter())?,
+        })
+    }
+}
+
+impl Default for AuthenticatedLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_inclusion() {
+        init_logger();
+        let mut log = AuthenticatedLog::new();
+        for entry in ["a", "b", "c"].iter() {
+            log.append(*entry).unwrap();
+        }
+        let proof = log.prove_inclusion(1).unwrap();
+        assert!(proof.verify(log.head(), 3, "b"));
+        assert!(!proof.verify(log.head(), 3, "c"));
+
+        // The same entry at another position is a different element.
+        let mut moved = proof.clone();
+        moved.index = 2;
+        assert!(!moved.verify(log.head(), 3, "b"));
+
+        // A length the verifier does not expect is rejected, whatever the proof claims.
+        assert!(!proof.verify(log.head(), 4, "b"));
+        let mut longer = proof.clone();
+        longer.log_len = 4;
+        assert!(!longer.verify(log.head(), 3, "b"));
+
+        let old = log.prove_inclusion_at(1, 2).unwrap();
+        assert!(old.verify(log.head_at(2).unwrap(), 2, "b"));
+        assert!(!old.verify(log.head_at(2).unwrap(), 3, "b"));
+        assert!(log.prove_inclusion_at(2, 2).is_err());
+    }
+
+    #[test]
+    fn test_consistency() {
+        init_logger();
+        let mut log = AuthenticatedLog::new();
+        for i in 0..5u64 {
+            log.append(&i).unwrap();
+        }
+        let proof = log.prove_consistency(2, 5).unwrap();
+        assert!(proof.verify(log.head_at(2).unwrap(), log.head()));
+        assert!(!proof.verify(log.head_at(3).unwrap(), log.head()));
+
+        let mut forked = AuthenticatedLog::new();
+        for i in [0u64, 9, 2, 3, 4].iter() {
+            forked.append(i).unwrap();
+        }
+        assert!(!proof.verify(forked.head_at(2).unwrap(), log.head()));
+        assert!(!proof.verify(log.head_at(2).unwrap(), forked.head()));
+    }
+}
@@ -0,0 +1,180 @@
+//! One set API over interchangeable commitment backends.
+//!
+//! [`SetAccumulator`] is implemented by the pairing-based
+//! [`DynamicAccumulator`](super::dynamic_accumulator::DynamicAccumulator), which needs the
+//! trusted setup, and by the hash-based [`MerkleAccumulator`](super::merkle::MerkleAccumulator),
+//! which does not. Integration code written against the trait, [`Commitment`] and [`SetProof`]
+//! switches backend by changing one type, e.g. from [`Pairing`] to [`SetupFree`].
+
+use super::{
+    dynamic_accumulator::{DynamicAccumulator, MembershipProof, NonMembershipProof, QueryResult},
+    handle::ElementHandle,
+    merkle::{MerkleAccumulator, MerkleMembershipProof, MerkleNonMembershipProof},
+    utils::digest_to_prime_field,
+    G1Affine,
+};
+use crate::digest::{Digest, Digestible};
+use anyhow::Result;
+
+/// The backend with constant-size proofs, which relies on the trusted setup.
+pub type Pairing = DynamicAccumulator;
+
+/// The backend that relies on the hash function only.
+pub type SetupFree = MerkleAccumulator;
+
+/// A published commitment to a set.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Commitment {
+    Pairing(G1Affine),
+    Merkle(Digest),
+}
+
+/// A proof that an element is or is not in a committed set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SetProof {
+    Membership(MembershipProof),
+    NonMembership(NonMembershipProof),
+    MerkleMembership(MerkleMembershipProof),
+    MerkleNonMembership(MerkleNonMembershipProof),
+}
+
+impl SetProof {
+    /// Returns true for proofs of membership.
+    pub fn is_membership(&self) -> bool {
+        matches!(self, Self::Membership(_) | Self::MerkleMembership(_))
+    }
+
+    /// Verifies the proof for `element` against `commitment`. Proofs of one backend never
+    /// verify against a commitment of the other.
+    pub fn verify(&self, commitment: &Commitment, element: &i64) -> bool {
+        let fr = ElementHandle::of(element);
+        match (self, commitment) {
+            (Self::Membership(p), Commitment::Pairing(acc)) => p.element == fr && p.verify(*acc),
+            (Self::NonMembership(p), Commitment::Pairing(acc)) => p.element == fr && p.verify(*acc),
+            (Self::MerkleMembership(p), Commitment::Merkle(root)) => {
+                p.element == fr && p.verify(root)
+            }
+            (Self::MerkleNonMembership(p), Commitment::Merkle(root)) => {
+                p.element == fr && p.verify(root)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// A dynamic set with membership and non-membership proofs.
+pub trait SetAccumulator {
+    fn add(&mut self, element: &i64) -> Result<()>;
+
+    fn delete(&mut self, element: &i64) -> Result<()>;
+
+    fn contains(&self, element: &i64) -> bool;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn commitment(&self) -> Commitment;
+
+    /// Proves that `element` is in the set, or that it is not.
+    fn query(&self, element: &i64) -> Result<SetProof>;
+}
+
+impl SetAccumulator for DynamicAccumulator {
+    fn add(&mut self, element: &i64) -> Result<()> {
+        DynamicAccumulator::add(self, element).map(|_| ())
+    }
+
+    fn delete(&mut self, element: &i64) -> Result<()> {
+        DynamicAccumulator::delete(self, element).map(|_| ())
+    }
+
+    fn contains(&self, element: &i64) -> bool {
+        self.elements
+            .has(&digest_to_prime_field(&element.to_digest()))
+    }
+
+    fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    fn commitment(&self) -> Commitment {
+        Commitment::Pairing(self.acc_value)
+    }
+
+    fn query(&self, element: &i64) -> Result<SetProof> {
+        Ok(match DynamicAccumulator::query(self, element)? {
+            QueryResult::Membership(p) => SetProof::Membership(p),
+            QueryResult::NonMembership(p) => SetProof::NonMembership(p),
+        })
+    }
+}
+
+impl SetAccumulator for MerkleAccumulator {
+    fn add(&mut self, element: &i64) -> Result<()> {
+        MerkleAccumulator::add(self, element)
+    }
+
+    fn delete(&mut self, element: &i64) -> Result<()> {
+        MerkleAccumulator::delete(self, element)
+    }
+
+    fn contains(&self, element: &i64) -> bool {
+        MerkleAccumulator::contains(self, element)
+    }
+
+    fn len(&self) -> usize {
+        MerkleAccumulator::len(self)
+    }
+
+    fn commitment(&self) -> Commitment {
+        Commitment::Merkle(MerkleAccumulator::commitment(self))
+    }
+
+    fn query(&self, element: &i64) -> Result<SetProof> {
+        Ok(if MerkleAccumulator::contains(self, element) {
+            SetProof::MerkleMembership(self.prove_membership(element)?)
+        } else {
+            SetProof::MerkleNonMembership(self.prove_non_membership(element)?)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    fn exercise<A: SetAccumulator + Default>() -> (Commitment, SetProof) {
+        let mut acc = A::default();
+        for x in [1, 2, 3] {
+            acc.add(&x).unwrap();
+        }
+        acc.delete(&2).unwrap();
+        assert!(acc.add(&1).is_err());
+        assert_eq!(acc.len(), 2);
+        assert!(acc.contains(&3) && !acc.contains(&2));
+
+        let commitment = acc.commitment();
+        let member = acc.query(&3).unwrap();
+        assert!(member.is_membership() && member.verify(&commitment, &3));
+        assert!(!member.verify(&commitment, &1));
+        let non_member = acc.query(&2).unwrap();
+        assert!(!non_member.is_membership() && non_member.verify(&commitment, &2));
+        (commitment, member)
+    }
+
+    #[test]
+    fn test_backends() {
+        init_logger();
+        let (pairing_commitment, pairing_proof) = exercise::<Pairing>();
+        let (merkle_commitment, merkle_proof) = exercise::<SetupFree>();
+        assert!(!pairing_proof.verify(&merkle_commitment, &3));
+        assert!(!merkle_proof.verify(&pairing_commitment, &3));
+    }
+}
@@ -0,0 +1,287 @@
+//! Batch verification of mixed proof types.
+//!
+//! Every proof check is a pairing-product equation prod e(P_i, Q_i) == 1. [`verify_all`] raises
+//! the G1 side of each equation to an independent random scalar and checks the product of all of
+//! them with a single multi-pairing, which holds for a bad batch with negligible probability.
+//! Terms paired with the G2 generator are folded into one pairing.
+
+use super::{
+    canonical::CanonicalForm,
+    dynamic_accumulator::{
+        AddProof, DeleteProof, IntersectionProof, MembershipProof, NonMembershipProof, UnionProof,
+    },
+    metrics,
+    prepared::PreparedPublicParams,
+    Curve, Fr, G1Affine, G1Projective, G2Affine,
+};
+use crate::digest::Digestible;
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{One, PrimeField, UniformRand, Zero};
+use rand::Rng;
+
+type G1Prepared = <Curve as PairingEngine>::G1Prepared;
+type G2Prepared = <Curve as PairingEngine>::G2Prepared;
+
+/// A proof together with the public values it is checked against.
+#[derive(Debug, Clone, Copy)]
+pub enum AnyProof<'a> {
+    Add(&'a AddProof),
+    Delete(&'a DeleteProof),
+    Membership {
+        proof: &'a MembershipProof,
+        accumulator: &'a G1Affine,
+    },
+    NonMembership {
+        proof: &'a NonMembershipProof,
+        accumulator: &'a G1Affine,
+    },
+    Intersection {
+        proof: &'a IntersectionProof,
+        acc1: &'a G1Affine,
+        acc2: &'a G1Affine,
+        intersection: &'a G1Affine,
+    },
+    Union {
+        proof: &'a UnionProof,
+        acc1: &'a G1Affine,
+        acc2: &'a G1Affine,
+        union: &'a G1Affine,
+    },
+}
+
+impl AnyProof<'_> {
+    fn is_canonical(&self) -> bool {
+        match self {
+            AnyProof::Add(p) => p.is_canonical(),
+            AnyProof::Delete(p) => p.is_canonical(),
+            AnyProof::Membership { proof, .. } => proof.is_canonical(),
+            AnyProof::NonMembership { proof, .. } => proof.is_canonical(),
+            AnyProof::Intersection { proof, .. } => proof.is_canonical(),
+            AnyProof::Union { proof, .. } => proof.is_canonical(),
+        }
+    }
+}
+
+/// The pairing equations of a batch, with the G2-generator terms folded together.
+struct Equations {
+    g2_generator_term: G1Projective,
+    pairs: Vec<(G1Affine, G2Affine)>,
+}
+
+impl Equations {
+    /// Adds the equation prod e(P_i, Q_i) == 1, where `None` stands for the G2 generator,
+    /// scaled by `r`.
+    fn push(&mut self, r: Fr, terms: &[(G1Affine, Option<G2Affine>)]) {
+        for (p, q) in terms {
+            let scaled = p.mul(r.into_repr());
+            match q {
+                None => self.g2_generator_term += scaled,
+                Some(q) => self.pairs.push((scaled.into_affine(), *q)),
+            }
+        }
+    }
+}
+
+fn g2_s_minus(element: Fr) -> Option<G2Affine> {
+    Some(super::g2_s_minus(element))
+}
+
+fn push_intersection<R: Rng + ?Sized>(
+    eqs: &mut Equations,
+    rng: &mut R,
+    proof: &IntersectionProof,
+    acc1: G1Affine,
+    acc2: G1Affine,
+    intersection: G1Affine,
+) {
+    let g1 = G1Affine::prime_subgroup_generator();
+    eqs.push(
+        Fr::rand(rng),
+        &[(acc1, None), (-intersection, Some(proof.witness_a))],
+    );
+    eqs.push(
+        Fr::rand(rng),
+        &[(acc2, None), (-intersection, Some(proof.witness_b))],
+    );
+    eqs.push(
+        Fr::rand(rng),
+        &[
+            (proof.witness_coprime_a, Some(proof.witness_a)),
+            (proof.witness_coprime_b, Some(proof.witness_b)),
+            (-g1, None),
+        ],
+    );
+}
+
+/// Verifies a batch of proofs of any type with one multi-pairing. Like the individual `verify`
+/// methods, it rejects proofs that are not in canonical form.
+pub fn verify_all(proofs: &[AnyProof]) -> bool {
+    verify_all_with_rng(proofs, &mut rand::thread_rng())
+}
+
+/// [`verify_all`] with the batching scalars drawn from `rng`.
+pub fn verify_all_with_rng<R: Rng + ?Sized>(proofs: &[AnyProof], rng: &mut R) -> bool {
+    let g1 = G1Affine::prime_subgroup_generator();
+    let mut eqs = Equations {
+        g2_generator_term: G1Projective::zero(),
+        pairs: Vec::new(),
+    };
+    for proof in proofs {
+        if !proof.is_canonical() {
+            return false;
+        }
+        match *proof {
+            AnyProof::Add(p) => eqs.push(
+                Fr::rand(rng),
+                &[
+                    (p.new_acc_value, None),
+                    (-p.old_acc_value, g2_s_minus(p.element.0)),
+                ],
+            ),
+            AnyProof::Delete(p) => eqs.push(
+                Fr::rand(rng),
+                &[
+                    (p.new_acc_value, g2_s_minus(p.element.0)),
+                    (-p.old_acc_value, None),
+                ],
+            ),
+            AnyProof::Membership { proof, accumulator } => {
+                if proof.acc_digest != accumulator.to_digest() {
+                    return false;
+                }
+                eqs.push(
+                    Fr::rand(rng),
+                    &[
+                        (proof.witness, g2_s_minus(proof.element.0)),
+                        (-*accumulator, None),
+                    ],
+                );
+            }
+            AnyProof::NonMembership { proof, accumulator } => {
+                if proof.acc_digest != accumulator.to_digest() {
+                    return false;
+                }
+                eqs.push(
+                    Fr::rand(rng),
+                    &[
+                        (*accumulator, Some(proof.witness)),
+                        (proof.g1_a, g2_s_minus(proof.element.0)),
+                        (-g1, None),
+                    ],
+                );
+            }
+            AnyProof::Intersection {
+                proof,
+                acc1,
+                acc2,
+                intersection,
+            } => push_intersection(&mut eqs, rng, proof, *acc1, *acc2, *intersection),
+            AnyProof::Union {
+                proof,
+                acc1,
+                acc2,
+                union,
+            } => {
+                push_intersection(
+                    &mut eqs,
+                    rng,
+                    &proof.intersection_proof,
+                    *acc1,
+                    *acc2,
+                    proof.intersection_acc_value,
+                );
+                eqs.push(
+                    Fr::rand(rng),
+                    &[
+                        (*union, None),
+                        (-*acc1, Some(proof.intersection_proof.witness_b)),
+                    ],
+                );
+            }
+        }
+    }
+
+    let mut pairs: Vec<(G1Prepared, G2Prepared)> = eqs
+        .pairs
+        .iter()
+        .map(|(p, q)| ((*p).into(), (*q).into()))
+        .collect();
+    pairs.push((
+        eqs.g2_generator_term.into_affine().into(),
+        PreparedPublicParams::global().g2().clone(),
+    ));
+    metrics::record_pairings(pairs.len() as u64);
+    Curve::product_of_pairings(&pairs).is_one()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::dynamic_accumulator::DynamicAccumulator;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_verify_all() {
+        init_logger();
+        let mut acc1 = DynamicAccumulator::new();
+        let add = acc1.add(&1).unwrap();
+        acc1.add_batch(&[2, 3, 4]).unwrap();
+        let delete = acc1.delete(&4).unwrap();
+        let mut acc2 = DynamicAccumulator::new();
+        acc2.add_batch(&[2, 3, 5]).unwrap();
+        let (inter, inter_proof) = acc1.prove_intersection(&acc2).unwrap();
+        let (union, union_proof) = acc1.prove_union(&acc2).unwrap();
+        let member = acc1.prove_membership(&2).unwrap();
+        let non_member = acc1.prove_non_membership(&5).unwrap();
+
+        let batch = [
+            AnyProof::Add(&add),
+            AnyProof::Delete(&delete),
+            AnyProof::Membership {
+                proof: &member,
+                accumulator: &acc1.acc_value,
+            },
+            AnyProof::NonMembership {
+                proof: &non_member,
+                accumulator: &acc1.acc_value,
+            },
+            AnyProof::Intersection {
+                proof: &inter_proof,
+                acc1: &acc1.acc_value,
+                acc2: &acc2.acc_value,
+                intersection: &inter.acc_value,
+            },
+            AnyProof::Union {
+                proof: &union_proof,
+                acc1: &acc1.acc_value,
+                acc2: &acc2.acc_value,
+                union: &union.acc_value,
+            },
+        ];
+        assert!(verify_all(&batch));
+        assert!(verify_all(&[]));
+
+        let mut bad_add = add.clone();
+        bad_add.element = member.element;
+        let mut tampered = batch;
+        tampered[0] = AnyProof::Add(&bad_add);
+        assert!(!verify_all(&tampered));
+        tampered = batch;
+        tampered[5] = AnyProof::Union {
+            proof: &union_proof,
+            acc1: &acc1.acc_value,
+            acc2: &acc2.acc_value,
+            union: &inter.acc_value,
+        };
+        assert!(!verify_all(&tampered));
+        tampered = batch;
+        tampered[2] = AnyProof::Membership {
+            proof: &member,
+            accumulator: &acc2.acc_value,
+        };
+        assert!(!verify_all(&tampered));
+    }
+}
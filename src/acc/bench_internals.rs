@@ -0,0 +1,172 @@
+//! An instrumented harness for the polynomial-heavy provers.
+//!
+//! Proving non-membership and intersection expands characteristic polynomials of whole sets,
+//! either factor by factor or with a subproduct tree (see [`RootsStrategy`]). [`run`] times both
+//! provers with each strategy over a range of set sizes, so that the crossover between the two
+//! can be checked on the hardware at hand. The `polynomial_scale` bench prints its results.
+//!
+//! Sizes are limited to [`MAX_DEGREE`]: the public parameters end there, and a larger set
+//! would be committed with powers emulated from the trapdoor, which is not what a deployment
+//! measures. At that size every proof, including those with the quadratic naive expansion,
+//! takes seconds in a release build.
+//!
+//! Only available with the `bench-internals` feature.
+
+use super::{
+    dynamic_accumulator::{DynamicAccumulator, IntersectionProof, NonMembershipProof},
+    utils::digest_to_prime_field,
+    MAX_DEGREE,
+};
+use crate::digest::Digestible;
+use anyhow::{bail, Result};
+use core::{fmt, time::Duration};
+use std::time::Instant;
+
+pub use super::polymath::RootsStrategy;
+
+/// The set sizes measured by default, from 500 elements up to [`MAX_DEGREE`].
+pub const DEFAULT_SIZES: [usize; 4] = [500, 1_000, 2_500, MAX_DEGREE];
+
+/// The operations the harness measures.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Operation {
+    ProveNonMembership,
+    ProveIntersection,
+}
+
+impl Operation {
+    pub const ALL: [Operation; 2] = [Operation::ProveNonMembership, Operation::ProveIntersection];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Operation::ProveNonMembership => "prove_non_membership",
+            Operation::ProveIntersection => "prove_intersection",
+        }
+    }
+}
+
+/// The time one operation took with one strategy at one set size.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Measurement {
+    pub operation: Operation,
+    pub strategy: RootsStrategy,
+    pub size: usize,
+    pub elapsed: Duration,
+}
+
+impl fmt::Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<22} {:<16} {:>9} {:>12.3?}",
+            self.operation.name(),
+            format!("{:?}", self.strategy),
+            self.size,
+            self.elapsed
+        )
+    }
+}
+
+/// [`DynamicAccumulator::prove_non_membership`] with the characteristic polynomial expanded
+/// with `strategy`.
+pub fn prove_non_membership_with(
+    acc: &DynamicAccumulator,
+    element: &i64,
+    strategy: RootsStrategy,
+) -> Result<NonMembershipProof> {
+    let fr_element = digest_to_prime_field(&element.to_digest());
+    if acc.elements.contains(&fr_element)? {
+        bail!("Cannot prove non-membership for an element in the set");
+    }
+    acc.non_membership_from_poly(&acc.characteristic_poly_with(strategy), fr_element)
+}
+
+/// [`DynamicAccumulator::prove_intersection`] with the characteristic polynomials expanded with
+/// `strategy`.
+pub fn prove_intersection_with(
+    acc: &DynamicAccumulator,
+    other: &DynamicAccumulator,
+    strategy: RootsStrategy,
+) -> Result<(DynamicAccumulator, IntersectionProof)> {
+    acc.prove_intersection_with(other, strategy)
+}
+
+/// An accumulator holding `start..start + size`.
+fn populated(start: i64, size: usize) -> Result<DynamicAccumulator> {
+    let mut acc = DynamicAccumulator::new();
+    let elements: Vec<i64> = (start..start + size as i64).collect();
+    acc.add_batch(&elements)?;
+    Ok(acc)
+}
+
+/// Times every operation with every strategy at every size. The intersection is measured
+/// between two sets of `size` elements that overlap in half of them. Every proof is verified,
+/// and an invalid one is reported as an error, as is a size above [`MAX_DEGREE`].
+pub fn run(sizes: &[usize], strategies: &[RootsStrategy]) -> Result<Vec<Measurement>> {
+    if let Some(size) = sizes.iter().find(|&&size| size > MAX_DEGREE) {
+        bail!(
+            "Set size {} exceeds the maximum degree {}",
+            size,
+            MAX_DEGREE
+        );
+    }
+    let mut measurements = Vec::with_capacity(sizes.len() * strategies.len() * 2);
+    for &size in sizes {
+        let acc = populated(0, size)?;
+        let other = populated(size as i64 / 2, size)?;
+        for &strategy in strategies {
+            let start = Instant::now();
+            let proof = prove_non_membership_with(&acc, &-1, strategy)?;
+            let elapsed = start.elapsed();
+            if !proof.verify(acc.acc_value) {
+                bail!("Invalid non-membership proof with {:?}", strategy);
+            }
+            measurements.push(Measurement {
+                operation: Operation::ProveNonMembership,
+                strategy,
+                size,
+                elapsed,
+            });
+
+            let start = Instant::now();
+            let (intersection, proof) = prove_intersection_with(&acc, &other, strategy)?;
+            let elapsed = start.elapsed();
+            if !DynamicAccumulator::verify_intersection(
+                acc.acc_value,
+                other.acc_value,
+                intersection.acc_value,
+                &proof,
+            ) {
+                bail!("Invalid intersection proof with {:?}", strategy);
+            }
+            measurements.push(Measurement {
+                operation: Operation::ProveIntersection,
+                strategy,
+                size,
+                elapsed,
+            });
+        }
+    }
+    Ok(measurements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_run() {
+        init_logger();
+        let strategies = [RootsStrategy::Naive, RootsStrategy::SubproductTree];
+        let measurements = run(&[10, 100], &strategies).unwrap();
+        assert_eq!(measurements.len(), 8);
+        assert!(run(&[MAX_DEGREE + 1], &strategies).is_err());
+        assert!(measurements
+            .iter()
+            .all(|m| m.to_string().contains(m.operation.name())));
+    }
+}
@@ -0,0 +1,162 @@
+//! Re-randomized membership proofs.
+//!
+//! A membership witness is the unique value acc^(1/(s-x)), so a holder presenting the same
+//! [`MembershipProof`] twice sends the same bytes, and verifiers can link the presentations.
+//! [`MembershipProof::rerandomize`] blinds it with a fresh `r` on every call: the proof carries
+//! `w^r` and `R = g2^r`, and the verifier checks e(w^r, g2^(s-x)) == e(acc, R). The plain
+//! membership equation cannot be kept, as it admits only one witness. Since g2^(s-x) is public,
+//! anyone can meet that check for any x with `w' = acc^k`, `R = g2^(k(s-x))`, so the proof also
+//! carries a Fiat-Shamir proof of knowledge of log_g2(R), which such a forger does not know.
+//!
+//! The element stays visible. Presentations that also hide it are in [`super::credential`].
+
+use super::{
+    canonical::{is_canonical_g1, is_canonical_g2},
+    commitment::params_id,
+    dynamic_accumulator::{ark_serde, MembershipProof},
+    handle::ElementHandle,
+    metrics,
+    prepared::PreparedPublicParams,
+    utils::digest_to_prime_field,
+    Curve, Fr, G1Affine, G2Affine,
+};
+use crate::digest::{Digest, Digestible};
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{One, PrimeField, ToBytes, UniformRand, Zero};
+use serde::{Deserialize, Serialize};
+
+/// A membership proof with a blinded witness, see the [module docs](self).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RandomizedMembershipProof {
+    /// w^r for the witness w.
+    #[serde(with = "ark_serde")]
+    pub witness: G1Affine,
+    /// g2^r.
+    #[serde(with = "ark_serde")]
+    pub blinding: G2Affine,
+    /// The commitment g2^t of the proof of knowledge of r.
+    #[serde(with = "ark_serde")]
+    pub commitment: G2Affine,
+    /// t + c * r for the challenge c.
+    #[serde(with = "ark_serde")]
+    pub response: Fr,
+    pub element: ElementHandle,
+    pub epoch: u64,
+    pub acc_digest: Digest,
+    pub params_id: Digest,
+}
+
+impl RandomizedMembershipProof {
+    fn challenge(&self) -> Fr {
+        let mut buf = Vec::<u8>::new();
+        buf.extend_from_slice(b"ESA_RUST_RERANDOMIZE");
+        self.witness
+            .write(&mut buf)
+            .and_then(|_| self.blinding.write(&mut buf))
+            .and_then(|_| self.commitment.write(&mut buf))
+            .and_then(|_| self.element.0.write(&mut buf))
+            .unwrap_or_else(|_| panic!("failed to serialize the proof transcript"));
+        buf.extend_from_slice(&self.epoch.to_le_bytes());
+        buf.extend_from_slice(&self.acc_digest.0);
+        digest_to_prime_field(&buf.to_digest())
+    }
+
+    /// Checks e(witness, g2^(s-element)) == e(accumulator, blinding) and the proof of knowledge
+    /// g2^response == commitment * blinding^c.
+    pub fn verify(&self, accumulator: G1Affine) -> bool {
+        if self.acc_digest != accumulator.to_digest()
+            || self.params_id != params_id()
+            || self.blinding.is_zero()
+            || !is_canonical_g1(&self.witness)
+            || !is_canonical_g2(&self.blinding)
+            || !is_canonical_g2(&self.commitment)
+        {
+            return false;
+        }
+        let g2 = G2Affine::prime_subgroup_generator();
+        let knows_r = g2.mul(self.response.into_repr())
+            == self.commitment.into_projective() + self.blinding.mul(self.challenge().into_repr());
+        let params = PreparedPublicParams::global();
+        metrics::record_pairings(2);
+        let pairs = [
+            (self.witness.into(), params.g2_s_minus(self.element.0)),
+            ((-accumulator).into(), self.blinding.into()),
+        ];
+        knows_r && Curve::product_of_pairings(&pairs).is_one()
+    }
+
+    /// Verifies the proof against the accumulator value published at `epoch`.
+    pub fn verify_at(&self, accumulator: G1Affine, epoch: u64) -> bool {
+        self.epoch == epoch && self.verify(accumulator)
+    }
+}
+
+impl MembershipProof {
+    /// Blinds the witness with a fresh factor from `rng`. Every call gives unrelated bytes.
+    pub fn rerandomize<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> RandomizedMembershipProof {
+        let r = loop {
+            let r = Fr::rand(rng);
+            if !r.is_zero() {
+                break r;
+            }
+        };
+        let t = Fr::rand(rng);
+        let g2 = G2Affine::prime_subgroup_generator();
+        let mut proof = RandomizedMembershipProof {
+            witness: self.witness.mul(r.into_repr()).into_affine(),
+            blinding: g2.mul(r.into_repr()).into_affine(),
+            commitment: g2.mul(t.into_repr()).into_affine(),
+            response: Fr::zero(),
+            element: self.element,
+            epoch: self.epoch,
+            acc_digest: self.acc_digest,
+            params_id: self.params_id,
+        };
+        proof.response = t + proof.challenge() * r;
+        proof
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::{G2_POWER, PRI_S};
+    use crate::DynamicAccumulator;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_rerandomize() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        acc.add(&1).unwrap();
+        acc.add(&2).unwrap();
+        let proof = acc.prove_membership(&1).unwrap();
+        let rng = &mut rand::thread_rng();
+        let (a, b) = (proof.rerandomize(rng), proof.rerandomize(rng));
+        assert!(a.verify_at(acc.acc_value, acc.epoch()) && b.verify(acc.acc_value));
+        assert_ne!(a.witness, b.witness);
+        assert_ne!(a.witness, proof.witness);
+
+        let mut forged = a.clone();
+        forged.element = ElementHandle::of(&3);
+        assert!(!forged.verify(acc.acc_value));
+
+        // Meeting the pairing check without a witness fails the proof of knowledge.
+        let k = Fr::rand(rng);
+        let x = ElementHandle::of(&3).0;
+        let mut forged = a;
+        forged.element = ElementHandle(x);
+        forged.witness = acc.acc_value.mul(k.into_repr()).into_affine();
+        forged.blinding = G2_POWER.apply(&(k * (*PRI_S - x))).into_affine();
+        let params = PreparedPublicParams::global();
+        assert!(Curve::product_of_pairings(&[
+            (forged.witness.into(), params.g2_s_minus(x)),
+            ((-acc.acc_value).into(), forged.blinding.into()),
+        ])
+        .is_one());
+        assert!(!forged.verify(acc.acc_value));
+    }
+}
@@ -0,0 +1,133 @@
+//! Block headers anchoring accumulator states.
+//!
+//! A [`BlockHeader`] binds an accumulator value and epoch to the previous header's digest and
+//! to the root of the operations that led to it, here the digest of their [`UpdateLog`], as in
+//! vChain-style authenticated chains. A light client that trusts a header, e.g. because it
+//! followed the chain of `prev_hash` links, checks membership and non-membership proofs
+//! against it with [`BlockHeader::verify_membership`] and
+//! [`BlockHeader::verify_non_membership`].
+
+use super::{
+    commitment::params_id,
+    dynamic_accumulator::{ark_serde, DynamicAccumulator, MembershipProof, NonMembershipProof},
+    update::UpdateLog,
+    G1Affine,
+};
+use crate::digest::{concat_digest, Digest, Digestible};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockHeader {
+    #[serde(with = "ark_serde")]
+    pub acc_value: G1Affine,
+    pub epoch: u64,
+    /// The digest of the previous header, or the zero digest for the genesis block.
+    pub prev_hash: Digest,
+    /// The digest of the update log from the previous block's epoch to this one.
+    pub ops_root: Digest,
+}
+
+impl Digestible for BlockHeader {
+    fn to_digest(&self) -> Digest {
+        concat_digest(
+            [
+                b"ESA_RUST_BLOCK"[..].to_digest(),
+                params_id(),
+                self.acc_value.to_digest(),
+                self.epoch.to_digest(),
+                self.prev_hash,
+                self.ops_root,
+            ]
+            .into_iter(),
+        )
+    }
+}
+
+impl BlockHeader {
+    /// The first header, for the current state of `acc`.
+    pub fn genesis(acc: &DynamicAccumulator) -> Self {
+        Self {
+            acc_value: acc.acc_value,
+            epoch: acc.epoch(),
+            prev_hash: Digest::default(),
+            ops_root: UpdateLog::new(acc.epoch()).to_digest(),
+        }
+    }
+
+    /// The header following this one for the current state of `acc`, reached by `ops`.
+    /// Fails if `ops` does not run from this header's epoch to the accumulator's.
+    pub fn next(&self, acc: &DynamicAccumulator, ops: &UpdateLog) -> Result<Self> {
+        if ops.start_epoch != self.epoch || ops.end_epoch() != acc.epoch() {
+            bail!(
+                "update log covers epochs {} to {}, expected {} to {}",
+                ops.start_epoch,
+                ops.end_epoch(),
+                self.epoch,
+                acc.epoch()
+            );
+        }
+        Ok(Self {
+            acc_value: acc.acc_value,
+            epoch: acc.epoch(),
+            prev_hash: self.to_digest(),
+            ops_root: ops.to_digest(),
+        })
+    }
+
+    /// Returns true if `next` follows this header by the updates in `ops`, which must chain
+    /// from this header's value to `next`'s.
+    pub fn verify_next(&self, next: &BlockHeader, ops: &UpdateLog) -> bool {
+        next.prev_hash == self.to_digest()
+            && next.ops_root == ops.to_digest()
+            && ops.start_epoch == self.epoch
+            && ops.end_epoch() == next.epoch
+            && ops
+                .verify_chain(self.acc_value)
+                .is_ok_and(|value| value == next.acc_value)
+    }
+
+    /// Checks a membership proof against the state of this block.
+    pub fn verify_membership(&self, proof: &MembershipProof) -> bool {
+        proof.verify_at(self.acc_value, self.epoch)
+    }
+
+    /// Checks a non-membership proof against the state of this block.
+    pub fn verify_non_membership(&self, proof: &NonMembershipProof) -> bool {
+        proof.verify_at(self.acc_value, self.epoch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_block_header() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        let genesis = BlockHeader::genesis(&acc);
+        let mut ops = UpdateLog::new(acc.epoch());
+        ops.push(acc.add(&1).unwrap());
+        ops.push(acc.add(&2).unwrap());
+        let block = genesis.next(&acc, &ops).unwrap();
+        assert!(genesis.verify_next(&block, &ops));
+        assert!(!genesis.verify_next(&block, &UpdateLog::new(0)));
+        assert!(block.next(&acc, &ops).is_err());
+
+        let proof = acc.prove_membership(&1).unwrap();
+        assert!(block.verify_membership(&proof));
+        assert!(!genesis.verify_membership(&proof));
+        assert!(block.verify_non_membership(&acc.prove_non_membership(&3).unwrap()));
+
+        // Each header commits to its predecessor.
+        let mut forged = block;
+        forged.prev_hash = Digest::default();
+        assert_ne!(forged.to_digest(), block.to_digest());
+        assert!(!genesis.verify_next(&forged, &ops));
+    }
+}
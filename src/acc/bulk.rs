@@ -0,0 +1,219 @@
+//! Bulk import of large element lists.
+//!
+//! [`BulkImport`] adds elements chunk by chunk, with one scalar multiplication per chunk. After
+//! every chunk it reports progress and checks a cancellation flag. A cancelled (or interrupted)
+//! import can be resumed from the [`BulkCheckpoint`] it returned, as long as the accumulator
+//! has not changed since.
+
+use super::{dynamic_accumulator::DynamicAccumulator, utils::digest_to_prime_field, G1Affine};
+use crate::digest::Digestible;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// The default number of elements added per chunk.
+pub const DEFAULT_CHUNK_SIZE: usize = 4096;
+
+/// Progress reported after every chunk.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct BulkProgress {
+    /// The number of input elements processed so far.
+    pub processed: usize,
+    pub total: usize,
+}
+
+/// The state of an import after its last completed chunk.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BulkCheckpoint {
+    /// The index of the first input element not processed yet.
+    pub next_index: usize,
+    pub epoch: u64,
+    #[serde(with = "super::serde_impl")]
+    pub acc_value: G1Affine,
+}
+
+/// How an import ended.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum BulkOutcome {
+    Completed(BulkCheckpoint),
+    Cancelled(BulkCheckpoint),
+}
+
+impl BulkOutcome {
+    pub fn checkpoint(&self) -> &BulkCheckpoint {
+        match self {
+            BulkOutcome::Completed(checkpoint) | BulkOutcome::Cancelled(checkpoint) => checkpoint,
+        }
+    }
+}
+
+/// A builder for importing many elements into an accumulator.
+pub struct BulkImport<'a> {
+    acc: &'a mut DynamicAccumulator,
+    elements: &'a [i64],
+    chunk_size: usize,
+    on_progress: Option<Box<dyn FnMut(BulkProgress) + 'a>>,
+    cancel: Option<&'a AtomicBool>,
+    resume: Option<BulkCheckpoint>,
+}
+
+impl<'a> BulkImport<'a> {
+    pub fn new(acc: &'a mut DynamicAccumulator, elements: &'a [i64]) -> Self {
+        Self {
+            acc,
+            elements,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            on_progress: None,
+            cancel: None,
+            resume: None,
+        }
+    }
+
+    /// Sets the number of elements added per chunk. Zero is treated as one.
+    pub fn chunk_size(mut self, n: usize) -> Self {
+        self.chunk_size = n.max(1);
+        self
+    }
+
+    /// Sets a callback invoked after every chunk.
+    pub fn on_progress(mut self, cb: impl FnMut(BulkProgress) + 'a) -> Self {
+        self.on_progress = Some(Box::new(cb));
+        self
+    }
+
+    /// Sets a flag checked before every chunk; once it is set the import stops.
+    pub fn cancel_flag(mut self, flag: &'a AtomicBool) -> Self {
+        self.cancel = Some(flag);
+        self
+    }
+
+    /// Resumes an earlier import of the same elements from its checkpoint.
+    pub fn resume_from(mut self, checkpoint: BulkCheckpoint) -> Self {
+        self.resume = Some(checkpoint);
+        self
+    }
+
+    /// Runs the import. Returns an error, keeping every chunk added so far, if a chunk cannot
+    /// be added (e.g. a duplicate under the default policy) or the checkpoint does not match
+    /// the accumulator.
+    pub fn run(mut self) -> Result<BulkOutcome> {
+        let total = self.elements.len();
+        let mut next_index = match &self.resume {
+            Some(checkpoint) => {
+                if checkpoint.acc_value != self.acc.acc_value
+                    || checkpoint.epoch != self.acc.epoch()
+                {
+                    bail!("The accumulator changed since the checkpoint");
+                }
+                if checkpoint.next_index > total {
+                    bail!("The checkpoint is beyond the end of the input");
+                }
+                checkpoint.next_index
+            }
+            None => 0,
+        };
+
+        while next_index < total {
+            if self.cancel.is_some_and(|flag| flag.load(Ordering::Relaxed)) {
+                return Ok(BulkOutcome::Cancelled(self.checkpoint(next_index)));
+            }
+            let end = (next_index + self.chunk_size).min(total);
+            let chunk: Vec<_> = self.elements[next_index..end]
+                .iter()
+                .map(|e| digest_to_prime_field(&e.to_digest()))
+                .collect();
+            self.acc.add_chunk_fr(&chunk)?;
+            for (fr_element, element) in chunk.iter().zip(&self.elements[next_index..end]) {
+                self.acc.retain_value(*fr_element, *element);
+            }
+            next_index = end;
+            if let Some(cb) = self.on_progress.as_mut() {
+                cb(BulkProgress {
+                    processed: next_index,
+                    total,
+                });
+            }
+        }
+        Ok(BulkOutcome::Completed(self.checkpoint(next_index)))
+    }
+
+    fn checkpoint(&self, next_index: usize) -> BulkCheckpoint {
+        BulkCheckpoint {
+            next_index,
+            epoch: self.acc.epoch(),
+            acc_value: self.acc.acc_value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_bulk_import() {
+        init_logger();
+        let elements: Vec<i64> = (0..10).collect();
+        let mut expected = DynamicAccumulator::new();
+        expected.add_batch(&elements).unwrap();
+
+        let mut acc = DynamicAccumulator::new();
+        let mut reports = Vec::new();
+        let outcome = BulkImport::new(&mut acc, &elements)
+            .chunk_size(4)
+            .on_progress(|p| reports.push(p.processed))
+            .run()
+            .unwrap();
+        assert!(matches!(outcome, BulkOutcome::Completed(_)));
+        assert_eq!(reports, vec![4, 8, 10]);
+        assert_eq!(acc, expected);
+        assert!(acc.verify_membership(&acc.prove_membership(&7).unwrap()));
+
+        // A duplicate fails the chunk without adding any of it.
+        let before = acc.clone();
+        assert!(BulkImport::new(&mut acc, &[20, 3]).run().is_err());
+        assert_eq!(acc, before);
+    }
+
+    #[test]
+    fn test_cancel_and_resume() {
+        init_logger();
+        let elements: Vec<i64> = (0..10).collect();
+        let cancel = AtomicBool::new(false);
+        let mut acc = DynamicAccumulator::new();
+        let outcome = BulkImport::new(&mut acc, &elements)
+            .chunk_size(3)
+            .cancel_flag(&cancel)
+            .on_progress(|p| cancel.store(p.processed >= 6, Ordering::Relaxed))
+            .run()
+            .unwrap();
+        let checkpoint = match outcome {
+            BulkOutcome::Cancelled(checkpoint) => checkpoint,
+            other => panic!("unexpected outcome {:?}", other),
+        };
+        assert_eq!(checkpoint.next_index, 6);
+        assert_eq!(acc.len(), 6);
+
+        let json = serde_json::to_string(&checkpoint).unwrap();
+        let checkpoint: BulkCheckpoint = serde_json::from_str(&json).unwrap();
+        let outcome = BulkImport::new(&mut acc, &elements)
+            .chunk_size(3)
+            .resume_from(checkpoint.clone())
+            .run()
+            .unwrap();
+        assert_eq!(outcome.checkpoint().next_index, 10);
+        let mut expected = DynamicAccumulator::new();
+        expected.add_batch(&elements).unwrap();
+        assert_eq!(acc, expected);
+
+        // The accumulator moved on, so the old checkpoint is stale.
+        assert!(BulkImport::new(&mut acc, &elements)
+            .resume_from(checkpoint)
+            .run()
+            .is_err());
+    }
+}
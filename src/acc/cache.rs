@@ -0,0 +1,132 @@
+//! Prover-side cache of membership witnesses.
+
+use super::{Fr, G1Affine};
+use core::fmt;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// The default maximum number of cached witnesses.
+pub const DEFAULT_WITNESS_CACHE_CAPACITY: usize = 1 << 16;
+
+/// Caches membership witnesses of the current accumulator state.
+///
+/// Entries are keyed by the accumulator value they were computed against as well as the
+/// element, so a lookup against any other value misses. The owner still calls
+/// [`WitnessCache::invalidate`] on every mutation to release stale entries. Once `capacity`
+/// entries are cached, further witnesses are not stored until the next invalidation.
+///
+/// The cache is not part of the accumulator's identity: two caches always compare equal.
+pub struct WitnessCache {
+    inner: RwLock<HashMap<Fr, (G1Affine, G1Affine)>>,
+    capacity: usize,
+}
+
+impl WitnessCache {
+    /// Creates an empty cache holding at most `capacity` witnesses. A capacity of zero
+    /// disables caching.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            inner: RwLock::new(HashMap::new()),
+            capacity,
+        }
+    }
+
+    /// Returns the cached witness of `element` under `acc_value`, if any.
+    pub fn get(&self, acc_value: &G1Affine, element: &Fr) -> Option<G1Affine> {
+        let inner = self.inner.read().ok()?;
+        match inner.get(element) {
+            Some((acc, witness)) if acc == acc_value => Some(*witness),
+            _ => None,
+        }
+    }
+
+    /// Caches the witness of `element` under `acc_value` unless the cache is full.
+    pub fn insert(&self, acc_value: G1Affine, element: Fr, witness: G1Affine) {
+        if let Ok(mut inner) = self.inner.write() {
+            if inner.len() < self.capacity || inner.contains_key(&element) {
+                inner.insert(element, (acc_value, witness));
+            }
+        }
+    }
+
+    /// Drops all cached witnesses.
+    pub fn invalidate(&self) {
+        if let Ok(mut inner) = self.inner.write() {
+            inner.clear();
+        }
+    }
+
+    /// Returns the number of cached witnesses.
+    pub fn len(&self) -> usize {
+        self.inner.read().map(|inner| inner.len()).unwrap_or(0)
+    }
+
+    /// Returns true if no witness is cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the maximum number of cached witnesses.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl Default for WitnessCache {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_WITNESS_CACHE_CAPACITY)
+    }
+}
+
+impl Clone for WitnessCache {
+    fn clone(&self) -> Self {
+        let inner = self
+            .inner
+            .read()
+            .map(|inner| inner.clone())
+            .unwrap_or_default();
+        Self {
+            inner: RwLock::new(inner),
+            capacity: self.capacity,
+        }
+    }
+}
+
+impl PartialEq for WitnessCache {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for WitnessCache {}
+
+impl fmt::Debug for WitnessCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WitnessCache")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+
+    #[test]
+    fn test_capacity_and_invalidate() {
+        let cache = WitnessCache::with_capacity(1);
+        let g = G1Affine::prime_subgroup_generator();
+        let acc = g.mul(Fr::from(3u32)).into_affine();
+        cache.insert(acc, Fr::from(1u32), g);
+        cache.insert(acc, Fr::from(2u32), g);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get(&acc, &Fr::from(1u32)), Some(g));
+        assert_eq!(cache.get(&acc, &Fr::from(2u32)), None);
+        assert_eq!(cache.get(&g, &Fr::from(1u32)), None);
+
+        cache.invalidate();
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,218 @@
+//! Canonical forms of proofs.
+//!
+//! A proof is canonical if every point in it is on the curve and in the prime-order subgroup,
+//! and the point at infinity is stored as [`Zero::zero`] rather than with arbitrary coordinates.
+//! Field elements are always reduced in memory. The `verify` methods of the proofs reject
+//! non-canonical proofs, so that two accepted proofs with equal fields encode to equal bytes.
+//!
+//! Canonical form does not make every proof unique: a non-membership proof (g2^B(s), g1^A(s)) can
+//! be re-randomized to (g2^(B(s) + k(s-x)), g1^(A(s) - kP(s))) by anyone. Deduplication and
+//! caching layers should therefore key proofs by [`Statement::statement_digest`], which
+//! covers what is proven rather than how.
+
+use super::{
+    consistency::ConsistencyProof,
+    dynamic_accumulator::{
+        AddProof, BatchAddProof, DeleteProof, IntersectionProof, MembershipProof,
+        NonMembershipProof, UnionProof,
+    },
+    update::fr_to_digest,
+    G1Affine, G2Affine,
+};
+use crate::digest::{concat_digest, Digest, Digestible};
+use ark_ff::Zero;
+
+pub(crate) fn is_canonical_g1(p: &G1Affine) -> bool {
+    if p.infinity {
+        return *p == G1Affine::zero();
+    }
+    p.is_on_curve() && p.is_in_correct_subgroup_assuming_on_curve()
+}
+
+pub(crate) fn is_canonical_g2(p: &G2Affine) -> bool {
+    if p.infinity {
+        return *p == G2Affine::zero();
+    }
+    p.is_on_curve() && p.is_in_correct_subgroup_assuming_on_curve()
+}
+
+/// Proofs with a canonical form.
+pub trait CanonicalForm {
+    /// Returns true if every point of the proof is in canonical form.
+    fn is_canonical(&self) -> bool;
+}
+
+/// Proofs that carry the whole statement they prove.
+pub trait Statement {
+    /// A digest of the statement the proof establishes, equal for all valid proofs of it.
+    fn statement_digest(&self) -> Digest;
+}
+
+fn statement(kind: &[u8], parts: impl IntoIterator<Item = Digest>) -> Digest {
+    concat_digest(core::iter::once(kind.to_digest()).chain(parts))
+}
+
+impl CanonicalForm for AddProof {
+    fn is_canonical(&self) -> bool {
+        is_canonical_g1(&self.old_acc_value) && is_canonical_g1(&self.new_acc_value)
+    }
+}
+
+impl Statement for AddProof {
+    fn statement_digest(&self) -> Digest {
+        statement(
+            b"Add",
+            [
+                self.old_acc_value.to_digest(),
+                self.new_acc_value.to_digest(),
+                fr_to_digest(&self.element.0),
+                self.epoch.to_digest(),
+            ],
+        )
+    }
+}
+
+impl CanonicalForm for BatchAddProof {
+    fn is_canonical(&self) -> bool {
+        is_canonical_g1(&self.old_acc_value) && is_canonical_g1(&self.new_acc_value)
+    }
+}
+
+impl Statement for BatchAddProof {
+    fn statement_digest(&self) -> Digest {
+        statement(
+            b"BatchAdd",
+            [
+                self.old_acc_value.to_digest(),
+                self.new_acc_value.to_digest(),
+                self.epoch.to_digest(),
+            ]
+            .into_iter()
+            .chain(self.elements.iter().map(fr_to_digest)),
+        )
+    }
+}
+
+impl CanonicalForm for DeleteProof {
+    fn is_canonical(&self) -> bool {
+        is_canonical_g1(&self.old_acc_value) && is_canonical_g1(&self.new_acc_value)
+    }
+}
+
+impl Statement for DeleteProof {
+    fn statement_digest(&self) -> Digest {
+        statement(
+            b"Delete",
+            [
+                self.old_acc_value.to_digest(),
+                self.new_acc_value.to_digest(),
+                fr_to_digest(&self.element.0),
+                self.epoch.to_digest(),
+            ],
+        )
+    }
+}
+
+impl CanonicalForm for MembershipProof {
+    fn is_canonical(&self) -> bool {
+        is_canonical_g1(&self.witness)
+    }
+}
+
+impl Statement for MembershipProof {
+    fn statement_digest(&self) -> Digest {
+        statement(
+            b"Membership",
+            [
+                fr_to_digest(&self.element.0),
+                self.epoch.to_digest(),
+                self.acc_digest,
+            ],
+        )
+    }
+}
+
+impl CanonicalForm for NonMembershipProof {
+    fn is_canonical(&self) -> bool {
+        is_canonical_g2(&self.witness) && is_canonical_g1(&self.g1_a)
+    }
+}
+
+impl Statement for NonMembershipProof {
+    fn statement_digest(&self) -> Digest {
+        statement(
+            b"NonMembership",
+            [
+                fr_to_digest(&self.element.0),
+                self.epoch.to_digest(),
+                self.acc_digest,
+            ],
+        )
+    }
+}
+
+impl CanonicalForm for IntersectionProof {
+    fn is_canonical(&self) -> bool {
+        is_canonical_g2(&self.witness_a)
+            && is_canonical_g2(&self.witness_b)
+            && is_canonical_g1(&self.witness_coprime_a)
+            && is_canonical_g1(&self.witness_coprime_b)
+    }
+}
+
+impl CanonicalForm for UnionProof {
+    fn is_canonical(&self) -> bool {
+        is_canonical_g1(&self.intersection_acc_value) && self.intersection_proof.is_canonical()
+    }
+}
+
+impl CanonicalForm for ConsistencyProof {
+    fn is_canonical(&self) -> bool {
+        is_canonical_g2(&self.added) && is_canonical_g2(&self.removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::{dynamic_accumulator::DynamicAccumulator, G2_POWER, PRI_S};
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_ff::{One, PrimeField};
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_canonical_form() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        let add = acc.add(&1).unwrap();
+        acc.add(&2).unwrap();
+        assert!(add.is_canonical() && add.verify());
+
+        // A point at infinity with stray coordinates is rejected.
+        let mut proof = acc.prove_membership(&1).unwrap();
+        assert!(proof.is_canonical());
+        proof.witness = G1Affine::new(proof.witness.x, proof.witness.y, true);
+        assert!(!proof.is_canonical());
+        assert!(!proof.verify(acc.acc_value));
+
+        // A re-randomized non-membership proof is canonical and valid, with the same statement.
+        let proof = acc.prove_non_membership(&3).unwrap();
+        let k = ark_bls12_381::Fr::one() + ark_bls12_381::Fr::one();
+        let mut malleated = proof.clone();
+        malleated.witness = (proof.witness.into_projective()
+            + G2_POWER.apply(&(k * (*PRI_S - proof.element.0))))
+        .into_affine();
+        malleated.g1_a =
+            (proof.g1_a.into_projective() - acc.acc_value.mul(k.into_repr())).into_affine();
+        assert_ne!(malleated, proof);
+        assert!(malleated.is_canonical() && malleated.verify(acc.acc_value));
+        assert_eq!(malleated.statement_digest(), proof.statement_digest());
+        assert_ne!(
+            proof.statement_digest(),
+            acc.prove_non_membership(&4).unwrap().statement_digest()
+        );
+    }
+}
@@ -0,0 +1,111 @@
+//! Which arithmetic backends this build uses.
+//!
+//! The speed of the provers and verifiers depends on cargo features of the arkworks crates:
+//! `parallel` for multi-threaded MSMs and FFTs, and `asm` for assembly field multiplication.
+//! This crate forwards them as its own `parallel` and `asm` features, both enabled by the
+//! default `fast-math` feature. [`capabilities`] reports what is in effect at runtime.
+//!
+//! The assembly is only compiled in when the build targets BMI2 and ADX, e.g. with
+//! `RUSTFLAGS="-C target-cpu=native"`, and arkworks 0.2 additionally needs a nightly compiler,
+//! which cannot be detected from here.
+//!
+//! Deployments that prefer auditability over speed build with
+//! `--no-default-features --features safe` instead. The `safe` feature forbids unsafe code in
+//! this crate and fails the build if `asm` is enabled too, leaving the portable Rust field
+//! arithmetic of arkworks, which also runs under MIRI. It can be combined with `parallel`.
+
+use core::fmt;
+
+/// The backends in effect, see [`capabilities`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Capabilities {
+    /// The `safe` feature is enabled, so `asm` is not.
+    pub safe: bool,
+    /// The `asm` feature is enabled.
+    pub asm: bool,
+    /// The build targets x86_64 with BMI2 and ADX, so enabled assembly is compiled in.
+    pub asm_target: bool,
+    /// The CPU running this supports BMI2 and ADX, so a build targeting them would run here.
+    pub cpu_bmi2_adx: bool,
+    /// The `parallel` feature is enabled.
+    pub parallel: bool,
+    /// The number of threads parallel operations run on.
+    pub threads: usize,
+}
+
+impl Capabilities {
+    /// Returns true if field multiplication uses assembly, assuming a nightly compiler.
+    pub fn asm_active(&self) -> bool {
+        self.asm && self.asm_target
+    }
+}
+
+/// Reports the backends of this build and what the CPU supports.
+pub fn capabilities() -> Capabilities {
+    Capabilities {
+        safe: cfg!(feature = "safe"),
+        asm: cfg!(feature = "asm"),
+        asm_target: cfg!(all(
+            target_arch = "x86_64",
+            target_feature = "bmi2",
+            target_feature = "adx"
+        )),
+        cpu_bmi2_adx: cpu_bmi2_adx(),
+        parallel: cfg!(feature = "parallel"),
+        threads: if cfg!(feature = "parallel") {
+            rayon::current_num_threads()
+        } else {
+            1
+        },
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn cpu_bmi2_adx() -> bool {
+    is_x86_feature_detected!("bmi2") && is_x86_feature_detected!("adx")
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn cpu_bmi2_adx() -> bool {
+    false
+}
+
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let on_off = |enabled: bool| if enabled { "enabled" } else { "disabled" };
+        if self.safe {
+            write!(f, "safe mode, ")?;
+        }
+        write!(f, "asm: {}", on_off(self.asm))?;
+        if self.asm && !self.asm_target {
+            if self.cpu_bmi2_adx {
+                write!(f, " (inactive, build with -C target-cpu=native)")?;
+            } else {
+                write!(f, " (inactive, the CPU lacks BMI2/ADX)")?;
+            }
+        }
+        write!(
+            f,
+            ", parallel: {} ({} threads)",
+            on_off(self.parallel),
+            self.threads
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities() {
+        let caps = capabilities();
+        assert_eq!(caps.safe, cfg!(feature = "safe"));
+        assert_eq!(caps.asm, cfg!(feature = "asm"));
+        assert!(!(caps.safe && caps.asm));
+        assert_eq!(caps.parallel, cfg!(feature = "parallel"));
+        assert!(caps.threads >= 1);
+        assert!(!caps.asm_target || caps.cpu_bmi2_adx);
+        assert!(caps.to_string().contains("asm: "));
+    }
+}
@@ -0,0 +1,175 @@
+//! Capacity planning against the degree of the public parameters.
+//!
+//! Public parameters are published up to [`MAX_DEGREE`], and the characteristic polynomial of
+//! the accumulated multiset must stay within it for degree-bound and public proofs to work. The
+//! degree is the number of accumulated occurrences, so [`DynamicAccumulator::polynomial_degree`]
+//! and [`DynamicAccumulator::headroom`] tell how close the accumulator is to the limit, and
+//! [`DynamicAccumulator::on_capacity_threshold`] calls back when the utilization first crosses
+//! one of the given fractions of the capacity, e.g. to plan a new setup or a shard split long
+//! before adds start to fail.
+
+use super::{dynamic_accumulator::DynamicAccumulator, MAX_DEGREE};
+use core::fmt;
+use std::sync::Arc;
+
+/// A crossing of a utilization threshold.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CapacityEvent {
+    /// The crossed fraction of the capacity.
+    pub threshold: f64,
+    pub degree: usize,
+    pub capacity: usize,
+    pub epoch: u64,
+}
+
+type Callback = Arc<dyn Fn(&CapacityEvent) + Send + Sync>;
+
+/// The thresholds and callback of an accumulator.
+///
+/// The watch is not part of the accumulator's identity: two watches always compare equal.
+#[derive(Clone, Default)]
+pub(crate) struct CapacityWatch {
+    thresholds: Vec<f64>,
+    callback: Option<Callback>,
+    /// The number of thresholds the utilization is at or above.
+    crossed: usize,
+}
+
+impl CapacityWatch {
+    pub(crate) fn is_active(&self) -> bool {
+        self.callback.is_some()
+    }
+
+    /// Calls back for every threshold crossed upwards since the last observation. Thresholds
+    /// fall back below as the degree drops, so crossing one again calls back again.
+    fn observe(&mut self, degree: usize, epoch: u64) {
+        let Some(callback) = &self.callback else {
+            return;
+        };
+        let utilization = degree as f64 / MAX_DEGREE as f64;
+        let crossed = self.thresholds.partition_point(|t| *t <= utilization);
+        for threshold in &self.thresholds[self.crossed.min(crossed)..crossed] {
+            callback(&CapacityEvent {
+                threshold: *threshold,
+                degree,
+                capacity: MAX_DEGREE,
+                epoch,
+            });
+        }
+        self.crossed = crossed;
+    }
+}
+
+impl PartialEq for CapacityWatch {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for CapacityWatch {}
+
+impl fmt::Debug for CapacityWatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CapacityWatch")
+            .field("thresholds", &self.thresholds)
+            .field("crossed", &self.crossed)
+            .finish()
+    }
+}
+
+impl DynamicAccumulator {
+    /// The largest polynomial degree, i.e. number of accumulated occurrences, the public
+    /// parameters support.
+    pub fn capacity(&self) -> usize {
+        MAX_DEGREE
+    }
+
+    /// The degree of the characteristic polynomial, which counts every occurrence of an element.
+    pub fn polynomial_degree(&self) -> usize {
+        self.len() + self.multiplicities.values().map(|c| c - 1).sum::<usize>()
+    }
+
+    /// How many more occurrences fit within the capacity.
+    pub fn headroom(&self) -> usize {
+        self.capacity().saturating_sub(self.polynomial_degree())
+    }
+
+    /// The degree as a fraction of the capacity.
+    pub fn utilization(&self) -> f64 {
+        self.polynomial_degree() as f64 / self.capacity() as f64
+    }
+
+    /// Calls `callback` whenever the utilization crosses one of `thresholds`, fractions of the
+    /// capacity, upwards. Thresholds the utilization is already at or above count as crossed.
+    /// Replaces an earlier callback.
+    pub fn on_capacity_threshold(
+        &mut self,
+        thresholds: &[f64],
+        callback: impl Fn(&CapacityEvent) + Send + Sync + 'static,
+    ) {
+        let mut thresholds = thresholds.to_vec();
+        thresholds.sort_by(f64::total_cmp);
+        let utilization = self.utilization();
+        self.capacity_watch = CapacityWatch {
+            crossed: thresholds.partition_point(|t| *t <= utilization),
+            thresholds,
+            callback: Some(Arc::new(callback)),
+        };
+    }
+
+    /// Removes the threshold callback.
+    pub fn clear_capacity_threshold(&mut self) {
+        self.capacity_watch = CapacityWatch::default();
+    }
+
+    /// Reports the current degree to the threshold callback, if any.
+    pub(crate) fn observe_capacity(&mut self) {
+        if self.capacity_watch.is_active() {
+            let (degree, epoch) = (self.polynomial_degree(), self.epoch());
+            self.capacity_watch.observe(degree, epoch);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::dynamic_accumulator::DuplicatePolicy;
+    use std::sync::Mutex;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_capacity() {
+        init_logger();
+        let mut acc =
+            DynamicAccumulator::with_duplicate_policy(DuplicatePolicy::IncrementMultiplicity);
+        acc.add_batch(&[1, 2, 3]).unwrap();
+        acc.add(&3).unwrap();
+        assert_eq!((acc.len(), acc.polynomial_degree()), (3, 4));
+        assert_eq!(acc.capacity(), MAX_DEGREE);
+        assert_eq!(acc.headroom(), MAX_DEGREE - 4);
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let (low, high) = (5.0 / MAX_DEGREE as f64, 8.0 / MAX_DEGREE as f64);
+        acc.on_capacity_threshold(&[high, low], move |e| sink.lock().unwrap().push(*e));
+        acc.add(&4).unwrap();
+        acc.add_batch(&[5, 6, 7]).unwrap();
+        let crossed: Vec<_> = events.lock().unwrap().iter().map(|e| e.degree).collect();
+        assert_eq!(crossed, [5, 8]);
+
+        // Dropping below a threshold and crossing it again calls back again.
+        acc.delete(&7).unwrap();
+        acc.add(&9).unwrap();
+        assert_eq!(events.lock().unwrap().len(), 3);
+        assert_eq!(events.lock().unwrap()[2].threshold, high);
+
+        acc.clear_capacity_threshold();
+        acc.delete(&9).unwrap();
+        acc.add(&10).unwrap();
+        assert_eq!(events.lock().unwrap().len(), 3);
+    }
+}
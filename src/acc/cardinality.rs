@@ -0,0 +1,154 @@
+//! Cardinality proofs, e.g. count-distinct across two accumulators.
+//!
+//! The size of a set equals the degree of its monic characteristic polynomial P(X). A degree
+//! proof shows that acc = g1^(s^n + R(s)) with deg R < n by committing to R shifted up to the
+//! maximum supported degree: g1^(s^(MAX_DEGREE - n + 1) * R(s)). No prover holding only the
+//! public powers up to `MAX_DEGREE` can produce that commitment if deg R >= n.
+
+use super::{
+    commit_g1,
+    commitment::params_id,
+    dynamic_accumulator::{DynamicAccumulator, UnionProof},
+    get_g1s, get_g2s, polymath, Curve, Fr, G1Affine, G2Affine, MAX_DEGREE,
+};
+use crate::digest::Digest;
+use anyhow::{bail, Result};
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::Zero;
+use ark_poly::{univariate::DensePolynomial, UVPolynomial};
+
+/// A proof that an accumulator commits to a set of exactly `n` distinct elements.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DegreeProof {
+    pub n: usize,
+    /// g1^(s^(MAX_DEGREE - n + 1) * R(s)) where P(X) = X^n + R(X).
+    pub shifted: G1Affine,
+    /// The identifier of the public parameters the proof was generated under, see
+    /// [`params_id`].
+    pub params_id: Digest,
+}
+
+impl DegreeProof {
+    /// Proves the degree of the characteristic polynomial of `acc`.
+    pub fn new(acc: &DynamicAccumulator) -> Result<Self> {
+        Self::from_elements(acc.elements_with_multiplicity().iter())
+    }
+
+    /// Proves the degree of the characteristic polynomial of the given elements.
+    pub(crate) fn from_elements<'a>(
+        elements: impl ExactSizeIterator<Item = &'a Fr>,
+    ) -> Result<Self> {
+        let n = elements.len();
+        if n > MAX_DEGREE {
+            bail!("set size {} exceeds the maximum degree {}", n, MAX_DEGREE);
+        }
+        let roots: Vec<Fr> = elements.copied().collect();
+        // The coefficients of R are those of P below X^n, shifted up by MAX_DEGREE - n + 1.
+        let mut coeffs = vec![Fr::zero(); MAX_DEGREE - n + 1];
+        coeffs.extend_from_slice(&polymath::from_roots(&roots).coeffs[..n]);
+        Ok(Self {
+            n,
+            shifted: commit_g1(DensePolynomial::from_coefficients_vec(coeffs))?,
+            params_id: params_id(),
+        })
+    }
+
+    /// Verifies that `acc_value` commits to a monic polynomial of degree exactly `self.n`.
+    /// It checks if e(acc / g1^(s^n), g2^(s^(MAX_DEGREE - n + 1))) == e(shifted, g2).
+    pub fn verify(&self, acc_value: G1Affine) -> bool {
+        if self.n > MAX_DEGREE || self.params_id != params_id() {
+            return false;
+        }
+        let r = acc_value.into_projective() - get_g1s(Fr::from(self.n as u64)).into_projective();
+        let g2_shift = get_g2s(Fr::from((MAX_DEGREE - self.n + 1) as u64));
+
+        let lhs = Curve::pairing(r.into_affine(), g2_shift);
+        let rhs = Curve::pairing(self.shifted, G2Affine::prime_subgroup_generator());
+        lhs == rhs
+    }
+}
+
+/// A proof that |A ∪ B| = n for two accumulators A and B, revealing neither set.
+#[derive(Debug, Clone)]
+pub struct UnionCardinalityProof {
+    pub union_acc_value: G1Affine,
+    pub union_proof: UnionProof,
+    pub degree_proof: DegreeProof,
+}
+
+impl UnionCardinalityProof {
+    /// The attested number of distinct elements.
+    pub fn cardinality(&self) -> usize {
+        self.degree_proof.n
+    }
+
+    /// Verifies the union proof and the degree of the union accumulator.
+    pub fn verify(&self, acc1_value: G1Affine, acc2_value: G1Affine) -> bool {
+        DynamicAccumulator::verify_union(
+            acc1_value,
+            acc2_value,
+            self.union_acc_value,
+            &self.union_proof,
+        ) && self.degree_proof.verify(self.union_acc_value)
+    }
+}
+
+impl DynamicAccumulator {
+    /// Proves that the union of this accumulator and `other` has exactly `n` distinct elements.
+    /// Returns an error if the actual union size differs from `n`.
+    pub fn prove_union_cardinality(
+        &self,
+        other: &DynamicAccumulator,
+        n: usize,
+    ) -> Result<UnionCardinalityProof> {
+        let (union_acc, union_proof) = self.prove_union(other)?;
+        if union_acc.len() != n {
+            bail!("union has {} elements, not {}", union_acc.len(), n);
+        }
+        Ok(UnionCardinalityProof {
+            union_acc_value: union_acc.acc_value,
+            union_proof,
+            degree_proof: DegreeProof::new(&union_acc)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_degree_proof() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        assert!(DegreeProof::new(&acc).unwrap().verify(acc.acc_value));
+
+        acc.add_batch(&[1, 2, 3]).unwrap();
+        let proof = DegreeProof::new(&acc).unwrap();
+        assert_eq!(proof.n, 3);
+        assert!(proof.verify(acc.acc_value));
+
+        let mut wrong = proof.clone();
+        wrong.n = 2;
+        assert!(!wrong.verify(acc.acc_value));
+    }
+
+    #[test]
+    fn test_union_cardinality() {
+        init_logger();
+        let mut acc1 = DynamicAccumulator::new();
+        acc1.add_batch(&[100, 200, 300]).unwrap();
+        let mut acc2 = DynamicAccumulator::new();
+        acc2.add_batch(&[200, 300, 400, 500]).unwrap();
+
+        assert!(acc1.prove_union_cardinality(&acc2, 4).is_err());
+        let proof = acc1.prove_union_cardinality(&acc2, 5).unwrap();
+        assert_eq!(proof.cardinality(), 5);
+        assert!(proof.verify(acc1.acc_value, acc2.acc_value));
+        assert!(!proof.verify(acc2.acc_value, acc2.acc_value));
+    }
+}
@@ -0,0 +1,217 @@
+//! Comparing local state with published checkpoints.
+//!
+//! A manager that replays its own operations, or a replica, can check that it still agrees with
+//! the signed states it published. On a mismatch the [`Divergence`] report narrows down where the
+//! histories split: [`TombstoneAccumulator`] keeps the value of every epoch, so checking several
+//! checkpoints finds the last one that agreed and the first one that did not.
+//!
+//! Signatures are not checked here; verify checkpoints with [`SignedState::verify`] first.
+
+use super::{
+    dynamic_accumulator::DynamicAccumulator, signing::SignedState, tombstone::TombstoneAccumulator,
+    G1Affine,
+};
+use crate::digest::{Digest, Digestible};
+use core::fmt;
+
+/// How local state disagrees with a published checkpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Divergence {
+    /// The local accumulator has not reached the checkpoint's epoch.
+    Behind {
+        local_epoch: u64,
+        checkpoint_epoch: u64,
+    },
+    /// The local accumulator is past the checkpoint's epoch and no longer knows its value then.
+    NoHistory {
+        local_epoch: u64,
+        checkpoint_epoch: u64,
+    },
+    /// The local value at `epoch` differs from the published one. The histories split after
+    /// `last_agreed_epoch`, if any checkpoint agreed, and at or before `epoch`.
+    Mismatch {
+        epoch: u64,
+        /// The digest of the local value.
+        local: Digest,
+        /// The digest of the published value.
+        published: Digest,
+        last_agreed_epoch: Option<u64>,
+    },
+}
+
+impl Divergence {
+    /// The first checkpoint epoch known to disagree.
+    pub fn epoch(&self) -> u64 {
+        match self {
+            Divergence::Behind {
+                checkpoint_epoch, ..
+            }
+            | Divergence::NoHistory {
+                checkpoint_epoch, ..
+            } => *checkpoint_epoch,
+            Divergence::Mismatch { epoch, .. } => *epoch,
+        }
+    }
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Divergence::Behind {
+                local_epoch,
+                checkpoint_epoch,
+            } => write!(
+                f,
+                "local state at epoch {} is behind checkpoint epoch {}",
+                local_epoch, checkpoint_epoch
+            ),
+            Divergence::NoHistory {
+                local_epoch,
+                checkpoint_epoch,
+            } => write!(
+                f,
+                "local state at epoch {} has no value for checkpoint epoch {}",
+                local_epoch, checkpoint_epoch
+            ),
+            Divergence::Mismatch {
+                epoch,
+                last_agreed_epoch: Some(agreed),
+                ..
+            } => write!(
+                f,
+                "local value diverges at epoch {}, after agreeing at epoch {}",
+                epoch, agreed
+            ),
+            Divergence::Mismatch { epoch, .. } => {
+                write!(f, "local value diverges at epoch {}", epoch)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Divergence {}
+
+fn compare(
+    local_epoch: u64,
+    value_at: impl Fn(u64) -> Option<G1Affine>,
+    checkpoint: &SignedState,
+    last_agreed_epoch: Option<u64>,
+) -> Result<(), Divergence> {
+    if local_epoch < checkpoint.epoch {
+        return Err(Divergence::Behind {
+            local_epoch,
+            checkpoint_epoch: checkpoint.epoch,
+        });
+    }
+    let local = value_at(checkpoint.epoch).ok_or(Divergence::NoHistory {
+        local_epoch,
+        checkpoint_epoch: checkpoint.epoch,
+    })?;
+    if local != checkpoint.acc_value {
+        return Err(Divergence::Mismatch {
+            epoch: checkpoint.epoch,
+            local: local.to_digest(),
+            published: checkpoint.acc_value.to_digest(),
+            last_agreed_epoch,
+        });
+    }
+    Ok(())
+}
+
+impl DynamicAccumulator {
+    /// Compares the current state with a published checkpoint. Without a history of past
+    /// values, only a checkpoint of the current epoch can be confirmed.
+    pub fn verify_against_checkpoint(&self, checkpoint: &SignedState) -> Result<(), Divergence> {
+        let epoch = self.epoch();
+        compare(
+            epoch,
+            |e| (e == epoch).then_some(self.acc_value),
+            checkpoint,
+            None,
+        )
+    }
+}
+
+impl TombstoneAccumulator {
+    /// Compares the value of the checkpoint's epoch with the published one.
+    pub fn verify_against_checkpoint(&self, checkpoint: &SignedState) -> Result<(), Divergence> {
+        self.verify_against_checkpoints(core::slice::from_ref(checkpoint))
+    }
+
+    /// Compares the local history with several checkpoints, in epoch order, and reports the
+    /// earliest disagreement together with the last checkpoint that agreed.
+    pub fn verify_against_checkpoints(
+        &self,
+        checkpoints: &[SignedState],
+    ) -> Result<(), Divergence> {
+        let mut sorted: Vec<&SignedState> = checkpoints.iter().collect();
+        sorted.sort_by_key(|c| c.epoch);
+        let mut last_agreed_epoch = None;
+        for checkpoint in sorted {
+            compare(
+                self.acc().epoch(),
+                |e| self.value_at(e),
+                checkpoint,
+                last_agreed_epoch,
+            )?;
+            last_agreed_epoch = Some(checkpoint.epoch);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::signing::SigningKey;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_verify_against_checkpoint() {
+        init_logger();
+        let key = SigningKey::from_seed(b"manager");
+        let mut published = TombstoneAccumulator::new();
+        let mut replica = TombstoneAccumulator::new();
+        let mut checkpoints = Vec::new();
+        for (i, x) in [1, 2, 3, 4].iter().enumerate() {
+            published.add(x).unwrap();
+            // The replica misses the third element and applies another one instead.
+            replica.add(if i == 2 { &5 } else { x }).unwrap();
+            checkpoints.push(published.acc().sign_state(&key));
+        }
+
+        assert!(published.verify_against_checkpoints(&checkpoints).is_ok());
+        assert!(replica.verify_against_checkpoint(&checkpoints[1]).is_ok());
+        let divergence = replica
+            .verify_against_checkpoints(&checkpoints)
+            .unwrap_err();
+        assert_eq!(divergence.epoch(), 3);
+        assert!(matches!(
+            divergence,
+            Divergence::Mismatch {
+                last_agreed_epoch: Some(2),
+                ..
+            }
+        ));
+
+        let acc = published.acc();
+        assert!(acc.verify_against_checkpoint(&checkpoints[3]).is_ok());
+        assert!(matches!(
+            acc.verify_against_checkpoint(&checkpoints[0]),
+            Err(Divergence::NoHistory { .. })
+        ));
+        let mut ahead = published.clone();
+        ahead.add(&6).unwrap();
+        let state = ahead.acc().sign_state(&key);
+        assert_eq!(
+            published.verify_against_checkpoint(&state),
+            Err(Divergence::Behind {
+                local_epoch: 4,
+                checkpoint_epoch: 5
+            })
+        );
+    }
+}
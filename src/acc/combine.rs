@@ -0,0 +1,208 @@
+//! A joint commitment to the disjoint union of several accumulated sets.
+//!
+//! The combined value is g1^(P_1(s) * ... * P_k(s)), which is the accumulator value of the
+//! union when the sets are pairwise disjoint. The proof consists of
+//!
+//! - every party's value in G2, linked to its G1 value by e(A_i, g2) == e(g1, B_i);
+//! - the running products C_1 = A_1, ..., C_k, each step checked by
+//!   e(C_i, g2) == e(C_(i-1), B_i), with C_k the combined value;
+//! - for every pair i < j, Bézout coefficients a*P_i + b*P_j = 1 committed in G2, checked by
+//!   e(A_i, g2^a(s)) * e(A_j, g2^b(s)) == e(g1, g2).
+//!
+//! Only the combiner needs the parties' elements; the published commitment and its verification
+//! use their accumulator values alone.
+
+use super::{
+    commit_g2,
+    commitment::params_id,
+    dynamic_accumulator::{ark_serde, DynamicAccumulator},
+    polymath,
+    prepared::PreparedPublicParams,
+    trapdoor, Curve, G1Affine, G2Affine,
+};
+use crate::digest::Digest;
+use anyhow::{anyhow, bail, Result};
+use ark_ec::{AffineCurve, PairingEngine};
+use ark_ff::One;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+type G1Prepared = <Curve as PairingEngine>::G1Prepared;
+type G2Prepared = <Curve as PairingEngine>::G2Prepared;
+
+/// A proof that two accumulated sets are disjoint: g2^a(s) and g2^b(s) with
+/// a(X)P_i(X) + b(X)P_j(X) = 1.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisjointnessProof {
+    #[serde(with = "ark_serde")]
+    pub coeff_a: G2Affine,
+    #[serde(with = "ark_serde")]
+    pub coeff_b: G2Affine,
+}
+
+impl DisjointnessProof {
+    pub fn verify(&self, acc_a: G1Affine, acc_b: G1Affine) -> bool {
+        let pairs: [(G1Prepared, G2Prepared); 2] = [
+            (acc_a.into(), self.coeff_a.into()),
+            (acc_b.into(), self.coeff_b.into()),
+        ];
+        Curve::product_of_pairings(&pairs) == PreparedPublicParams::global().e_g1_g2()
+    }
+}
+
+/// A commitment to the disjoint union of several accumulated sets, with its proof.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CombinedCommitment {
+    /// g1^(P_1(s) * ... * P_k(s)).
+    #[serde(with = "ark_serde")]
+    pub value: G1Affine,
+    /// Every party's accumulator value in G2.
+    #[serde(with = "ark_serde")]
+    pub parts_g2: Vec<G2Affine>,
+    /// The running products C_2, ..., C_(k-1); C_1 is the first party's value and C_k is
+    /// `value`.
+    #[serde(with = "ark_serde")]
+    pub partial_products: Vec<G1Affine>,
+    /// One proof per pair i < j, in the order (0, 1), (0, 2), ..., (1, 2), ...
+    pub disjointness: Vec<DisjointnessProof>,
+    /// The identifier of the public parameters the commitment was generated under, see
+    /// [`params_id`].
+    pub params_id: Digest,
+}
+
+fn pairs(k: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..k).flat_map(move |i| (i + 1..k).map(move |j| (i, j)))
+}
+
+impl CombinedCommitment {
+    /// Verifies the commitment against the parties' accumulator values, in the order they were
+    /// combined.
+    pub fn verify(&self, parts: &[G1Affine]) -> bool {
+        let k = parts.len();
+        if k == 0
+            || self.params_id != params_id()
+            || self.parts_g2.len() != k
+            || self.partial_products.len() != k.saturating_sub(2)
+            || self.disjointness.len() != k * (k - 1) / 2
+        {
+            return false;
+        }
+        let g1 = G1Affine::prime_subgroup_generator();
+        let g2 = G2Affine::prime_subgroup_generator();
+        let same = |a: G1Affine, b: G2Affine, c: G1Affine, d: G2Affine| {
+            let pairs: [(G1Prepared, G2Prepared); 2] =
+                [(a.into(), b.into()), ((-c).into(), d.into())];
+            Curve::product_of_pairings(&pairs).is_one()
+        };
+        let linked = parts
+            .par_iter()
+            .zip(&self.parts_g2)
+            .all(|(a, b)| same(*a, g2, g1, *b));
+        let running: Vec<G1Affine> = core::iter::once(parts[0])
+            .chain(self.partial_products.iter().copied())
+            .chain((k > 1).then_some(self.value))
+            .collect();
+        let chained = running[running.len() - 1] == self.value
+            && (1..k)
+                .into_par_iter()
+                .all(|i| same(running[i], g2, running[i - 1], self.parts_g2[i]));
+        let disjoint = pairs(k)
+            .zip(&self.disjointness)
+            .collect::<Vec<_>>()
+            .par_iter()
+            .all(|((i, j), proof)| proof.verify(parts[*i], parts[*j]));
+        linked && chained && disjoint
+    }
+}
+
+impl DynamicAccumulator {
+    /// Commits to the union of pairwise disjoint accumulated sets. Returns an error if there are
+    /// no sets or two of them share an element.
+    pub fn combine(accs: &[&DynamicAccumulator]) -> Result<CombinedCommitment> {
+        if accs.is_empty() {
+            bail!("No accumulators to combine");
+        }
+        for acc in accs {
+            acc.ensure_elements()?;
+        }
+        let polys: Vec<_> = accs
+            .par_iter()
+            .map(|acc| acc.characteristic_poly())
+            .collect();
+        let disjointness = pairs(accs.len())
+            .collect::<Vec<_>>()
+            .par_iter()
+            .map(|&(i, j)| {
+                let (a, b) = polymath::bezout_coprime(&polys[i], &polys[j])
+                    .ok_or_else(|| anyhow!("Accumulators {} and {} are not disjoint", i, j))?;
+                Ok(DisjointnessProof {
+                    coeff_a: commit_g2(a)?,
+                    coeff_b: commit_g2(b)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let parts_g2 = polys
+            .into_par_iter()
+            .map(commit_g2)
+            .collect::<Result<Vec<_>>>()?;
+        let signer = trapdoor::signer();
+        let mut running = Vec::with_capacity(accs.len());
+        let mut value = accs[0].acc_value;
+        for acc in accs.iter().skip(1) {
+            value = signer.mul_s_minus(value, &acc.elements_with_multiplicity())?;
+            running.push(value);
+        }
+        running.pop();
+        Ok(CombinedCommitment {
+            value,
+            parts_g2,
+            partial_products: running,
+            disjointness,
+            params_id: params_id(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_combine() {
+        init_logger();
+        let parties: Vec<DynamicAccumulator> = [vec![1, 2], vec![3], vec![4, 5, 6]]
+            .iter()
+            .map(|elements| {
+                let mut acc = DynamicAccumulator::new();
+                acc.add_batch(elements).unwrap();
+                acc
+            })
+            .collect();
+        let refs: Vec<&DynamicAccumulator> = parties.iter().collect();
+        let values: Vec<G1Affine> = parties.iter().map(|acc| acc.acc_value).collect();
+
+        let combined = DynamicAccumulator::combine(&refs).unwrap();
+        let mut union = DynamicAccumulator::new();
+        union.add_batch(&[1, 2, 3, 4, 5, 6]).unwrap();
+        assert_eq!(combined.value, union.acc_value);
+        assert!(combined.verify(&values));
+        assert!(!combined.verify(&values[..2]));
+        let mut reordered = values.clone();
+        reordered.swap(0, 2);
+        assert!(!combined.verify(&reordered));
+
+        let single = DynamicAccumulator::combine(&refs[..1]).unwrap();
+        assert!(single.verify(&values[..1]));
+
+        let mut overlapping = DynamicAccumulator::new();
+        overlapping.add_batch(&[6, 7]).unwrap();
+        assert!(DynamicAccumulator::combine(&[refs[0], &overlapping]).is_ok());
+        assert!(DynamicAccumulator::combine(&[refs[2], &overlapping]).is_err());
+        assert!(DynamicAccumulator::combine(&[]).is_err());
+    }
+}
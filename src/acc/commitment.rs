@@ -0,0 +1,83 @@
+//! Short commitments to accumulator states.
+//!
+//! [`DynamicAccumulator::digest`] hashes the accumulator value, the epoch and the identifier of
+//! the public parameters into one [`Digest`]. Since [`DynamicAccumulator`] implements
+//! [`Digestible`] with it, a state can be signed, logged, or accumulated as an element of another
+//! accumulator like any other value. Verifiers that only know a published value and epoch get the
+//! same digest from [`state_digest`].
+
+use super::{dynamic_accumulator::DynamicAccumulator, g1_s, G1Affine};
+use crate::digest::{concat_digest, Digest, Digestible};
+
+lazy_static! {
+    static ref PARAMS_ID: Digest = params_id_of(&g1_s());
+}
+
+/// Identifies the public parameters by a digest of g1^s, which any verifier can compute from the
+/// published parameters.
+pub fn params_id() -> Digest {
+    *PARAMS_ID
+}
+
+/// The identifier of the parameters with g1^s = `g1_s`.
+pub(crate) fn params_id_of(g1_s: &G1Affine) -> Digest {
+    concat_digest([b"ESA_RUST_PARAMS"[..].to_digest(), g1_s.to_digest()].into_iter())
+}
+
+/// The commitment to the state with the given value and epoch under the current parameters.
+pub fn state_digest(acc_value: &G1Affine, epoch: u64) -> Digest {
+    concat_digest(
+        [
+            b"ESA_RUST_STATE"[..].to_digest(),
+            params_id(),
+            acc_value.to_digest(),
+            epoch.to_digest(),
+        ]
+        .into_iter(),
+    )
+}
+
+impl DynamicAccumulator {
+    /// A short commitment to the accumulator value, the epoch and the parameters.
+    pub fn digest(&self) -> Digest {
+        state_digest(&self.acc_value, self.epoch())
+    }
+}
+
+impl Digestible for DynamicAccumulator {
+    fn to_digest(&self) -> Digest {
+        self.digest()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::utils::digest_to_prime_field;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_state_digest() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        acc.add(&1).unwrap();
+        let first = acc.digest();
+        assert_eq!(first, state_digest(&acc.acc_value, acc.epoch()));
+        assert_eq!(first, acc.to_digest());
+
+        // The same value at a later epoch is a different state.
+        acc.add(&2).unwrap();
+        acc.delete(&2).unwrap();
+        assert_ne!(acc.digest(), first);
+        assert_eq!(acc.digest(), state_digest(&acc.acc_value, acc.epoch()));
+
+        // States can be accumulated themselves.
+        let element = digest_to_prime_field(&acc.to_digest());
+        let mut states = DynamicAccumulator::new();
+        states.add_encoded(element).unwrap();
+        assert!(states.prove_membership_encoded(element).is_ok());
+    }
+}
@@ -0,0 +1,161 @@
+//! Conformance vectors for the element-to-field mapping.
+//!
+//! An `i64` element is accumulated as `digest_to_prime_field(&x.to_digest())`, where the digest
+//! is BLAKE2b-256 of the little-endian bytes of `x`. Other implementations must reproduce this
+//! mapping bit for bit. [`reduce_digest`] states the reduction with byte arithmetic alone, and
+//! [`VECTORS`] pins inputs to their digests and field elements; [`check_vectors`] checks both
+//! against this crate.
+
+use super::{
+    utils::{digest_to_prime_field, fr_from_bytes, fr_to_bytes, Endianness},
+    Fr,
+};
+use crate::digest::{Digest, Digestible, DIGEST_LEN};
+use anyhow::{ensure, Result};
+
+/// The order of `Fr`, big-endian.
+pub const FR_MODULUS_BE: [u8; 32] = [
+    0x73, 0xed, 0xa7, 0x53, 0x29, 0x9d, 0x7d, 0x48, 0x33, 0x39, 0xd8, 0x08, 0x09, 0xa1, 0xd8, 0x05,
+    0x53, 0xbd, 0xa4, 0x02, 0xff, 0xfe, 0x5b, 0xfe, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x01,
+];
+
+/// One input with its digest and field element, both as big-endian hex.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ConformanceVector {
+    pub input: i64,
+    pub digest: &'static str,
+    pub field_element: &'static str,
+}
+
+const fn vector(
+    input: i64,
+    digest: &'static str,
+    field_element: &'static str,
+) -> ConformanceVector {
+    ConformanceVector {
+        input,
+        digest,
+        field_element,
+    }
+}
+
+pub const VECTORS: &[ConformanceVector] = &[
+    vector(
+        0,
+        "81e47a19e6b29b0a65b9591762ce5143ed30d0261e5d24a3201752506b20f15c",
+        "0000000000000000327f810f592c793e99732c231e5ec8a4201752516b20f15b",
+    ),
+    vector(
+        1,
+        "1dbd7d0b561a41d23c2a469ad42fbd70d5438bae826f6fd607413190c37c363b",
+        "00000000000000003c2a469ad42fbd70d5438bae826f6fd607413190c37c363b",
+    ),
+    vector(
+        -1,
+        "e2d93df6a2e919e879551686bc301480fc50c54dc949b14b916d5834113bb061",
+        "0000000000000000461b3e7eb28e3c7ba893214ac94b554c916d5835113bb060",
+    ),
+    vector(
+        2,
+        "e88b43fded6323ef02ffeffbd8c40846ee09bf316271bd22369659c959dd733a",
+        "00000000000000009c8c3febc580583c468e772b62750524369659cb59dd7338",
+    ),
+    vector(
+        42,
+        "5a1f0afdd3d6ee56b265373709961343fe0645c9542b1a6f17539ff60db66fd1",
+        "0000000000000000b265373709961343fe0645c9542b1a6f17539ff60db66fd1",
+    ),
+    vector(
+        1000,
+        "3bd11b81d77a0159db51f49c523b875e507d97c9c15f757239e41fa8ed56483d",
+        "0000000000000000db51f49c523b875e507d97c9c15f757239e41fa8ed56483d",
+    ),
+    vector(
+        -123456789,
+        "433a5837630fb5bb5b249db02858c270147a88195a271715b1411601666a0ede",
+        "00000000000000005b249db02858c270147a88195a271715b1411601666a0ede",
+    ),
+    vector(
+        i64::MAX,
+        "129abb914b6a08a9d77c05c05cdf55e295a19eb0dbf8769a1913059fccc3fd7c",
+        "0000000000000000d77c05c05cdf55e295a19eb0dbf8769a1913059fccc3fd7c",
+    ),
+    vector(
+        i64::MIN,
+        "40a401f3c0af4883f77eb37553351d1418e67090e4b1c5fd1256baf8d6033ff1",
+        "0000000000000000f77eb37553351d1418e67090e4b1c5fd1256baf8d6033ff1",
+    ),
+];
+
+/// The reduction of `digest_to_prime_field`, on big-endian bytes:
+///
+/// 1. read the digest as a 256-bit big-endian integer `x`;
+/// 2. while `x >= r`, set `x = x - r` (at most twice, as 2^256 < 3r);
+/// 3. clear the top 64 bits, keeping `x mod 2^192`.
+///
+/// Returns the field element, big-endian.
+pub fn reduce_digest(digest: &[u8; DIGEST_LEN]) -> [u8; 32] {
+    let mut x = *digest;
+    while x >= FR_MODULUS_BE {
+        let mut borrow = 0u16;
+        for i in (0..32).rev() {
+            let diff = 0x100 + x[i] as u16 - FR_MODULUS_BE[i] as u16 - borrow;
+            x[i] = diff as u8;
+            borrow = 1 - (diff >> 8);
+        }
+    }
+    x[..8].fill(0);
+    x
+}
+
+/// Checks every vector against the digest, [`reduce_digest`] and `digest_to_prime_field`.
+pub fn check_vectors() -> Result<()> {
+    for v in VECTORS {
+        let digest = v.input.to_digest();
+        ensure!(
+            hex::encode(digest.0) == v.digest,
+            "Digest of {} mismatched",
+            v.input
+        );
+        ensure!(
+            hex::encode(reduce_digest(&digest.0)) == v.field_element,
+            "Reduction of {} mismatched",
+            v.input
+        );
+        let fr: Fr = digest_to_prime_field(&digest);
+        ensure!(
+            hex::encode(fr_to_bytes(&fr, Endianness::Big)) == v.field_element,
+            "Field element of {} mismatched",
+            v.input
+        );
+    }
+    Ok(())
+}
+
+/// Maps a digest with the reference reduction, as a field element.
+pub fn reference_field_element(digest: &Digest) -> Fr {
+    fr_from_bytes(&reduce_digest(&digest.0), Endianness::Big)
+        .expect("reduced digests are below the modulus")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::{BigInteger, FpParameters, PrimeField};
+
+    #[test]
+    fn test_conformance_vectors() {
+        check_vectors().unwrap();
+        assert_eq!(
+            FR_MODULUS_BE.to_vec(),
+            <Fr as PrimeField>::Params::MODULUS.to_bytes_be()
+        );
+        for digest in [[0u8; 32], [0xff; 32], FR_MODULUS_BE] {
+            let digest = Digest(digest);
+            assert_eq!(
+                reference_field_element(&digest),
+                digest_to_prime_field::<Fr>(&digest)
+            );
+        }
+    }
+}
@@ -0,0 +1,295 @@
+//! Succinct consistency proofs between two epochs of an update log.
+//!
+//! The updates between epochs a and b change the accumulator by the net added elements A and
+//! removed elements R. A [`ConsistencyProof`] carries g2^A(s) and g2^R(s) for them, so a light
+//! client checks e(acc_b, g2^R(s)) == e(acc_a, g2^A(s)) with two pairings instead of two per
+//! update.
+//!
+//! [`ConsistencyProof::verify`] alone only shows that some net change leads from one value to
+//! the other. [`ConsistencyProof::verify_segment`] takes the published log segment, recomputes
+//! g2^A(s) and g2^R(s) from its net changes with one MSM each over the public powers, and so
+//! shows that exactly those adds and deletes lead there.
+//!
+//! A mirror can also skip verifying the individual updates and jump to a later state with
+//! [`DynamicAccumulator::adopt_state`].
+
+use super::{
+    canonical::CanonicalForm,
+    commitment::params_id,
+    dynamic_accumulator::{ark_serde, DynamicAccumulator},
+    metrics, polymath,
+    update::Update,
+    update::UpdateLog,
+    Acc1, Curve, Fr, G1Affine, G2Affine,
+};
+use crate::digest::{Digest, Digestible};
+use anyhow::{bail, Result};
+use ark_ec::PairingEngine;
+use ark_ff::Zero;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A proof that the accumulator at `to_epoch` is reachable from `from_epoch` by the updates
+/// whose digest is `updates_digest`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsistencyProof {
+    pub from_epoch: u64,
+    pub to_epoch: u64,
+    /// The digest of the [`UpdateLog`] holding exactly the updates between the two epochs.
+    pub updates_digest: Digest,
+    /// g2 raised to the product of (s - x) over the net added elements.
+    #[serde(with = "ark_serde")]
+    pub added: G2Affine,
+    /// g2 raised to the product of (s - x) over the net removed elements.
+    #[serde(with = "ark_serde")]
+    pub removed: G2Affine,
+    /// The identifier of the public parameters the proof was generated under, see
+    /// [`params_id`].
+    pub params_id: Digest,
+}
+
+impl ConsistencyProof {
+    /// Verifies that `to_value` follows from `from_value` by the net changes of the proof. This
+    /// does not say which changes those are, see [`Self::verify_segment`].
+    pub fn verify(&self, from_value: G1Affine, to_value: G1Affine) -> bool {
+        // g2^0 would satisfy the equation for any two values.
+        if self.from_epoch > self.to_epoch
+            || self.params_id != params_id()
+            || !self.is_canonical()
+            || self.added.is_zero()
+            || self.removed.is_zero()
+        {
+            return false;
+        }
+        let lhs = Curve::pairing(to_value, self.removed);
+        let rhs = Curve::pairing(from_value, self.added);
+        metrics::record_pairings(2);
+        lhs == rhs
+    }
+
+    /// Same as [`ConsistencyProof::verify`], also checking that the proof covers exactly the
+    /// published `segment` and that its net changes are the ones of the segment's updates.
+    pub fn verify_segment(
+        &self,
+        from_value: G1Affine,
+        to_value: G1Affine,
+        segment: &UpdateLog,
+    ) -> bool {
+        if segment.start_epoch != self.from_epoch
+            || segment.end_epoch() != self.to_epoch
+            || segment.to_digest() != self.updates_digest
+        {
+            return false;
+        }
+        let (added, removed) = net_commitments(segment);
+        added == self.added && removed == self.removed && self.verify(from_value, to_value)
+    }
+}
+
+/// Commits to the net added and removed elements of `segment` with the public powers; an
+/// element added and later deleted cancels out.
+fn net_commitments(segment: &UpdateLog) -> (G2Affine, G2Affine) {
+    let mut net: HashMap<Fr, i64> = HashMap::new();
+    for update in &segment.updates {
+        let delta = match update {
+            Update::Add(proof) if proof.is_noop() => 0,
+            Update::Add(_) => 1,
+            Update::Delete(_) => -1,
+        };
+        *net.entry(update.element().0).or_insert(0) += delta;
+    }
+    let (mut added, mut removed) = (Vec::new(), Vec::new());
+    for (element, delta) in net {
+        let roots = if delta > 0 { &mut added } else { &mut removed };
+        roots.extend(core::iter::repeat_n(element, delta.unsigned_abs() as usize));
+    }
+    (
+        Acc1::poly_to_g2(polymath::from_roots(&added)),
+        Acc1::poly_to_g2(polymath::from_roots(&removed)),
+    )
+}
+
+impl UpdateLog {
+    /// Returns the updates between `from_epoch` and `to_epoch` as a log of their own.
+    pub fn segment(&self, from_epoch: u64, to_epoch: u64) -> Result<UpdateLog> {
+        if from_epoch > to_epoch || from_epoch < self.start_epoch || to_epoch > self.end_epoch() {
+            bail!(
+                "epochs {} to {} are not covered by the log ({} to {})",
+                from_epoch,
+                to_epoch,
+                self.start_epoch,
+                self.end_epoch()
+            );
+        }
+        let start = (from_epoch - self.start_epoch) as usize;
+        let end = (to_epoch - self.start_epoch) as usize;
+        Ok(UpdateLog {
+            start_epoch: from_epoch,
+            updates: self.updates[start..end].to_vec(),
+        })
+    }
+
+    /// Rolls the updates between `from_epoch` and `to_epoch` up into one proof.
+    pub fn prove_consistency(&self, from_epoch: u64, to_epoch: u64) -> Result<ConsistencyProof> {
+        let segment = self.segment(from_epoch, to_epoch)?;
+        let (added, removed) = net_commitments(&segment);
+        Ok(ConsistencyProof {
+            from_epoch,
+            to_epoch,
+            updates_digest: segment.to_digest(),
+            added,
+            removed,
+            params_id: params_id(),
+        })
+    }
+}
+
+impl DynamicAccumulator {
+    /// Moves to the state with `value` at `epoch`, after checking that `proof` rolls up the
+    /// published `segment` of updates from the current state to there. The element set is
+    /// forgotten and the accumulator becomes value-only: it keeps following and checking
+    /// states with `adopt_state`, but every operation on its elements fails with
+    /// [`LifecycleError::ValueOnly`](super::lifecycle::LifecycleError::ValueOnly) until it is
+    /// rebuilt from the elements.
+    pub fn adopt_state(
+        &mut self,
+        value: G1Affine,
+        epoch: u64,
+        proof: &ConsistencyProof,
+        segment: &UpdateLog,
+    ) -> Result<()> {
+        self.ensure_mutable()?;
+        if proof.from_epoch != self.epoch() || proof.to_epoch != epoch {
+            bail!(
+                "proof covers epochs {} to {}, expected {} to {}",
+                proof.from_epoch,
+                proof.to_epoch,
+                self.epoch(),
+                epoch
+            );
+        }
+        if !proof.verify_segment(self.acc_value, value, segment) {
+            bail!("invalid consistency proof");
+        }
+        self.replace_value(value, epoch);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::{dynamic_accumulator::DynamicAccumulator, lifecycle::LifecycleError};
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_consistency() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        let mut log = UpdateLog::new(acc.epoch());
+        let mut values = vec![acc.acc_value];
+        for op in 0..6i64 {
+            if op % 3 == 2 {
+                log.push(acc.delete(&(op - 1)).unwrap());
+            } else {
+                log.push(acc.add(&op).unwrap());
+            }
+            values.push(acc.acc_value);
+        }
+
+        let proof = log.prove_consistency(1, 6).unwrap();
+        let segment = log.segment(1, 6).unwrap();
+        assert_eq!(segment.verify_chain(values[1]).unwrap(), values[6]);
+        assert!(proof.verify_segment(values[1], values[6], &segment));
+        assert!(!proof.verify(values[0], values[6]));
+        assert!(!proof.verify(values[1], values[5]));
+        assert!(!proof.verify_segment(values[1], values[6], &log));
+        let mut other_params = proof.clone();
+        other_params.params_id = Digest::default();
+        assert!(!other_params.verify_segment(values[1], values[6], &segment));
+
+        // Trivial commitments would hold between any two values.
+        let mut forged = proof.clone();
+        forged.added = G2Affine::zero();
+        forged.removed = G2Affine::zero();
+        assert!(!forged.verify(values[0], values[5]));
+        // Net changes other than the segment's are rejected even if they explain the values.
+        let mut forged = log.prove_consistency(0, 6).unwrap();
+        (forged.from_epoch, forged.updates_digest) = (1, segment.to_digest());
+        assert!(forged.verify(values[0], values[6]));
+        assert!(!forged.verify_segment(values[0], values[6], &segment));
+
+        let empty = log.prove_consistency(3, 3).unwrap();
+        assert!(empty.verify(values[3], values[3]));
+        assert!(log.prove_consistency(2, 7).is_err());
+    }
+
+    #[test]
+    fn test_adopt_state() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        acc.add(&1).unwrap();
+        let mut mirror = acc.clone();
+        let mut log = UpdateLog::new(acc.epoch());
+        log.push(acc.add(&2).unwrap());
+        log.push(acc.add(&3).unwrap());
+        log.push(acc.delete(&1).unwrap());
+
+        let proof = log.prove_consistency(1, 4).unwrap();
+        let segment = log.segment(1, 4).unwrap();
+        assert!(mirror
+            .adopt_state(acc.acc_value, 3, &proof, &segment)
+            .is_err());
+        let stale = log.prove_consistency(2, 4).unwrap();
+        assert!(mirror
+            .adopt_state(acc.acc_value, 4, &stale, &log.segment(2, 4).unwrap())
+            .is_err());
+        assert!(mirror
+            .adopt_state(mirror.acc_value, 4, &proof, &segment)
+            .is_err());
+        assert!(mirror
+            .adopt_state(acc.acc_value, 4, &proof, &log.segment(1, 3).unwrap())
+            .is_err());
+
+        // A degenerate proof does not move the mirror to an arbitrary state.
+        let mut forged = proof.clone();
+        (forged.to_epoch, forged.added, forged.removed) = (99, G2Affine::zero(), G2Affine::zero());
+        assert!(mirror
+            .adopt_state(G1Affine::zero(), 99, &forged, &segment)
+            .is_err());
+        assert_eq!(mirror.epoch(), 1);
+
+        mirror
+            .adopt_state(acc.acc_value, 4, &proof, &segment)
+            .unwrap();
+        assert_eq!(mirror.digest(), acc.digest());
+        assert!(mirror.elements_fr().is_empty() && mirror.is_value_only());
+
+        // Operations on the forgotten elements fail instead of diverging.
+        let err = mirror.add(&5).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<LifecycleError>(),
+            Some(&LifecycleError::ValueOnly { epoch: 4 })
+        );
+        assert!(mirror.delete(&2).is_err());
+        assert!(mirror.prove_membership(&2).is_err());
+        assert!(mirror.prove_non_membership(&1).is_err());
+        assert!(mirror.self_check().is_err());
+        for element in [2, 1] {
+            let err = mirror.query(&element).unwrap_err();
+            assert_eq!(
+                err.downcast_ref::<LifecycleError>(),
+                Some(&LifecycleError::ValueOnly { epoch: 4 })
+            );
+        }
+
+        mirror.freeze().unwrap();
+        let empty = log.prove_consistency(4, 4).unwrap();
+        assert!(mirror
+            .adopt_state(acc.acc_value, 4, &empty, &log.segment(4, 4).unwrap())
+            .is_err());
+    }
+}
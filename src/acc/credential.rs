@@ -0,0 +1,262 @@
+//! Holder-side "show" protocol for accumulator-based credentials.
+//!
+//! The accumulator holds the elements of all valid (not revoked) credentials. A holder with a
+//! membership witness `w` for its element `x`, i.e. e(w, g2^(s-x)) == e(acc, g2), presents the
+//! credential without revealing `x` or `w`:
+//!
+//! - the witness is blinded as `W = w + h*r` and `r` is committed to as `R = k*r`;
+//! - a Fiat-Shamir proof of knowledge of `(x, r, d = x*r)` shows
+//!   `R = k*r`, `0 = R*x - k*d` and
+//!   `e(W, g2^s) / e(acc, g2) = e(W, g2)^x * e(h, g2^s)^r * e(h, g2)^(-d)`;
+//! - the challenge is bound to a verifier-chosen nonce, so presentations cannot be replayed.
+//!
+//! `h` and `k` are hashed to G1, so nobody knows their discrete logarithms.
+
+use super::{
+    dynamic_accumulator::{ark_serde, MembershipProof},
+    get_g2s,
+    signing::hash_to_g1,
+    update::Update,
+    utils::digest_to_prime_field,
+    Curve, Fq12, Fr, G1Affine, G2Affine,
+};
+use crate::digest::Digestible;
+use anyhow::{bail, Result};
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{Field, One, PrimeField, ToBytes, UniformRand};
+use serde::{Deserialize, Serialize};
+
+lazy_static! {
+    static ref GEN_H: G1Affine = hash_to_g1(b"ESA_RUST_CREDENTIAL_H");
+    static ref GEN_K: G1Affine = hash_to_g1(b"ESA_RUST_CREDENTIAL_K");
+    static ref G2_S: G2Affine = get_g2s(Fr::one());
+    static ref E_H_G2: Fq12 = Curve::pairing(*GEN_H, G2Affine::prime_subgroup_generator());
+    static ref E_H_G2S: Fq12 = Curve::pairing(*GEN_H, *G2_S);
+}
+
+/// A credential held by a user: a membership witness for its (secret) element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credential {
+    pub witness: MembershipProof,
+}
+
+/// A zero-knowledge presentation of a credential, bound to a nonce.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Presentation {
+    /// The epoch of the accumulator state the presentation is for.
+    pub epoch: u64,
+    /// The blinded witness `w + h*r`.
+    #[serde(with = "ark_serde")]
+    pub blinded_witness: G1Affine,
+    /// The commitment `k*r` to the blinding factor.
+    #[serde(with = "ark_serde")]
+    pub blinding_commitment: G1Affine,
+    #[serde(with = "ark_serde")]
+    pub challenge: Fr,
+    #[serde(with = "ark_serde")]
+    pub z_element: Fr,
+    #[serde(with = "ark_serde")]
+    pub z_blinding: Fr,
+    #[serde(with = "ark_serde")]
+    pub z_product: Fr,
+}
+
+/// e(W, g2^s) / e(acc, g2), the target-group value the presentation opens.
+fn statement(blinded_witness: G1Affine, acc_value: G1Affine) -> Option<Fq12> {
+    let lhs = Curve::pairing(blinded_witness, *G2_S);
+    let rhs = Curve::pairing(acc_value, G2Affine::prime_subgroup_generator());
+    rhs.inverse().map(|inv| lhs * inv)
+}
+
+/// The Fiat-Shamir challenge over `[acc, W, R, t1, t2]`, `t3`, the epoch and the nonce.
+fn challenge(points: &[G1Affine; 5], t3: &Fq12, epoch: u64, nonce: &[u8]) -> Fr {
+    let mut buf = Vec::<u8>::new();
+    buf.extend_from_slice(b"ESA_RUST_CREDENTIAL_SHOW");
+    points
+        .iter()
+        .try_for_each(|p| p.write(&mut buf))
+        .and_then(|_| t3.write(&mut buf))
+        .unwrap_or_else(|_| panic!("failed to serialize the presentation transcript"));
+    buf.extend_from_slice(&epoch.to_le_bytes());
+    buf.extend_from_slice(nonce);
+    digest_to_prime_field(&buf.to_digest())
+}
+
+impl Credential {
+    pub fn new(witness: MembershipProof) -> Self {
+        Self { witness }
+    }
+
+    /// Keeps the witness current with an update published by the manager.
+    /// Returns an error if the credential was revoked by the update.
+    pub fn update(&mut self, update: &Update) -> Result<()> {
+        match update {
+            Update::Add(proof) => self.witness.update_on_add(proof),
+            Update::Delete(proof) => self.witness.update_on_delete(proof),
+        }
+    }
+
+    /// Presents the credential against `acc_value`, binding the presentation to `nonce`.
+    /// Blinding factors are drawn from the thread-local generator.
+    /// Returns an error if the witness is not valid for `acc_value`.
+    pub fn show(&self, acc_value: G1Affine, nonce: &[u8]) -> Result<Presentation> {
+        self.show_with_rng(acc_value, nonce, &mut rand::thread_rng())
+    }
+
+    /// Same as [`Credential::show`], drawing blinding factors from `rng`.
+    pub fn show_with_rng<R: rand::Rng + ?Sized>(
+        &self,
+        acc_value: G1Affine,
+        nonce: &[u8],
+        rng: &mut R,
+    ) -> Result<Presentation> {
+        if !self.witness.verify(acc_value) {
+            bail!("The credential witness is not valid for the accumulator value");
+        }
+        let x = self.witness.element.0;
+        let r = Fr::rand(rng);
+        let d = x * r;
+        let blinded_witness =
+            (self.witness.witness.into_projective() + GEN_H.mul(r.into_repr())).into_affine();
+        let blinding_commitment = GEN_K.mul(r.into_repr()).into_affine();
+
+        let (rho_x, rho_r, rho_d) = (Fr::rand(rng), Fr::rand(rng), Fr::rand(rng));
+        let t1 = GEN_K.mul(rho_r.into_repr()).into_affine();
+        let t2 = (blinding_commitment.mul(rho_x.into_repr()) - GEN_K.mul(rho_d.into_repr()))
+            .into_affine();
+        let e_w_g2 = Curve::pairing(blinded_witness, G2Affine::prime_subgroup_generator());
+        let t3 = e_w_g2.pow(rho_x.into_repr())
+            * E_H_G2S.pow(rho_r.into_repr())
+            * E_H_G2.pow((-rho_d).into_repr());
+
+        let epoch = self.witness.epoch;
+        let c = challenge(
+            &[acc_value, blinded_witness, blinding_commitment, t1, t2],
+            &t3,
+            epoch,
+            nonce,
+        );
+        Ok(Presentation {
+            epoch,
+            blinded_witness,
+            blinding_commitment,
+            challenge: c,
+            z_element: rho_x + c * x,
+            z_blinding: rho_r + c * r,
+            z_product: rho_d + c * d,
+        })
+    }
+}
+
+impl Presentation {
+    /// Verifies the presentation against the accumulator value of `self.epoch` and the nonce
+    /// the verifier handed out.
+    pub fn verify(&self, acc_value: G1Affine, nonce: &[u8]) -> bool {
+        let t = match statement(self.blinded_witness, acc_value) {
+            Some(t) => t,
+            None => return false,
+        };
+        let c = self.challenge.into_repr();
+        let t1 = (GEN_K.mul(self.z_blinding.into_repr()) - self.blinding_commitment.mul(c))
+            .into_affine();
+        let t2 = (self.blinding_commitment.mul(self.z_element.into_repr())
+            - GEN_K.mul(self.z_product.into_repr()))
+        .into_affine();
+        let e_w_g2 = Curve::pairing(self.blinded_witness, G2Affine::prime_subgroup_generator());
+        let t_c_inv = match t.pow(c).inverse() {
+            Some(inv) => inv,
+            None => return false,
+        };
+        let t3 = e_w_g2.pow(self.z_element.into_repr())
+            * E_H_G2S.pow(self.z_blinding.into_repr())
+            * E_H_G2.pow((-self.z_product).into_repr())
+            * t_c_inv;
+        self.challenge
+            == challenge(
+                &[
+                    acc_value,
+                    self.blinded_witness,
+                    self.blinding_commitment,
+                    t1,
+                    t2,
+                ],
+                &t3,
+                self.epoch,
+                nonce,
+            )
+    }
+
+    /// Verifies the presentation against the value published at `epoch`.
+    pub fn verify_at(&self, acc_value: G1Affine, epoch: u64, nonce: &[u8]) -> bool {
+        self.epoch == epoch && self.verify(acc_value, nonce)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::{dynamic_accumulator::DynamicAccumulator, rng::DeterministicRng};
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_show() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        acc.add_batch(&[1, 2, 3]).unwrap();
+        let credential = Credential::new(acc.prove_membership(&2).unwrap());
+
+        let presentation = credential.show(acc.acc_value, b"nonce-1").unwrap();
+        assert!(presentation.verify_at(acc.acc_value, acc.epoch(), b"nonce-1"));
+        assert!(!presentation.verify(acc.acc_value, b"nonce-2"));
+
+        // Two presentations of the same credential are unlinkable.
+        let again = credential.show(acc.acc_value, b"nonce-1").unwrap();
+        assert_ne!(again.blinded_witness, presentation.blinded_witness);
+
+        // A seeded generator reproduces the exact presentation.
+        let seeded = |seed: &[u8]| {
+            credential
+                .show_with_rng(
+                    acc.acc_value,
+                    b"nonce-1",
+                    &mut DeterministicRng::from_seed(seed),
+                )
+                .unwrap()
+        };
+        assert_eq!(seeded(b"audit"), seeded(b"audit"));
+        assert_ne!(seeded(b"audit"), seeded(b"other"));
+
+        let json = serde_json::to_string(&presentation).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Presentation>(&json).unwrap(),
+            presentation
+        );
+    }
+
+    #[test]
+    fn test_revoked_credential() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        acc.add_batch(&[1, 2]).unwrap();
+        let mut credential = Credential::new(acc.prove_membership(&1).unwrap());
+        let old_value = acc.acc_value;
+
+        let presentation = credential.show(old_value, b"nonce").unwrap();
+        credential
+            .update(&Update::Add(acc.add(&3).unwrap()))
+            .unwrap();
+        assert!(!presentation.verify(acc.acc_value, b"nonce"));
+        assert!(credential
+            .show(acc.acc_value, b"nonce")
+            .unwrap()
+            .verify(acc.acc_value, b"nonce"));
+
+        assert!(credential
+            .update(&Update::Delete(acc.delete(&1).unwrap()))
+            .is_err());
+        assert!(credential.show(acc.acc_value, b"nonce").is_err());
+    }
+}
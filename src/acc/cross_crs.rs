@@ -0,0 +1,279 @@
+//! Same-set proofs between accumulators built under different parameter sets.
+//!
+//! Under a CRS with trapdoor `s`, a set with characteristic polynomial P(X) accumulates to
+//! g1^P(s). Two deployments with independent trapdoors `s_a` and `s_b` therefore hold different
+//! values for the same set. A [`SameSetProof`] shows they commit to the same polynomial by
+//! opening both at a Fiat-Shamir point `z` to the same value `y = P(z)`: for each CRS it carries
+//! `g1^((P(s) - y) / (s - z))`. Only the single evaluation `y` is revealed, not the elements.
+//!
+//! Each parameter set has a secret [`ProverKey`], which accumulates and opens, and a public
+//! [`VerifierKey`] carrying only `g2^s`, which is all a verifier needs. The prover key of the
+//! global parameter set, the one of [`DynamicAccumulator`], holds no trapdoor itself: it goes
+//! through the [`trapdoor::signer`].
+
+use super::{
+    dynamic_accumulator::{ark_serde, DynamicAccumulator},
+    g2_digest, g2_s, trapdoor,
+    utils::digest_to_prime_field,
+    Curve, Fr, G1Affine, G2Affine, G1_POWER, G2_POWER,
+};
+use crate::digest::{concat_digest, Digestible};
+use anyhow::{anyhow, Result};
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{Field, One, PrimeField};
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+/// The secret key of a parameter set, needed to accumulate and to open accumulator values.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ProverKey {
+    /// The trapdoor of a seeded set; `None` for the global set, whose trapdoor is the signer's.
+    s: Option<Fr>,
+}
+
+/// The public key of a parameter set, enough to verify [`SameSetProof`]s under it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifierKey {
+    /// g2^s.
+    #[serde(with = "ark_serde")]
+    pub g2_s: G2Affine,
+}
+
+impl ProverKey {
+    /// The parameter set used by [`DynamicAccumulator`].
+    pub fn global() -> Self {
+        Self { s: None }
+    }
+
+    /// Derives an independent parameter set from secret seed material.
+    pub fn from_seed(seed: &[u8]) -> Self {
+        Self {
+            s: Some(digest_to_prime_field(&seed.to_digest())),
+        }
+    }
+
+    /// The public key to hand to verifiers.
+    pub fn verifier_key(&self) -> VerifierKey {
+        match self.s {
+            Some(s) => VerifierKey {
+                g2_s: G2_POWER.apply(&s).into_affine(),
+            },
+            None => VerifierKey::global(),
+        }
+    }
+
+    /// Evaluates the characteristic polynomial of `elements` at `x`.
+    fn eval<'a>(elements: impl Iterator<Item = &'a Fr>, x: Fr) -> Fr {
+        elements.fold(Fr::one(), |acc, e| acc * (x - e))
+    }
+
+    /// Accumulates the given (hashed) elements under this parameter set.
+    pub fn accumulate<'a>(&self, elements: impl IntoIterator<Item = &'a Fr>) -> Result<G1Affine> {
+        match self.s {
+            Some(s) => Ok(G1_POWER
+                .apply(&Self::eval(elements.into_iter(), s))
+                .into_affine()),
+            None => trapdoor::signer().mul_s_minus(
+                G1Affine::prime_subgroup_generator(),
+                &elements.into_iter().copied().collect::<Vec<_>>(),
+            ),
+        }
+    }
+
+    /// point^(1/(s-z)).
+    fn div_s_minus(&self, point: G1Affine, z: Fr) -> Result<G1Affine> {
+        match self.s {
+            Some(s) => {
+                let s_minus_z_inv = (s - z)
+                    .inverse()
+                    .ok_or_else(|| anyhow!("Failed to compute inverse"))?;
+                Ok(point.mul(s_minus_z_inv.into_repr()).into_affine())
+            }
+            None => trapdoor::signer().div_s_minus(point, z),
+        }
+    }
+}
+
+impl fmt::Debug for ProverKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProverKey(..)")
+    }
+}
+
+impl VerifierKey {
+    /// The public key of the parameter set used by [`DynamicAccumulator`], from the published
+    /// `g2^s`.
+    pub fn global() -> Self {
+        Self { g2_s: g2_s() }
+    }
+
+    /// g2^(s-z).
+    fn g2_s_minus(&self, z: Fr) -> G2Affine {
+        (self.g2_s.into_projective() - G2_POWER.apply(&z)).into_affine()
+    }
+}
+
+/// A proof that two accumulator values under different parameter sets commit to the same set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SameSetProof {
+    /// P(z) at the challenge point.
+    #[serde(with = "ark_serde")]
+    pub value: Fr,
+    /// g1^((P(s_a) - y) / (s_a - z)).
+    #[serde(with = "ark_serde")]
+    pub opening_a: G1Affine,
+    /// g1^((P(s_b) - y) / (s_b - z)).
+    #[serde(with = "ark_serde")]
+    pub opening_b: G1Affine,
+}
+
+fn challenge(vk_a: &VerifierKey, acc_a: &G1Affine, vk_b: &VerifierKey, acc_b: &G1Affine) -> Fr {
+    digest_to_prime_field(&concat_digest(
+        [
+            b"SameSetProof"[..].to_digest(),
+            g2_digest(&vk_a.g2_s),
+            acc_a.to_digest(),
+            g2_digest(&vk_b.g2_s),
+            acc_b.to_digest(),
+        ]
+        .iter()
+        .copied(),
+    ))
+}
+
+fn open(key: &ProverKey, acc: G1Affine, value: Fr, z: Fr) -> Result<G1Affine> {
+    let lhs = acc.into_projective() - G1_POWER.apply(&value);
+    key.div_s_minus(lhs.into_affine(), z)
+}
+
+/// It checks if e(acc - g1^y, g2) == e(opening, g2^(s-z)).
+fn verify_opening(vk: &VerifierKey, acc: G1Affine, opening: G1Affine, value: Fr, z: Fr) -> bool {
+    let lhs = acc.into_projective() - G1_POWER.apply(&value);
+    Curve::pairing(lhs.into_affine(), G2Affine::prime_subgroup_generator())
+        == Curve::pairing(opening, vk.g2_s_minus(z))
+}
+
+impl SameSetProof {
+    /// Proves that the (hashed) elements accumulate to the same set under both parameter sets.
+    /// Returns the two accumulator values together with the proof.
+    pub fn prove(
+        key_a: &ProverKey,
+        key_b: &ProverKey,
+        elements: &[Fr],
+    ) -> Result<(G1Affine, G1Affine, Self)> {
+        let acc_a = key_a.accumulate(elements)?;
+        let acc_b = key_b.accumulate(elements)?;
+        let z = challenge(&key_a.verifier_key(), &acc_a, &key_b.verifier_key(), &acc_b);
+        let value = ProverKey::eval(elements.iter(), z);
+        let proof = Self {
+            value,
+            opening_a: open(key_a, acc_a, value, z)?,
+            opening_b: open(key_b, acc_b, value, z)?,
+        };
+        Ok((acc_a, acc_b, proof))
+    }
+
+    /// Verifies that `acc_a` under `vk_a` and `acc_b` under `vk_b` commit to the same set.
+    pub fn verify(
+        &self,
+        vk_a: &VerifierKey,
+        acc_a: G1Affine,
+        vk_b: &VerifierKey,
+        acc_b: G1Affine,
+    ) -> bool {
+        let z = challenge(vk_a, &acc_a, vk_b, &acc_b);
+        verify_opening(vk_a, acc_a, self.opening_a, self.value, z)
+            && verify_opening(vk_b, acc_b, self.opening_b, self.value, z)
+    }
+}
+
+impl DynamicAccumulator {
+    /// Migrates the accumulated set to another parameter set. Returns the accumulator value
+    /// under `target` and a proof that it commits to the same set as `self.acc_value`.
+    pub fn prove_same_set(&self, target: &ProverKey) -> Result<(G1Affine, SameSetProof)> {
+        let elements = self.elements_with_multiplicity();
+        let (_, acc_b, proof) = SameSetProof::prove(&ProverKey::global(), target, &elements)?;
+        Ok((acc_b, proof))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_same_set_proof() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        acc.add_batch(&[1, 2, 3]).unwrap();
+        let target = ProverKey::from_seed(b"other deployment");
+        let (global_vk, target_vk) = (VerifierKey::global(), target.verifier_key());
+        assert_eq!(ProverKey::global().verifier_key(), global_vk);
+
+        let (migrated, proof) = acc.prove_same_set(&target).unwrap();
+        assert_ne!(migrated, acc.acc_value);
+        assert_eq!(
+            migrated,
+            target.accumulate(acc.elements_fr().iter()).unwrap()
+        );
+        assert!(proof.verify(&global_vk, acc.acc_value, &target_vk, migrated));
+
+        // A different set under the target parameters is rejected.
+        let mut other = acc.clone();
+        other.add(&4).unwrap();
+        let other_migrated = target.accumulate(other.elements_fr().iter()).unwrap();
+        assert!(!proof.verify(&global_vk, acc.acc_value, &target_vk, other_migrated));
+        assert!(!proof.verify(&global_vk, other.acc_value, &target_vk, migrated));
+
+        // So is a proof checked under the wrong parameters.
+        let wrong = ProverKey::from_seed(b"unrelated").verifier_key();
+        assert!(!proof.verify(&global_vk, acc.acc_value, &wrong, migrated));
+    }
+
+    #[test]
+    fn test_verifier_key_cannot_forge() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        acc.add_batch(&[1, 2, 3]).unwrap();
+        let target = ProverKey::from_seed(b"other deployment");
+        let target_vk = target.verifier_key();
+        let (migrated, proof) = acc.prove_same_set(&target).unwrap();
+
+        // The holder of the verifier keys sees an honest proof, and the value of another set
+        // under the target parameters, and tries to claim it is the same set.
+        let mut other = acc.clone();
+        other.add(&4).unwrap();
+        let other_migrated = target.accumulate(other.elements_fr().iter()).unwrap();
+        let global_vk = VerifierKey::global();
+        let z = challenge(&global_vk, &acc.acc_value, &target_vk, &other_migrated);
+        let value = ProverKey::eval(acc.elements_fr().iter(), z);
+        let g1 = G1Affine::prime_subgroup_generator();
+        let candidates = [
+            proof.clone(),
+            SameSetProof { value, ..proof },
+            SameSetProof {
+                value,
+                opening_a: proof.opening_a,
+                opening_b: (other_migrated.into_projective() - G1_POWER.apply(&value))
+                    .into_affine(),
+            },
+            SameSetProof {
+                value: Fr::one(),
+                opening_a: g1,
+                opening_b: g1,
+            },
+        ];
+        assert!(candidates.iter().all(|forged| !forged.verify(
+            &global_vk,
+            acc.acc_value,
+            &target_vk,
+            other_migrated
+        )));
+        // The honest proof still verifies for the set it was made for.
+        assert!(candidates[0].verify(&global_vk, acc.acc_value, &target_vk, migrated));
+    }
+}
@@ -0,0 +1,200 @@
+//! Idempotent accumulator mutations keyed by operation ID.
+//!
+//! Callers that may retry requests tag each add or delete with an operation ID. The
+//! accumulator remembers the proofs of the most recent operation IDs, so a replayed operation
+//! returns the original proof instead of failing or, in multiset mode, counting the element
+//! twice. The operation ID is echoed in the returned proof.
+
+use super::{
+    dynamic_accumulator::{AddProof, DeleteProof, DynamicAccumulator},
+    update::Update,
+    utils::digest_to_prime_field,
+    Fr,
+};
+use crate::digest::Digestible;
+use anyhow::{bail, Result};
+use core::fmt;
+use std::collections::{HashMap, VecDeque};
+
+/// An operation ID chosen by the caller, e.g. a request ID.
+pub type OpId = u64;
+
+/// The default number of operation IDs remembered.
+pub const DEFAULT_OP_WINDOW: usize = 1 << 12;
+
+/// The proofs of the most recent operations, by operation ID. The oldest operation is
+/// forgotten once `capacity` operations are remembered.
+///
+/// The window is not part of the accumulator's identity: two windows always compare equal.
+pub struct OpWindow {
+    proofs: HashMap<OpId, Update>,
+    order: VecDeque<OpId>,
+    capacity: usize,
+}
+
+impl OpWindow {
+    /// Creates an empty window remembering at most `capacity` operations. A capacity of zero
+    /// disables deduplication.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            proofs: HashMap::new(),
+            order: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// Returns the proof of a remembered operation.
+    pub fn get(&self, op_id: OpId) -> Option<&Update> {
+        self.proofs.get(&op_id)
+    }
+
+    /// Remembers the proof of an operation, forgetting the oldest one if the window is full.
+    pub fn insert(&mut self, op_id: OpId, proof: Update) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.proofs.insert(op_id, proof).is_none() {
+            self.order.push_back(op_id);
+            while self.order.len() > self.capacity {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.proofs.remove(&oldest);
+                }
+            }
+        }
+    }
+
+    /// Returns the number of remembered operations.
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    /// Returns true if no operation is remembered.
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+}
+
+impl Default for OpWindow {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_OP_WINDOW)
+    }
+}
+
+impl Clone for OpWindow {
+    fn clone(&self) -> Self {
+        Self {
+            proofs: self.proofs.clone(),
+            order: self.order.clone(),
+            capacity: self.capacity,
+        }
+    }
+}
+
+impl PartialEq for OpWindow {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for OpWindow {}
+
+impl fmt::Debug for OpWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OpWindow")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+impl DynamicAccumulator {
+    /// Sets how many operation IDs are remembered for deduplication.
+    pub fn set_op_window(&mut self, capacity: usize) {
+        self.op_window = OpWindow::with_capacity(capacity);
+    }
+
+    /// Adds an element as operation `op_id`. Replaying an operation ID still in the window
+    /// returns the original proof without changing the accumulator. Returns an error if
+    /// `op_id` was used for a different operation.
+    pub fn add_with_op_id(&mut self, element: &i64, op_id: OpId) -> Result<AddProof> {
+        let fr_element = digest_to_prime_field(&element.to_digest());
+        match self.replayed(op_id, fr_element)? {
+            Some(Update::Add(proof)) => return Ok(proof),
+            Some(Update::Delete(_)) => bail!("Operation {} was a delete", op_id),
+            None => {}
+        }
+        let mut proof = self.add_fr(fr_element)?;
+        self.retain_value(fr_element, *element);
+        proof.op_id = Some(op_id);
+        self.op_window.insert(op_id, Update::Add(proof.clone()));
+        Ok(proof)
+    }
+
+    /// Deletes an element as operation `op_id`. Replaying an operation ID still in the window
+    /// returns the original proof without changing the accumulator. Returns an error if
+    /// `op_id` was used for a different operation.
+    pub fn delete_with_op_id(&mut self, element: &i64, op_id: OpId) -> Result<DeleteProof> {
+        let fr_element = digest_to_prime_field(&element.to_digest());
+        match self.replayed(op_id, fr_element)? {
+            Some(Update::Delete(proof)) => return Ok(proof),
+            Some(Update::Add(_)) => bail!("Operation {} was an add", op_id),
+            None => {}
+        }
+        let mut proof = self.delete_fr(fr_element)?;
+        proof.op_id = Some(op_id);
+        self.op_window.insert(op_id, Update::Delete(proof.clone()));
+        Ok(proof)
+    }
+
+    fn replayed(&self, op_id: OpId, fr_element: Fr) -> Result<Option<Update>> {
+        match self.op_window.get(op_id) {
+            Some(update) if update.element().0 != fr_element => {
+                bail!("Operation {} was for a different element", op_id)
+            }
+            other => Ok(other.cloned()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::dynamic_accumulator::DuplicatePolicy;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_replayed_operations() {
+        init_logger();
+        let mut acc =
+            DynamicAccumulator::with_duplicate_policy(DuplicatePolicy::IncrementMultiplicity);
+        let proof = acc.add_with_op_id(&1, 7).unwrap();
+        assert_eq!(proof.op_id, Some(7));
+        assert!(proof.verify());
+        assert_eq!(acc.add_with_op_id(&1, 7).unwrap(), proof);
+        assert_eq!(acc.multiplicity(&1), 1);
+        assert_eq!(acc.epoch(), 1);
+
+        assert!(acc.add_with_op_id(&2, 7).is_err());
+        assert!(acc.delete_with_op_id(&1, 7).is_err());
+
+        let delete = acc.delete_with_op_id(&1, 8).unwrap();
+        assert_eq!(acc.delete_with_op_id(&1, 8).unwrap(), delete);
+        assert_eq!(acc.epoch(), 2);
+    }
+
+    #[test]
+    fn test_op_window_eviction() {
+        init_logger();
+        let mut acc =
+            DynamicAccumulator::with_duplicate_policy(DuplicatePolicy::IncrementMultiplicity);
+        acc.set_op_window(1);
+        acc.add_with_op_id(&1, 1).unwrap();
+        acc.add_with_op_id(&2, 2).unwrap();
+        // Operation 1 fell out of the window and is applied again.
+        acc.add_with_op_id(&1, 1).unwrap();
+        assert_eq!(acc.multiplicity(&1), 2);
+    }
+}
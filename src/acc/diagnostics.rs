@@ -0,0 +1,249 @@
+//! Reasons for rejected proofs, with the `diagnostics` feature.
+//!
+//! The `verify` methods of the proofs only answer yes or no. When a third-party prover's proofs
+//! are rejected, the `diagnose` methods here run the same checks and return a [`VerifyError`]
+//! naming the first one that failed: a proof for another accumulator value or epoch, a
+//! malformed point, or the pairing equation itself. Every rejection is also emitted as a
+//! `tracing` debug event, so any subscriber or `log` backend can record it.
+
+use super::{
+    canonical::{is_canonical_g1, is_canonical_g2},
+    commitment::params_id,
+    dynamic_accumulator::{AddProof, DeleteProof, MembershipProof, NonMembershipProof},
+    G1Affine,
+};
+use crate::digest::{Digest, Digestible};
+use core::fmt;
+use tracing::debug;
+
+/// Why a proof was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyError {
+    /// The proof was generated for another accumulator value.
+    AccumulatorMismatch,
+    /// The proof was generated under other public parameters, see [`super::params`].
+    ParamsMismatch,
+    /// The proof was generated for another epoch.
+    EpochMismatch { expected: u64, actual: u64 },
+    /// A point of the proof is not in canonical form, see [`super::canonical`].
+    NonCanonical { component: &'static str },
+    /// The pairing equation does not hold.
+    PairingFailed { equation: &'static str },
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::AccumulatorMismatch => {
+                write!(f, "the proof is for another accumulator value")
+            }
+            VerifyError::ParamsMismatch => {
+                write!(f, "the proof is for other public parameters")
+            }
+            VerifyError::EpochMismatch { expected, actual } => write!(
+                f,
+                "the proof is for epoch {} but epoch {} was expected",
+                actual, expected
+            ),
+            VerifyError::NonCanonical { component } => {
+                write!(f, "`{}` is not a canonical point", component)
+            }
+            VerifyError::PairingFailed { equation } => {
+                write!(f, "the pairing equation {} does not hold", equation)
+            }
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+fn ensure(holds: bool, err: VerifyError) -> Result<(), VerifyError> {
+    if holds {
+        Ok(())
+    } else {
+        Err(err)
+    }
+}
+
+fn ensure_params(proof_params_id: &Digest) -> Result<(), VerifyError> {
+    ensure(*proof_params_id == params_id(), VerifyError::ParamsMismatch)
+}
+
+fn ensure_g1(p: &G1Affine, component: &'static str) -> Result<(), VerifyError> {
+    ensure(is_canonical_g1(p), VerifyError::NonCanonical { component })
+}
+
+fn logged(kind: &str, result: Result<(), VerifyError>) -> Result<(), VerifyError> {
+    if let Err(err) = &result {
+        debug!("{} proof rejected: {}", kind, err);
+    }
+    result
+}
+
+impl AddProof {
+    /// Like [`AddProof::verify`], returning why the proof was rejected.
+    pub fn diagnose(&self) -> Result<(), VerifyError> {
+        logged("add", {
+            ensure_params(&self.params_id)
+                .and_then(|_| ensure_g1(&self.old_acc_value, "old_acc_value"))
+                .and_then(|_| ensure_g1(&self.new_acc_value, "new_acc_value"))
+                .and_then(|_| {
+                    ensure(
+                        self.verify(),
+                        VerifyError::PairingFailed {
+                            equation: "e(new_acc, g2) == e(old_acc, g2^(s-x))",
+                        },
+                    )
+                })
+        })
+    }
+}
+
+impl DeleteProof {
+    /// Like [`DeleteProof::verify`], returning why the proof was rejected.
+    pub fn diagnose(&self) -> Result<(), VerifyError> {
+        logged("delete", {
+            ensure_params(&self.params_id)
+                .and_then(|_| ensure_g1(&self.old_acc_value, "old_acc_value"))
+                .and_then(|_| ensure_g1(&self.new_acc_value, "new_acc_value"))
+                .and_then(|_| {
+                    ensure(
+                        self.verify(),
+                        VerifyError::PairingFailed {
+                            equation: "e(new_acc, g2^(s-x)) == e(old_acc, g2)",
+                        },
+                    )
+                })
+        })
+    }
+}
+
+impl MembershipProof {
+    /// Like [`MembershipProof::verify`], returning why the proof was rejected.
+    pub fn diagnose(&self, accumulator: G1Affine) -> Result<(), VerifyError> {
+        logged("membership", {
+            ensure(
+                self.acc_digest == accumulator.to_digest(),
+                VerifyError::AccumulatorMismatch,
+            )
+            .and_then(|_| ensure_params(&self.params_id))
+            .and_then(|_| ensure_g1(&self.witness, "witness"))
+            .and_then(|_| {
+                ensure(
+                    self.verify(accumulator),
+                    VerifyError::PairingFailed {
+                        equation: "e(witness, g2^(s-x)) == e(acc, g2)",
+                    },
+                )
+            })
+        })
+    }
+
+    /// Like [`MembershipProof::verify_at`], returning why the proof was rejected.
+    pub fn diagnose_at(&self, accumulator: G1Affine, epoch: u64) -> Result<(), VerifyError> {
+        logged("membership", epoch_matches(epoch, self.epoch))?;
+        self.diagnose(accumulator)
+    }
+}
+
+impl NonMembershipProof {
+    /// Like [`NonMembershipProof::verify`], returning why the proof was rejected.
+    pub fn diagnose(&self, accumulator: G1Affine) -> Result<(), VerifyError> {
+        logged("non-membership", {
+            ensure(
+                self.acc_digest == accumulator.to_digest(),
+                VerifyError::AccumulatorMismatch,
+            )
+            .and_then(|_| ensure_params(&self.params_id))
+            .and_then(|_| {
+                ensure(
+                    is_canonical_g2(&self.witness),
+                    VerifyError::NonCanonical {
+                        component: "witness",
+                    },
+                )
+            })
+            .and_then(|_| ensure_g1(&self.g1_a, "g1_a"))
+            .and_then(|_| {
+                ensure(
+                    self.verify(accumulator),
+                    VerifyError::PairingFailed {
+                        equation: "e(acc, witness) * e(g1_a, g2^(s-x)) == e(g1, g2)",
+                    },
+                )
+            })
+        })
+    }
+
+    /// Like [`NonMembershipProof::verify_at`], returning why the proof was rejected.
+    pub fn diagnose_at(&self, accumulator: G1Affine, epoch: u64) -> Result<(), VerifyError> {
+        logged("non-membership", epoch_matches(epoch, self.epoch))?;
+        self.diagnose(accumulator)
+    }
+}
+
+fn epoch_matches(expected: u64, actual: u64) -> Result<(), VerifyError> {
+    ensure(
+        expected == actual,
+        VerifyError::EpochMismatch { expected, actual },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DynamicAccumulator;
+    use ark_ec::AffineCurve;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_diagnose() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        let add = acc.add(&1).unwrap();
+        assert_eq!(add.diagnose(), Ok(()));
+        let mut forged = add.clone();
+        forged.new_acc_value = forged.old_acc_value;
+        assert!(matches!(
+            forged.diagnose(),
+            Err(VerifyError::PairingFailed { .. })
+        ));
+        let mut other_params = add.clone();
+        other_params.params_id = Digest::default();
+        assert_eq!(other_params.diagnose(), Err(VerifyError::ParamsMismatch));
+
+        let proof = acc.prove_membership(&1).unwrap();
+        assert_eq!(proof.diagnose_at(acc.acc_value, 1), Ok(()));
+        assert_eq!(
+            proof.diagnose_at(acc.acc_value, 2),
+            Err(VerifyError::EpochMismatch {
+                expected: 2,
+                actual: 1
+            })
+        );
+        assert_eq!(
+            proof.diagnose(add.old_acc_value),
+            Err(VerifyError::AccumulatorMismatch)
+        );
+        let mut malformed = proof.clone();
+        malformed.witness = G1Affine::new(malformed.witness.y, malformed.witness.x, false);
+        assert_eq!(
+            malformed.diagnose(acc.acc_value),
+            Err(VerifyError::NonCanonical {
+                component: "witness"
+            })
+        );
+
+        let proof = acc.prove_non_membership(&2).unwrap();
+        assert_eq!(proof.diagnose(acc.acc_value), Ok(()));
+        let mut forged = proof;
+        forged.g1_a = forged.g1_a.mul(2u64).into();
+        assert!(matches!(
+            forged.diagnose(acc.acc_value),
+            Err(VerifyError::PairingFailed { .. })
+        ));
+    }
+}
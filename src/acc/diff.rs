@@ -0,0 +1,161 @@
+//! Proven differences between two states of the same accumulator.
+//!
+//! A [`DiffProof`] lists the elements added and removed between two published values and shows
+//! that they account for the whole change: with A the product of (s - a) over the added and R
+//! the product of (s - r) over the removed elements, e(new, g2^R) == e(old, g2^A). A replica at
+//! the old value can verify it and catch up with [`DynamicAccumulator::apply_diff`].
+
+use super::{
+    commit_g2,
+    commitment::params_id,
+    dynamic_accumulator::{ark_serde, DynamicAccumulator},
+    metrics, polymath, Curve, Fr, G1Affine,
+};
+use crate::digest::Digest;
+use anyhow::{bail, Result};
+use ark_ec::PairingEngine;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A proof that one accumulator value is another with the listed elements added and removed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffProof {
+    #[serde(with = "ark_serde")]
+    pub old_acc_value: G1Affine,
+    pub old_epoch: u64,
+    #[serde(with = "ark_serde")]
+    pub new_acc_value: G1Affine,
+    pub new_epoch: u64,
+    #[serde(with = "ark_serde")]
+    pub added: Vec<Fr>,
+    #[serde(with = "ark_serde")]
+    pub removed: Vec<Fr>,
+    /// The identifier of the public parameters the proof was generated under, see
+    /// [`params_id`].
+    pub params_id: Digest,
+}
+
+impl DiffProof {
+    /// Verifies that the new value is the old one with `added` added and `removed` removed.
+    /// The changes are committed to with the published powers, so diffs adding or removing more
+    /// than [`MAX_DEGREE`](super::MAX_DEGREE) elements do not verify.
+    pub fn verify(&self) -> bool {
+        if self.params_id != params_id() {
+            return false;
+        }
+        let (removed, added) = match (
+            commit_g2(polymath::from_roots(&self.removed)),
+            commit_g2(polymath::from_roots(&self.added)),
+        ) {
+            (Ok(removed), Ok(added)) => (removed, added),
+            _ => return false,
+        };
+        let lhs = Curve::pairing(self.new_acc_value, removed);
+        let rhs = Curve::pairing(self.old_acc_value, added);
+        metrics::record_pairings(2);
+        lhs == rhs
+    }
+}
+
+/// Occurrence counts of every element, including repeats.
+fn occurrences(acc: &DynamicAccumulator) -> HashMap<Fr, usize> {
+    let mut counts = HashMap::new();
+    for e in acc.elements_with_multiplicity() {
+        *counts.entry(e).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// The elements occurring more often in `a` than in `b`, repeated by the difference.
+fn surplus(a: &HashMap<Fr, usize>, b: &HashMap<Fr, usize>) -> Vec<Fr> {
+    a.iter()
+        .flat_map(|(e, count)| {
+            let extra = count.saturating_sub(b.get(e).copied().unwrap_or(0));
+            std::iter::repeat_n(*e, extra)
+        })
+        .collect()
+}
+
+impl DynamicAccumulator {
+    /// Computes what changed from this state to `other`, a later state of the same set.
+    /// Returns the added elements, the removed elements and a proof of the change.
+    pub fn diff(&self, other: &DynamicAccumulator) -> (Vec<Fr>, Vec<Fr>, DiffProof) {
+        let (old, new) = (occurrences(self), occurrences(other));
+        let added = surplus(&new, &old);
+        let removed = surplus(&old, &new);
+        let proof = DiffProof {
+            old_acc_value: self.acc_value,
+            old_epoch: self.epoch(),
+            new_acc_value: other.acc_value,
+            new_epoch: other.epoch(),
+            added: added.clone(),
+            removed: removed.clone(),
+            params_id: params_id(),
+        };
+        (added, removed, proof)
+    }
+
+    /// Brings this accumulator to the new state of a diff computed from its current state.
+    pub fn apply_diff(&mut self, proof: &DiffProof) -> Result<()> {
+        self.ensure_mutable()?;
+        self.ensure_elements()?;
+        if proof.old_acc_value != self.acc_value || proof.old_epoch != self.epoch() {
+            bail!("The diff does not start at the current accumulator state");
+        }
+        if !proof.removed.iter().try_fold(true, |all, e| {
+            Ok::<_, anyhow::Error>(all && self.elements.contains(e)?)
+        })? {
+            bail!("The diff removes an element that is not accumulated");
+        }
+        if !proof.verify() {
+            bail!("Invalid diff proof");
+        }
+        self.apply_changes(
+            &proof.added,
+            &proof.removed,
+            proof.new_acc_value,
+            proof.new_epoch,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_diff() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        acc.add_batch(&[1, 2, 3]).unwrap();
+        let mut replica = acc.clone();
+        let old = acc.clone();
+
+        acc.delete(&2).unwrap();
+        acc.add_batch(&[4, 5]).unwrap();
+        acc.delete(&4).unwrap();
+
+        let (added, removed, proof) = old.diff(&acc);
+        assert_eq!(added.len(), 1);
+        assert_eq!(removed.len(), 1);
+        assert!(proof.verify());
+
+        let json = serde_json::to_string(&proof).unwrap();
+        let proof: DiffProof = serde_json::from_str(&json).unwrap();
+        replica.apply_diff(&proof).unwrap();
+        assert_eq!(replica, acc);
+        assert!(replica.verify_membership(&replica.prove_membership(&5).unwrap()));
+
+        // A diff that hides a change does not verify.
+        let mut forged = proof.clone();
+        forged.removed.clear();
+        assert!(!forged.verify());
+        assert!(old.clone().apply_diff(&forged).is_err());
+        // Nor does it apply twice.
+        assert!(replica.apply_diff(&proof).is_err());
+    }
+}
@@ -1,21 +1,32 @@
 //! Implements a dynamic cryptographic accumulator that supports additions and deletions.
 
 use super::{
-    utils::{digest_to_prime_field, xgcd},
-    Curve, Fr, G1Affine, G1Projective, G2Affine, G2Projective,
+    cache::WitnessCache,
+    capacity::CapacityWatch,
+    canonical::CanonicalForm,
+    commitment::params_id,
+    index::{ElementIndex, ElementSet},
+    dedup::{OpId, OpWindow},
+    handle::ElementHandle,
+    hardened,
+    metrics::{self, Op},
+    polymath::{self, RootsStrategy},
+    prepared::PreparedPublicParams,
+    public_prover, trapdoor,
+    utils::digest_to_prime_field,
+    Curve, Fr, G1Affine, G1Projective, G2Affine,
 };
-use crate::digest::Digestible;
+use crate::digest::{Digest, Digestible};
 use crate::{Acc1, MultiSet};
 use crate::acc::Accumulator;
 use anyhow::{anyhow, Result};
 use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
 use ark_ff::{Field, One, PrimeField, Zero};
-use ark_poly::{univariate::{DensePolynomial, DenseOrSparsePolynomial}, Polynomial, UVPolynomial};
-use std::collections::HashSet;
-use std::ops::Neg;
+use ark_poly::{univariate::{DensePolynomial, DenseOrSparsePolynomial}, Polynomial};
+use std::collections::{HashMap, HashSet};
 use serde::{Serialize, Deserialize};
 
-mod ark_serde {
+pub(crate) mod ark_serde {
     use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
     use serde::{Deserializer, Serializer};
 
@@ -41,83 +52,262 @@ mod ark_serde {
 }
 
 /// A proof that an 'add' operation was performed correctly.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AddProof {
+    #[serde(with = "ark_serde")]
     pub old_acc_value: G1Affine,
+    #[serde(with = "ark_serde")]
     pub new_acc_value: G1Affine,
-    pub element: Fr,
+    pub element: ElementHandle,
+    /// The epoch of the accumulator after the operation.
+    pub epoch: u64,
+    /// The caller's operation ID, for operations submitted with one.
+    #[serde(default)]
+    pub op_id: Option<OpId>,
+    /// The identifier of the public parameters the proof was generated under, see
+    /// [`params_id`].
+    pub params_id: Digest,
 }
 
 impl AddProof {
     /// Verifies that the new accumulator is the result of adding the element to the old one.
     /// It checks if e(new_acc, g2) == e(old_acc, g2^(s-element)).
+    /// No-op proofs (see [`AddProof::is_noop`]) do not verify.
     pub fn verify(&self) -> bool {
-        // Calculate g2^(s-element)
-        let s_minus_elem: Fr = *super::PRI_S - self.element;
-        let g2_s_minus_elem = super::G2_POWER.apply(&s_minus_elem);
+        if self.params_id != params_id() || !self.is_canonical() {
+            return false;
+        }
+        let params = PreparedPublicParams::global();
+        metrics::record_pairings(2);
+        params.pairing_check(
+            self.old_acc_value,
+            params.g2_s_minus(self.element.0),
+            -self.new_acc_value,
+        )
+    }
+
+    /// Verifies the proof as the operation that moved the accumulator to `epoch`.
+    pub fn verify_at(&self, epoch: u64) -> bool {
+        self.epoch == epoch && self.verify()
+    }
+
+    /// Returns true if the proof records an ignored duplicate add, which left the accumulator
+    /// unchanged (see [`DuplicatePolicy::Ignore`]).
+    pub fn is_noop(&self) -> bool {
+        self.old_acc_value == self.new_acc_value
+    }
+}
+
+/// A proof that a batch of elements was added with a single folded exponent.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchAddProof {
+    #[serde(with = "ark_serde")]
+    pub old_acc_value: G1Affine,
+    #[serde(with = "ark_serde")]
+    pub new_acc_value: G1Affine,
+    /// The elements actually added, in input order.
+    #[serde(with = "ark_serde")]
+    pub elements: Vec<Fr>,
+    /// The epoch of the accumulator after the batch. Every added element starts a new epoch.
+    pub epoch: u64,
+    /// The identifier of the public parameters the proof was generated under, see
+    /// [`params_id`].
+    pub params_id: Digest,
+}
 
-        let lhs = Curve::pairing(self.new_acc_value, G2Affine::prime_subgroup_generator());
-        let rhs = Curve::pairing(self.old_acc_value, g2_s_minus_elem);
+impl BatchAddProof {
+    /// Verifies that the new accumulator is the old one with all elements added.
+    /// It checks if e(new_acc, g2) == e(old_acc, g2^(product of (s-element))), committing to the
+    /// product with the published powers, so batches of more than [`super::MAX_DEGREE`]
+    /// elements do not verify.
+    pub fn verify(&self) -> bool {
+        if self.params_id != params_id() || !self.is_canonical() {
+            return false;
+        }
+        let g2_exponent = match super::commit_g2(polymath::from_roots(&self.elements)) {
+            Ok(g2_exponent) => g2_exponent,
+            Err(_) => return false,
+        };
+        metrics::record_pairings(2);
+        PreparedPublicParams::global().pairing_check(
+            self.old_acc_value,
+            g2_exponent.into(),
+            -self.new_acc_value,
+        )
+    }
 
-        lhs == rhs
+    /// Verifies the proof as the batch that moved the accumulator to `epoch`.
+    pub fn verify_at(&self, epoch: u64) -> bool {
+        self.epoch == epoch && self.verify()
     }
 }
 
 /// A proof that a 'delete' operation was performed correctly.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DeleteProof {
+    #[serde(with = "ark_serde")]
     pub old_acc_value: G1Affine,
+    #[serde(with = "ark_serde")]
     pub new_acc_value: G1Affine,
-    pub element: Fr,
+    pub element: ElementHandle,
+    /// The epoch of the accumulator after the operation.
+    pub epoch: u64,
+    /// The caller's operation ID, for operations submitted with one.
+    #[serde(default)]
+    pub op_id: Option<OpId>,
+    /// The identifier of the public parameters the proof was generated under, see
+    /// [`params_id`].
+    pub params_id: Digest,
 }
 
 impl DeleteProof {
     /// Verifies that the new accumulator is the result of deleting the element from the old one.
     /// It checks if e(new_acc, g2^(s-element)) == e(old_acc, g2).
     pub fn verify(&self) -> bool {
-        // Calculate g2^(s-element)
-        let s_minus_elem: Fr = *super::PRI_S - self.element;
-        let g2_s_minus_elem = super::G2_POWER.apply(&s_minus_elem);
-
-        let lhs = Curve::pairing(self.new_acc_value, g2_s_minus_elem);
-        let rhs = Curve::pairing(self.old_acc_value, G2Affine::prime_subgroup_generator());
+        if self.params_id != params_id() || !self.is_canonical() {
+            return false;
+        }
+        let params = PreparedPublicParams::global();
+        metrics::record_pairings(2);
+        params.pairing_check(
+            self.new_acc_value,
+            params.g2_s_minus(self.element.0),
+            -self.old_acc_value,
+        )
+    }
 
-        lhs == rhs
+    /// Verifies the proof as the operation that moved the accumulator to `epoch`.
+    pub fn verify_at(&self, epoch: u64) -> bool {
+        self.epoch == epoch && self.verify()
     }
 }
 
 /// A proof of membership for an element in the accumulator.
 /// The witness is an accumulator of the set without the element.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct MembershipProof {
+    #[serde(with = "ark_serde")]
     pub witness: G1Affine,
-    pub element: Fr,
+    pub element: ElementHandle,
+    /// The epoch of the accumulator state the proof was generated for.
+    pub epoch: u64,
+    /// The digest of the accumulator value the proof was generated for.
+    pub acc_digest: Digest,
+    /// The identifier of the public parameters the proof was generated under, see
+    /// [`params_id`].
+    pub params_id: Digest,
 }
 
 impl MembershipProof {
     /// Verifies that this proof is valid for the given accumulator value.
-    /// It checks if e(witness, g2^(s-element)) == e(accumulator, g2).
+    /// It checks that the proof was generated for this value and
+    /// if e(witness, g2^(s-element)) == e(accumulator, g2).
     pub fn verify(&self, accumulator: G1Affine) -> bool {
-        // Calculate g2^(s-element)
-        let s_minus_elem: Fr = *super::PRI_S - self.element;
-        let g2_s_minus_elem = super::G2_POWER.apply(&s_minus_elem);
+        if self.acc_digest != accumulator.to_digest()
+            || self.params_id != params_id()
+            || !self.is_canonical()
+        {
+            return false;
+        }
+
+        let params = PreparedPublicParams::global();
+        metrics::record_pairings(2);
+        params.pairing_check(self.witness, params.g2_s_minus(self.element.0), -accumulator)
+    }
+
+    /// Verifies the proof against the accumulator value published at `epoch`.
+    pub fn verify_at(&self, accumulator: G1Affine, epoch: u64) -> bool {
+        self.epoch == epoch && self.verify(accumulator)
+    }
 
-        let lhs = Curve::pairing(self.witness, g2_s_minus_elem);
-        let rhs = Curve::pairing(accumulator, G2Affine::prime_subgroup_generator());
+    /// Moves the witness forward over an add operation without the trapdoor.
+    /// Since old_acc = w^(s-y), the new witness w^(s-x) equals old_acc * w^(y-x).
+    pub fn update_on_add(&mut self, proof: &AddProof) -> Result<()> {
+        if self.acc_digest != proof.old_acc_value.to_digest() {
+            return Err(anyhow!("Add proof does not start at the witness's accumulator value"));
+        }
+        let y_minus_x = self.element.0 - proof.element.0;
+        let witness = proof.old_acc_value.into_projective()
+            + self.witness.mul(y_minus_x.into_repr());
+        self.witness = witness.into_affine();
+        self.epoch = proof.epoch;
+        self.acc_digest = proof.new_acc_value.to_digest();
+        Ok(())
+    }
 
-        lhs == rhs
+    /// Moves the witness forward over a delete operation without the trapdoor.
+    /// Since new_acc = w^((s-y)/(s-x)), the new witness w^(1/(s-x)) equals
+    /// (w / new_acc)^(1/(y-x)).
+    /// Returns an error if the deleted element is the witnessed one.
+    pub fn update_on_delete(&mut self, proof: &DeleteProof) -> Result<()> {
+        if self.acc_digest != proof.old_acc_value.to_digest() {
+            return Err(anyhow!("Delete proof does not start at the witness's accumulator value"));
+        }
+        let y_minus_x_inv = (self.element.0 - proof.element.0)
+            .inverse()
+            .ok_or_else(|| anyhow!("The witnessed element was deleted"))?;
+        let witness = self.witness.into_projective() - proof.new_acc_value.into_projective();
+        self.witness = witness.mul(y_minus_x_inv.into_repr()).into_affine();
+        self.epoch = proof.epoch;
+        self.acc_digest = proof.new_acc_value.to_digest();
+        Ok(())
     }
 }
 
 /// A proof of non-membership for an element in the accumulator.
 /// This proof shows that the element is not in the set represented by the accumulator.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct NonMembershipProof {
-    pub element: Fr,
+    pub element: ElementHandle,
     /// Witness for non-membership, g2^B(s)
+    #[serde(with = "ark_serde")]
     pub witness: G2Affine,
     /// g1^A(s), the other part of the proof
+    #[serde(with = "ark_serde")]
     pub g1_a: G1Affine,
+    /// The epoch of the accumulator state the proof was generated for.
+    pub epoch: u64,
+    /// The digest of the accumulator value the proof was generated for.
+    pub acc_digest: Digest,
+    /// The identifier of the public parameters the proof was generated under, see
+    /// [`params_id`].
+    pub params_id: Digest,
+}
+
+impl NonMembershipProof {
+    /// Verifies that this proof is valid for the given accumulator value.
+    /// It checks that the proof was generated for this value and
+    /// if e(accumulator, witness) * e(g1_a, g2^(s-element)) == e(g1, g2).
+    pub fn verify(&self, accumulator: G1Affine) -> bool {
+        if self.acc_digest != accumulator.to_digest()
+            || self.params_id != params_id()
+            || !self.is_canonical()
+        {
+            return false;
+        }
+
+        // Verification equation: e(Acc, witness) * e(g1_a, g2^(s-x)) == e(g1, g2)
+        // Here, witness = g2^B(s) and g1_a = g1^A(s).
+        // So, e(g1^P(s), g2^B(s)) * e(g1^A(s), g2^(s-x)) == e(g1, g2)
+        // which simplifies to e(g1,g2)^(B(s)*P(s) + A(s)*(s-x)) == e(g1,g2)^1
+        // This holds if B(s)*P(s) + A(s)*(s-x) = 1.
+
+        // e(g1, g2) is precomputed, so only the left-hand side needs a Miller loop.
+        let params = PreparedPublicParams::global();
+        let pairs = [
+            (accumulator.into(), self.witness.into()),
+            (self.g1_a.into(), params.g2_s_minus(self.element.0)),
+        ];
+
+        metrics::record_pairings(3);
+
+        Curve::product_of_pairings(&pairs) == params.e_g1_g2()
+    }
+
+    /// Verifies the proof against the accumulator value published at `epoch`.
+    pub fn verify_at(&self, accumulator: G1Affine, epoch: u64) -> bool {
+        self.epoch == epoch && self.verify(accumulator)
+    }
 }
 
 /// A proof that a given accumulator represents the intersection of two other accumulators.
@@ -137,6 +327,11 @@ pub struct IntersectionProof {
     /// g1^B(s), witness for coprimality of Q2 from A(X)Q1(X) + B(X)Q2(X) = 1
     #[serde(with = "ark_serde")]
     pub witness_coprime_b: G1Affine,
+    /// The epochs of the two accumulator states the proof was generated for.
+    pub epochs: (u64, u64),
+    /// The identifier of the public parameters the proof was generated under, see
+    /// [`params_id`].
+    pub params_id: Digest,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -146,8 +341,21 @@ pub struct UnionProof {
     pub intersection_proof: IntersectionProof,
 }
 
+impl UnionProof {
+    /// The epochs of the two accumulator states the proof was generated for.
+    pub fn epochs(&self) -> (u64, u64) {
+        self.intersection_proof.epochs
+    }
+
+    /// The identifier of the public parameters the proof was generated under, taken from the
+    /// embedded intersection proof.
+    pub fn params_id(&self) -> Digest {
+        self.intersection_proof.params_id
+    }
+}
+
 /// Represents the result of a query against the accumulator.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum QueryResult {
     /// The element is in the set, and here is the proof.
     Membership(MembershipProof),
@@ -155,6 +363,43 @@ pub enum QueryResult {
     NonMembership(NonMembershipProof),
 }
 
+/// The outcome of [`DynamicAccumulator::check_then_prove`]: the lookup is done, the proof is
+/// only built by [`Check::prove`].
+#[derive(Debug, Clone, Copy)]
+pub struct Check<'a> {
+    acc: &'a DynamicAccumulator,
+    element: i64,
+    present: bool,
+}
+
+impl Check<'_> {
+    pub fn is_member(&self) -> bool {
+        self.present
+    }
+
+    /// Builds the proof for the checked answer, as [`DynamicAccumulator::query`] would.
+    pub fn prove(&self) -> Result<QueryResult> {
+        // The accumulator is borrowed, so the answer still holds.
+        if self.present {
+            Ok(QueryResult::Membership(self.acc.prove_membership(&self.element)?))
+        } else {
+            Ok(QueryResult::NonMembership(self.acc.prove_non_membership(&self.element)?))
+        }
+    }
+}
+
+/// What [`DynamicAccumulator::add`] does when the element is already accumulated.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum DuplicatePolicy {
+    /// Return an error.
+    #[default]
+    Error,
+    /// Leave the accumulator unchanged and return a no-op proof for the current epoch.
+    Ignore,
+    /// Accumulate the element once more (multiset mode). Deletes remove one occurrence.
+    IncrementMultiplicity,
+}
+
 /// A dynamic cryptographic accumulator based on the Acc1 scheme.
 /// It maintains the accumulator value and the set of elements internally.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -162,7 +407,26 @@ pub struct DynamicAccumulator {
     /// The current accumulator value, g1^P(s).
     pub acc_value: G1Affine,
     /// The set of elements (as field elements).
-    elements: HashSet<Fr>,
+    pub(crate) elements: ElementSet,
+    /// The number of mutations applied so far. Every add or delete starts a new epoch.
+    epoch: u64,
+    /// Membership witnesses computed since the last mutation.
+    witness_cache: WitnessCache,
+    duplicate_policy: DuplicatePolicy,
+    /// Occurrence counts of the elements accumulated more than once.
+    pub(crate) multiplicities: HashMap<Fr, usize>,
+    /// Proofs of recent operations submitted with an operation ID.
+    pub(crate) op_window: OpWindow,
+    /// The original values of the elements added by value, if retained.
+    pub(crate) values: Option<HashMap<Fr, i64>>,
+    /// Whether mutations are rejected, see [`DynamicAccumulator::freeze`].
+    pub(crate) frozen: bool,
+    /// The ID of the prepared transaction holding the freeze, see [`DynamicAccumulator::prepare`].
+    pub(crate) transaction: Option<u64>,
+    /// The epoch since which only the value is known, see [`DynamicAccumulator::adopt_state`].
+    pub(crate) value_only: Option<u64>,
+    /// The utilization thresholds, see [`DynamicAccumulator::on_capacity_threshold`].
+    pub(crate) capacity_watch: CapacityWatch,
 }
 
 impl DynamicAccumulator {
@@ -173,44 +437,273 @@ impl DynamicAccumulator {
             acc_value: G1Projective::from(G1Affine::prime_subgroup_generator())
                 .mul(Fr::one().into_repr())
                 .into_affine(),
-            elements: HashSet::new(),
+            elements: ElementSet::default(),
+            epoch: 0,
+            witness_cache: WitnessCache::default(),
+            duplicate_policy: DuplicatePolicy::default(),
+            multiplicities: HashMap::new(),
+            op_window: OpWindow::default(),
+            values: None,
+            frozen: false,
+            transaction: None,
+            value_only: None,
+            capacity_watch: CapacityWatch::default(),
+        }
+    }
+
+    /// Creates a new, empty dynamic accumulator storing its elements in `index`.
+    /// Returns an error if the index is not empty.
+    pub fn with_index(index: impl ElementIndex + 'static) -> Result<Self> {
+        if !index.is_empty() {
+            return Err(anyhow!("The element index must be empty"));
+        }
+        Ok(Self {
+            elements: ElementSet::new(index),
+            ..Self::new()
+        })
+    }
+
+    /// Returns an independent copy for speculative updates. It takes constant time for the
+    /// element set if the accumulator was created with a
+    /// [`PersistentIndex`](super::index::PersistentIndex); other indexes, the multiplicities and
+    /// retained values are copied as by `clone`.
+    pub fn fork(&self) -> Self {
+        self.clone()
+    }
+
+    /// Creates a new, empty dynamic accumulator with the given duplicate-add policy.
+    pub fn with_duplicate_policy(policy: DuplicatePolicy) -> Self {
+        Self {
+            duplicate_policy: policy,
+            ..Self::new()
+        }
+    }
+
+    /// Returns the duplicate-add policy.
+    pub fn duplicate_policy(&self) -> DuplicatePolicy {
+        self.duplicate_policy
+    }
+
+    /// Returns how many times the element is accumulated.
+    pub fn multiplicity(&self, element: &i64) -> usize {
+        let fr_element = digest_to_prime_field(&element.to_digest());
+        match self.multiplicities.get(&fr_element) {
+            Some(count) => *count,
+            None => self.elements.has(&fr_element) as usize,
+        }
+    }
+
+    /// Returns true if some element is accumulated more than once.
+    pub(crate) fn is_multiset(&self) -> bool {
+        !self.multiplicities.is_empty()
+    }
+
+    /// Returns every accumulated element, repeated according to its multiplicity.
+    pub(crate) fn elements_with_multiplicity(&self) -> Vec<Fr> {
+        self.elements
+            .iter()
+            .flat_map(|e| {
+                let count = self.multiplicities.get(&e).copied().unwrap_or(1);
+                std::iter::repeat_n(e, count)
+            })
+            .collect()
+    }
+
+    fn insert_occurrence(&mut self, fr_element: Fr) -> Result<()> {
+        if !self.elements.insert(fr_element)? {
+            *self.multiplicities.entry(fr_element).or_insert(1) += 1;
+        }
+        Ok(())
+    }
+
+    fn remove_occurrence(&mut self, fr_element: &Fr) -> Result<()> {
+        match self.multiplicities.get_mut(fr_element) {
+            Some(count) if *count > 2 => *count -= 1,
+            Some(_) => {
+                self.multiplicities.remove(fr_element);
+            }
+            None => {
+                self.elements.remove(fr_element)?;
+                if let Some(values) = self.values.as_mut() {
+                    values.remove(fr_element);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces the state with one derived from it by already verified changes. If the index
+    /// fails part way, the value is still adopted but the element set is forgotten, as in
+    /// [`Self::replace_value`], and the error is returned.
+    pub(crate) fn apply_changes(&mut self, added: &[Fr], removed: &[Fr], acc_value: G1Affine, epoch: u64) -> Result<()> {
+        let updated = removed
+            .iter()
+            .try_for_each(|e| self.remove_occurrence(e))
+            .and_then(|_| added.iter().try_for_each(|e| self.insert_occurrence(*e)));
+        if let Err(e) = updated {
+            self.replace_value(acc_value, epoch);
+            return Err(e);
+        }
+        self.acc_value = acc_value;
+        self.epoch = epoch;
+        self.witness_cache.invalidate();
+        self.observe_capacity();
+        Ok(())
+    }
+
+    /// Replaces the state with a verified value whose element set is unknown, which is forgotten.
+    pub(crate) fn replace_value(&mut self, acc_value: G1Affine, epoch: u64) {
+        self.elements = ElementSet::default();
+        self.multiplicities.clear();
+        if let Some(values) = self.values.as_mut() {
+            values.clear();
         }
+        self.acc_value = acc_value;
+        self.epoch = epoch;
+        self.value_only = Some(epoch);
+        self.witness_cache.invalidate();
+        self.observe_capacity();
+    }
+
+    /// Returns the current epoch, i.e. the number of mutations applied so far.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Starts a new epoch without changing the set.
+    pub(crate) fn advance_epoch(&mut self) {
+        self.epoch += 1;
+    }
+
+    /// Sets the maximum number of membership witnesses cached between mutations.
+    /// A capacity of zero disables the cache.
+    pub fn set_witness_cache_capacity(&mut self, capacity: usize) {
+        self.witness_cache = WitnessCache::with_capacity(capacity);
+    }
+
+    /// Returns the number of membership witnesses currently cached.
+    pub fn cached_witnesses(&self) -> usize {
+        self.witness_cache.len()
     }
 
     /// Adds a new element to the accumulator and returns a proof of the operation.
-    /// If the element already exists, it returns an error.
+    /// If the element already exists, the outcome depends on the [`DuplicatePolicy`].
     /// The accumulator value is updated by scalar multiplying it with (s-element).
     pub fn add(&mut self, element: &i64) -> Result<AddProof> {
         let fr_element = digest_to_prime_field(&element.to_digest());
-        if self.elements.contains(&fr_element) {
-            return Err(anyhow!("Element already in accumulator"));
+        let proof = self.add_fr(fr_element)?;
+        self.retain_value(fr_element, *element);
+        Ok(proof)
+    }
+
+    /// Adds an already hashed element to the accumulator.
+    pub(crate) fn add_fr(&mut self, fr_element: Fr) -> Result<AddProof> {
+        self.ensure_mutable()?;
+        self.ensure_elements()?;
+        if self.elements.contains(&fr_element)? {
+            match self.duplicate_policy {
+                DuplicatePolicy::Error => return Err(anyhow!("Element already in accumulator")),
+                DuplicatePolicy::Ignore => {
+                    return Ok(AddProof {
+                        old_acc_value: self.acc_value,
+                        new_acc_value: self.acc_value,
+                        element: ElementHandle(fr_element),
+                        epoch: self.epoch,
+                        op_id: None,
+                        params_id: params_id(),
+                    })
+                }
+                DuplicatePolicy::IncrementMultiplicity => {}
+            }
         }
         let old_acc = self.acc_value;
 
         // Update accumulator value: acc' = acc^(s-element)
-        let s_minus_elem: Fr = *super::PRI_S - fr_element;
-        self.acc_value = self
-            .acc_value
-            .into_projective()
-            .mul(s_minus_elem.into_repr())
-            .into_affine();
+        let new_acc = trapdoor::signer().mul_s_minus(self.acc_value, &[fr_element])?;
 
         // Update the element set
-        self.elements.insert(fr_element);
+        self.insert_occurrence(fr_element)?;
+        self.acc_value = new_acc;
+        self.epoch += 1;
+        self.witness_cache.invalidate();
+        self.observe_capacity();
 
         Ok(AddProof {
             old_acc_value: old_acc,
             new_acc_value: self.acc_value,
-            element: fr_element,
+            element: ElementHandle(fr_element),
+            epoch: self.epoch,
+            op_id: None,
+            params_id: params_id(),
         })
     }
 
     /// Adds multiple elements to the accumulator in a batch.
-    pub fn add_batch(&mut self, elements: &[i64]) -> Result<()> {
-        for element in elements {
-            self.add(element)?;
+    /// The product of all (s-element) is folded in Fr first, so the accumulator value is
+    /// updated with a single scalar multiplication. See [`Self::add_chunk_fr`] for how
+    /// duplicates are handled.
+    pub fn add_batch(&mut self, elements: &[i64]) -> Result<BatchAddProof> {
+        metrics::timed(Op::BatchAdd, || {
+            let chunk: Vec<Fr> = elements
+                .iter()
+                .map(|e| digest_to_prime_field(&e.to_digest()))
+                .collect();
+            let proof = self.add_chunk_fr(&chunk)?;
+            for (fr_element, element) in chunk.iter().zip(elements) {
+                self.retain_value(*fr_element, *element);
+            }
+            Ok(proof)
+        })
+    }
+
+    /// Adds a chunk of already hashed elements with a single scalar multiplication. The chunk
+    /// is applied atomically: under [`DuplicatePolicy::Error`] nothing is added if any element
+    /// is already accumulated or repeated within the chunk. The epoch advances once per added
+    /// element.
+    pub(crate) fn add_chunk_fr(&mut self, chunk: &[Fr]) -> Result<BatchAddProof> {
+        let proof = self.batch_add_fr(chunk)?;
+        self.apply_changes(&proof.elements, &[], proof.new_acc_value, proof.epoch)?;
+        Ok(proof)
+    }
+
+    /// Computes the proof [`Self::add_chunk_fr`] would return, without changing the state.
+    pub(crate) fn batch_add_fr(&self, chunk: &[Fr]) -> Result<BatchAddProof> {
+        self.ensure_mutable()?;
+        self.ensure_elements()?;
+        let mut seen = HashSet::with_capacity(chunk.len());
+        let mut added = Vec::with_capacity(chunk.len());
+        for fr_element in chunk {
+            let duplicate = self.elements.contains(fr_element)? || !seen.insert(*fr_element);
+            match (duplicate, self.duplicate_policy) {
+                (true, DuplicatePolicy::Error) => {
+                    return Err(anyhow!("Element already in accumulator"))
+                }
+                (true, DuplicatePolicy::Ignore) => {}
+                _ => added.push(*fr_element),
+            }
         }
-        Ok(())
+
+        // acc' = acc^(product of (s-element))
+        let new_acc_value = trapdoor::signer().mul_s_minus(self.acc_value, &added)?;
+        Ok(BatchAddProof {
+            old_acc_value: self.acc_value,
+            new_acc_value,
+            epoch: self.epoch + added.len() as u64,
+            elements: added,
+            params_id: params_id(),
+        })
+    }
+
+    /// Computes the value and proof [`Self::add_batch`] would produce, without changing the
+    /// state, e.g. to propose the new value before committing. Calling `add_batch` with the same
+    /// elements on the unchanged accumulator then yields the same proof.
+    pub fn preview_add_batch(&self, elements: &[i64]) -> Result<(G1Affine, BatchAddProof)> {
+        let chunk: Vec<Fr> = elements
+            .iter()
+            .map(|e| digest_to_prime_field(&e.to_digest()))
+            .collect();
+        let proof = self.batch_add_fr(&chunk)?;
+        Ok((proof.new_acc_value, proof))
     }
 
     /// Updates an element in the accumulator from an old value to a new one.
@@ -228,74 +721,172 @@ impl DynamicAccumulator {
     /// The accumulator value is updated by scalar multiplying it with the inverse of (s-element).
     /// Returns an error if the element is not in the accumulator.
     pub fn delete(&mut self, element: &i64) -> Result<DeleteProof> {
-        let fr_element = digest_to_prime_field(&element.to_digest());
+        self.delete_fr(digest_to_prime_field(&element.to_digest()))
+    }
+
+    /// Adds one more occurrence of an already hashed element, whatever the duplicate policy.
+    pub(crate) fn add_occurrence_fr(&mut self, fr_element: Fr) -> Result<AddProof> {
+        let policy = std::mem::replace(
+            &mut self.duplicate_policy,
+            DuplicatePolicy::IncrementMultiplicity,
+        );
+        let proof = self.add_fr(fr_element);
+        self.duplicate_policy = policy;
+        proof
+    }
+
+    /// Deletes an already hashed element from the accumulator.
+    pub(crate) fn delete_fr(&mut self, fr_element: Fr) -> Result<DeleteProof> {
+        self.ensure_mutable()?;
+        self.ensure_elements()?;
         let old_acc = self.acc_value;
 
-        if !self.elements.contains(&fr_element) {
+        if !self.elements.contains(&fr_element)? {
             return Err(anyhow!("Element not in accumulator"));
         }
 
         // Update accumulator value: acc' = acc^((s-element)^-1)
-        let s_minus_elem: Fr = *super::PRI_S - fr_element;
-        let s_minus_elem_inv = s_minus_elem
-            .inverse()
-            .ok_or_else(|| anyhow!("Failed to compute inverse"))?;
-        self.acc_value = self
-            .acc_value
-            .into_projective()
-            .mul(s_minus_elem_inv.into_repr())
-            .into_affine();
+        let new_acc = trapdoor::signer().div_s_minus(self.acc_value, fr_element)?;
 
         // Update the element set
-        self.elements.remove(&fr_element);
+        self.remove_occurrence(&fr_element)?;
+        self.acc_value = new_acc;
+        self.epoch += 1;
+        self.witness_cache.invalidate();
+        self.observe_capacity();
 
         Ok(DeleteProof {
             old_acc_value: old_acc,
             new_acc_value: self.acc_value,
-            element: fr_element,
+            element: ElementHandle(fr_element),
+            epoch: self.epoch,
+            op_id: None,
+            params_id: params_id(),
         })
     }
 
+    /// Verifies an `AddProof` produced by a (possibly untrusted) manager against the local value
+    /// and then adopts its `new_acc_value`, tracking the added element.
+    /// This lets a mirror follow a remote manager without the trapdoor.
+    /// Returns an error, leaving the state untouched, if the proof does not start at the local
+    /// value or fails verification.
+    pub fn apply_add_proof(&mut self, proof: &AddProof) -> Result<()> {
+        self.ensure_mutable()?;
+        self.ensure_elements()?;
+        if proof.is_noop() {
+            if proof.old_acc_value != self.acc_value
+                || proof.epoch != self.epoch
+                || !self.elements.contains(&proof.element.0)?
+            {
+                return Err(anyhow!("Invalid no-op add proof"));
+            }
+            return Ok(());
+        }
+        if proof.old_acc_value != self.acc_value {
+            return Err(anyhow!("Add proof does not start at the local accumulator value"));
+        }
+        if proof.epoch != self.epoch + 1 {
+            return Err(anyhow!("Add proof is not for the next epoch"));
+        }
+        if !proof.verify() {
+            return Err(anyhow!("Invalid add proof"));
+        }
+        self.insert_occurrence(proof.element.0)?;
+        self.acc_value = proof.new_acc_value;
+        self.epoch = proof.epoch;
+        self.witness_cache.invalidate();
+        self.observe_capacity();
+        Ok(())
+    }
+
+    /// Verifies a `DeleteProof` produced by a (possibly untrusted) manager against the local
+    /// value and then adopts its `new_acc_value`, dropping the deleted element.
+    /// Returns an error, leaving the state untouched, if the proof does not start at the local
+    /// value or fails verification.
+    pub fn apply_delete_proof(&mut self, proof: &DeleteProof) -> Result<()> {
+        self.ensure_mutable()?;
+        self.ensure_elements()?;
+        if proof.old_acc_value != self.acc_value {
+            return Err(anyhow!("Delete proof does not start at the local accumulator value"));
+        }
+        if proof.epoch != self.epoch + 1 {
+            return Err(anyhow!("Delete proof is not for the next epoch"));
+        }
+        if !proof.verify() {
+            return Err(anyhow!("Invalid delete proof"));
+        }
+        self.remove_occurrence(&proof.element.0)?;
+        self.acc_value = proof.new_acc_value;
+        self.epoch = proof.epoch;
+        self.witness_cache.invalidate();
+        self.observe_capacity();
+        Ok(())
+    }
+
     /// Generates a membership proof for a given element.
     /// The proof's witness is an accumulator for the set of all other elements.
     /// Returns an error if the element is not in the accumulator.
     pub fn prove_membership(&self, element: &i64) -> Result<MembershipProof> {
-        let fr_element = digest_to_prime_field(&element.to_digest());
+        metrics::timed(Op::ProveMembership, || {
+            self.prove_membership_fr(digest_to_prime_field(&element.to_digest()))
+        })
+    }
 
-        if !self.elements.contains(&fr_element) {
+    /// Generates a membership proof for an already hashed element.
+    pub(crate) fn prove_membership_fr(&self, fr_element: Fr) -> Result<MembershipProof> {
+        self.ensure_elements()?;
+        if cfg!(feature = "hardened") {
+            return hardened::prove_membership(self, fr_element);
+        }
+        if !self.elements.contains(&fr_element)? {
             return Err(anyhow!(
                 "Cannot prove membership for an element not in the set"
             ));
         }
 
+        if let Some(witness) = self.witness_cache.get(&self.acc_value, &fr_element) {
+            return Ok(MembershipProof {
+                witness,
+                element: ElementHandle(fr_element),
+                epoch: self.epoch,
+                acc_digest: self.acc_value.to_digest(),
+                params_id: params_id(),
+            });
+        }
+
         // Calculate witness: acc^((s-element)^-1)
-        let s_minus_elem: Fr = *super::PRI_S - fr_element;
-        let s_minus_elem_inv = s_minus_elem
-            .inverse()
-            .ok_or_else(|| anyhow!("Failed to compute inverse"))?;
-        let witness = self
-            .acc_value
-            .into_projective()
-            .mul(s_minus_elem_inv.into_repr())
-            .into_affine();
+        let witness = trapdoor::signer().div_s_minus(self.acc_value, fr_element)?;
+        self.witness_cache.insert(self.acc_value, fr_element, witness);
 
         Ok(MembershipProof {
             witness,
-            element: fr_element,
+            element: ElementHandle(fr_element),
+            epoch: self.epoch,
+            acc_digest: self.acc_value.to_digest(),
+            params_id: params_id(),
         })
     }
 
-    /// Verifies a membership proof against the current accumulator value.
+    /// Verifies a membership proof against the current accumulator value and epoch.
     pub fn verify_membership(&self, proof: &MembershipProof) -> bool {
-        proof.verify(self.acc_value)
+        metrics::timed(Op::VerifyMembership, || proof.verify_at(self.acc_value, self.epoch))
     }
 
     /// Generates a non-membership proof for a given element.
     /// Returns an error if the element IS in the accumulator.
     pub fn prove_non_membership(&self, element: &i64) -> Result<NonMembershipProof> {
-        let fr_element = digest_to_prime_field(&element.to_digest());
+        metrics::timed(Op::ProveNonMembership, || {
+            self.prove_non_membership_fr(digest_to_prime_field(&element.to_digest()))
+        })
+    }
 
-        if self.elements.contains(&fr_element) {
+    /// Generates a non-membership proof for an already hashed element.
+    pub(crate) fn prove_non_membership_fr(&self, fr_element: Fr) -> Result<NonMembershipProof> {
+        self.ensure_elements()?;
+        if cfg!(feature = "hardened") {
+            return hardened::prove_non_membership(self, fr_element);
+        }
+        if self.elements.contains(&fr_element)? {
             return Err(anyhow!(
                 "Cannot prove non-membership for an element in the set"
             ));
@@ -309,82 +900,54 @@ impl DynamicAccumulator {
         // which is B(s)*P(s) + A(s)*(s-x) = 1.
 
         // 1. Construct the accumulator polynomial P(X) = product(X-e_i).
-        let mut p_poly = DensePolynomial::from_coefficients_vec(vec![Fr::one()]);
-        for elem in &self.elements {
-            // X - e
-            let e_poly = DensePolynomial::from_coefficients_vec(vec![elem.neg(), Fr::one()]);
-            p_poly = &p_poly * &e_poly;
-        }
+        let p_poly = self.characteristic_poly();
 
-        // 2. Construct the polynomial for the non-member, Q(X) = X-x.
-        let q_poly = DensePolynomial::from_coefficients_vec(vec![fr_element.neg(), Fr::one()]); // X-x
-
-        // 3. Run XGCD on Q(X) and P(X) to find A(X) and B(X).
-        // We want A(X)*Q(X) + B(X)*P(X) = 1
-        if let Some((gcd, a_poly, b_poly)) = xgcd(q_poly, p_poly.clone()) {
-            // GCD must be a non-zero constant for the proof to be valid.
-            if !gcd.is_zero() && gcd.degree() == 0 {
-                // The equation is a_poly*Q(X) + b_poly*P(X) = gcd.
-                // We need it to be 1, so we must divide by the constant value of gcd.
-                let gcd_val = gcd.coeffs.first().cloned().unwrap_or_else(Fr::one);
-                let gcd_inv = gcd_val
-                    .inverse()
-                    .ok_or_else(|| anyhow!("Failed to compute gcd inverse"))?;
-
-                let a_poly_norm = DensePolynomial::from_coefficients_vec(
-                    a_poly.coeffs.iter().map(|c| *c * gcd_inv).collect(),
-                );
-                let b_poly_norm = DensePolynomial::from_coefficients_vec(
-                    b_poly.coeffs.iter().map(|c| *c * gcd_inv).collect(),
-                );
-
-                // 4. Evaluate the normalized polynomials at the secret `s`.
-                let a_s = a_poly_norm.evaluate(&*super::PRI_S);
-                let b_s = b_poly_norm.evaluate(&*super::PRI_S);
-
-                // 5. Compute the witness parts: g1^A(s) and g2^B(s)
-                let g1_a = G1Projective::prime_subgroup_generator()
-                    .mul(a_s.into_repr())
-                    .into_affine();
-                let witness_b = G2Projective::prime_subgroup_generator()
-                    .mul(b_s.into_repr())
-                    .into_affine();
-
-                return Ok(NonMembershipProof {
-                    element: fr_element,
-                    witness: witness_b, // This is g2^B(s)
-                    g1_a,               // This is g1^A(s)
-                });
-            }
-        }
+        self.non_membership_from_poly(&p_poly, fr_element)
+    }
 
-        Err(anyhow!("Failed to create non-membership proof"))
+    /// Builds the characteristic polynomial P(X) = product(X-e_i) of the element (multi)set.
+    pub(crate) fn characteristic_poly(&self) -> DensePolynomial<Fr> {
+        self.characteristic_poly_with(RootsStrategy::default())
     }
 
-    /// Verifies a non-membership proof against the current accumulator value.
-    pub fn verify_non_membership(&self, proof: &NonMembershipProof) -> bool {
-        // Verification equation: e(Acc, witness) * e(g1_a, g2^(s-x)) == e(g1, g2)
-        // Here, witness = g2^B(s) and g1_a = g1^A(s).
-        // So, e(g1^P(s), g2^B(s)) * e(g1^A(s), g2^(s-x)) == e(g1, g2)
-        // which simplifies to e(g1,g2)^(B(s)*P(s) + A(s)*(s-x)) == e(g1,g2)^1
-        // This holds if B(s)*P(s) + A(s)*(s-x) = 1.
+    /// [`Self::characteristic_poly`] expanded with the given strategy.
+    pub(crate) fn characteristic_poly_with(&self, strategy: RootsStrategy) -> DensePolynomial<Fr> {
+        let p_poly = polymath::from_roots_with(&self.elements_with_multiplicity(), strategy);
+        metrics::record_degree(p_poly.degree());
+        p_poly
+    }
 
-        // 1. Calculate g2^(s-x)
-        let s_minus_x = *super::PRI_S - proof.element;
-        let g2_s_minus_x = super::G2_POWER.apply(&s_minus_x);
+    /// Generates a non-membership proof for `fr_element` against a precomputed characteristic
+    /// polynomial, so callers proving many non-members can build P(X) only once.
+    pub(crate) fn non_membership_from_poly(
+        &self,
+        p_poly: &DensePolynomial<Fr>,
+        fr_element: Fr,
+    ) -> Result<NonMembershipProof> {
+        // 2. Find A(X), B(X) with A(X)*(X-x) + B(X)*P(X) = 1 and commit to them with the
+        // public powers g^(s^i). They only exist if x is not a root of P(X).
+        if let Some((g1_a, witness_b)) = public_prover::non_membership_witness(p_poly, fr_element) {
+            return Ok(NonMembershipProof {
+                element: ElementHandle(fr_element),
+                witness: witness_b, // This is g2^B(s)
+                g1_a,               // This is g1^A(s)
+                epoch: self.epoch,
+                acc_digest: self.acc_value.to_digest(),
+                params_id: params_id(),
+            });
+        }
 
-        // 2. Calculate the pairings
-        let lhs1 = Curve::pairing(self.acc_value, proof.witness);
-        let lhs2 = Curve::pairing(proof.g1_a, g2_s_minus_x);
-        let rhs = Curve::pairing(
-            G1Affine::prime_subgroup_generator(),
-            G2Affine::prime_subgroup_generator(),
-        );
+        Err(anyhow!("Failed to create non-membership proof"))
+    }
 
-        lhs1 * lhs2 == rhs
+    /// Verifies a non-membership proof against the current accumulator value and epoch.
+    pub fn verify_non_membership(&self, proof: &NonMembershipProof) -> bool {
+        metrics::timed(Op::VerifyNonMembership, || {
+            proof.verify_at(self.acc_value, self.epoch)
+        })
     }
 
-    /// Returns the number of elements in the accumulator.
+    /// Returns the number of distinct elements in the accumulator.
     pub fn len(&self) -> usize {
         self.elements.len()
     }
@@ -397,32 +960,63 @@ impl DynamicAccumulator {
     /// Returns a vector of field elements (Fr) contained in the accumulator.
     /// Note: Original application values cannot be recovered from Fr digests.
     pub fn elements_fr(&self) -> Vec<Fr> {
-        self.elements.iter().cloned().collect()
+        self.elements.iter().collect()
     }
 
     /// Queries the accumulator for a given element and returns a cryptographic proof
     /// of either membership or non-membership.
-    pub fn query(&self, element: &i64) -> QueryResult {
+    /// Returns an error if the element set is unknown or the element index fails a lookup.
+    pub fn query(&self, element: &i64) -> Result<QueryResult> {
+        self.ensure_elements()?;
         let fr_element = digest_to_prime_field(&element.to_digest());
-        if self.elements.contains(&fr_element) {
-            // This unwrap is safe because we've just checked for the element's existence.
-            let proof = self.prove_membership(element).unwrap();
-            QueryResult::Membership(proof)
+        if self.elements.contains(&fr_element)? {
+            Ok(QueryResult::Membership(self.prove_membership(element)?))
         } else {
-            // This unwrap is safe because we've just checked for the element's non-existence.
-            let proof = self.prove_non_membership(element).unwrap();
-            QueryResult::NonMembership(proof)
+            Ok(QueryResult::NonMembership(self.prove_non_membership(element)?))
         }
     }
 
+    /// Returns whether the element is in the set with a set lookup only, for callers that need
+    /// the answer but not the proof [`Self::query`] always builds.
+    /// Returns an error if the element set is unknown or the element index fails a lookup.
+    pub fn check(&self, element: &i64) -> Result<bool> {
+        self.ensure_elements()?;
+        self.elements
+            .contains(&digest_to_prime_field(&element.to_digest()))
+    }
+
+    /// Looks the element up now and builds the proof only if [`Check::prove`] is called.
+    pub fn check_then_prove(&self, element: &i64) -> Result<Check<'_>> {
+        Ok(Check {
+            acc: self,
+            element: *element,
+            present: self.check(element)?,
+        })
+    }
+
     /// Computes the intersection of this accumulator with another accumulator and generates a proof.
     /// Returns the intersection accumulator and a proof that it represents the intersection.
     /// This uses the Bézout identity: A(X)*P1(X) + B(X)*P2(X) = P_intersect(X)
+    /// Returns an error if either accumulator holds an element more than once.
     pub fn prove_intersection(&self, other: &DynamicAccumulator) -> Result<(DynamicAccumulator, IntersectionProof)> {
+        metrics::timed(Op::ProveIntersection, || {
+            self.prove_intersection_with(other, RootsStrategy::default())
+        })
+    }
+
+    /// [`Self::prove_intersection`] with the characteristic polynomials expanded with the given
+    /// strategy.
+    pub(crate) fn prove_intersection_with(&self, other: &DynamicAccumulator, strategy: RootsStrategy) -> Result<(DynamicAccumulator, IntersectionProof)> {
+        self.ensure_elements()?;
+        other.ensure_elements()?;
+        if !self.multiplicities.is_empty() || !other.multiplicities.is_empty() {
+            return Err(anyhow!("Set operations are not supported on multisets"));
+        }
+
         // 1. Compute the actual intersection of the two sets
-        let intersection_elements: std::collections::HashSet<Fr> = self.elements
-            .intersection(&other.elements)
-            .cloned()
+        let intersection_elements: ElementSet = self.elements
+            .intersection(&other.elements)?
+            .into_iter()
             .collect();
 
         // 2. Create the intersection accumulator
@@ -430,34 +1024,20 @@ impl DynamicAccumulator {
         intersection_acc.elements = intersection_elements;
         
         // Calculate the intersection accumulator value
-        let mut acc_value = G1Projective::from(G1Affine::prime_subgroup_generator());
-        for elem in &intersection_acc.elements {
-            let s_minus_elem = *super::PRI_S - elem;
-            acc_value = acc_value.mul(s_minus_elem.into_repr());
-        }
-        intersection_acc.acc_value = acc_value.into_affine();
+        intersection_acc.acc_value = trapdoor::signer().mul_s_minus(
+            G1Affine::prime_subgroup_generator(),
+            &intersection_acc.elements_fr(),
+        )?;
 
         // 3. Construct polynomials for each set
         // P1(X) = product(X - e_i) for elements in self
-        let mut p1_poly = DensePolynomial::from_coefficients_vec(vec![Fr::one()]);
-        for elem in &self.elements {
-            let e_poly = DensePolynomial::from_coefficients_vec(vec![elem.neg(), Fr::one()]);
-            p1_poly = &p1_poly * &e_poly;
-        }
+        let p1_poly = polymath::from_roots_with(&self.elements_fr(), strategy);
 
         // P2(X) = product(X - e_i) for elements in other
-        let mut p2_poly = DensePolynomial::from_coefficients_vec(vec![Fr::one()]);
-        for elem in &other.elements {
-            let e_poly = DensePolynomial::from_coefficients_vec(vec![elem.neg(), Fr::one()]);
-            p2_poly = &p2_poly * &e_poly;
-        }
+        let p2_poly = polymath::from_roots_with(&other.elements_fr(), strategy);
 
         // P_intersect(X) = product(X - e_i) for elements in intersection
-        let mut p_intersect_poly = DensePolynomial::from_coefficients_vec(vec![Fr::one()]);
-        for elem in &intersection_acc.elements {
-            let e_poly = DensePolynomial::from_coefficients_vec(vec![elem.neg(), Fr::one()]);
-            p_intersect_poly = &p_intersect_poly * &e_poly;
-        }
+        let p_intersect_poly = polymath::from_roots_with(&intersection_acc.elements_fr(), strategy);
 
         // 4. Use extended GCD to find Bézout coefficients
         // We need to find A(X) and B(X) such that A(X)*P1(X) + B(X)*P2(X) = P_intersect(X)
@@ -483,53 +1063,26 @@ impl DynamicAccumulator {
             return Err(anyhow!("P_intersect does not divide P2 - invalid intersection"));
         }
 
-        // 5. Evaluate the quotient polynomials at the secret s
-        let q1_s = q1_poly.evaluate(&*super::PRI_S);
-        let q2_s = q2_poly.evaluate(&*super::PRI_S);
-
-        // 6. Compute the witnesses for quotients: g2^Q1(s) and g2^Q2(s)
-        let witness_a = G2Projective::prime_subgroup_generator()
-            .mul(q1_s.into_repr())
-            .into_affine();
-        let witness_b = G2Projective::prime_subgroup_generator()
-            .mul(q2_s.into_repr())
-            .into_affine();
+        // 5-6. Commit to the quotient polynomials with the published powers: g2^Q1(s) and g2^Q2(s)
+        let witness_a = super::commit_g2(q1_poly.clone())?;
+        let witness_b = super::commit_g2(q2_poly.clone())?;
 
         // 7. Prove that Q1(X) and Q2(X) are coprime using XGCD
         // We find A(X), B(X) such that A(X)Q1(X) + B(X)Q2(X) = 1
-        if let Some((gcd, a_poly, b_poly)) = xgcd(q1_poly, q2_poly) {
-            if !gcd.is_zero() && gcd.degree() == 0 {
-                let gcd_val = gcd.coeffs.first().cloned().unwrap_or_else(Fr::one);
-                let gcd_inv = gcd_val
-                    .inverse()
-                    .ok_or_else(|| anyhow!("Failed to compute gcd inverse for coprimality proof"))?;
-                
-                let a_poly_norm = DensePolynomial::from_coefficients_vec(
-                    a_poly.coeffs.iter().map(|c| *c * gcd_inv).collect(),
-                );
-                let b_poly_norm = DensePolynomial::from_coefficients_vec(
-                    b_poly.coeffs.iter().map(|c| *c * gcd_inv).collect(),
-                );
-
-                let a_s = a_poly_norm.evaluate(&*super::PRI_S);
-                let b_s = b_poly_norm.evaluate(&*super::PRI_S);
-
-                let witness_coprime_a = G1Projective::prime_subgroup_generator()
-                    .mul(a_s.into_repr())
-                    .into_affine();
-                let witness_coprime_b = G1Projective::prime_subgroup_generator()
-                    .mul(b_s.into_repr())
-                    .into_affine();
-                
-                let proof = IntersectionProof {
-                    witness_a,
-                    witness_b,
-                    witness_coprime_a,
-                    witness_coprime_b,
-                };
-        
-                return Ok((intersection_acc, proof));
-            }
+        if let Some((a_poly_norm, b_poly_norm)) = polymath::bezout_coprime(&q1_poly, &q2_poly) {
+            let witness_coprime_a = super::commit_g1(a_poly_norm)?;
+            let witness_coprime_b = super::commit_g1(b_poly_norm)?;
+            
+            let proof = IntersectionProof {
+                witness_a,
+                witness_b,
+                witness_coprime_a,
+                witness_coprime_b,
+                epochs: (self.epoch, other.epoch),
+                params_id: params_id(),
+            };
+    
+            return Ok((intersection_acc, proof));
         }
         
         Err(anyhow!("Failed to create intersection proof, quotients might not be coprime"))
@@ -564,26 +1117,39 @@ impl DynamicAccumulator {
         intersection_value: G1Affine,
         proof: &IntersectionProof,
     ) -> bool {
+        if proof.params_id != params_id() || !proof.is_canonical() {
+            return false;
+        }
         // Verification equation 1: e(acc1, g2) == e(intersection, witness_a)
         // This verifies that acc1 = intersection^Q1(s), i.e., P1(s) = Q1(s) * P_intersect(s)
-        let lhs1 = Curve::pairing(acc1_value, G2Affine::prime_subgroup_generator());
-        let rhs1 = Curve::pairing(intersection_value, proof.witness_a);
+        let params = PreparedPublicParams::global();
+        let eq1 = params.pairing_check(intersection_value, proof.witness_a.into(), -acc1_value);
 
         // Verification equation 2: e(acc2, g2) == e(intersection, witness_b)  
         // This verifies that acc2 = intersection^Q2(s), i.e., P2(s) = Q2(s) * P_intersect(s)
-        let lhs2 = Curve::pairing(acc2_value, G2Affine::prime_subgroup_generator());
-        let rhs2 = Curve::pairing(intersection_value, proof.witness_b);
+        let eq2 = params.pairing_check(intersection_value, proof.witness_b.into(), -acc2_value);
 
         // Verification equation 3: e(g1^A(s), g2^Q1(s)) * e(g1^B(s), g2^Q2(s)) == e(g1, g2)
         // This verifies that A(s)Q1(s) + B(s)Q2(s) = 1, proving Q1 and Q2 are coprime.
-        let coprimality_lhs1 = Curve::pairing(proof.witness_coprime_a, proof.witness_a);
-        let coprimality_lhs2 = Curve::pairing(proof.witness_coprime_b, proof.witness_b);
-        let coprimality_rhs = Curve::pairing(
-            G1Affine::prime_subgroup_generator(),
-            G2Affine::prime_subgroup_generator(),
-        );
+        let coprimality_pairs = [
+            (proof.witness_coprime_a.into(), proof.witness_a.into()),
+            (proof.witness_coprime_b.into(), proof.witness_b.into()),
+        ];
 
-        lhs1 == rhs1 && lhs2 == rhs2 && (coprimality_lhs1 * coprimality_lhs2 == coprimality_rhs)
+        eq1 && eq2 && Curve::product_of_pairings(&coprimality_pairs) == params.e_g1_g2()
+    }
+
+    /// Same as [`Self::verify_intersection`] against the accumulator values published at
+    /// `epochs`.
+    pub fn verify_intersection_at(
+        acc1_value: G1Affine,
+        acc2_value: G1Affine,
+        intersection_value: G1Affine,
+        proof: &IntersectionProof,
+        epochs: (u64, u64),
+    ) -> bool {
+        proof.epochs == epochs
+            && Self::verify_intersection(acc1_value, acc2_value, intersection_value, proof)
     }
 
     /// One-shot API: compute intersection, return query result on it, the proof, the accumulator, and elements.
@@ -594,7 +1160,7 @@ impl DynamicAccumulator {
         element: &i64,
     ) -> Result<(QueryResult, IntersectionProof, DynamicAccumulator, Vec<Fr>)> {
         let (intersection_acc, proof) = self.prove_intersection(other)?;
-        let q = intersection_acc.query(element);
+        let q = intersection_acc.query(element)?;
         let elements = intersection_acc.elements_fr();
         Ok((q, proof, intersection_acc, elements))
     }
@@ -631,9 +1197,9 @@ impl DynamicAccumulator {
         let (intersection_acc, intersection_proof) = self.prove_intersection(other)?;
 
         // 2. Compute the union of the element sets cryptographically.
-        let union_elements: std::collections::HashSet<Fr> = self.elements
-            .union(&other.elements)
-            .cloned()
+        let union_elements: ElementSet = self.elements
+            .iter()
+            .chain(other.elements.iter())
             .collect();
         
         // 3. Create the union accumulator from the union elements.
@@ -641,12 +1207,10 @@ impl DynamicAccumulator {
         union_acc.elements = union_elements;
         
         // Calculate the cryptographic value of the union accumulator.
-        let mut acc_value = G1Projective::from(G1Affine::prime_subgroup_generator());
-        for elem in &union_acc.elements {
-            let s_minus_elem = *super::PRI_S - elem;
-            acc_value = acc_value.mul(s_minus_elem.into_repr());
-        }
-        union_acc.acc_value = acc_value.into_affine();
+        union_acc.acc_value = trapdoor::signer().mul_s_minus(
+            G1Affine::prime_subgroup_generator(),
+            &union_acc.elements_fr(),
+        )?;
 
         // 4. Construct the union proof using the intersection proof data.
         let union_proof = UnionProof {
@@ -729,6 +1293,9 @@ impl DynamicAccumulator {
         union_acc_value: G1Affine,
         proof: &UnionProof,
     ) -> bool {
+        if !proof.is_canonical() {
+            return false;
+        }
         // 1. Verify the embedded intersection proof. This is the cryptographic core of the verification.
         let is_intersection_valid = Self::verify_intersection(
             acc1_value,
@@ -741,14 +1308,26 @@ impl DynamicAccumulator {
             return false;
         }
 
-        // 2. Verify the accumulator relationship: P_A(s) + P_B(s) = P_union(s) + P_intersection(s)
-        // This is checked in the elliptic curve group by point addition:
-        // acc_A + acc_B = acc_union + acc_intersection
-        // In projective coordinates for efficient computation:
-        let lhs = acc1_value.into_projective() + acc2_value.into_projective();
-        let rhs = union_acc_value.into_projective() + proof.intersection_acc_value.into_projective();
+        // 2. Verify the accumulator relationship: P_union(X) = P_A(X) * Q2(X),
+        // where Q2(X) = P_B(X) / P_intersection(X) is already committed as witness_b.
+        // This is checked by pairing: e(acc_union, g2) == e(acc_A, g2^Q2(s))
+        PreparedPublicParams::global().pairing_check(
+            acc1_value,
+            proof.intersection_proof.witness_b.into(),
+            -union_acc_value,
+        )
+    }
 
-        lhs == rhs
+    /// Same as [`Self::verify_union`] against the accumulator values published at `epochs`.
+    pub fn verify_union_at(
+        acc1_value: G1Affine,
+        acc2_value: G1Affine,
+        union_acc_value: G1Affine,
+        proof: &UnionProof,
+        epochs: (u64, u64),
+    ) -> bool {
+        proof.epochs() == epochs
+            && Self::verify_union(acc1_value, acc2_value, union_acc_value, proof)
     }
 
     /// Verifier API: verifies the union proof using provided clear-text union and intersection values.
@@ -825,6 +1404,7 @@ mod tests {
         // Verify proofs
         assert!(add_proof1.verify());
         assert!(add_proof2.verify());
+        assert!(add_proof2.verify_at(2) && !add_proof2.verify_at(1));
         assert!(add_proof3_res.is_err()); // Should fail to add duplicate
 
         let set = MultiSet::from_vec(vec![1i64, 2]);
@@ -834,10 +1414,10 @@ mod tests {
         assert_eq!(dyn_acc.elements.len(), 2);
         assert!(dyn_acc
             .elements
-            .contains(&digest_to_prime_field(&1i64.to_digest())));
+            .has(&digest_to_prime_field(&1i64.to_digest())));
         assert!(dyn_acc
             .elements
-            .contains(&digest_to_prime_field(&2i64.to_digest())));
+            .has(&digest_to_prime_field(&2i64.to_digest())));
     }
 
     #[test]
@@ -850,13 +1430,14 @@ mod tests {
         // Delete 1
         let delete_proof1 = dyn_acc.delete(&1i64).unwrap();
         assert!(delete_proof1.verify());
+        assert!(delete_proof1.verify_at(3) && !delete_proof1.verify_at(2));
 
         let set1 = MultiSet::from_vec(vec![2i64]);
         let static_acc1 = Acc1::cal_acc_g1_sk(&set1);
         assert_eq!(dyn_acc.acc_value, static_acc1);
         assert!(!dyn_acc
             .elements
-            .contains(&digest_to_prime_field(&1i64.to_digest())));
+            .has(&digest_to_prime_field(&1i64.to_digest())));
 
         // Try to delete 1 again (should fail)
         assert!(dyn_acc.delete(&1i64).is_err());
@@ -874,6 +1455,106 @@ mod tests {
         assert!(dyn_acc.delete(&3i64).is_err());
     }
 
+    #[test]
+    fn test_add_batch_proof() {
+        init_logger();
+        let mut dyn_acc = DynamicAccumulator::new();
+        dyn_acc.add(&1i64).unwrap();
+        let proof = dyn_acc.add_batch(&[2i64, 3, 4]).unwrap();
+        assert!(proof.verify());
+        assert_eq!(proof.elements.len(), 3);
+        assert_eq!(proof.epoch, 4);
+        assert_eq!(dyn_acc.epoch(), 4);
+        let static_acc = Acc1::cal_acc_g1_sk(&MultiSet::from_vec(vec![1i64, 2, 3, 4]));
+        assert_eq!(dyn_acc.acc_value, static_acc);
+
+        let mut tampered = proof.clone();
+        tampered.elements.pop();
+        assert!(!tampered.verify());
+
+        // The batch is atomic: a duplicate leaves the accumulator untouched.
+        assert!(dyn_acc.add_batch(&[5i64, 1]).is_err());
+        assert!(dyn_acc.add_batch(&[5i64, 5]).is_err());
+        assert_eq!(dyn_acc.acc_value, static_acc);
+    }
+
+    #[test]
+    fn test_preview_add_batch() {
+        init_logger();
+        let mut dyn_acc = DynamicAccumulator::new();
+        dyn_acc.add(&1).unwrap();
+        let before = dyn_acc.clone();
+
+        let (value, proof) = dyn_acc.preview_add_batch(&[2, 3]).unwrap();
+        assert!(proof.verify());
+        assert_eq!(dyn_acc, before);
+        assert!(dyn_acc.preview_add_batch(&[1]).is_err());
+
+        assert_eq!(dyn_acc.add_batch(&[2, 3]).unwrap(), proof);
+        assert_eq!(dyn_acc.acc_value, value);
+        assert_eq!(dyn_acc.epoch(), proof.epoch);
+    }
+
+    #[test]
+    fn test_duplicate_policy() {
+        init_logger();
+        let mut ignoring = DynamicAccumulator::with_duplicate_policy(DuplicatePolicy::Ignore);
+        ignoring.add(&1i64).unwrap();
+        let mut mirror = ignoring.clone();
+        let noop = ignoring.add(&1i64).unwrap();
+        assert!(noop.is_noop());
+        assert!(!noop.verify());
+        assert_eq!(ignoring.epoch(), 1);
+        mirror.apply_add_proof(&noop).unwrap();
+        assert_eq!(mirror.acc_value, ignoring.acc_value);
+
+        let mut multiset =
+            DynamicAccumulator::with_duplicate_policy(DuplicatePolicy::IncrementMultiplicity);
+        multiset.add(&1i64).unwrap();
+        let proof = multiset.add(&1i64).unwrap();
+        assert!(proof.verify());
+        multiset.add(&2i64).unwrap();
+        assert_eq!(multiset.multiplicity(&1i64), 2);
+        assert_eq!(multiset.len(), 2);
+        let static_acc = Acc1::cal_acc_g1_sk(&MultiSet::from_vec(vec![1i64, 1, 2]));
+        assert_eq!(multiset.acc_value, static_acc);
+        assert!(multiset.verify_membership(&multiset.prove_membership(&1i64).unwrap()));
+        assert!(multiset.verify_non_membership(&multiset.prove_non_membership(&3i64).unwrap()));
+        assert!(multiset.prove_intersection(&ignoring).is_err());
+
+        multiset.delete(&1i64).unwrap();
+        assert_eq!(multiset.multiplicity(&1i64), 1);
+        assert!(multiset.verify_membership(&multiset.prove_membership(&1i64).unwrap()));
+        multiset.delete(&1i64).unwrap();
+        assert_eq!(multiset.multiplicity(&1i64), 0);
+        assert!(multiset.delete(&1i64).is_err());
+    }
+
+    #[test]
+    fn test_apply_proofs_on_mirror() {
+        init_logger();
+        let mut manager = DynamicAccumulator::new();
+        let mut mirror = DynamicAccumulator::new();
+
+        let add1 = manager.add(&1i64).unwrap();
+        let add2 = manager.add(&2i64).unwrap();
+        let del1 = manager.delete(&1i64).unwrap();
+
+        // Proofs must be applied in order.
+        assert!(mirror.apply_add_proof(&add2).is_err());
+        mirror.apply_add_proof(&add1).unwrap();
+        mirror.apply_add_proof(&add2).unwrap();
+
+        // A forged transition is rejected and leaves the mirror untouched.
+        let mut forged = del1.clone();
+        forged.new_acc_value = add1.new_acc_value;
+        assert!(mirror.apply_delete_proof(&forged).is_err());
+        assert_eq!(mirror.acc_value, add2.new_acc_value);
+
+        mirror.apply_delete_proof(&del1).unwrap();
+        assert_eq!(mirror, manager);
+    }
+
     #[test]
     fn test_membership_proof() {
         init_logger();
@@ -894,13 +1575,83 @@ mod tests {
 
         // 3. A proof for a different element should fail
         let mut wrong_proof = proof.clone();
-        wrong_proof.element = digest_to_prime_field(&999i64.to_digest());
+        wrong_proof.element = ElementHandle::of(&999);
         assert!(!dyn_acc.verify_membership(&wrong_proof));
 
         // 4. Cannot prove membership for an element not in the set
         assert!(dyn_acc.prove_membership(&999i64).is_err());
     }
 
+    #[test]
+    #[cfg(not(feature = "hardened"))]
+    fn test_membership_witness_cache() {
+        init_logger();
+        let mut dyn_acc = DynamicAccumulator::new();
+        dyn_acc.add_batch(&[100, 200, 300]).unwrap();
+
+        let proof = dyn_acc.prove_membership(&200).unwrap();
+        assert_eq!(dyn_acc.cached_witnesses(), 1);
+        assert_eq!(dyn_acc.prove_membership(&200).unwrap(), proof);
+
+        // Mutations invalidate cached witnesses.
+        dyn_acc.add(&400).unwrap();
+        assert_eq!(dyn_acc.cached_witnesses(), 0);
+        let refreshed = dyn_acc.prove_membership(&200).unwrap();
+        assert_ne!(refreshed, proof);
+        assert!(dyn_acc.verify_membership(&refreshed));
+
+        dyn_acc.set_witness_cache_capacity(0);
+        dyn_acc.prove_membership(&200).unwrap();
+        assert_eq!(dyn_acc.cached_witnesses(), 0);
+    }
+
+    #[test]
+    fn test_epoch_binding() {
+        init_logger();
+        let mut dyn_acc = DynamicAccumulator::new();
+        assert_eq!(dyn_acc.epoch(), 0);
+        let add_proof = dyn_acc.add(&100).unwrap();
+        assert_eq!(add_proof.epoch, 1);
+        dyn_acc.add(&200).unwrap();
+
+        let proof = dyn_acc.prove_membership(&100).unwrap();
+        let non_proof = dyn_acc.prove_non_membership(&300).unwrap();
+        assert_eq!(proof.epoch, 2);
+        assert!(dyn_acc.verify_membership(&proof));
+        assert!(!proof.verify_at(dyn_acc.acc_value, 1));
+
+        // After a mutation, proofs for the old state no longer verify.
+        let old_value = dyn_acc.acc_value;
+        dyn_acc.delete(&200).unwrap();
+        assert_eq!(dyn_acc.epoch(), 3);
+        assert!(!dyn_acc.verify_membership(&proof));
+        assert!(!dyn_acc.verify_non_membership(&non_proof));
+        assert!(proof.verify_at(old_value, 2));
+        assert!(non_proof.verify_at(old_value, 2));
+    }
+
+    #[test]
+    fn test_witness_update_without_trapdoor() {
+        init_logger();
+        let mut dyn_acc = DynamicAccumulator::new();
+        dyn_acc.add_batch(&[100, 200]).unwrap();
+        let mut proof = dyn_acc.prove_membership(&100).unwrap();
+
+        let add_proof = dyn_acc.add(&300).unwrap();
+        proof.update_on_add(&add_proof).unwrap();
+        assert!(dyn_acc.verify_membership(&proof));
+
+        let delete_proof = dyn_acc.delete(&200).unwrap();
+        proof.update_on_delete(&delete_proof).unwrap();
+        assert!(dyn_acc.verify_membership(&proof));
+        assert_eq!(proof, dyn_acc.prove_membership(&100).unwrap());
+
+        // Updates must be applied in order, and deleting the element itself fails.
+        assert!(proof.update_on_add(&add_proof).is_err());
+        let delete_self = dyn_acc.delete(&100).unwrap();
+        assert!(proof.update_on_delete(&delete_self).is_err());
+    }
+
     #[test]
     fn test_non_membership_proof() {
         init_logger();
@@ -917,7 +1668,7 @@ mod tests {
 
         // 3. A tampered proof should fail verification
         let mut tampered_proof = proof.clone();
-        tampered_proof.element = digest_to_prime_field(&400i64.to_digest());
+        tampered_proof.element = ElementHandle::of(&400);
         assert!(!dyn_acc.verify_non_membership(&tampered_proof));
 
         // 4. An empty accumulator should be able to prove non-membership
@@ -939,7 +1690,7 @@ mod tests {
         assert!(add_proof.verify());
 
         // Verify 100 is gone
-        match dyn_acc.query(&100) {
+        match dyn_acc.query(&100).unwrap() {
             QueryResult::NonMembership(proof) => {
                 assert!(dyn_acc.verify_non_membership(&proof));
             }
@@ -947,7 +1698,7 @@ mod tests {
         }
 
         // Verify 150 is present
-        match dyn_acc.query(&150) {
+        match dyn_acc.query(&150).unwrap() {
             QueryResult::Membership(proof) => {
                 assert!(dyn_acc.verify_membership(&proof));
             }
@@ -955,7 +1706,7 @@ mod tests {
         }
 
         // Verify 200 is still present
-        match dyn_acc.query(&200) {
+        match dyn_acc.query(&200).unwrap() {
             QueryResult::Membership(proof) => {
                 assert!(dyn_acc.verify_membership(&proof));
             }
@@ -966,6 +1717,25 @@ mod tests {
         assert!(dyn_acc.update(&999, &1000).is_err());
     }
 
+    #[test]
+    fn test_check_then_prove() {
+        init_logger();
+        let mut dyn_acc = DynamicAccumulator::new();
+        dyn_acc.add(&100).unwrap();
+        assert!(dyn_acc.check(&100).unwrap());
+        assert!(!dyn_acc.check(&200).unwrap());
+
+        let check = dyn_acc.check_then_prove(&100).unwrap();
+        assert!(check.is_member());
+        match check.prove().unwrap() {
+            QueryResult::Membership(proof) => assert!(dyn_acc.verify_membership(&proof)),
+            _ => panic!("Should have been a membership proof for 100"),
+        }
+        let check = dyn_acc.check_then_prove(&200).unwrap();
+        assert!(!check.is_member());
+        assert_eq!(check.prove().unwrap(), dyn_acc.query(&200).unwrap());
+    }
+
     #[test]
     fn test_intersection_proof() {
         init_logger();
@@ -986,8 +1756,8 @@ mod tests {
         
         // 2. Verify the intersection contains the expected elements
         assert_eq!(intersection_acc.elements.len(), 2);
-        assert!(intersection_acc.elements.contains(&digest_to_prime_field(&200i64.to_digest())));
-        assert!(intersection_acc.elements.contains(&digest_to_prime_field(&300i64.to_digest())));
+        assert!(intersection_acc.elements.has(&digest_to_prime_field(&200i64.to_digest())));
+        assert!(intersection_acc.elements.has(&digest_to_prime_field(&300i64.to_digest())));
 
         // 3. Verify the intersection proof
         assert!(DynamicAccumulator::verify_intersection(
@@ -1063,4 +1833,51 @@ mod tests {
             &proof
         ));
     }
+
+    #[test]
+    fn test_union_proof() {
+        init_logger();
+
+        let mut acc1 = DynamicAccumulator::new();
+        acc1.add_batch(&[100, 200, 300]).unwrap();
+
+        let mut acc2 = DynamicAccumulator::new();
+        acc2.add_batch(&[200, 300, 400]).unwrap();
+
+        let (union_acc, proof) = acc1.prove_union(&acc2).unwrap();
+        assert_eq!(union_acc.len(), 4);
+        assert!(DynamicAccumulator::verify_union(
+            acc1.acc_value,
+            acc2.acc_value,
+            union_acc.acc_value,
+            &proof
+        ));
+        let values = (acc1.acc_value, acc2.acc_value, union_acc.acc_value);
+        assert!(DynamicAccumulator::verify_union_at(values.0, values.1, values.2, &proof, (3, 3)));
+        assert!(!DynamicAccumulator::verify_union_at(values.0, values.1, values.2, &proof, (3, 2)));
+        assert!(DynamicAccumulator::verify_intersection_at(
+            values.0,
+            values.1,
+            proof.intersection_acc_value,
+            &proof.intersection_proof,
+            (3, 3)
+        ));
+        assert!(!DynamicAccumulator::verify_intersection_at(
+            values.0,
+            values.1,
+            proof.intersection_acc_value,
+            &proof.intersection_proof,
+            (2, 3)
+        ));
+
+        // A union accumulator missing an element must be rejected.
+        let mut wrong_union = DynamicAccumulator::new();
+        wrong_union.add_batch(&[100, 200, 300]).unwrap();
+        assert!(!DynamicAccumulator::verify_union(
+            acc1.acc_value,
+            acc2.acc_value,
+            wrong_union.acc_value,
+            &proof
+        ));
+    }
 }
@@ -0,0 +1,266 @@
+//! Adding elements under verifiable encryption to a regulator.
+//!
+//! [`DynamicAccumulator::add_encrypted`] adds an element without publishing it. The proof
+//! carries an exponential ElGamal encryption `(g1^r, g1^x * pk^r)` of the element `x` under the
+//! regulator's [`EncryptionKey`], and a Fiat-Shamir proof of knowledge of `(x, r)` showing
+//! that the ciphertext encrypts the element that was added:
+//!
+//! - `c1 = g1^r` and `c2 = g1^x * pk^r`;
+//! - `e(old, g2^s) / e(new, g2) = e(old, g2)^x`, i.e. `new = old^(s-x)`.
+//!
+//! Verifiers learn nothing about `x` beyond what they can test by guessing candidates. The
+//! regulator decrypts `g1^x` with its [`DecryptionKey`] and checks it against the disputed
+//! element with [`DecryptionKey::opens_to`].
+
+use super::{
+    commitment::params_id,
+    dynamic_accumulator::{ark_serde, DynamicAccumulator},
+    get_g2s, metrics,
+    utils::digest_to_prime_field,
+    Curve, Fq12, Fr, G1Affine, G2Affine,
+};
+use crate::digest::{Digest, Digestible};
+use anyhow::{bail, Result};
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{Field, One, PrimeField, ToBytes, UniformRand, Zero};
+use serde::{Deserialize, Serialize};
+
+lazy_static! {
+    static ref G2_S: G2Affine = get_g2s(Fr::one());
+}
+
+const KEYGEN_DST: &[u8] = b"ESA_RUST_ESCROW_KEYGEN";
+
+/// The regulator's secret key.
+#[derive(Clone, PartialEq, Eq)]
+pub struct DecryptionKey(Fr);
+
+/// The regulator's public key `g1^sk`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptionKey(#[serde(with = "ark_serde")] pub G1Affine);
+
+/// An exponential ElGamal ciphertext of an element.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ciphertext {
+    #[serde(with = "ark_serde")]
+    pub c1: G1Affine,
+    #[serde(with = "ark_serde")]
+    pub c2: G1Affine,
+}
+
+impl DecryptionKey {
+    /// Derives a key from secret seed material.
+    pub fn from_seed(seed: &[u8]) -> Self {
+        let mut counter: u32 = 0;
+        loop {
+            let digest = [KEYGEN_DST, seed, &counter.to_le_bytes()]
+                .concat()
+                .to_digest();
+            let sk: Fr = digest_to_prime_field(&digest);
+            if !sk.is_zero() {
+                return Self(sk);
+            }
+            counter += 1;
+        }
+    }
+
+    pub fn encryption_key(&self) -> EncryptionKey {
+        EncryptionKey(
+            G1Affine::prime_subgroup_generator()
+                .mul(self.0.into_repr())
+                .into_affine(),
+        )
+    }
+
+    /// Decrypts to `g1^x`; the element itself is recovered by comparing with candidates.
+    pub fn decrypt(&self, ciphertext: &Ciphertext) -> G1Affine {
+        (ciphertext.c2.into_projective() - ciphertext.c1.mul(self.0.into_repr())).into_affine()
+    }
+
+    /// Returns true if `ciphertext` encrypts `element`.
+    pub fn opens_to(&self, ciphertext: &Ciphertext, element: &i64) -> bool {
+        let x: Fr = digest_to_prime_field(&element.to_digest());
+        self.decrypt(ciphertext)
+            == G1Affine::prime_subgroup_generator()
+                .mul(x.into_repr())
+                .into_affine()
+    }
+}
+
+/// A proof that an encrypted element was added to the accumulator.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptedAddProof {
+    #[serde(with = "ark_serde")]
+    pub old_acc_value: G1Affine,
+    #[serde(with = "ark_serde")]
+    pub new_acc_value: G1Affine,
+    /// The epoch of the accumulator after the operation.
+    pub epoch: u64,
+    pub ciphertext: Ciphertext,
+    #[serde(with = "ark_serde")]
+    pub challenge: Fr,
+    #[serde(with = "ark_serde")]
+    pub z_element: Fr,
+    #[serde(with = "ark_serde")]
+    pub z_randomness: Fr,
+    /// The identifier of the public parameters the proof was generated under, see
+    /// [`params_id`].
+    pub params_id: Digest,
+}
+
+/// e(old, g2^s) / e(new, g2), which must equal e(old, g2)^x.
+fn statement(old: G1Affine, new: G1Affine) -> Option<Fq12> {
+    let lhs = Curve::pairing(old, *G2_S);
+    let rhs = Curve::pairing(new, G2Affine::prime_subgroup_generator());
+    rhs.inverse().map(|inv| lhs * inv)
+}
+
+/// The Fiat-Shamir challenge over `[pk, old, new, c1, c2, t1, t2]`, `t3` and the epoch.
+fn challenge(points: &[G1Affine; 7], t3: &Fq12, epoch: u64) -> Fr {
+    let mut buf = Vec::<u8>::new();
+    buf.extend_from_slice(b"ESA_RUST_ENCRYPTED_ADD");
+    points
+        .iter()
+        .try_for_each(|p| p.write(&mut buf))
+        .and_then(|_| t3.write(&mut buf))
+        .unwrap_or_else(|_| panic!("failed to serialize the encrypted add transcript"));
+    buf.extend_from_slice(&epoch.to_le_bytes());
+    digest_to_prime_field(&buf.to_digest())
+}
+
+impl DynamicAccumulator {
+    /// Adds `element` and proves it was encrypted to `key`, without revealing it.
+    /// Randomness is drawn from the thread-local generator.
+    pub fn add_encrypted(
+        &mut self,
+        element: &i64,
+        key: &EncryptionKey,
+    ) -> Result<EncryptedAddProof> {
+        self.add_encrypted_with_rng(element, key, &mut rand::thread_rng())
+    }
+
+    /// Same as [`DynamicAccumulator::add_encrypted`], drawing randomness from `rng`.
+    pub fn add_encrypted_with_rng<R: rand::Rng + ?Sized>(
+        &mut self,
+        element: &i64,
+        key: &EncryptionKey,
+        rng: &mut R,
+    ) -> Result<EncryptedAddProof> {
+        let x: Fr = digest_to_prime_field(&element.to_digest());
+        if self.elements.contains(&x)? {
+            bail!("Element already in accumulator");
+        }
+        let proof = self.add(element)?;
+        let g1 = G1Affine::prime_subgroup_generator();
+        let r = Fr::rand(rng);
+        let ciphertext = Ciphertext {
+            c1: g1.mul(r.into_repr()).into_affine(),
+            c2: (g1.mul(x.into_repr()) + key.0.mul(r.into_repr())).into_affine(),
+        };
+
+        let (rho_x, rho_r) = (Fr::rand(rng), Fr::rand(rng));
+        let t1 = g1.mul(rho_r.into_repr()).into_affine();
+        let t2 = (g1.mul(rho_x.into_repr()) + key.0.mul(rho_r.into_repr())).into_affine();
+        let t3 = Curve::pairing(proof.old_acc_value, G2Affine::prime_subgroup_generator())
+            .pow(rho_x.into_repr());
+        let c = challenge(
+            &[
+                key.0,
+                proof.old_acc_value,
+                proof.new_acc_value,
+                ciphertext.c1,
+                ciphertext.c2,
+                t1,
+                t2,
+            ],
+            &t3,
+            proof.epoch,
+        );
+        Ok(EncryptedAddProof {
+            old_acc_value: proof.old_acc_value,
+            new_acc_value: proof.new_acc_value,
+            epoch: proof.epoch,
+            ciphertext,
+            challenge: c,
+            z_element: rho_x + c * x,
+            z_randomness: rho_r + c * r,
+            params_id: params_id(),
+        })
+    }
+}
+
+impl EncryptedAddProof {
+    /// Verifies that the new accumulator is the old one with the element encrypted under `key`
+    /// added.
+    pub fn verify(&self, key: &EncryptionKey) -> bool {
+        if self.params_id != params_id() {
+            return false;
+        }
+        let t = match statement(self.old_acc_value, self.new_acc_value) {
+            Some(t) => t,
+            None => return false,
+        };
+        let t_c_inv = match t.pow(self.challenge.into_repr()).inverse() {
+            Some(inv) => inv,
+            None => return false,
+        };
+        metrics::record_pairings(3);
+        let g1 = G1Affine::prime_subgroup_generator();
+        let c = self.challenge.into_repr();
+        let (z_x, z_r) = (self.z_element.into_repr(), self.z_randomness.into_repr());
+        let t1 = (g1.mul(z_r) - self.ciphertext.c1.mul(c)).into_affine();
+        let t2 = (g1.mul(z_x) + key.0.mul(z_r) - self.ciphertext.c2.mul(c)).into_affine();
+        let t3 = Curve::pairing(self.old_acc_value, G2Affine::prime_subgroup_generator()).pow(z_x)
+            * t_c_inv;
+        self.challenge
+            == challenge(
+                &[
+                    key.0,
+                    self.old_acc_value,
+                    self.new_acc_value,
+                    self.ciphertext.c1,
+                    self.ciphertext.c2,
+                    t1,
+                    t2,
+                ],
+                &t3,
+                self.epoch,
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::rng::DeterministicRng;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_encrypted_add() {
+        init_logger();
+        let regulator = DecryptionKey::from_seed(b"regulator");
+        let key = regulator.encryption_key();
+        let mut acc = DynamicAccumulator::new();
+        acc.add(&1).unwrap();
+        let mut rng = DeterministicRng::from_seed(b"escrow");
+        let proof = acc.add_encrypted_with_rng(&7, &key, &mut rng).unwrap();
+        assert!(proof.verify(&key));
+        assert_eq!(proof.new_acc_value, acc.acc_value);
+        assert!(acc.prove_membership(&7).unwrap().verify(acc.acc_value));
+
+        assert!(regulator.opens_to(&proof.ciphertext, &7));
+        assert!(!regulator.opens_to(&proof.ciphertext, &1));
+
+        // The proof is bound to the key and the ciphertext.
+        let other = DecryptionKey::from_seed(b"other").encryption_key();
+        assert!(!proof.verify(&other));
+        let mut swapped = proof.clone();
+        swapped.ciphertext.c2 = swapped.ciphertext.c1;
+        assert!(!swapped.verify(&key));
+
+        assert!(acc.add_encrypted(&7, &key).is_err());
+    }
+}
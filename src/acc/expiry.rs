@@ -0,0 +1,196 @@
+//! Membership witnesses that expire.
+//!
+//! [`DynamicAccumulator::prove_membership_until`] issues a witness valid up to epoch E. The
+//! witness is acc^(1/((s-x)(s-t))) for the element x and the expiry point t = H(E), so E is bound
+//! into the pairing check and cannot be moved without the trapdoor. Holders keep the witness in
+//! step with the accumulator through [`TimedMembershipProof::update_on_add`] and
+//! [`TimedMembershipProof::update_on_delete`], helped by the witness of t, acc^(1/(s-t)), carried
+//! in the proof. Past epoch E, updates fail and [`TimedMembershipProof::verify_at`] rejects the
+//! proof, so the holder has to ask the manager for a fresh one.
+
+use super::{
+    commit_g2,
+    commitment::params_id,
+    dynamic_accumulator::{ark_serde, AddProof, DeleteProof, DynamicAccumulator},
+    handle::ElementHandle,
+    polymath,
+    prepared::PreparedPublicParams,
+    trapdoor,
+    utils::digest_to_prime_field,
+    Fr, G1Affine,
+};
+use crate::digest::{concat_digest, Digest, Digestible};
+use anyhow::{anyhow, bail, Result};
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{Field, PrimeField};
+use serde::{Deserialize, Serialize};
+
+/// The point t = H(E) bound into witnesses expiring at epoch E.
+pub fn expiry_point(expires_at: u64) -> Fr {
+    digest_to_prime_field(&concat_digest(
+        [b"ESA_RUST_EXPIRY"[..].to_digest(), expires_at.to_digest()].into_iter(),
+    ))
+}
+
+/// A membership proof valid up to and including epoch `expires_at`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimedMembershipProof {
+    /// acc^(1/((s-element)(s-t))).
+    #[serde(with = "ark_serde")]
+    pub witness: G1Affine,
+    /// acc^(1/(s-t)), needed to update the witness.
+    #[serde(with = "ark_serde")]
+    pub expiry_witness: G1Affine,
+    pub element: ElementHandle,
+    pub expires_at: u64,
+    pub epoch: u64,
+    pub acc_digest: Digest,
+    /// The identifier of the public parameters the proof was generated under, see
+    /// [`params_id`].
+    pub params_id: Digest,
+}
+
+impl TimedMembershipProof {
+    /// Checks e(witness, g2^((s-element)(s-t))) == e(accumulator, g2), ignoring expiry.
+    pub fn verify(&self, accumulator: G1Affine) -> bool {
+        if self.acc_digest != accumulator.to_digest() || self.params_id != params_id() {
+            return false;
+        }
+        let t = expiry_point(self.expires_at);
+        let g2_q = match commit_g2(polymath::from_roots(&[self.element.0, t])) {
+            Ok(g2_q) => g2_q,
+            Err(_) => return false,
+        };
+        let params = PreparedPublicParams::global();
+        params.pairing_check(self.witness, g2_q.into(), -accumulator)
+    }
+
+    /// Verifies the proof against the accumulator value published at `epoch`, which must not
+    /// be past the expiry.
+    pub fn verify_at(&self, accumulator: G1Affine, epoch: u64) -> bool {
+        self.epoch == epoch && epoch <= self.expires_at && self.verify(accumulator)
+    }
+
+    /// Moves the witness forward over an add of y without the trapdoor: with
+    /// u = acc^(1/(s-t)), the new witnesses are u * w^(x-y) and acc * u^(t-y).
+    pub fn update_on_add(&mut self, proof: &AddProof) -> Result<()> {
+        self.ensure_updatable(proof.old_acc_value, proof.epoch)?;
+        let t = expiry_point(self.expires_at);
+        let witness = self.expiry_witness.into_projective()
+            + self
+                .witness
+                .mul((self.element.0 - proof.element.0).into_repr());
+        let expiry_witness = proof.old_acc_value.into_projective()
+            + self.expiry_witness.mul((t - proof.element.0).into_repr());
+        self.witness = witness.into_affine();
+        self.expiry_witness = expiry_witness.into_affine();
+        self.epoch = proof.epoch;
+        self.acc_digest = proof.new_acc_value.to_digest();
+        Ok(())
+    }
+
+    /// Moves the witness forward over a delete of y without the trapdoor: the new witnesses are
+    /// u' = (u / acc')^(1/(t-y)) and (w / u')^(1/(x-y)).
+    pub fn update_on_delete(&mut self, proof: &DeleteProof) -> Result<()> {
+        self.ensure_updatable(proof.old_acc_value, proof.epoch)?;
+        let t = expiry_point(self.expires_at);
+        let x_minus_y_inv = (self.element.0 - proof.element.0)
+            .inverse()
+            .ok_or_else(|| anyhow!("The witnessed element was deleted"))?;
+        let t_minus_y_inv = (t - proof.element.0)
+            .inverse()
+            .ok_or_else(|| anyhow!("The deleted element is the expiry point"))?;
+        let expiry_witness = (self.expiry_witness.into_projective()
+            - proof.new_acc_value.into_projective())
+        .mul(t_minus_y_inv.into_repr())
+        .into_affine();
+        let witness = (self.witness.into_projective() - expiry_witness.into_projective())
+            .mul(x_minus_y_inv.into_repr());
+        self.witness = witness.into_affine();
+        self.expiry_witness = expiry_witness;
+        self.epoch = proof.epoch;
+        self.acc_digest = proof.new_acc_value.to_digest();
+        Ok(())
+    }
+
+    fn ensure_updatable(&self, old_acc_value: G1Affine, epoch: u64) -> Result<()> {
+        if self.acc_digest != old_acc_value.to_digest() {
+            bail!("Update proof does not start at the witness's accumulator value");
+        }
+        if epoch > self.expires_at {
+            bail!("The witness expired at epoch {}", self.expires_at);
+        }
+        Ok(())
+    }
+}
+
+impl DynamicAccumulator {
+    /// Proves membership of `element` with a witness that expires after epoch `expires_at`.
+    pub fn prove_membership_until(
+        &self,
+        element: &i64,
+        expires_at: u64,
+    ) -> Result<TimedMembershipProof> {
+        let fr_element: Fr = digest_to_prime_field(&element.to_digest());
+        if !self.elements.contains(&fr_element)? {
+            bail!("Cannot prove membership for an element not in the set");
+        }
+        if expires_at < self.epoch() {
+            bail!(
+                "Expiry epoch {} is before the current epoch {}",
+                expires_at,
+                self.epoch()
+            );
+        }
+        let signer = trapdoor::signer();
+        let expiry_witness = signer.div_s_minus(self.acc_value, expiry_point(expires_at))?;
+        Ok(TimedMembershipProof {
+            witness: signer.div_s_minus(expiry_witness, fr_element)?,
+            expiry_witness,
+            element: ElementHandle(fr_element),
+            expires_at,
+            epoch: self.epoch(),
+            acc_digest: self.acc_value.to_digest(),
+            params_id: params_id(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_timed_witness() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        acc.add(&1).unwrap();
+        acc.add(&2).unwrap();
+        let mut proof = acc.prove_membership_until(&1, 4).unwrap();
+        assert!(proof.verify_at(acc.acc_value, 2));
+        assert!(acc.prove_membership_until(&1, 1).is_err());
+        assert!(acc.prove_membership_until(&3, 4).is_err());
+
+        // The holder cannot extend the expiry.
+        let mut extended = proof.clone();
+        extended.expires_at = 10;
+        assert!(!extended.verify(acc.acc_value));
+
+        proof.update_on_add(&acc.add(&3).unwrap()).unwrap();
+        proof.update_on_delete(&acc.delete(&2).unwrap()).unwrap();
+        assert!(proof.verify_at(acc.acc_value, 4));
+        assert_eq!(proof, acc.prove_membership_until(&1, 4).unwrap());
+
+        // Past the expiry, the witness can no longer be updated.
+        let add = acc.add(&5).unwrap();
+        assert!(proof.update_on_add(&add).is_err());
+        let timed = acc.prove_membership_until(&1, 5).unwrap();
+        assert!(timed.verify_at(acc.acc_value, 5));
+        let add = acc.add(&6).unwrap();
+        assert!(timed.clone().update_on_add(&add).is_err());
+    }
+}
@@ -0,0 +1,317 @@
+//! Proofs for composed set operations.
+//!
+//! A [`SetExpr`] combines accumulators with intersections, unions and differences, e.g.
+//! `SetExpr::inter(&a, SetExpr::union(&b, &c))`. [`SetExpr::prove`] evaluates it bottom-up along
+//! the plan of [`SetExpr::plan`], proving each operation on the accumulators of its operands, and
+//! returns the accumulator of the result with one [`ExprProof`] holding every step. A verifier
+//! holding the values of the leaf accumulators, in the order they appear in the expression,
+//! checks it with [`ExprProof::verify`]. Since the plan may reorder intersections and unions, the
+//! verifier also compares [`ExprProof::shape`] with the [`ExprShape`] of the expression it asked
+//! for.
+//!
+//! Every operation is proven through the intersection I of its operands A and B, with
+//! A = I * Q_A and B = I * Q_B for coprime Q_A and Q_B:
+//!
+//! - the intersection is I itself,
+//! - the union is A * Q_B, as in [`DynamicAccumulator::verify_union`],
+//! - the difference A \ B is Q_A, committed in G2 as the intersection witness g2^Q_A(s), so
+//!   e(acc_diff, g2) == e(g1, g2^Q_A(s)).
+
+use super::{
+    dynamic_accumulator::{ark_serde, DynamicAccumulator, UnionProof},
+    planner::Plan,
+    prepared::PreparedPublicParams,
+    trapdoor, G1Affine,
+};
+use anyhow::{anyhow, bail, Result};
+use ark_ec::AffineCurve;
+use serde::{Deserialize, Serialize};
+
+/// A set operation.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum SetOp {
+    Inter,
+    Union,
+    Diff,
+}
+
+/// An expression over accumulators.
+#[derive(Debug, Clone)]
+pub enum SetExpr<'a> {
+    Acc(&'a DynamicAccumulator),
+    Op(SetOp, Box<SetExpr<'a>>, Box<SetExpr<'a>>),
+}
+
+impl<'a> From<&'a DynamicAccumulator> for SetExpr<'a> {
+    fn from(acc: &'a DynamicAccumulator) -> Self {
+        SetExpr::Acc(acc)
+    }
+}
+
+/// An expression up to the order of its intersections and unions, with leaves numbered in
+/// expression order. Chains of the same intersection or union are flattened and sorted.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ExprShape {
+    Leaf(usize),
+    Op(SetOp, Vec<ExprShape>),
+}
+
+impl ExprShape {
+    pub fn op(op: SetOp, a: ExprShape, b: ExprShape) -> Self {
+        if op == SetOp::Diff {
+            return ExprShape::Op(op, vec![a, b]);
+        }
+        let mut operands = Vec::new();
+        for shape in [a, b] {
+            match shape {
+                ExprShape::Op(o, children) if o == op => operands.extend(children),
+                shape => operands.push(shape),
+            }
+        }
+        operands.sort();
+        ExprShape::Op(op, operands)
+    }
+}
+
+/// The proof of a [`SetExpr`], one step per operation of its plan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ExprProof {
+    /// The leaf at this index in expression order.
+    Leaf(usize),
+    Op {
+        op: SetOp,
+        left: Box<ExprProof>,
+        right: Box<ExprProof>,
+        /// The accumulator value of the result.
+        #[serde(with = "ark_serde")]
+        value: G1Affine,
+        /// The intersection of the operands, with its proof.
+        proof: Box<UnionProof>,
+    },
+}
+
+impl<'a> SetExpr<'a> {
+    pub fn op(op: SetOp, a: impl Into<SetExpr<'a>>, b: impl Into<SetExpr<'a>>) -> Self {
+        SetExpr::Op(op, Box::new(a.into()), Box::new(b.into()))
+    }
+
+    pub fn inter(a: impl Into<SetExpr<'a>>, b: impl Into<SetExpr<'a>>) -> Self {
+        Self::op(SetOp::Inter, a, b)
+    }
+
+    pub fn union(a: impl Into<SetExpr<'a>>, b: impl Into<SetExpr<'a>>) -> Self {
+        Self::op(SetOp::Union, a, b)
+    }
+
+    /// The elements of `a` that are not in `b`.
+    pub fn diff(a: impl Into<SetExpr<'a>>, b: impl Into<SetExpr<'a>>) -> Self {
+        Self::op(SetOp::Diff, a, b)
+    }
+
+    /// Evaluates the expression, returning the accumulator of the result and its proof.
+    pub fn prove(&self) -> Result<(DynamicAccumulator, ExprProof)> {
+        self.plan().prove()
+    }
+
+    pub fn shape(&self) -> ExprShape {
+        self.shape_with(&mut 0)
+    }
+
+    fn shape_with(&self, next: &mut usize) -> ExprShape {
+        match self {
+            SetExpr::Acc(_) => {
+                *next += 1;
+                ExprShape::Leaf(*next - 1)
+            }
+            SetExpr::Op(op, a, b) => {
+                let a = a.shape_with(next);
+                ExprShape::op(*op, a, b.shape_with(next))
+            }
+        }
+    }
+}
+
+impl Plan<'_> {
+    /// Proves the planned steps bottom-up.
+    pub fn prove(&self) -> Result<(DynamicAccumulator, ExprProof)> {
+        match self {
+            Plan::Leaf { index, acc } => Ok(((*acc).clone(), ExprProof::Leaf(*index))),
+            Plan::Op {
+                op, left, right, ..
+            } => {
+                let (a, left) = left.prove()?;
+                let (b, right) = right.prove()?;
+                let (result, proof) = prove_op(*op, &a, &b)?;
+                let value = result.acc_value;
+                Ok((
+                    result,
+                    ExprProof::Op {
+                        op: *op,
+                        left: Box::new(left),
+                        right: Box::new(right),
+                        value,
+                        proof: Box::new(proof),
+                    },
+                ))
+            }
+        }
+    }
+}
+
+fn prove_op(
+    op: SetOp,
+    a: &DynamicAccumulator,
+    b: &DynamicAccumulator,
+) -> Result<(DynamicAccumulator, UnionProof)> {
+    if op == SetOp::Union {
+        return a.prove_union(b);
+    }
+    let (intersection, intersection_proof) = a.prove_intersection(b)?;
+    let proof = UnionProof {
+        intersection_acc_value: intersection.acc_value,
+        intersection_proof,
+    };
+    match op {
+        SetOp::Inter => Ok((intersection, proof)),
+        _ => {
+            let elements = a.elements.difference(&intersection.elements)?;
+            let value =
+                trapdoor::signer().mul_s_minus(G1Affine::prime_subgroup_generator(), &elements)?;
+            let mut diff = DynamicAccumulator::new();
+            diff.apply_changes(&elements, &[], value, 0)?;
+            Ok((diff, proof))
+        }
+    }
+}
+
+impl ExprProof {
+    /// Checks the proof against the values of the leaves, in expression order, and returns the
+    /// value of the result. Fails if a step does not verify or the proof does not use every leaf
+    /// exactly once.
+    pub fn evaluate(&self, leaves: &[G1Affine]) -> Result<G1Affine> {
+        let mut used = vec![false; leaves.len()];
+        let value = self.evaluate_with(leaves, &mut used)?;
+        if used.contains(&false) {
+            bail!("More leaf values than leaves in the expression");
+        }
+        Ok(value)
+    }
+
+    /// Returns true if the proof shows that its expression over `leaves` has value `result`.
+    pub fn verify(&self, leaves: &[G1Affine], result: G1Affine) -> bool {
+        self.evaluate(leaves).is_ok_and(|value| value == result)
+    }
+
+    /// The shape of the proven expression.
+    pub fn shape(&self) -> ExprShape {
+        match self {
+            ExprProof::Leaf(index) => ExprShape::Leaf(*index),
+            ExprProof::Op {
+                op, left, right, ..
+            } => ExprShape::op(*op, left.shape(), right.shape()),
+        }
+    }
+
+    fn evaluate_with(&self, leaves: &[G1Affine], used: &mut [bool]) -> Result<G1Affine> {
+        let (op, left, right, value, proof) = match self {
+            ExprProof::Leaf(index) => {
+                let value = leaves
+                    .get(*index)
+                    .ok_or_else(|| anyhow!("Fewer leaf values than leaves in the expression"))?;
+                if std::mem::replace(&mut used[*index], true) {
+                    bail!("Leaf {} is used twice", index);
+                }
+                return Ok(*value);
+            }
+            ExprProof::Op {
+                op,
+                left,
+                right,
+                value,
+                proof,
+            } => (*op, left, right, *value, proof),
+        };
+        let a = left.evaluate_with(leaves, used)?;
+        let b = right.evaluate_with(leaves, used)?;
+        let valid = match op {
+            SetOp::Inter => {
+                value == proof.intersection_acc_value
+                    && DynamicAccumulator::verify_intersection(
+                        a,
+                        b,
+                        value,
+                        &proof.intersection_proof,
+                    )
+            }
+            SetOp::Union => DynamicAccumulator::verify_union(a, b, value, proof),
+            SetOp::Diff => {
+                let params = PreparedPublicParams::global();
+                let g1 = G1Affine::prime_subgroup_generator();
+                DynamicAccumulator::verify_intersection(
+                    a,
+                    b,
+                    proof.intersection_acc_value,
+                    &proof.intersection_proof,
+                ) && params.pairing_check(g1, proof.intersection_proof.witness_a.into(), -value)
+            }
+        };
+        if !valid {
+            bail!("The {:?} step does not verify", op);
+        }
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    fn acc_of(elements: &[i64]) -> DynamicAccumulator {
+        let mut acc = DynamicAccumulator::new();
+        for e in elements {
+            acc.add(e).unwrap();
+        }
+        acc
+    }
+
+    #[test]
+    fn test_set_expr() {
+        init_logger();
+        let (a, b, c) = (acc_of(&[1, 2, 3, 4]), acc_of(&[2, 5]), acc_of(&[3, 6]));
+        let leaves = [a.acc_value, b.acc_value, c.acc_value];
+
+        let expr = SetExpr::inter(&a, SetExpr::union(&b, &c));
+        let (result, proof) = expr.prove().unwrap();
+        assert_eq!(result.acc_value, acc_of(&[2, 3]).acc_value);
+        assert!(proof.verify(&leaves, result.acc_value));
+        assert_eq!(proof.shape(), expr.shape());
+        assert!(!proof.verify(&leaves, a.acc_value));
+        assert!(proof.evaluate(&leaves[..2]).is_err());
+        assert!(!proof.verify(&[a.acc_value, c.acc_value, b.acc_value], result.acc_value));
+
+        let expr = SetExpr::diff(&a, SetExpr::union(&b, &c));
+        let (result, proof) = expr.prove().unwrap();
+        assert_eq!(result.acc_value, acc_of(&[1, 4]).acc_value);
+        assert!(proof.verify(&leaves, result.acc_value));
+        let json = serde_json::to_string(&proof).unwrap();
+        let proof: ExprProof = serde_json::from_str(&json).unwrap();
+        assert!(proof.verify(&leaves, result.acc_value));
+        assert_eq!(proof.shape(), expr.shape());
+        assert_ne!(
+            proof.shape(),
+            SetExpr::diff(SetExpr::union(&b, &c), &a).shape()
+        );
+
+        // A difference that keeps an element of the subtrahend does not verify.
+        let forged_value = acc_of(&[1, 2, 4]).acc_value;
+        let mut forged = proof;
+        if let ExprProof::Op { value, .. } = &mut forged {
+            *value = forged_value;
+        }
+        assert!(!forged.verify(&leaves, forged_value));
+    }
+}
@@ -0,0 +1,122 @@
+//! A deduplication filter over the dynamic accumulator.
+//!
+//! Adding an item only if it is new takes a query and an add, and between the two another
+//! writer may add the same item. [`DedupFilter`] holds the accumulator behind a lock and
+//! [`DedupFilter::insert_if_absent`] checks and adds under one write lock, so of concurrent
+//! inserts of one item exactly one adds it. Items are accumulated by their digest, as
+//! [`DynamicAccumulator::add`] does for `i64`.
+
+use super::{
+    dynamic_accumulator::{AddProof, DynamicAccumulator, MembershipProof},
+    utils::digest_to_prime_field,
+    Fr, G1Affine,
+};
+use crate::digest::Digestible;
+use anyhow::{anyhow, Result};
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// A set of seen items with proofs, safe to share between writers.
+#[derive(Debug, Default)]
+pub struct DedupFilter {
+    acc: RwLock<DynamicAccumulator>,
+}
+
+impl DedupFilter {
+    pub fn new(acc: DynamicAccumulator) -> Self {
+        Self {
+            acc: RwLock::new(acc),
+        }
+    }
+
+    /// Maps an item to the element accumulated for it.
+    pub fn element(item: &(impl Digestible + ?Sized)) -> Fr {
+        digest_to_prime_field(&item.to_digest())
+    }
+
+    /// Adds `item` unless it was seen. Returns true and the proof of the add for a new item,
+    /// and false and no proof otherwise.
+    pub fn insert_if_absent(
+        &self,
+        item: &(impl Digestible + ?Sized),
+    ) -> Result<(bool, Option<AddProof>)> {
+        let element = Self::element(item);
+        let mut acc = self.write()?;
+        if acc.elements.contains(&element)? {
+            return Ok((false, None));
+        }
+        Ok((true, Some(acc.add_fr(element)?)))
+    }
+
+    pub fn contains(&self, item: &(impl Digestible + ?Sized)) -> Result<bool> {
+        self.read()?.elements.contains(&Self::element(item))
+    }
+
+    /// Proves that `item` was seen, at the current epoch.
+    pub fn prove_seen(&self, item: &(impl Digestible + ?Sized)) -> Result<MembershipProof> {
+        self.read()?.prove_membership_fr(Self::element(item))
+    }
+
+    pub fn acc_value(&self) -> Result<G1Affine> {
+        Ok(self.read()?.acc_value)
+    }
+
+    pub fn epoch(&self) -> Result<u64> {
+        Ok(self.read()?.epoch())
+    }
+
+    pub fn into_inner(self) -> Result<DynamicAccumulator> {
+        self.acc
+            .into_inner()
+            .map_err(|_| anyhow!("Dedup filter lock poisoned"))
+    }
+
+    fn read(&self) -> Result<RwLockReadGuard<'_, DynamicAccumulator>> {
+        self.acc
+            .read()
+            .map_err(|_| anyhow!("Dedup filter lock poisoned"))
+    }
+
+    fn write(&self) -> Result<RwLockWriteGuard<'_, DynamicAccumulator>> {
+        self.acc
+            .write()
+            .map_err(|_| anyhow!("Dedup filter lock poisoned"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_insert_if_absent() {
+        init_logger();
+        let filter = DedupFilter::default();
+        let inserted: Vec<bool> = std::thread::scope(|s| {
+            let threads: Vec<_> = (0..4)
+                .map(|_| s.spawn(|| filter.insert_if_absent(&7i64).unwrap()))
+                .collect();
+            threads
+                .into_iter()
+                .map(|t| {
+                    let (new, proof) = t.join().unwrap();
+                    assert_eq!(new, proof.is_some_and(|p| p.verify()));
+                    new
+                })
+                .collect()
+        });
+        assert_eq!(inserted.iter().filter(|new| **new).count(), 1);
+        assert_eq!(filter.epoch().unwrap(), 1);
+
+        assert!(filter.contains(&7i64).unwrap() && !filter.contains(&8i64).unwrap());
+        let proof = filter.prove_seen(&7i64).unwrap();
+        assert!(proof.verify(filter.acc_value().unwrap()));
+        assert!(filter.prove_seen(&8i64).is_err());
+
+        let acc = filter.into_inner().unwrap();
+        assert!(acc.check(&7).unwrap());
+    }
+}
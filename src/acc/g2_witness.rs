@@ -0,0 +1,186 @@
+//! Membership witnesses in G2.
+//!
+//! A [`MembershipProof`] has its witness in G1, like the accumulator, and the verifier raises g2
+//! to s - x. With the witness in G2 the verifier instead works in G1 only: it checks
+//! e(g1^(s-x), witness) == e(acc, g2). The same proof also verifies against an accumulator value
+//! pinned to G2 (see [`DynamicAccumulator::acc_value_g2`]), as
+//! e(g1^(s-x), witness) == e(g1, acc_g2). Deployments choose the layout with [`WitnessGroup`].
+
+use super::{
+    commit_g2,
+    commitment::params_id,
+    dynamic_accumulator::{DynamicAccumulator, MembershipProof},
+    g1_s_minus,
+    handle::ElementHandle,
+    metrics, polymath,
+    utils::digest_to_prime_field,
+    Curve, G1Affine, G2Affine,
+};
+use crate::digest::{Digest, Digestible};
+use anyhow::{anyhow, bail, Result};
+use ark_ec::{AffineCurve, PairingEngine};
+use ark_ff::One;
+use ark_poly::univariate::DenseOrSparsePolynomial;
+
+type G1Prepared = <Curve as PairingEngine>::G1Prepared;
+type G2Prepared = <Curve as PairingEngine>::G2Prepared;
+
+/// The group membership witnesses live in.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum WitnessGroup {
+    #[default]
+    G1,
+    G2,
+}
+
+/// A membership proof with the witness g2^(P(s)/(s-x)).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct G2MembershipProof {
+    pub witness: G2Affine,
+    pub element: ElementHandle,
+    /// The epoch of the accumulator state the proof was generated for.
+    pub epoch: u64,
+    /// The digest of the G1 accumulator value the proof was generated for.
+    pub acc_digest: Digest,
+    /// The identifier of the public parameters the proof was generated under, see
+    /// [`params_id`].
+    pub params_id: Digest,
+}
+
+impl G2MembershipProof {
+    /// Verifies the proof against a G1 accumulator value.
+    /// It checks if e(g1^(s-element), witness) == e(accumulator, g2).
+    pub fn verify(&self, accumulator: G1Affine) -> bool {
+        self.acc_digest == accumulator.to_digest()
+            && self.check(accumulator, G2Affine::prime_subgroup_generator())
+    }
+
+    pub fn verify_at(&self, accumulator: G1Affine, epoch: u64) -> bool {
+        self.epoch == epoch && self.verify(accumulator)
+    }
+
+    /// Verifies the proof against an accumulator value in G2.
+    /// It checks if e(g1^(s-element), witness) == e(g1, acc_g2). Unlike
+    /// [`G2MembershipProof::verify`], the accumulator digest is not checked since it is over the
+    /// G1 value.
+    pub fn verify_g2(&self, acc_g2: G2Affine) -> bool {
+        self.check(G1Affine::prime_subgroup_generator(), acc_g2)
+    }
+
+    fn check(&self, lhs: G1Affine, rhs: G2Affine) -> bool {
+        if self.params_id != params_id() {
+            return false;
+        }
+        let g1_s_minus_elem = g1_s_minus(self.element.0);
+        let pairs: [(G1Prepared, G2Prepared); 2] = [
+            (g1_s_minus_elem.into(), self.witness.into()),
+            ((-lhs).into(), rhs.into()),
+        ];
+        metrics::record_pairings(2);
+        Curve::product_of_pairings(&pairs).is_one()
+    }
+}
+
+/// A membership proof in either layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutMembershipProof {
+    G1(MembershipProof),
+    G2(G2MembershipProof),
+}
+
+impl LayoutMembershipProof {
+    pub fn group(&self) -> WitnessGroup {
+        match self {
+            Self::G1(_) => WitnessGroup::G1,
+            Self::G2(_) => WitnessGroup::G2,
+        }
+    }
+
+    pub fn element(&self) -> ElementHandle {
+        match self {
+            Self::G1(p) => p.element,
+            Self::G2(p) => p.element,
+        }
+    }
+
+    /// Verifies the proof against a G1 accumulator value.
+    pub fn verify(&self, accumulator: G1Affine) -> bool {
+        match self {
+            Self::G1(p) => p.verify(accumulator),
+            Self::G2(p) => p.verify(accumulator),
+        }
+    }
+}
+
+impl DynamicAccumulator {
+    /// Generates a membership proof with the witness in G2, committing to P(X)/(X-x) with the
+    /// published powers.
+    pub fn prove_membership_g2(&self, element: &i64) -> Result<G2MembershipProof> {
+        let fr_element = digest_to_prime_field(&element.to_digest());
+        if !self.elements.contains(&fr_element)? {
+            bail!("Cannot prove membership for an element not in the set");
+        }
+        let (quotient, _) = DenseOrSparsePolynomial::from(self.characteristic_poly())
+            .divide_with_q_and_r(&polymath::linear(fr_element).into())
+            .ok_or_else(|| anyhow!("Failed to divide the characteristic polynomial"))?;
+        Ok(G2MembershipProof {
+            witness: commit_g2(quotient)?,
+            element: ElementHandle(fr_element),
+            epoch: self.epoch(),
+            acc_digest: self.acc_value.to_digest(),
+            params_id: params_id(),
+        })
+    }
+
+    /// Generates a membership proof with the witness in `group`.
+    pub fn prove_membership_in(
+        &self,
+        element: &i64,
+        group: WitnessGroup,
+    ) -> Result<LayoutMembershipProof> {
+        match group {
+            WitnessGroup::G1 => self
+                .prove_membership(element)
+                .map(LayoutMembershipProof::G1),
+            WitnessGroup::G2 => self
+                .prove_membership_g2(element)
+                .map(LayoutMembershipProof::G2),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_g2_witness() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        acc.add_batch(&[1, 2, 3]).unwrap();
+        let acc_g2 = acc.acc_value_g2().unwrap();
+        assert_eq!(
+            Curve::pairing(acc.acc_value, G2Affine::prime_subgroup_generator()),
+            Curve::pairing(G1Affine::prime_subgroup_generator(), acc_g2)
+        );
+
+        let proof = acc.prove_membership_g2(&2).unwrap();
+        assert!(proof.verify_at(acc.acc_value, acc.epoch()));
+        assert!(proof.verify_g2(acc_g2));
+        assert!(acc.prove_membership_g2(&4).is_err());
+
+        let mut wrong = proof.clone();
+        wrong.element = acc.prove_membership(&3).unwrap().element;
+        assert!(!wrong.verify(acc.acc_value) && !wrong.verify_g2(acc_g2));
+
+        for group in [WitnessGroup::G1, WitnessGroup::G2] {
+            let proof = acc.prove_membership_in(&1, group).unwrap();
+            assert_eq!(proof.group(), group);
+            assert!(proof.verify(acc.acc_value));
+        }
+    }
+}
@@ -0,0 +1,216 @@
+//! Deletions with a mandated grace period.
+//!
+//! A [`GraceAccumulator`] never deletes an element right away. [`GraceAccumulator::schedule_delete`]
+//! issues a signed [`DeletionNotice`] at epoch E naming the epoch E + k from which the element may
+//! be removed, and [`GraceAccumulator::process_due`] removes the elements whose notice has come
+//! due. Each removal comes with a [`GracefulDeleteProof`] that anyone holding the manager's
+//! verifying key can check against the policy's grace period.
+
+use super::{
+    dynamic_accumulator::{AddProof, DeleteProof, DynamicAccumulator},
+    signing::{Signature, SigningKey, VerifyingKey},
+    update::fr_to_digest,
+    utils::digest_to_prime_field,
+    Fr,
+};
+use crate::digest::{concat_digest, Digest, Digestible};
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The manager's signed announcement that `element` will be deleted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeletionNotice {
+    pub element_digest: Digest,
+    /// The epoch the deletion was scheduled at.
+    pub marked_at: u64,
+    /// The first epoch the element may be deleted at.
+    pub effective_at: u64,
+    pub signature: Signature,
+}
+
+fn notice_digest(element_digest: &Digest, marked_at: u64, effective_at: u64) -> Digest {
+    concat_digest(
+        [
+            b"DeletionNotice"[..].to_digest(),
+            *element_digest,
+            marked_at.to_digest(),
+            effective_at.to_digest(),
+        ]
+        .iter()
+        .copied(),
+    )
+}
+
+impl DeletionNotice {
+    pub fn verify(&self, vk: &VerifyingKey) -> bool {
+        vk.verify(
+            &notice_digest(&self.element_digest, self.marked_at, self.effective_at),
+            &self.signature,
+        )
+    }
+}
+
+/// A deletion together with the notice that announced it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GracefulDeleteProof {
+    pub notice: DeletionNotice,
+    pub delete_proof: DeleteProof,
+}
+
+impl GracefulDeleteProof {
+    /// Checks that the notice is signed by `vk`, announced at least `grace` epochs ahead, and
+    /// that the deletion is valid, of the announced element, and not earlier than announced.
+    pub fn verify(&self, vk: &VerifyingKey, grace: u64) -> bool {
+        self.notice.effective_at >= self.notice.marked_at.saturating_add(grace)
+            && self.delete_proof.epoch >= self.notice.effective_at
+            && self.notice.element_digest == fr_to_digest(&self.delete_proof.element.0)
+            && self.notice.verify(vk)
+            && self.delete_proof.verify()
+    }
+}
+
+/// A dynamic accumulator that deletes elements only after a grace period of `grace` epochs.
+#[derive(Debug, Clone)]
+pub struct GraceAccumulator {
+    acc: DynamicAccumulator,
+    key: SigningKey,
+    grace: u64,
+    pending: BTreeMap<Fr, DeletionNotice>,
+}
+
+impl GraceAccumulator {
+    pub fn new(key: SigningKey, grace: u64) -> Self {
+        Self {
+            acc: DynamicAccumulator::new(),
+            key,
+            grace,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// The wrapped accumulator, e.g. to prove membership of pending elements.
+    pub fn acc(&self) -> &DynamicAccumulator {
+        &self.acc
+    }
+
+    pub fn grace(&self) -> u64 {
+        self.grace
+    }
+
+    pub fn add(&mut self, element: &i64) -> Result<AddProof> {
+        self.acc.add(element)
+    }
+
+    /// Starts a new epoch without changing the set, so that pending deletions can come due.
+    pub fn bump_epoch(&mut self) -> Result<u64> {
+        Ok(self.acc.bump_epoch()?)
+    }
+
+    /// Schedules the deletion of a member. The element stays in the set until
+    /// [`Self::process_due`] runs at or after the notice's `effective_at` epoch.
+    pub fn schedule_delete(&mut self, element: &i64) -> Result<DeletionNotice> {
+        let fr_element = digest_to_prime_field(&element.to_digest());
+        if !self.acc.elements.contains(&fr_element)? {
+            bail!("Element not in accumulator");
+        }
+        if self.pending.contains_key(&fr_element) {
+            bail!("Deletion already scheduled");
+        }
+        let element_digest = fr_to_digest(&fr_element);
+        let marked_at = self.acc.epoch();
+        let effective_at = marked_at + self.grace;
+        let notice = DeletionNotice {
+            element_digest,
+            marked_at,
+            effective_at,
+            signature: self
+                .key
+                .sign(&notice_digest(&element_digest, marked_at, effective_at)),
+        };
+        self.pending.insert(fr_element, notice.clone());
+        Ok(notice)
+    }
+
+    /// Withdraws a scheduled deletion.
+    pub fn cancel_delete(&mut self, element: &i64) -> Result<DeletionNotice> {
+        self.pending
+            .remove(&digest_to_prime_field(&element.to_digest()))
+            .ok_or_else(|| anyhow!("No deletion scheduled"))
+    }
+
+    /// Returns the notice of a scheduled deletion, if any.
+    pub fn pending(&self, element: &i64) -> Option<&DeletionNotice> {
+        self.pending
+            .get(&digest_to_prime_field(&element.to_digest()))
+    }
+
+    /// Deletes every element whose grace period has passed.
+    pub fn process_due(&mut self) -> Result<Vec<GracefulDeleteProof>> {
+        let epoch = self.acc.epoch();
+        let due: Vec<Fr> = self
+            .pending
+            .iter()
+            .filter(|(_, notice)| notice.effective_at <= epoch)
+            .map(|(element, _)| *element)
+            .collect();
+        let mut proofs = Vec::with_capacity(due.len());
+        for element in due {
+            let delete_proof = self.acc.delete_fr(element)?;
+            let notice = self
+                .pending
+                .remove(&element)
+                .expect("due elements are pending");
+            proofs.push(GracefulDeleteProof {
+                notice,
+                delete_proof,
+            });
+        }
+        Ok(proofs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_grace_period() {
+        init_logger();
+        let key = SigningKey::from_seed(b"manager");
+        let vk = key.verifying_key();
+        let mut acc = GraceAccumulator::new(key, 2);
+        acc.add(&1).unwrap();
+        acc.add(&2).unwrap();
+
+        let notice = acc.schedule_delete(&1).unwrap();
+        assert!(notice.verify(&vk));
+        assert_eq!((notice.marked_at, notice.effective_at), (2, 4));
+        assert!(acc.schedule_delete(&1).is_err());
+        assert!(acc.schedule_delete(&3).is_err());
+        acc.schedule_delete(&2).unwrap();
+        acc.cancel_delete(&2).unwrap();
+
+        assert!(acc.process_due().unwrap().is_empty());
+        acc.bump_epoch().unwrap();
+        assert!(acc.process_due().unwrap().is_empty());
+        assert!(acc.acc().prove_membership(&1).is_ok());
+        acc.bump_epoch().unwrap();
+        let proofs = acc.process_due().unwrap();
+        assert_eq!(proofs.len(), 1);
+        assert!(proofs[0].verify(&vk, 2));
+        assert!(!proofs[0].verify(&vk, 3));
+        assert!(acc.pending(&1).is_none());
+        assert!(acc.acc().prove_non_membership(&1).is_ok());
+        assert!(acc.acc().prove_membership(&2).is_ok());
+
+        // A notice for another element does not cover the deletion.
+        let mut swapped = proofs[0].clone();
+        swapped.notice = acc.schedule_delete(&2).unwrap();
+        assert!(!swapped.verify(&vk, 0));
+    }
+}
@@ -0,0 +1,147 @@
+//! Accumulator values lifted to the target group.
+//!
+//! `acc_gt = e(acc, g2) = e(g1, g2)^P(s)` commits to the same set as the G1 value but can be
+//! combined multiplicatively: multiplying by `e(g1, g2)^Q(s)` of another set gives the commitment
+//! of the multiset union. Proofs here check against such target-group values directly.
+
+use super::{
+    commit_g2,
+    commitment::params_id,
+    dynamic_accumulator::{ark_serde, AddProof, DeleteProof, DynamicAccumulator, MembershipProof},
+    g2_s_minus, Curve, Fq12, G1Affine, G2Affine,
+};
+use crate::digest::Digest;
+use anyhow::Result;
+use ark_ec::{AffineCurve, PairingEngine};
+use serde::{Deserialize, Serialize};
+
+/// Lifts an accumulator value to the target group: e(acc, g2).
+pub fn acc_gt(acc_value: G1Affine) -> Fq12 {
+    Curve::pairing(acc_value, G2Affine::prime_subgroup_generator())
+}
+
+/// A proof that `product_gt` commits to the multiset union of two accumulated sets, given the
+/// first set's G1 value and the second set's target-group value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProductGtProof {
+    /// The second accumulator in G2, g2^Q(s).
+    #[serde(with = "ark_serde")]
+    pub rhs_g2: G2Affine,
+    /// e(g1, g2)^(P(s)Q(s)).
+    #[serde(with = "ark_serde")]
+    pub product_gt: Fq12,
+    /// The identifier of the public parameters the proof was generated under, see
+    /// [`params_id`].
+    pub params_id: Digest,
+}
+
+impl ProductGtProof {
+    /// It checks if e(g1, rhs_g2) == rhs_gt and e(lhs, rhs_g2) == product_gt.
+    pub fn verify(&self, lhs: G1Affine, rhs_gt: Fq12) -> bool {
+        self.params_id == params_id()
+            && Curve::pairing(G1Affine::prime_subgroup_generator(), self.rhs_g2) == rhs_gt
+            && Curve::pairing(lhs, self.rhs_g2) == self.product_gt
+    }
+}
+
+impl MembershipProof {
+    /// Verifies the proof against a target-group accumulator value.
+    /// It checks if e(witness, g2^(s-element)) == acc_gt. Unlike [`MembershipProof::verify`],
+    /// the accumulator digest is not checked since it is over the G1 value.
+    pub fn verify_gt(&self, acc_gt: Fq12) -> bool {
+        let g2_s_minus_elem = g2_s_minus(self.element.0);
+        self.params_id == params_id() && Curve::pairing(self.witness, g2_s_minus_elem) == acc_gt
+    }
+}
+
+impl AddProof {
+    /// Verifies the proof and that it moves between the given target-group values.
+    pub fn verify_gt(&self, old_gt: Fq12, new_gt: Fq12) -> bool {
+        acc_gt(self.old_acc_value) == old_gt
+            && acc_gt(self.new_acc_value) == new_gt
+            && self.verify()
+    }
+}
+
+impl DeleteProof {
+    /// Verifies the proof and that it moves between the given target-group values.
+    pub fn verify_gt(&self, old_gt: Fq12, new_gt: Fq12) -> bool {
+        acc_gt(self.old_acc_value) == old_gt
+            && acc_gt(self.new_acc_value) == new_gt
+            && self.verify()
+    }
+}
+
+impl DynamicAccumulator {
+    /// The accumulator value in the target group, e(acc, g2).
+    pub fn acc_value_gt(&self) -> Fq12 {
+        acc_gt(self.acc_value)
+    }
+
+    /// The accumulator value in G2, g2^P(s), committed with the published powers. Fails for
+    /// more than [`MAX_DEGREE`](super::MAX_DEGREE) elements.
+    pub fn acc_value_g2(&self) -> Result<G2Affine> {
+        commit_g2(self.characteristic_poly())
+    }
+
+    /// Proves the target-group commitment of the multiset union of `self` and `other`, without
+    /// publishing the union in G1.
+    pub fn prove_product_gt(&self, other: &DynamicAccumulator) -> Result<ProductGtProof> {
+        let rhs_g2 = other.acc_value_g2()?;
+        Ok(ProductGtProof {
+            rhs_g2,
+            product_gt: Curve::pairing(self.acc_value, rhs_g2),
+            params_id: params_id(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_gt_membership_and_updates() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        acc.add(&1).unwrap();
+        let old_gt = acc.acc_value_gt();
+        let add = acc.add(&2).unwrap();
+        assert!(add.verify_gt(old_gt, acc.acc_value_gt()));
+        assert!(!add.verify_gt(acc.acc_value_gt(), old_gt));
+
+        let proof = acc.prove_membership(&1).unwrap();
+        assert!(proof.verify_gt(acc.acc_value_gt()));
+        assert!(!proof.verify_gt(old_gt));
+
+        let old_gt = acc.acc_value_gt();
+        let delete = acc.delete(&2).unwrap();
+        assert!(delete.verify_gt(old_gt, acc.acc_value_gt()));
+    }
+
+    #[test]
+    fn test_product_gt() {
+        init_logger();
+        let mut a = DynamicAccumulator::new();
+        a.add_batch(&[1, 2]).unwrap();
+        let mut b = DynamicAccumulator::new();
+        b.add_batch(&[3, 4]).unwrap();
+        let mut union = a.clone();
+        union.add_batch(&[3, 4]).unwrap();
+
+        let proof = a.prove_product_gt(&b).unwrap();
+        assert!(proof.verify(a.acc_value, b.acc_value_gt()));
+        assert_eq!(proof.product_gt, union.acc_value_gt());
+        assert!(!proof.verify(a.acc_value, a.acc_value_gt()));
+
+        let json = serde_json::to_string(&proof).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ProductGtProof>(&json).unwrap(),
+            proof
+        );
+    }
+}
@@ -0,0 +1,61 @@
+//! Opaque references to accumulated elements.
+//!
+//! Proofs name their element by an [`ElementHandle`] rather than by the field element it hashes
+//! to, so that callers compare, store and serialize elements without depending on the field of
+//! the current scheme. [`ElementHandle::of`] gives the handle of a value, and
+//! [`ElementHandle::to_fr`] and [`ElementHandle::from_fr`] convert explicitly where the field
+//! element is really needed. The serialized form is that of the field element.
+
+use super::{dynamic_accumulator::ark_serde, utils::digest_to_prime_field, Fr};
+use crate::digest::Digestible;
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+/// The element a proof or update is about.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ElementHandle(#[serde(with = "ark_serde")] pub(crate) Fr);
+
+impl ElementHandle {
+    /// The handle of `element` as accumulated by [`DynamicAccumulator::add`].
+    ///
+    /// [`DynamicAccumulator::add`]: crate::DynamicAccumulator::add
+    pub fn of(element: &i64) -> Self {
+        Self(digest_to_prime_field(&element.to_digest()))
+    }
+
+    pub fn from_fr(fr: Fr) -> Self {
+        Self(fr)
+    }
+
+    pub fn to_fr(self) -> Fr {
+        self.0
+    }
+}
+
+impl fmt::Debug for ElementHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ElementHandle({})", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DynamicAccumulator;
+
+    #[test]
+    fn test_element_handle() {
+        let mut acc = DynamicAccumulator::new();
+        let proof = acc.add(&7).unwrap();
+        assert_eq!(proof.element, ElementHandle::of(&7));
+        assert_ne!(proof.element, ElementHandle::of(&8));
+        assert_eq!(ElementHandle::from_fr(proof.element.to_fr()), proof.element);
+
+        let bin = bincode::serialize(&proof.element).unwrap();
+        assert_eq!(
+            bincode::deserialize::<ElementHandle>(&bin).unwrap(),
+            proof.element
+        );
+    }
+}
@@ -0,0 +1,99 @@
+//! Provers that do not leak membership through timing, used with the `hardened` feature.
+//!
+//! By default, a request for a proof that does not hold fails right after the set lookup, and
+//! membership witnesses computed earlier are served from a cache, so the time a request takes
+//! tells whether the element is accumulated before any proof is released. With the `hardened`
+//! feature, [`DynamicAccumulator::prove_membership`] and
+//! [`DynamicAccumulator::prove_non_membership`] go through the provers here, which do the work
+//! of a successful proof before acting on the lookup: a membership request for a non-member
+//! still computes a witness, a non-membership request for a member still commits to Bézout
+//! coefficients for a random non-root, and the witness cache is bypassed. Both outcomes then
+//! cost the same, up to the set lookup itself, whose timing is not hidden.
+//!
+//! Which kind of proof [`DynamicAccumulator::query`] builds still depends on membership, but
+//! the answer reveals that anyway.
+
+use super::{
+    commitment::params_id,
+    dynamic_accumulator::{DynamicAccumulator, MembershipProof, NonMembershipProof},
+    handle::ElementHandle,
+    public_prover, trapdoor, Fr,
+};
+use crate::digest::Digestible;
+use anyhow::{anyhow, bail, Result};
+use ark_ff::UniformRand;
+
+pub(crate) fn prove_membership(
+    acc: &DynamicAccumulator,
+    fr_element: Fr,
+) -> Result<MembershipProof> {
+    let witness = trapdoor::signer().div_s_minus(acc.acc_value, fr_element);
+    if !acc.elements.contains(&fr_element)? {
+        bail!("Cannot prove membership for an element not in the set");
+    }
+    Ok(MembershipProof {
+        witness: witness?,
+        element: ElementHandle(fr_element),
+        epoch: acc.epoch(),
+        acc_digest: acc.acc_value.to_digest(),
+        params_id: params_id(),
+    })
+}
+
+pub(crate) fn prove_non_membership(
+    acc: &DynamicAccumulator,
+    fr_element: Fr,
+) -> Result<NonMembershipProof> {
+    let p_poly = acc.characteristic_poly();
+    let member = acc.elements.contains(&fr_element)?;
+    // A random point is a root of P(X) only with negligible probability.
+    let target = if member {
+        Fr::rand(&mut rand::thread_rng())
+    } else {
+        fr_element
+    };
+    let witness = public_prover::non_membership_witness(&p_poly, target);
+    if member {
+        bail!("Cannot prove non-membership for an element in the set");
+    }
+    let (g1_a, witness) =
+        witness.ok_or_else(|| anyhow!("Failed to create non-membership proof"))?;
+    Ok(NonMembershipProof {
+        element: ElementHandle(fr_element),
+        witness,
+        g1_a,
+        epoch: acc.epoch(),
+        acc_digest: acc.acc_value.to_digest(),
+        params_id: params_id(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::utils::digest_to_prime_field;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_hardened_provers() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        acc.add(&1).unwrap();
+        acc.add(&2).unwrap();
+        let (one, three): (Fr, Fr) = (
+            digest_to_prime_field(&1i64.to_digest()),
+            digest_to_prime_field(&3i64.to_digest()),
+        );
+
+        let proof = prove_membership(&acc, one).unwrap();
+        assert_eq!(proof, acc.prove_membership(&1).unwrap());
+        assert!(prove_membership(&acc, three).is_err());
+
+        let proof = prove_non_membership(&acc, three).unwrap();
+        assert_eq!(proof, acc.prove_non_membership(&3).unwrap());
+        assert!(prove_non_membership(&acc, one).is_err());
+    }
+}
@@ -0,0 +1,199 @@
+//! Verifier-side reuse of Miller-loop values.
+//!
+//! Every membership check against an accumulator value computes e(acc, g2). A verifier keeps a
+//! [`HintCache`] of the Miller-loop values of that pairing: once a value is cached, checks
+//! against the same accumulator take a single Miller loop and one final exponentiation, and
+//! the first check against a new accumulator computes the value and caches it.
+//!
+//! A [`PairingHint`] carries a precomputed Miller-loop value. Checking it costs as much as
+//! computing it, so it is only good for priming a cache with [`HintCache::prime`] from a source
+//! the verifier trusts, e.g. a peer of the same verification cluster sharing the values it
+//! computed. A verifier that does not trust the source should not prime its cache with it.
+
+use super::{
+    dynamic_accumulator::{ark_serde, MembershipProof},
+    g2_s_minus, Curve, Fq12, G1Affine, G2Affine,
+};
+use crate::digest::{Digest, Digestible};
+use ark_ec::{AffineCurve, PairingEngine};
+use ark_ff::{Field, One};
+use core::fmt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// The default maximum number of cached Miller-loop values.
+pub const DEFAULT_HINT_CACHE_CAPACITY: usize = 1 << 10;
+
+fn miller_loop(g1: G1Affine, g2: G2Affine) -> Fq12 {
+    Curve::miller_loop(&[(g1.into(), g2.into())])
+}
+
+/// The Miller-loop value of e(acc_value, g2), for priming a [`HintCache`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PairingHint {
+    #[serde(with = "ark_serde")]
+    pub acc_value: G1Affine,
+    #[serde(with = "ark_serde")]
+    pub miller: Fq12,
+}
+
+impl PairingHint {
+    pub fn new(acc_value: G1Affine) -> Self {
+        Self {
+            acc_value,
+            miller: miller_loop(acc_value, G2Affine::prime_subgroup_generator()),
+        }
+    }
+}
+
+/// Miller-loop values of e(acc, g2), keyed by accumulator value.
+/// Once `capacity` values are cached, further values are not stored until [`Self::clear`].
+pub struct HintCache {
+    inner: RwLock<HashMap<Digest, Fq12>>,
+    capacity: usize,
+}
+
+impl HintCache {
+    /// Creates an empty cache holding at most `capacity` values.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            inner: RwLock::new(HashMap::new()),
+            capacity,
+        }
+    }
+
+    /// Returns the Miller-loop value of e(acc_value, g2), from the cache if it holds one and
+    /// otherwise computed and cached.
+    pub fn miller(&self, acc_value: G1Affine) -> Fq12 {
+        let key = acc_value.to_digest();
+        let cached = self
+            .inner
+            .read()
+            .ok()
+            .and_then(|inner| inner.get(&key).copied());
+        if let Some(miller) = cached {
+            return miller;
+        }
+        let miller = miller_loop(acc_value, G2Affine::prime_subgroup_generator());
+        self.insert(key, miller);
+        miller
+    }
+
+    /// Caches the value of `hint` without checking it. A wrong value makes checks against its
+    /// accumulator fail or, worse, pass for invalid proofs, so only prime the cache with hints
+    /// from a trusted source.
+    pub fn prime(&self, hint: &PairingHint) {
+        self.insert(hint.acc_value.to_digest(), hint.miller);
+    }
+
+    fn insert(&self, key: Digest, miller: Fq12) {
+        if let Ok(mut inner) = self.inner.write() {
+            if inner.len() < self.capacity || inner.contains_key(&key) {
+                inner.insert(key, miller);
+            }
+        }
+    }
+
+    /// Drops all cached values.
+    pub fn clear(&self) {
+        if let Ok(mut inner) = self.inner.write() {
+            inner.clear();
+        }
+    }
+
+    /// Returns the number of cached values.
+    pub fn len(&self) -> usize {
+        self.inner.read().map(|inner| inner.len()).unwrap_or(0)
+    }
+
+    /// Returns true if no value is cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for HintCache {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_HINT_CACHE_CAPACITY)
+    }
+}
+
+impl fmt::Debug for HintCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HintCache")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+impl MembershipProof {
+    /// Same as [`MembershipProof::verify`], but takes the e(acc, g2) side from `cache`. It
+    /// checks if e(witness, g2^(s-element)) / e(accumulator, g2) == 1 with a single final
+    /// exponentiation.
+    pub fn verify_with_cache(&self, accumulator: G1Affine, cache: &HintCache) -> bool {
+        if self.acc_digest != accumulator.to_digest() {
+            return false;
+        }
+        let g2_s_minus_elem = g2_s_minus(self.element.0);
+        let lhs = miller_loop(self.witness, g2_s_minus_elem);
+        let rhs_inv = match cache.miller(accumulator).inverse() {
+            Some(inv) => inv,
+            None => return false,
+        };
+        Curve::final_exponentiation(&(lhs * rhs_inv)) == Some(Fq12::one())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::dynamic_accumulator::DynamicAccumulator;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_verify_with_cache() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        acc.add_batch(&[1, 2]).unwrap();
+        let cache = HintCache::default();
+
+        let proof = acc.prove_membership(&1).unwrap();
+        assert!(proof.verify_with_cache(acc.acc_value, &cache));
+        assert_eq!(cache.len(), 1);
+        // The cached value is served for later checks against the same accumulator.
+        assert_eq!(
+            cache.miller(acc.acc_value),
+            PairingHint::new(acc.acc_value).miller
+        );
+        assert!(acc
+            .prove_membership(&2)
+            .unwrap()
+            .verify_with_cache(acc.acc_value, &cache));
+        assert_eq!(cache.len(), 1);
+
+        let old_value = acc.acc_value;
+        acc.add(&3).unwrap();
+        assert!(!proof.verify_with_cache(acc.acc_value, &cache));
+        assert!(!acc
+            .prove_membership(&3)
+            .unwrap()
+            .verify_with_cache(old_value, &cache));
+
+        // A primed value is used as is, so priming takes a trusted hint.
+        let primed = HintCache::default();
+        primed.prime(&PairingHint::new(acc.acc_value));
+        assert_eq!(primed.len(), 1);
+        let proof = acc.prove_membership(&3).unwrap();
+        assert!(proof.verify_with_cache(acc.acc_value, &primed));
+        primed.prime(&PairingHint {
+            acc_value: acc.acc_value,
+            miller: Fq12::one(),
+        });
+        assert!(!proof.verify_with_cache(acc.acc_value, &primed));
+    }
+}
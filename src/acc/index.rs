@@ -0,0 +1,393 @@
+//! Pluggable storage for the set of accumulated elements.
+//!
+//! A [`DynamicAccumulator`](super::dynamic_accumulator::DynamicAccumulator) keeps its elements
+//! in an [`ElementIndex`]. [`HashIndex`] is the default; [`BTreeIndex`] keeps the elements
+//! ordered by their field representation; [`PersistentIndex`] shares its structure between
+//! copies, so that [`DynamicAccumulator::fork`](super::dynamic_accumulator::DynamicAccumulator::fork)
+//! is cheap for any set size. Other backends, e.g. one backed by disk for very
+//! large sets, implement the trait and are passed to
+//! [`DynamicAccumulator::with_index`](super::dynamic_accumulator::DynamicAccumulator::with_index).
+//! Lookups and updates return a `Result`, so that such a backend reports I/O failures instead of
+//! answering wrongly; operations of the accumulator pass them on, and predicates returning a
+//! `bool` treat a failed lookup as absent.
+
+use super::Fr;
+use anyhow::Result;
+use core::fmt;
+use std::collections::{BTreeSet, HashSet};
+
+/// A set of field elements.
+pub trait ElementIndex: fmt::Debug + Send + Sync {
+    fn contains(&self, element: &Fr) -> Result<bool>;
+
+    /// Inserts an element. Returns false if it was already present.
+    fn insert(&mut self, element: Fr) -> Result<bool>;
+
+    /// Removes an element. Returns false if it was not present.
+    fn remove(&mut self, element: &Fr) -> Result<bool>;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterates over the elements, in ascending order if [`ElementIndex::is_ordered`].
+    fn iter(&self) -> Box<dyn Iterator<Item = Fr> + '_>;
+
+    /// Returns true if [`ElementIndex::iter`] yields the elements in ascending order.
+    fn is_ordered(&self) -> bool {
+        false
+    }
+
+    /// Returns an independent copy of the index.
+    fn boxed_clone(&self) -> Box<dyn ElementIndex>;
+
+    /// Returns true if [`ElementIndex::boxed_clone`] shares structure with the original
+    /// instead of copying every element.
+    fn shares_structure(&self) -> bool {
+        false
+    }
+}
+
+/// An in-memory hash set. This is the default index.
+#[derive(Debug, Clone, Default)]
+pub struct HashIndex(HashSet<Fr>);
+
+impl ElementIndex for HashIndex {
+    fn contains(&self, element: &Fr) -> Result<bool> {
+        Ok(self.0.contains(element))
+    }
+
+    fn insert(&mut self, element: Fr) -> Result<bool> {
+        Ok(self.0.insert(element))
+    }
+
+    fn remove(&mut self, element: &Fr) -> Result<bool> {
+        Ok(self.0.remove(element))
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = Fr> + '_> {
+        Box::new(self.0.iter().copied())
+    }
+
+    fn boxed_clone(&self) -> Box<dyn ElementIndex> {
+        Box::new(self.clone())
+    }
+}
+
+/// An in-memory ordered set, for ordered iteration and range queries.
+#[derive(Debug, Clone, Default)]
+pub struct BTreeIndex(BTreeSet<Fr>);
+
+impl BTreeIndex {
+    /// Iterates over the elements in `range`, in ascending order.
+    pub fn range<R: core::ops::RangeBounds<Fr>>(&self, range: R) -> impl Iterator<Item = &Fr> {
+        self.0.range(range)
+    }
+}
+
+impl ElementIndex for BTreeIndex {
+    fn contains(&self, element: &Fr) -> Result<bool> {
+        Ok(self.0.contains(element))
+    }
+
+    fn insert(&mut self, element: Fr) -> Result<bool> {
+        Ok(self.0.insert(element))
+    }
+
+    fn remove(&mut self, element: &Fr) -> Result<bool> {
+        Ok(self.0.remove(element))
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = Fr> + '_> {
+        Box::new(self.0.iter().copied())
+    }
+
+    fn is_ordered(&self) -> bool {
+        true
+    }
+
+    fn boxed_clone(&self) -> Box<dyn ElementIndex> {
+        Box::new(self.clone())
+    }
+}
+
+/// A persistent hash set: copies share their structure and only diverge where they are
+/// modified, so copying takes constant time.
+#[derive(Debug, Clone, Default)]
+pub struct PersistentIndex(im::HashSet<Fr>);
+
+impl ElementIndex for PersistentIndex {
+    fn contains(&self, element: &Fr) -> Result<bool> {
+        Ok(self.0.contains(element))
+    }
+
+    fn insert(&mut self, element: Fr) -> Result<bool> {
+        Ok(self.0.insert(element).is_none())
+    }
+
+    fn remove(&mut self, element: &Fr) -> Result<bool> {
+        Ok(self.0.remove(element).is_some())
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = Fr> + '_> {
+        Box::new(self.0.iter().copied())
+    }
+
+    fn boxed_clone(&self) -> Box<dyn ElementIndex> {
+        Box::new(self.clone())
+    }
+
+    fn shares_structure(&self) -> bool {
+        true
+    }
+}
+
+/// The element set of an accumulator, backed by any [`ElementIndex`].
+///
+/// Two sets compare equal if they hold the same elements, whatever their backends.
+pub struct ElementSet(Box<dyn ElementIndex>);
+
+impl ElementSet {
+    pub fn new(index: impl ElementIndex + 'static) -> Self {
+        Self(Box::new(index))
+    }
+
+    pub fn contains(&self, element: &Fr) -> Result<bool> {
+        self.0.contains(element)
+    }
+
+    /// [`Self::contains`], treating a failed lookup as absent.
+    pub fn has(&self, element: &Fr) -> bool {
+        self.0.contains(element).unwrap_or(false)
+    }
+
+    pub fn insert(&mut self, element: Fr) -> Result<bool> {
+        self.0.insert(element)
+    }
+
+    pub fn remove(&mut self, element: &Fr) -> Result<bool> {
+        self.0.remove(element)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> Box<dyn Iterator<Item = Fr> + '_> {
+        self.0.iter()
+    }
+
+    pub fn is_ordered(&self) -> bool {
+        self.0.is_ordered()
+    }
+
+    pub fn shares_structure(&self) -> bool {
+        self.0.shares_structure()
+    }
+
+    /// The elements of the set that are also in `other`.
+    pub fn intersection(&self, other: &ElementSet) -> Result<Vec<Fr>> {
+        self.filter_by(|e| other.contains(e))
+    }
+
+    /// The elements of the set that are not in `other`.
+    pub fn difference(&self, other: &ElementSet) -> Result<Vec<Fr>> {
+        self.filter_by(|e| other.contains(e).map(|found| !found))
+    }
+
+    fn filter_by(&self, mut keep: impl FnMut(&Fr) -> Result<bool>) -> Result<Vec<Fr>> {
+        let mut kept = Vec::new();
+        for e in self.iter() {
+            if keep(&e)? {
+                kept.push(e);
+            }
+        }
+        Ok(kept)
+    }
+
+    /// Returns the backend.
+    pub fn index(&self) -> &dyn ElementIndex {
+        self.0.as_ref()
+    }
+}
+
+impl Default for ElementSet {
+    fn default() -> Self {
+        Self::new(HashIndex::default())
+    }
+}
+
+impl Clone for ElementSet {
+    fn clone(&self) -> Self {
+        Self(self.0.boxed_clone())
+    }
+}
+
+impl fmt::Debug for ElementSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl PartialEq for ElementSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|e| other.has(&e))
+    }
+}
+
+impl Eq for ElementSet {}
+
+impl FromIterator<Fr> for ElementSet {
+    fn from_iter<I: IntoIterator<Item = Fr>>(iter: I) -> Self {
+        Self::new(HashIndex(iter.into_iter().collect()))
+    }
+}
+
+impl<'a> IntoIterator for &'a ElementSet {
+    type Item = Fr;
+    type IntoIter = Box<dyn Iterator<Item = Fr> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::dynamic_accumulator::DynamicAccumulator;
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_btree_index() {
+        init_logger();
+        let mut ordered = DynamicAccumulator::with_index(BTreeIndex::default()).unwrap();
+        let mut hashed = DynamicAccumulator::new();
+        for acc in [&mut ordered, &mut hashed] {
+            acc.add_batch(&[5, 3, 9, 1]).unwrap();
+            acc.delete(&9).unwrap();
+        }
+        assert_eq!(ordered, hashed);
+        assert!(ordered.verify_membership(&ordered.prove_membership(&3).unwrap()));
+        assert!(ordered.verify_non_membership(&ordered.prove_non_membership(&9).unwrap()));
+
+        let elements = ordered.elements_fr();
+        assert!(elements.windows(2).all(|w| w[0] < w[1]));
+        assert!(ordered.elements.is_ordered() && !hashed.elements.is_ordered());
+
+        let mut non_empty = BTreeIndex::default();
+        non_empty.insert(elements[0]).unwrap();
+        assert!(DynamicAccumulator::with_index(non_empty).is_err());
+    }
+
+    #[test]
+    fn test_fork() {
+        init_logger();
+        let mut acc = DynamicAccumulator::with_index(PersistentIndex::default()).unwrap();
+        acc.add_batch(&[1, 2, 3]).unwrap();
+        let mut fork = acc.fork();
+        assert!(fork.elements.shares_structure());
+        fork.add(&4).unwrap();
+        fork.delete(&1).unwrap();
+        assert_eq!(acc.elements_fr().len(), 3);
+        assert!(acc.contains(&1) && !acc.contains(&4));
+        assert!(fork.verify_membership(&fork.prove_membership(&4).unwrap()));
+        assert!(acc.verify_non_membership(&acc.prove_non_membership(&4).unwrap()));
+
+        // Other backends are copied.
+        let hashed = DynamicAccumulator::new().fork();
+        assert!(!hashed.elements.shares_structure());
+    }
+
+    /// A backend whose storage fails once `broken` is set.
+    #[derive(Debug, Clone, Default)]
+    struct Flaky {
+        inner: HashIndex,
+        broken: Arc<AtomicBool>,
+    }
+
+    impl Flaky {
+        fn check(&self) -> Result<()> {
+            anyhow::ensure!(!self.broken.load(Ordering::SeqCst), "Storage unavailable");
+            Ok(())
+        }
+    }
+
+    impl ElementIndex for Flaky {
+        fn contains(&self, element: &Fr) -> Result<bool> {
+            self.check()?;
+            self.inner.contains(element)
+        }
+
+        fn insert(&mut self, element: Fr) -> Result<bool> {
+            self.check()?;
+            self.inner.insert(element)
+        }
+
+        fn remove(&mut self, element: &Fr) -> Result<bool> {
+            self.check()?;
+            self.inner.remove(element)
+        }
+
+        fn len(&self) -> usize {
+            self.inner.len()
+        }
+
+        fn iter(&self) -> Box<dyn Iterator<Item = Fr> + '_> {
+            self.inner.iter()
+        }
+
+        fn boxed_clone(&self) -> Box<dyn ElementIndex> {
+            Box::new(self.clone())
+        }
+    }
+
+    #[test]
+    fn test_failing_index() {
+        init_logger();
+        let index = Flaky::default();
+        let broken = index.broken.clone();
+        let mut acc = DynamicAccumulator::with_index(index).unwrap();
+        acc.add_batch(&[1, 2]).unwrap();
+        broken.store(true, Ordering::SeqCst);
+
+        let (value, epoch) = (acc.acc_value, acc.epoch());
+        assert!(acc.add(&3).is_err());
+        assert!(acc.delete(&1).is_err());
+        assert_eq!((acc.acc_value, acc.epoch()), (value, epoch));
+        assert!(acc.prove_membership(&1).is_err());
+        assert!(acc.prove_non_membership(&3).is_err());
+        assert!(acc.check(&1).is_err() && acc.check_then_prove(&1).is_err());
+        assert!(acc.query(&1).is_err());
+        // Predicates returning a bool treat a failed lookup as absent.
+        assert!(!acc.contains(&1));
+
+        broken.store(false, Ordering::SeqCst);
+        assert!(acc.check(&1).unwrap() && acc.add(&3).unwrap().verify());
+    }
+}
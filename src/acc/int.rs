@@ -0,0 +1,225 @@
+//! Integer elements of any width.
+//!
+//! The accumulator API takes `i64` elements, and casting a larger ID with `as` silently maps
+//! distinct IDs to one element, e.g. `u64::MAX as i64 == -1`. [`to_i64`] converts with a check
+//! instead, and the `*_int` methods of [`DynamicAccumulator`] take any [`IntElement`] directly.
+//! These integers are accumulated by value, see [`IntElement::int_digest`]: one that fits in
+//! `i64` is the element of that `i64`, whatever its type, and one that does not has its own
+//! digest. The `Digestible` impls of the integer types keep hashing their native bytes. With
+//! the `bigint` feature, `ethnum::U256` and `ethnum::I256` are elements too.
+
+use super::{
+    dynamic_accumulator::{
+        AddProof, DeleteProof, DynamicAccumulator, MembershipProof, NonMembershipProof,
+    },
+    utils::digest_to_prime_field,
+    Fr,
+};
+use crate::digest::{blake2, Digest, Digestible};
+use anyhow::{anyhow, Result};
+use core::fmt;
+
+/// Digests an integer outside the range of `i64` by its sign and big-endian magnitude, under its
+/// own personalization so that no `i64` or byte string digest can equal it.
+fn wide_int_digest(negative: bool, magnitude: &[u8; 32]) -> Digest {
+    let mut state = blake2().personal(b"esa_rust_int").to_state();
+    state.update(&[negative as u8]);
+    state.update(magnitude);
+    Digest::from(state.finalize())
+}
+
+fn magnitude_of(value: u128) -> [u8; 32] {
+    let mut magnitude = [0u8; 32];
+    magnitude[16..].copy_from_slice(&value.to_be_bytes());
+    magnitude
+}
+
+/// An integer type the accumulator takes as elements.
+pub trait IntElement: Copy + fmt::Display {
+    /// The value as an `i64`, if it fits.
+    fn as_i64(self) -> Option<i64>;
+
+    /// The sign and big-endian magnitude of the value.
+    fn sign_magnitude(self) -> (bool, [u8; 32]);
+
+    /// The digest of the value: the one of the `i64` if it fits, and one of its own otherwise.
+    fn int_digest(self) -> Digest {
+        match self.as_i64() {
+            Some(value) => value.to_digest(),
+            None => {
+                let (negative, magnitude) = self.sign_magnitude();
+                wide_int_digest(negative, &magnitude)
+            }
+        }
+    }
+
+    /// The field element accumulated for the value.
+    fn element(self) -> Fr {
+        digest_to_prime_field(&self.int_digest())
+    }
+}
+
+macro_rules! impl_int_element_unsigned {
+    ($($x: ty),*) => {$(
+        impl IntElement for $x {
+            fn as_i64(self) -> Option<i64> {
+                i64::try_from(self).ok()
+            }
+
+            fn sign_magnitude(self) -> (bool, [u8; 32]) {
+                (false, magnitude_of(self as u128))
+            }
+        }
+    )*}
+}
+
+macro_rules! impl_int_element_signed {
+    ($($x: ty),*) => {$(
+        impl IntElement for $x {
+            fn as_i64(self) -> Option<i64> {
+                i64::try_from(self).ok()
+            }
+
+            fn sign_magnitude(self) -> (bool, [u8; 32]) {
+                (self < 0, magnitude_of(self.unsigned_abs() as u128))
+            }
+        }
+    )*}
+}
+
+impl_int_element_unsigned!(u8, u16, u32, u64, u128);
+impl_int_element_signed!(i8, i16, i32, i64, i128);
+
+#[cfg(feature = "bigint")]
+impl IntElement for ethnum::U256 {
+    fn as_i64(self) -> Option<i64> {
+        i64::try_from(self).ok()
+    }
+
+    fn sign_magnitude(self) -> (bool, [u8; 32]) {
+        (false, self.to_be_bytes())
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl IntElement for ethnum::I256 {
+    fn as_i64(self) -> Option<i64> {
+        i64::try_from(self).ok()
+    }
+
+    fn sign_magnitude(self) -> (bool, [u8; 32]) {
+        (self.is_negative(), self.unsigned_abs().to_be_bytes())
+    }
+}
+
+/// Converts `value` to an `i64` element, failing instead of truncating.
+pub fn to_i64<T: IntElement>(value: T) -> Result<i64> {
+    value
+        .as_i64()
+        .ok_or_else(|| anyhow!("{} does not fit in an i64 element", value))
+}
+
+impl DynamicAccumulator {
+    /// [`Self::add`] for any integer element.
+    pub fn add_int<T: IntElement>(&mut self, element: T) -> Result<AddProof> {
+        match element.as_i64() {
+            Some(value) => self.add(&value),
+            None => self.add_fr(element.element()),
+        }
+    }
+
+    /// [`Self::delete`] for any integer element.
+    pub fn delete_int<T: IntElement>(&mut self, element: T) -> Result<DeleteProof> {
+        self.delete_fr(element.element())
+    }
+
+    /// [`Self::check`] for any integer element.
+    pub fn check_int<T: IntElement>(&self, element: T) -> Result<bool> {
+        self.ensure_elements()?;
+        self.elements.contains(&element.element())
+    }
+
+    /// [`Self::prove_membership`] for any integer element.
+    pub fn prove_membership_int<T: IntElement>(&self, element: T) -> Result<MembershipProof> {
+        self.prove_membership_fr(element.element())
+    }
+
+    /// [`Self::prove_non_membership`] for any integer element.
+    pub fn prove_non_membership_int<T: IntElement>(
+        &self,
+        element: T,
+    ) -> Result<NonMembershipProof> {
+        self.prove_non_membership_fr(element.element())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_int_elements() {
+        init_logger();
+        // Values that truncate or wrap to the same i64 stay distinct elements.
+        let wide = [
+            (-1i64).element(),
+            u64::MAX.element(),
+            (1u64 << 63).element(),
+            i64::MIN.element(),
+            (i64::MIN as i128 - 1).element(),
+            (-(u64::MAX as i128)).element(),
+            (1i128 << 64).element(),
+            (-(1i128 << 64)).element(),
+            i128::MIN.element(),
+            i128::MAX.element(),
+            u128::MAX.element(),
+            (1u128 << 127).element(),
+        ];
+        assert_eq!(wide.iter().collect::<HashSet<_>>().len(), wide.len());
+        // The same value is the same element whatever its type.
+        assert_eq!(5u64.element(), 5i64.element());
+        assert_eq!(5u8.element(), 5i64.element());
+        assert_eq!((-5i8).element(), (-5i64).element());
+        assert_eq!(u32::MAX.element(), (u32::MAX as i64).element());
+        assert_eq!(i32::MIN.element(), (i32::MIN as i64).element());
+        assert_eq!(i64::MAX.int_digest(), i64::MAX.to_digest());
+        assert_eq!((-5i128).element(), (-5i64).element());
+        assert_eq!((i64::MAX as u128).element(), i64::MAX.element());
+
+        // The Digestible impls keep hashing the native bytes.
+        assert_eq!(u64::MAX.to_digest(), u64::MAX.to_le_bytes().to_digest());
+        assert_eq!(1i128.to_digest(), 1i128.to_le_bytes().to_digest());
+        assert_ne!(5u128.int_digest(), 5u128.to_digest());
+
+        assert_eq!(to_i64(7u128).unwrap(), 7);
+        assert!(to_i64(u64::MAX).is_err());
+        assert!(to_i64(i128::MIN).is_err());
+
+        let mut acc = DynamicAccumulator::new();
+        acc.add_int(5u64).unwrap();
+        acc.add_int(u64::MAX).unwrap();
+        assert!(acc.check(&5).unwrap() && acc.check_int(5i128).unwrap());
+        assert!(acc.check_int(u64::MAX).unwrap() && !acc.check(&-1).unwrap());
+        let proof = acc.prove_membership_int(u64::MAX).unwrap();
+        assert!(acc.verify_membership(&proof));
+        assert!(acc.verify_non_membership(&acc.prove_non_membership_int(-1i64).unwrap()));
+        assert!(acc.delete_int(u128::from(u64::MAX)).unwrap().verify());
+        assert!(!acc.check_int(u64::MAX).unwrap());
+
+        #[cfg(feature = "bigint")]
+        {
+            use ethnum::{I256, U256};
+            assert_eq!(U256::from(5u64).element(), 5i64.element());
+            assert_eq!(I256::from(-5i64).element(), (-5i64).element());
+            assert_eq!(U256::from(u128::MAX).element(), u128::MAX.element());
+            assert_ne!(U256::MAX.element(), (-1i64).element());
+            assert_ne!(I256::MIN.element(), I256::MAX.element());
+            assert!(to_i64(U256::MAX).is_err());
+        }
+    }
+}
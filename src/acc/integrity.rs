@@ -0,0 +1,103 @@
+//! Checking an accumulator value against its element set.
+//!
+//! After a restore from a backup or a replica, the stored value and the stored elements may no
+//! longer belong together, e.g. when one of them was restored from an older snapshot.
+//! [`DynamicAccumulator::self_check`] recomputes the value from the elements along the public
+//! path, one MSM of the characteristic polynomial over the powers g1^(s^i), without the
+//! trapdoor, and reports whether it matches.
+
+use super::{dynamic_accumulator::DynamicAccumulator, lifecycle::LifecycleError, Acc1, G1Affine};
+use crate::digest::Digestible;
+use core::fmt;
+
+/// The outcome of [`DynamicAccumulator::self_check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfCheckReport {
+    pub epoch: u64,
+    /// The number of distinct elements.
+    pub elements: usize,
+    /// The degree of the characteristic polynomial, which counts repeated elements.
+    pub degree: usize,
+    /// The value recomputed from the elements.
+    pub expected: G1Affine,
+    /// The stored value.
+    pub actual: G1Affine,
+}
+
+impl SelfCheckReport {
+    /// Returns true if the stored value is the one of the elements.
+    pub fn is_ok(&self) -> bool {
+        self.expected == self.actual
+    }
+}
+
+impl fmt::Display for SelfCheckReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            write!(
+                f,
+                "value at epoch {} matches its {} elements",
+                self.epoch, self.elements
+            )
+        } else {
+            write!(
+                f,
+                "value at epoch {} is {}, but its {} elements give {}",
+                self.epoch,
+                self.actual.to_digest(),
+                self.elements,
+                self.expected.to_digest()
+            )
+        }
+    }
+}
+
+impl DynamicAccumulator {
+    /// Recomputes the accumulator value from the element set, see the [module docs](self).
+    /// Returns an error if the accumulator only knows its value.
+    pub fn self_check(&self) -> Result<SelfCheckReport, LifecycleError> {
+        self.ensure_elements()?;
+        let poly = self.characteristic_poly();
+        let degree = poly.coeffs.len().saturating_sub(1);
+        Ok(SelfCheckReport {
+            epoch: self.epoch(),
+            elements: self.len(),
+            degree,
+            expected: Acc1::poly_to_g1(poly),
+            actual: self.acc_value,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::dynamic_accumulator::DuplicatePolicy;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_self_check() {
+        init_logger();
+        let report = DynamicAccumulator::new().self_check().unwrap();
+        assert!(report.is_ok() && report.degree == 0);
+
+        let mut acc =
+            DynamicAccumulator::with_duplicate_policy(DuplicatePolicy::IncrementMultiplicity);
+        acc.add_batch(&[1, 2, 3]).unwrap();
+        acc.add(&2).unwrap();
+        acc.delete(&3).unwrap();
+        let report = acc.self_check().unwrap();
+        assert!(report.is_ok(), "{}", report);
+        assert_eq!((report.epoch, report.elements, report.degree), (5, 2, 3));
+
+        // A value restored from an older snapshot no longer matches the elements.
+        let mut restored = acc.clone();
+        restored.acc_value = DynamicAccumulator::new().acc_value;
+        let report = restored.self_check().unwrap();
+        assert!(!report.is_ok());
+        assert!(report.to_string().contains("but its 2 elements"));
+    }
+}
@@ -0,0 +1,545 @@
+//! An intersection proof built from the gcd of the characteristic polynomials.
+//!
+//! For sets with characteristic polynomials P1 and P2 the prover computes the monic gcd
+//! G = gcd(P1, P2), which is the characteristic polynomial of the intersection, and Bézout
+//! coefficients with x*P1 + y*P2 = G. With Q = (P1 + γ*P2)/G for a Fiat-Shamir challenge γ, the
+//! two identities
+//!
+//! - P1 + γ*P2 = G*Q, showing that G divides both P1 and P2, and
+//! - x*P1 + y*P2 = G, showing that every common divisor of P1 and P2 divides G,
+//!
+//! are checked at a random point z. The three polynomials x, y and Q are packed into one
+//! commitment g1^H(s) with H(X) = x(X^3) + X*y(X^3) + X^2*Q(X^3), whose remainder modulo
+//! X^3 - z is x(z) + y(z)*X + Q(z)*X^2, so a single opening of H at the cube roots of z reveals
+//! all three evaluations. A second opening shows that the linear combination of the
+//! accumulator values with these evaluations as coefficients vanishes at z.
+//!
+//! The proof holds three G1 points and three field elements, 240 bytes, against the two G1 and
+//! two G2 points, 288 bytes, of an
+//! [`IntersectionProof`](super::dynamic_accumulator::IntersectionProof). It verifies with one
+//! three-term multi-pairing instead of seven pairings, and the verifier only needs the three
+//! accumulator values, so unlike
+//! [`verify_intersection_with_values`](super::dynamic_accumulator::DynamicAccumulator::verify_intersection_with_values)
+//! no plaintext elements are involved. As H has three times the degree of the sets, it is
+//! limited to sets of up to [`MAX_DEGREE`](super::MAX_DEGREE) / 3 elements.
+//!
+//! [`DynamicAccumulator::query_intersection`] answers a single-element query against the
+//! intersection of several sets without building the intersection accumulator.
+
+use super::{
+    aggregate::AggregateMembershipProof,
+    commit_g1, commit_g2,
+    commitment::params_id,
+    dynamic_accumulator::{ark_serde, DynamicAccumulator, NonMembershipProof},
+    g2_s,
+    handle::ElementHandle,
+    metrics, polymath,
+    prepared::PreparedPublicParams,
+    update::fr_to_digest,
+    utils::digest_to_prime_field,
+    Curve, Fr, G1Affine, G1Projective,
+};
+use crate::digest::{concat_digest, Digest, Digestible};
+use anyhow::{bail, Context, Result};
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{One, PrimeField, Zero};
+use ark_poly::{
+    univariate::{DenseOrSparsePolynomial, DensePolynomial},
+    Polynomial, UVPolynomial,
+};
+use serde::{Deserialize, Serialize};
+
+/// A proof that an accumulator value commits to the intersection of two accumulated sets.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GcdIntersectionProof {
+    /// g1^H(s) with H(X) = x(X^3) + X*y(X^3) + X^2*Q(X^3), committing to both Bézout
+    /// coefficients and the combined quotient at once.
+    #[serde(with = "ark_serde")]
+    pub commitment: G1Affine,
+    /// g1^((H - R)/(X^3 - z))(s), where R = x(z) + y(z)*X + Q(z)*X^2 is the remainder of H.
+    #[serde(with = "ark_serde")]
+    pub opening: G1Affine,
+    /// g1^(L/(X - z))(s) for the linear combination L of P1, P2 and G that vanishes at z.
+    #[serde(with = "ark_serde")]
+    pub linear_opening: G1Affine,
+    /// x(z), the Bézout coefficient of the first set at z.
+    #[serde(with = "ark_serde")]
+    pub bezout_a: Fr,
+    /// y(z), the Bézout coefficient of the second set at z.
+    #[serde(with = "ark_serde")]
+    pub bezout_b: Fr,
+    /// Q(z), the combined quotient (P1 + γ*P2)/G at z.
+    #[serde(with = "ark_serde")]
+    pub quotient: Fr,
+    /// The epochs of the two accumulator states the proof was generated for.
+    pub epochs: (u64, u64),
+    /// The identifier of the public parameters the proof was generated under, see
+    /// [`params_id`].
+    pub params_id: Digest,
+}
+
+/// The statement: the three accumulator values and the epochs of the two sets.
+fn statement_digest(
+    acc1_value: &G1Affine,
+    acc2_value: &G1Affine,
+    intersection: &G1Affine,
+    epochs: (u64, u64),
+) -> Digest {
+    concat_digest(
+        [
+            acc1_value.to_digest(),
+            acc2_value.to_digest(),
+            intersection.to_digest(),
+            epochs.0.to_digest(),
+            epochs.1.to_digest(),
+        ]
+        .into_iter(),
+    )
+}
+
+/// The challenge γ that combines the two divisibility checks, drawn before the quotient is
+/// committed to.
+fn gamma(statement: Digest) -> Fr {
+    digest_to_prime_field(&concat_digest([statement, 0u64.to_digest()].into_iter()))
+}
+
+/// The evaluation point z and the challenge β that combines the two identities, drawn after
+/// H is committed to.
+fn point_and_beta(statement: Digest, commitment: &G1Affine) -> (Fr, Fr) {
+    let challenge = |tag: u64| {
+        digest_to_prime_field(&concat_digest(
+            [statement, tag.to_digest(), commitment.to_digest()].into_iter(),
+        ))
+    };
+    (challenge(1), challenge(2))
+}
+
+/// The challenge ρ that folds the two opening checks into one multi-pairing, drawn after the
+/// whole proof is fixed.
+fn rho(statement: Digest, proof: &GcdIntersectionProof) -> Fr {
+    digest_to_prime_field(&concat_digest(
+        [
+            statement,
+            3u64.to_digest(),
+            proof.commitment.to_digest(),
+            proof.opening.to_digest(),
+            proof.linear_opening.to_digest(),
+            fr_to_digest(&proof.bezout_a),
+            fr_to_digest(&proof.bezout_b),
+            fr_to_digest(&proof.quotient),
+        ]
+        .into_iter(),
+    ))
+}
+
+/// The coefficients of P1, P2 and G in the linear combination L = (x(z) + β)*P1 +
+/// (y(z) + β*γ)*P2 - (1 + β*Q(z))*G, which vanishes at z iff both identities hold there.
+fn linear_coefficients(
+    (bezout_a, bezout_b, quotient): (Fr, Fr, Fr),
+    gamma: Fr,
+    beta: Fr,
+) -> [Fr; 3] {
+    [
+        bezout_a + beta,
+        bezout_b + beta * gamma,
+        -(Fr::one() + beta * quotient),
+    ]
+}
+
+impl GcdIntersectionProof {
+    /// Verifies that `intersection_value` accumulates exactly the elements common to the sets
+    /// behind `acc1_value` and `acc2_value`.
+    pub fn verify(
+        &self,
+        acc1_value: G1Affine,
+        acc2_value: G1Affine,
+        intersection_value: G1Affine,
+    ) -> bool {
+        if self.params_id != params_id() {
+            return false;
+        }
+        let statement =
+            statement_digest(&acc1_value, &acc2_value, &intersection_value, self.epochs);
+        let gamma = gamma(statement);
+        let (point, beta) = point_and_beta(statement, &self.commitment);
+        let rho = rho(statement, self).into_repr();
+        let evaluations = (self.bezout_a, self.bezout_b, self.quotient);
+        let (remainder, vanishing) = match (
+            commit_g1(DensePolynomial::from_coefficients_vec(vec![
+                self.bezout_a,
+                self.bezout_b,
+                self.quotient,
+            ])),
+            commit_g2(cube_minus(point)),
+        ) {
+            (Ok(remainder), Ok(vanishing)) => (remainder, vanishing),
+            _ => return false,
+        };
+        let linear = [acc1_value, acc2_value, intersection_value]
+            .iter()
+            .zip(linear_coefficients(evaluations, gamma, beta))
+            .map(|(value, c)| value.mul(c.into_repr()))
+            .sum::<G1Projective>();
+        // e(C - g1^R + ρ*(L + z*π_L), g2) * e(-π_H, g2^(s^3 - z)) * e(-ρ*π_L, g2^s) == 1
+        // holds iff H opens to R at the cube roots of z and L opens to 0 at z, except with
+        // negligible probability over ρ.
+        let folded = self.commitment.into_projective() - remainder.into_projective()
+            + (linear + self.linear_opening.mul(point.into_repr())).mul(rho);
+        let pairs = [
+            (
+                folded.into_affine().into(),
+                PreparedPublicParams::global().g2().clone(),
+            ),
+            ((-self.opening).into(), vanishing.into()),
+            (
+                (-self.linear_opening.mul(rho).into_affine()).into(),
+                g2_s().into(),
+            ),
+        ];
+        metrics::record_pairings(pairs.len() as u64);
+        Curve::product_of_pairings(&pairs).is_one()
+    }
+
+    /// Same as [`Self::verify`] against the accumulator values published at `epochs`.
+    pub fn verify_at(
+        &self,
+        acc1_value: G1Affine,
+        acc2_value: G1Affine,
+        intersection_value: G1Affine,
+        epochs: (u64, u64),
+    ) -> bool {
+        self.epochs == epochs && self.verify(acc1_value, acc2_value, intersection_value)
+    }
+}
+
+/// X^3 - z.
+fn cube_minus(point: Fr) -> DensePolynomial<Fr> {
+    DensePolynomial::from_coefficients_vec(vec![-point, Fr::zero(), Fr::zero(), Fr::one()])
+}
+
+/// The quotient and remainder of `p` divided by `g`.
+fn divide_with_remainder(
+    p: &DensePolynomial<Fr>,
+    g: &DensePolynomial<Fr>,
+) -> Result<(DensePolynomial<Fr>, DensePolynomial<Fr>)> {
+    DenseOrSparsePolynomial::from(p)
+        .divide_with_q_and_r(&DenseOrSparsePolynomial::from(g))
+        .context("Division by the zero polynomial")
+}
+
+fn divide(p: &DensePolynomial<Fr>, g: &DensePolynomial<Fr>) -> Result<DensePolynomial<Fr>> {
+    match divide_with_remainder(p, g)? {
+        (q, r) if r.coeffs.is_empty() => Ok(q),
+        _ => bail!("The divisor does not divide the polynomial"),
+    }
+}
+
+/// H(X) = x(X^3) + X*y(X^3) + X^2*Q(X^3).
+fn pack(polys: [&DensePolynomial<Fr>; 3]) -> DensePolynomial<Fr> {
+    let len = polys.iter().map(|p| 3 * p.coeffs.len()).max().unwrap_or(0);
+    let mut coeffs = vec![Fr::zero(); len];
+    for (offset, poly) in polys.iter().enumerate() {
+        for (i, c) in poly.coeffs.iter().enumerate() {
+            coeffs[3 * i + offset] = *c;
+        }
+    }
+    DensePolynomial::from_coefficients_vec(coeffs)
+}
+
+/// `poly` with every coefficient multiplied by `scalar`.
+fn scale(poly: &DensePolynomial<Fr>, scalar: Fr) -> DensePolynomial<Fr> {
+    DensePolynomial::from_coefficients_vec(poly.coeffs.iter().map(|c| *c * scalar).collect())
+}
+
+impl DynamicAccumulator {
+    /// Computes the intersection with `other` and proves it with a [`GcdIntersectionProof`].
+    /// Returns an error on multisets and on sets of more than
+    /// [`MAX_DEGREE`](super::MAX_DEGREE) / 3 elements.
+    pub fn prove_intersection_gcd(
+        &self,
+        other: &DynamicAccumulator,
+    ) -> Result<(DynamicAccumulator, GcdIntersectionProof)> {
+        self.ensure_elements()?;
+        other.ensure_elements()?;
+        if self.is_multiset() || other.is_multiset() {
+            bail!("Set operations are not supported on multisets");
+        }
+        let (p1, p2) = rayon::join(
+            || self.characteristic_poly(),
+            || other.characteristic_poly(),
+        );
+        let (g, x, y) = polymath::xgcd(&p1, &p2);
+
+        let mut intersection = DynamicAccumulator::new();
+        intersection.elements = self
+            .elements
+            .intersection(&other.elements)?
+            .into_iter()
+            .collect();
+        intersection.acc_value = commit_g1(g.clone())?;
+
+        let epochs = (self.epoch(), other.epoch());
+        let statement = statement_digest(
+            &self.acc_value,
+            &other.acc_value,
+            &intersection.acc_value,
+            epochs,
+        );
+        let gamma = gamma(statement);
+        let quotient = divide(&(&p1 + &scale(&p2, gamma)), &g)?;
+        let packed = pack([&x, &y, &quotient]);
+        let commitment = commit_g1(packed.clone())?;
+
+        let (point, beta) = point_and_beta(statement, &commitment);
+        let evaluations = (
+            x.evaluate(&point),
+            y.evaluate(&point),
+            quotient.evaluate(&point),
+        );
+        let (packed_quotient, _) = divide_with_remainder(&packed, &cube_minus(point))?;
+        let [c1, c2, c3] = linear_coefficients(evaluations, gamma, beta);
+        let linear = &(&scale(&p1, c1) + &scale(&p2, c2)) + &scale(&g, c3);
+        let linear_quotient = divide(
+            &linear,
+            &DensePolynomial::from_coefficients_vec(vec![-point, Fr::one()]),
+        )?;
+        let proof = GcdIntersectionProof {
+            commitment,
+            opening: commit_g1(packed_quotient)?,
+            linear_opening: commit_g1(linear_quotient)?,
+            bezout_a: evaluations.0,
+            bezout_b: evaluations.1,
+            quotient: evaluations.2,
+            epochs,
+            params_id: params_id(),
+        };
+        Ok((intersection, proof))
+    }
+}
+
+/// The answer to [`DynamicAccumulator::query_intersection`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntersectionQueryResult {
+    /// The element is in every set: one membership proof per set, in query order.
+    Membership(AggregateMembershipProof),
+    /// The element is missing from the set at `index`. Nothing is said about the other sets.
+    NonMembership {
+        index: usize,
+        proof: Box<NonMembershipProof>,
+    },
+}
+
+impl IntersectionQueryResult {
+    /// Verifies the result for `element` against the accumulator values of the queried sets,
+    /// in query order.
+    pub fn verify(&self, accumulators: &[G1Affine], element: &Fr) -> bool {
+        match self {
+            Self::Membership(aggregate) => {
+                aggregate
+                    .proofs
+                    .iter()
+                    .all(|p| p.element == ElementHandle(*element))
+                    && aggregate.verify(accumulators)
+            }
+            Self::NonMembership { index, proof } => {
+                proof.element == ElementHandle(*element)
+                    && accumulators
+                        .get(*index)
+                        .is_some_and(|acc| proof.verify(*acc))
+            }
+        }
+    }
+}
+
+impl DynamicAccumulator {
+    /// Queries `element` against the intersection of `self` and `others`. The sets are indexed
+    /// in the order `self, others[0], others[1], ...`.
+    pub fn query_intersection(
+        &self,
+        others: &[&DynamicAccumulator],
+        element: &i64,
+    ) -> Result<IntersectionQueryResult> {
+        let fr_element = digest_to_prime_field(&element.to_digest());
+        let sets: Vec<&DynamicAccumulator> = core::iter::once(self)
+            .chain(others.iter().copied())
+            .collect();
+        // A single non-membership proof is enough, so it is built only for the first miss.
+        let mut miss = None;
+        for (index, acc) in sets.iter().enumerate() {
+            if !acc.elements.contains(&fr_element)? {
+                miss = Some(index);
+                break;
+            }
+        }
+        if let Some(index) = miss {
+            let proof = sets[index]
+                .prove_non_membership(element)
+                .with_context(|| format!("Set {} cannot prove non-membership", index))?;
+            return Ok(IntersectionQueryResult::NonMembership {
+                index,
+                proof: Box::new(proof),
+            });
+        }
+        let proofs = sets
+            .iter()
+            .map(|acc| acc.prove_membership(element))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(IntersectionQueryResult::Membership(
+            AggregateMembershipProof::new(proofs)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::dynamic_accumulator::DuplicatePolicy;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_prove_intersection_gcd() {
+        init_logger();
+        let mut a = DynamicAccumulator::new();
+        a.add_batch(&[1, 2, 3, 4]).unwrap();
+        let mut b = DynamicAccumulator::new();
+        b.add_batch(&[3, 4, 5]).unwrap();
+
+        let (intersection, proof) = a.prove_intersection_gcd(&b).unwrap();
+        let (expected, _) = a.prove_intersection(&b).unwrap();
+        assert_eq!(intersection.acc_value, expected.acc_value);
+        assert_eq!(intersection.elements, expected.elements);
+        assert!(proof.verify(a.acc_value, b.acc_value, intersection.acc_value));
+        assert!(proof.verify_at(a.acc_value, b.acc_value, intersection.acc_value, (4, 3)));
+        assert!(!proof.verify_at(a.acc_value, b.acc_value, intersection.acc_value, (3, 4)));
+        assert!(!proof.verify(b.acc_value, a.acc_value, intersection.acc_value));
+        let mut other_params = proof.clone();
+        other_params.params_id = Digest::default();
+        assert!(!other_params.verify(a.acc_value, b.acc_value, intersection.acc_value));
+
+        // A common divisor that is not the gcd is rejected.
+        let mut partial = DynamicAccumulator::new();
+        partial.add(&3).unwrap();
+        assert!(!proof.verify(a.acc_value, b.acc_value, partial.acc_value));
+
+        // Disjoint sets intersect in the empty set.
+        let mut c = DynamicAccumulator::new();
+        c.add_batch(&[7, 8]).unwrap();
+        let (empty, proof) = a.prove_intersection_gcd(&c).unwrap();
+        assert!(empty.elements.is_empty());
+        assert_eq!(empty.acc_value, G1Affine::prime_subgroup_generator());
+        assert!(proof.verify(a.acc_value, c.acc_value, empty.acc_value));
+
+        let mut multi =
+            DynamicAccumulator::with_duplicate_policy(DuplicatePolicy::IncrementMultiplicity);
+        multi.add_batch(&[3, 3]).unwrap();
+        assert!(multi.prove_intersection_gcd(&b).is_err());
+    }
+
+    #[test]
+    fn test_forged_intersection_gcd() {
+        init_logger();
+        let mut a = DynamicAccumulator::new();
+        a.add_batch(&[1, 2, 3]).unwrap();
+        let mut b = DynamicAccumulator::new();
+        b.add_batch(&[2, 3, 4]).unwrap();
+        let (p1, p2) = (a.characteristic_poly(), b.characteristic_poly());
+        let (g, x, y) = polymath::xgcd(&p1, &p2);
+
+        // Claim the empty intersection G' = 1 with the real Bézout coefficients and the exact
+        // quotient P1 + γ*P2, and fix x(z) after z is known so that L vanishes at z. H no
+        // longer opens to the claimed evaluations.
+        let empty = G1Affine::prime_subgroup_generator();
+        let epochs = (a.epoch(), b.epoch());
+        let statement = statement_digest(&a.acc_value, &b.acc_value, &empty, epochs);
+        let gamma = gamma(statement);
+        let quotient = &p1 + &scale(&p2, gamma);
+        let packed = pack([&x, &y, &quotient]);
+        let commitment = commit_g1(packed.clone()).unwrap();
+        let (point, beta) = point_and_beta(statement, &commitment);
+        let bezout_a = x.evaluate(&point) + (Fr::one() - g.evaluate(&point)) / p1.evaluate(&point);
+        let evaluations = (bezout_a, y.evaluate(&point), quotient.evaluate(&point));
+        let [c1, c2, c3] = linear_coefficients(evaluations, gamma, beta);
+        let linear = &(&scale(&p1, c1) + &scale(&p2, c2))
+            + &DensePolynomial::from_coefficients_vec(vec![c3]);
+        let linear_quotient = divide(
+            &linear,
+            &DensePolynomial::from_coefficients_vec(vec![-point, Fr::one()]),
+        )
+        .unwrap();
+        let (packed_quotient, _) = divide_with_remainder(&packed, &cube_minus(point)).unwrap();
+        let forged = GcdIntersectionProof {
+            commitment,
+            opening: commit_g1(packed_quotient).unwrap(),
+            linear_opening: commit_g1(linear_quotient).unwrap(),
+            bezout_a: evaluations.0,
+            bezout_b: evaluations.1,
+            quotient: evaluations.2,
+            epochs,
+            params_id: params_id(),
+        };
+        assert!(!forged.verify(a.acc_value, b.acc_value, empty));
+
+        // Any change to an honest proof is rejected.
+        let (intersection, proof) = a.prove_intersection_gcd(&b).unwrap();
+        assert!(proof.verify(a.acc_value, b.acc_value, intersection.acc_value));
+        let mut tampered = proof.clone();
+        tampered.bezout_b += Fr::one();
+        assert!(!tampered.verify(a.acc_value, b.acc_value, intersection.acc_value));
+        let mut tampered = proof;
+        tampered.linear_opening = tampered.opening;
+        assert!(!tampered.verify(a.acc_value, b.acc_value, intersection.acc_value));
+    }
+
+    #[test]
+    fn test_gcd_proof_size() {
+        init_logger();
+        let mut a = DynamicAccumulator::new();
+        a.add_batch(&[1, 2, 3, 4]).unwrap();
+        let mut b = DynamicAccumulator::new();
+        b.add_batch(&[3, 4, 5]).unwrap();
+
+        let (_, gcd) = a.prove_intersection_gcd(&b).unwrap();
+        let (_, plain) = a.prove_intersection(&b).unwrap();
+        let gcd_len = bincode::serialize(&gcd).unwrap().len();
+        let plain_len = bincode::serialize(&plain).unwrap().len();
+        assert!(gcd_len < plain_len, "{} >= {}", gcd_len, plain_len);
+        assert_eq!(
+            bincode::deserialize::<GcdIntersectionProof>(&bincode::serialize(&gcd).unwrap())
+                .unwrap(),
+            gcd
+        );
+    }
+
+    #[test]
+    fn test_query_intersection() {
+        init_logger();
+        let sets: Vec<DynamicAccumulator> = [[1, 2, 3], [2, 3, 4], [3, 4, 5]]
+            .iter()
+            .map(|elements| {
+                let mut acc = DynamicAccumulator::new();
+                acc.add_batch(elements).unwrap();
+                acc
+            })
+            .collect();
+        let values: Vec<G1Affine> = sets.iter().map(|acc| acc.acc_value).collect();
+        let others = [&sets[1], &sets[2]];
+        let fr = |x: i64| digest_to_prime_field(&x.to_digest());
+
+        let result = sets[0].query_intersection(&others, &3).unwrap();
+        assert!(matches!(result, IntersectionQueryResult::Membership(_)));
+        assert!(result.verify(&values, &fr(3)));
+        assert!(!result.verify(&values, &fr(2)));
+
+        let result = sets[0].query_intersection(&others, &2).unwrap();
+        assert!(matches!(
+            result,
+            IntersectionQueryResult::NonMembership { index: 2, .. }
+        ));
+        assert!(result.verify(&values, &fr(2)));
+        assert!(!result.verify(&values[..2], &fr(2)));
+        assert!(!result.verify(&values, &fr(3)));
+    }
+}
@@ -0,0 +1,283 @@
+//! Interval elements, for range blocklists and allow-lists.
+//!
+//! An [`IntervalAccumulator`] partitions the whole `i64` domain into maximal segments, each
+//! either covered or not, and accumulates every segment as one element encoded by
+//! [`Interval::encode`]. Whether a point `x` is covered by some inserted interval or by none is
+//! then shown the same way: by a membership proof for the segment containing `x`, together with
+//! its flag. Verifiers rely on the manager keeping the segments a partition, which
+//! [`IntervalAccumulator::insert`] and [`IntervalAccumulator::remove`] maintain.
+
+use super::{
+    dynamic_accumulator::{DynamicAccumulator, MembershipProof},
+    handle::ElementHandle,
+    utils::digest_to_prime_field,
+    Fr, G1Affine,
+};
+use crate::digest::{blake2, Digest};
+use anyhow::{bail, Result};
+use std::{collections::BTreeMap, net::Ipv4Addr};
+
+const INTERVAL_TAG: &[u8] = b"ESA_RUST_INTERVAL";
+
+/// The inclusive range `[start, end]`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Interval {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl Interval {
+    /// Returns an error if `start > end`.
+    pub fn new(start: i64, end: i64) -> Result<Self> {
+        if start > end {
+            bail!("Interval start {} is after its end {}", start, end);
+        }
+        Ok(Self { start, end })
+    }
+
+    /// The addresses of an IPv4 CIDR block, e.g. `10.0.0.0/8`, as integers.
+    pub fn ipv4_cidr(addr: Ipv4Addr, prefix: u8) -> Result<Self> {
+        if prefix > 32 {
+            bail!("Invalid IPv4 prefix length {}", prefix);
+        }
+        let mask = u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0);
+        let start = u32::from(addr) & mask;
+        Self::new(start as i64, (start | !mask) as i64)
+    }
+
+    pub fn contains(&self, x: i64) -> bool {
+        self.start <= x && x <= self.end
+    }
+
+    /// The field element accumulated for this interval as a segment with the given flag:
+    /// BLAKE2b-256 of the tag `ESA_RUST_INTERVAL`, one flag byte (1 if covered), and the
+    /// big-endian bytes of `start` and `end`, mapped with `digest_to_prime_field`.
+    pub fn encode(&self, covered: bool) -> Fr {
+        let mut state = blake2().to_state();
+        state
+            .update(INTERVAL_TAG)
+            .update(&[covered as u8])
+            .update(&self.start.to_be_bytes())
+            .update(&self.end.to_be_bytes());
+        digest_to_prime_field(&Digest::from(state.finalize()))
+    }
+}
+
+/// A proof that the segment containing a point is covered, or is not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoverageProof {
+    pub segment: Interval,
+    pub covered: bool,
+    pub membership: MembershipProof,
+}
+
+impl CoverageProof {
+    /// Verifies that `x` lies in the proven segment and that the segment, with its flag, is in
+    /// the accumulator. [`Self::covered`] then tells whether `x` is covered.
+    pub fn verify(&self, accumulator: G1Affine, x: i64) -> bool {
+        self.segment.contains(x)
+            && self.membership.element == ElementHandle(self.segment.encode(self.covered))
+            && self.membership.verify(accumulator)
+    }
+}
+
+/// A set of covered ranges of `i64`, with coverage proofs for points.
+#[derive(Debug, Clone)]
+pub struct IntervalAccumulator {
+    acc: DynamicAccumulator,
+    /// The segments of the partition by start, with their end and flag.
+    segments: BTreeMap<i64, (i64, bool)>,
+}
+
+impl Default for IntervalAccumulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IntervalAccumulator {
+    /// Creates an accumulator in which no point is covered.
+    pub fn new() -> Self {
+        let mut acc = DynamicAccumulator::new();
+        let whole = Interval::new(i64::MIN, i64::MAX).expect("non-empty interval");
+        acc.add_fr(whole.encode(false))
+            .expect("first element of an empty accumulator");
+        Self {
+            acc,
+            segments: [(i64::MIN, (i64::MAX, false))].into_iter().collect(),
+        }
+    }
+
+    /// The wrapped accumulator.
+    pub fn acc(&self) -> &DynamicAccumulator {
+        &self.acc
+    }
+
+    /// Returns the maximal covered intervals in ascending order.
+    pub fn covered(&self) -> impl Iterator<Item = Interval> + '_ {
+        self.segments
+            .iter()
+            .filter(|(_, (_, covered))| *covered)
+            .map(|(start, (end, _))| Interval {
+                start: *start,
+                end: *end,
+            })
+    }
+
+    /// Returns the segment containing `x` and whether it is covered.
+    pub fn segment(&self, x: i64) -> (Interval, bool) {
+        let (start, (end, covered)) = self
+            .segments
+            .range(..=x)
+            .next_back()
+            .expect("the segments cover the whole domain");
+        (
+            Interval {
+                start: *start,
+                end: *end,
+            },
+            *covered,
+        )
+    }
+
+    pub fn is_covered(&self, x: i64) -> bool {
+        self.segment(x).1
+    }
+
+    /// Covers every point of `interval`.
+    pub fn insert(&mut self, interval: Interval) -> Result<()> {
+        self.set_coverage(interval, true)
+    }
+
+    /// Uncovers every point of `interval`.
+    pub fn remove(&mut self, interval: Interval) -> Result<()> {
+        self.set_coverage(interval, false)
+    }
+
+    fn set_coverage(&mut self, interval: Interval, covered: bool) -> Result<()> {
+        // Split the segments at the interval's bounds, overwrite the flag inside it and merge
+        // neighbours with equal flags, so that the partition stays canonical.
+        let mut flat: Vec<(i64, i64, bool)> = Vec::with_capacity(self.segments.len() + 2);
+        for (&start, &(end, flag)) in &self.segments {
+            let mut pieces = vec![(start, end, flag)];
+            if start < interval.start && interval.start <= end {
+                pieces = vec![
+                    (start, interval.start - 1, flag),
+                    (interval.start, end, flag),
+                ];
+            }
+            let (last_start, last_end, _) = *pieces.last().expect("at least one piece");
+            if last_start <= interval.end && interval.end < last_end {
+                pieces.pop();
+                pieces.push((last_start, interval.end, flag));
+                pieces.push((interval.end + 1, last_end, flag));
+            }
+            flat.extend(pieces);
+        }
+        let mut merged: Vec<(i64, i64, bool)> = Vec::with_capacity(flat.len());
+        for (start, end, flag) in flat {
+            let flag = if interval.contains(start) {
+                covered
+            } else {
+                flag
+            };
+            match merged.last_mut() {
+                Some(last) if last.2 == flag => last.1 = end,
+                _ => merged.push((start, end, flag)),
+            }
+        }
+        let segments: BTreeMap<i64, (i64, bool)> = merged
+            .into_iter()
+            .map(|(start, end, flag)| (start, (end, flag)))
+            .collect();
+
+        let encode = |(start, (end, flag)): (&i64, &(i64, bool))| {
+            Interval {
+                start: *start,
+                end: *end,
+            }
+            .encode(*flag)
+        };
+        let old: Vec<Fr> = self.segments.iter().map(encode).collect();
+        let new: Vec<Fr> = segments.iter().map(encode).collect();
+        let mut acc = self.acc.clone();
+        for element in new.iter().filter(|e| !old.contains(e)) {
+            acc.add_fr(*element)?;
+        }
+        for element in old.iter().filter(|e| !new.contains(e)) {
+            acc.delete_fr(*element)?;
+        }
+        self.acc = acc;
+        self.segments = segments;
+        Ok(())
+    }
+
+    /// Proves whether `x` is covered.
+    pub fn prove(&self, x: i64) -> Result<CoverageProof> {
+        let (segment, covered) = self.segment(x);
+        Ok(CoverageProof {
+            segment,
+            covered,
+            membership: self.acc.prove_membership_fr(segment.encode(covered))?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_interval_coverage() {
+        init_logger();
+        let mut acc = IntervalAccumulator::new();
+        assert!(Interval::new(2, 1).is_err());
+        acc.insert(Interval::new(10, 20).unwrap()).unwrap();
+        acc.insert(Interval::new(15, 30).unwrap()).unwrap();
+        acc.insert(Interval::new(40, 50).unwrap()).unwrap();
+        acc.remove(Interval::new(45, 45).unwrap()).unwrap();
+        assert_eq!(
+            acc.covered().collect::<Vec<_>>(),
+            [
+                Interval::new(10, 30).unwrap(),
+                Interval::new(40, 44).unwrap(),
+                Interval::new(46, 50).unwrap()
+            ]
+        );
+        assert_eq!(acc.acc().elements.len(), 7);
+
+        let value = acc.acc().acc_value;
+        for (x, expected) in [(9, false), (10, true), (30, true), (31, false), (45, false)] {
+            let proof = acc.prove(x).unwrap();
+            assert!(proof.verify(value, x));
+            assert_eq!(proof.covered, expected);
+        }
+        let proof = acc.prove(12).unwrap();
+        assert!(!proof.verify(value, 35));
+        let mut flipped = proof.clone();
+        flipped.covered = false;
+        assert!(!flipped.verify(value, 12));
+
+        let block = Interval::ipv4_cidr(Ipv4Addr::new(10, 1, 2, 3), 16).unwrap();
+        assert_eq!(block, Interval::new(0x0a01_0000, 0x0a01_ffff).unwrap());
+        assert_eq!(
+            Interval::ipv4_cidr(Ipv4Addr::new(1, 2, 3, 4), 0).unwrap(),
+            Interval::new(0, u32::MAX as i64).unwrap()
+        );
+        assert!(Interval::ipv4_cidr(Ipv4Addr::LOCALHOST, 33).is_err());
+        acc.insert(block).unwrap();
+        assert!(acc.is_covered(u32::from(Ipv4Addr::new(10, 1, 200, 7)) as i64));
+
+        acc.remove(Interval::new(i64::MIN, i64::MAX).unwrap())
+            .unwrap();
+        assert_eq!(acc.covered().count(), 0);
+        assert!(acc
+            .prove(i64::MAX)
+            .unwrap()
+            .verify(acc.acc().acc_value, i64::MAX));
+    }
+}
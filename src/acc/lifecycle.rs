@@ -0,0 +1,138 @@
+//! Freezing an accumulator, and explicit epochs.
+//!
+//! A frozen accumulator rejects every mutation with [`LifecycleError::Frozen`]. Its value and
+//! epoch cannot change, so proofs issued for the frozen state stay valid until it is reopened.
+//! [`DynamicAccumulator::bump_epoch`] starts a new epoch without changing the set, e.g. at the
+//! end of a period, which retires proofs checked with `verify_at` against the old epoch.
+//!
+//! An accumulator that jumped to a state with [`DynamicAccumulator::adopt_state`] only knows
+//! its value, not its elements. Until it is rebuilt from the elements, every operation that
+//! needs them fails with [`LifecycleError::ValueOnly`].
+
+use super::dynamic_accumulator::DynamicAccumulator;
+use core::fmt;
+
+/// Errors of lifecycle operations. They are returned inside [`anyhow::Error`] and can be
+/// recovered with `downcast_ref`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LifecycleError {
+    /// The accumulator was frozen at `epoch` and cannot be mutated.
+    Frozen { epoch: u64 },
+    /// [`DynamicAccumulator::freeze`] was called on a frozen accumulator.
+    AlreadyFrozen,
+    /// [`DynamicAccumulator::reopen`] was called on an accumulator that is not frozen.
+    NotFrozen,
+    /// The accumulator only tracks its value since it adopted the state at `epoch`.
+    ValueOnly { epoch: u64 },
+}
+
+impl fmt::Display for LifecycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LifecycleError::Frozen { epoch } => {
+                write!(f, "accumulator is frozen at epoch {}", epoch)
+            }
+            LifecycleError::AlreadyFrozen => write!(f, "accumulator is already frozen"),
+            LifecycleError::NotFrozen => write!(f, "accumulator is not frozen"),
+            LifecycleError::ValueOnly { epoch } => write!(
+                f,
+                "accumulator only knows its value since epoch {}, not its elements",
+                epoch
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LifecycleError {}
+
+impl DynamicAccumulator {
+    /// Rejects any further mutation until [`DynamicAccumulator::reopen`].
+    pub fn freeze(&mut self) -> Result<(), LifecycleError> {
+        if self.frozen {
+            return Err(LifecycleError::AlreadyFrozen);
+        }
+        self.frozen = true;
+        Ok(())
+    }
+
+    /// Allows mutations again. The epoch is unchanged until the next mutation.
+    pub fn reopen(&mut self) -> Result<(), LifecycleError> {
+        if !self.frozen {
+            return Err(LifecycleError::NotFrozen);
+        }
+        self.frozen = false;
+        self.transaction = None;
+        Ok(())
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// Returns true if the accumulator knows its value but not its elements.
+    pub fn is_value_only(&self) -> bool {
+        self.value_only.is_some()
+    }
+
+    /// Starts a new epoch without changing the set and returns it.
+    pub fn bump_epoch(&mut self) -> Result<u64, LifecycleError> {
+        self.ensure_mutable()?;
+        self.advance_epoch();
+        Ok(self.epoch())
+    }
+
+    /// Returns [`LifecycleError::Frozen`] if the accumulator is frozen.
+    pub(crate) fn ensure_mutable(&self) -> Result<(), LifecycleError> {
+        if self.frozen {
+            return Err(LifecycleError::Frozen {
+                epoch: self.epoch(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns [`LifecycleError::ValueOnly`] if the element set is unknown.
+    pub(crate) fn ensure_elements(&self) -> Result<(), LifecycleError> {
+        match self.value_only {
+            Some(epoch) => Err(LifecycleError::ValueOnly { epoch }),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_freeze_and_reopen() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        acc.add_batch(&[1, 2]).unwrap();
+        let proof = acc.prove_membership(&1).unwrap();
+        acc.freeze().unwrap();
+        assert_eq!(acc.freeze(), Err(LifecycleError::AlreadyFrozen));
+
+        let err = acc.add(&3).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<LifecycleError>(),
+            Some(&LifecycleError::Frozen { epoch: 2 })
+        );
+        assert!(acc.delete(&1).is_err());
+        assert!(acc.add_batch(&[4]).is_err());
+        assert!(acc.bump_epoch().is_err());
+        assert!(acc.verify_membership(&proof));
+
+        acc.reopen().unwrap();
+        assert_eq!(acc.reopen(), Err(LifecycleError::NotFrozen));
+        assert!(acc.verify_membership(&proof));
+        assert_eq!(acc.bump_epoch(), Ok(3));
+        assert!(!acc.verify_membership(&proof));
+        assert!(proof.verify(acc.acc_value));
+        acc.add(&3).unwrap();
+    }
+}
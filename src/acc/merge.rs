@@ -0,0 +1,203 @@
+//! Deterministic reconciliation of two diverged managers.
+//!
+//! Two managers that start from the same accumulator state and apply updates independently end
+//! up with two [`UpdateLog`]s from a common base. [`DynamicAccumulator::merge`] reconciles them
+//! per element: an element touched by one side takes that side's final state, and one the sides
+//! disagree on is resolved by the [`MergePolicy`]. The result comes with a [`MergeProof`] that
+//! anyone can check from the base value and the two logs alone: the state of an element before
+//! the divergence is read off the first update touching it, so the verifier needs no element set.
+
+use super::{
+    dynamic_accumulator::DynamicAccumulator,
+    update::{Update, UpdateLog},
+    Fr, G1Affine,
+};
+use anyhow::{bail, ensure, Result};
+use std::collections::BTreeMap;
+
+/// How to resolve an element that one side ends with and the other does not.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum MergePolicy {
+    /// The element is kept.
+    #[default]
+    AddWins,
+    /// The element is removed.
+    RemoveWins,
+}
+
+/// For every element a log touches: whether it was present before the log and after it.
+fn net_effects(log: &UpdateLog) -> BTreeMap<Fr, (bool, bool)> {
+    let mut effects = BTreeMap::new();
+    for update in &log.updates {
+        let is_add = matches!(update, Update::Add(_));
+        effects
+            .entry(update.element().0)
+            .and_modify(|(_, after)| *after = is_add)
+            .or_insert((!is_add, is_add));
+    }
+    effects
+}
+
+/// Returns, for every element whose state changes, whether it must be present after the merge.
+fn resolve(left: &UpdateLog, right: &UpdateLog, policy: MergePolicy) -> Result<BTreeMap<Fr, bool>> {
+    let left = net_effects(left);
+    let mut changes = BTreeMap::new();
+    let mut right = net_effects(right);
+    for (element, (before, left_after)) in left {
+        let after = match right.remove(&element) {
+            Some((right_before, right_after)) => {
+                ensure!(
+                    before == right_before,
+                    "The logs disagree on the state of an element before they diverged"
+                );
+                if left_after == right_after {
+                    left_after
+                } else {
+                    policy == MergePolicy::AddWins
+                }
+            }
+            None => left_after,
+        };
+        if after != before {
+            changes.insert(element, after);
+        }
+    }
+    for (element, (before, after)) in right {
+        if after != before {
+            changes.insert(element, after);
+        }
+    }
+    Ok(changes)
+}
+
+/// A proof that `merged` applies exactly the resolution of `left` and `right` under `policy`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeProof {
+    pub policy: MergePolicy,
+    pub base_value: G1Affine,
+    pub left: UpdateLog,
+    pub right: UpdateLog,
+    /// The updates from the base to the merged state, in ascending element order.
+    pub merged: UpdateLog,
+}
+
+impl MergeProof {
+    /// Verifies the three logs and the resolution, and returns the merged accumulator value.
+    pub fn verify(&self) -> Result<G1Affine> {
+        let base_epoch = self.left.start_epoch;
+        ensure!(
+            self.right.start_epoch == base_epoch && self.merged.start_epoch == base_epoch,
+            "The logs do not start at the same epoch"
+        );
+        let _ = self.left.verify_chain(self.base_value)?;
+        let _ = self.right.verify_chain(self.base_value)?;
+        let value = self.merged.verify_chain(self.base_value)?;
+
+        let expected: Vec<(Fr, bool)> = resolve(&self.left, &self.right, self.policy)?
+            .into_iter()
+            .collect();
+        let applied: Vec<(Fr, bool)> = self
+            .merged
+            .updates
+            .iter()
+            .map(|u| (u.element().0, matches!(u, Update::Add(_))))
+            .collect();
+        if applied != expected {
+            bail!("The merged log does not apply the resolution of the two logs");
+        }
+        Ok(value)
+    }
+}
+
+impl DynamicAccumulator {
+    /// Reconciles two logs that both start at this accumulator's state, and returns the merged
+    /// accumulator with a proof of the merge. The result only depends on the logs and the
+    /// policy, so both managers reach the same accumulator value.
+    pub fn merge(
+        &self,
+        left: &UpdateLog,
+        right: &UpdateLog,
+        policy: MergePolicy,
+    ) -> Result<(DynamicAccumulator, MergeProof)> {
+        for log in [left, right] {
+            ensure!(
+                log.start_epoch == self.epoch(),
+                "The log starts at epoch {}, the base is at epoch {}",
+                log.start_epoch,
+                self.epoch()
+            );
+            let _ = log.verify_chain(self.acc_value)?;
+        }
+        let mut merged_acc = self.clone();
+        let mut merged = UpdateLog::new(self.epoch());
+        for (element, present) in resolve(left, right, policy)? {
+            if present {
+                merged.push(merged_acc.add_fr(element)?);
+            } else {
+                merged.push(merged_acc.delete_fr(element)?);
+            }
+        }
+        let proof = MergeProof {
+            policy,
+            base_value: self.acc_value,
+            left: left.clone(),
+            right: right.clone(),
+            merged,
+        };
+        Ok((merged_acc, proof))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    fn diverge(base: &DynamicAccumulator, ops: &[(bool, i64)]) -> UpdateLog {
+        let mut acc = base.clone();
+        let mut log = UpdateLog::new(base.epoch());
+        for (add, x) in ops {
+            if *add {
+                log.push(acc.add(x).unwrap());
+            } else {
+                log.push(acc.delete(x).unwrap());
+            }
+        }
+        log
+    }
+
+    #[test]
+    fn test_merge() {
+        init_logger();
+        let mut base = DynamicAccumulator::new();
+        base.add_batch(&[1, 2, 3]).unwrap();
+        // Both delete 1; left re-adds it. Left deletes 2, right adds 4 and 5, right deletes 3.
+        let left = diverge(&base, &[(false, 1), (false, 2), (true, 1)]);
+        let right = diverge(&base, &[(false, 1), (true, 4), (false, 3), (true, 5)]);
+
+        let (add_wins, proof) = base.merge(&left, &right, MergePolicy::AddWins).unwrap();
+        assert_eq!(proof.verify().unwrap(), add_wins.acc_value);
+        let mut expected = DynamicAccumulator::new();
+        expected.add_batch(&[1, 4, 5]).unwrap();
+        assert_eq!(add_wins.acc_value, expected.acc_value);
+
+        let (remove_wins, proof) = base.merge(&left, &right, MergePolicy::RemoveWins).unwrap();
+        assert!(proof.verify().is_ok());
+        let mut expected = DynamicAccumulator::new();
+        expected.add_batch(&[4, 5]).unwrap();
+        assert_eq!(remove_wins.acc_value, expected.acc_value);
+
+        // The merge is symmetric.
+        let (swapped, _) = base.merge(&right, &left, MergePolicy::AddWins).unwrap();
+        assert_eq!(swapped.acc_value, add_wins.acc_value);
+
+        // A merged log that does not follow the policy is rejected.
+        let mut forged = proof;
+        forged.policy = MergePolicy::AddWins;
+        assert!(forged.verify().is_err());
+        assert!(add_wins.merge(&left, &right, MergePolicy::AddWins).is_err());
+    }
+}
@@ -0,0 +1,309 @@
+//! A setup-free set commitment: a Merkle tree over the sorted elements.
+//!
+//! [`MerkleAccumulator`] needs no trusted setup and relies on the hash function only, at the
+//! cost of logarithmic proofs and linear-time updates. Elements are mapped to field elements
+//! as in the [`DynamicAccumulator`](super::dynamic_accumulator::DynamicAccumulator) and kept
+//! in ascending order. A non-membership proof opens the two adjacent leaves around the missing
+//! element, or a single boundary leaf. The commitment binds the number of leaves, so boundary
+//! openings cannot be forged by claiming a different size.
+
+use super::{handle::ElementHandle, update::fr_to_digest, utils::digest_to_prime_field, Fr};
+use crate::digest::{blake2, Digest, Digestible};
+use anyhow::{bail, Result};
+use std::collections::BTreeSet;
+
+const LEAF_TAG: u8 = 0;
+const NODE_TAG: u8 = 1;
+const ROOT_TAG: u8 = 2;
+
+fn hash_leaf(element: &Fr) -> Digest {
+    let mut state = blake2().to_state();
+    state.update(&[LEAF_TAG]).update(&fr_to_digest(element).0);
+    Digest::from(state.finalize())
+}
+
+fn hash_node(left: &Digest, right: &Digest) -> Digest {
+    let mut state = blake2().to_state();
+    state.update(&[NODE_TAG]).update(&left.0).update(&right.0);
+    Digest::from(state.finalize())
+}
+
+fn hash_root(len: u64, tree_root: &Digest) -> Digest {
+    let mut state = blake2().to_state();
+    state
+        .update(&[ROOT_TAG])
+        .update(&len.to_le_bytes())
+        .update(&tree_root.0);
+    Digest::from(state.finalize())
+}
+
+/// The sibling hashes from a leaf up to the root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerklePath {
+    pub index: u64,
+    pub siblings: Vec<Digest>,
+}
+
+impl MerklePath {
+    /// Returns the commitment of a tree of `len` leaves in which `element` sits at this path.
+    fn commitment(&self, element: &Fr, len: u64) -> Option<Digest> {
+        if self.index >= len || self.siblings.len() != depth(len as usize) {
+            return None;
+        }
+        let mut node = hash_leaf(element);
+        for (level, sibling) in self.siblings.iter().enumerate() {
+            node = if (self.index >> level) & 1 == 0 {
+                hash_node(&node, sibling)
+            } else {
+                hash_node(sibling, &node)
+            };
+        }
+        Some(hash_root(len, &node))
+    }
+}
+
+fn depth(len: usize) -> usize {
+    len.next_power_of_two().trailing_zeros() as usize
+}
+
+/// A proof that `element` is a leaf of the tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleMembershipProof {
+    pub element: ElementHandle,
+    pub len: u64,
+    pub path: MerklePath,
+}
+
+impl MerkleMembershipProof {
+    pub fn verify(&self, commitment: &Digest) -> bool {
+        self.path.commitment(&self.element.0, self.len).as_ref() == Some(commitment)
+    }
+}
+
+/// A proof that `element` is not a leaf: its neighbours in the sorted order are adjacent
+/// leaves. `lower` is absent if the element is below the smallest leaf, `upper` if it is above
+/// the largest one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleNonMembershipProof {
+    pub element: ElementHandle,
+    pub len: u64,
+    pub lower: Option<(ElementHandle, MerklePath)>,
+    pub upper: Option<(ElementHandle, MerklePath)>,
+}
+
+impl MerkleNonMembershipProof {
+    pub fn verify(&self, commitment: &Digest) -> bool {
+        let opens = |(leaf, path): &(ElementHandle, MerklePath)| {
+            path.commitment(&leaf.0, self.len).as_ref() == Some(commitment)
+        };
+        match (&self.lower, &self.upper) {
+            (None, None) => self.len == 0 && *commitment == hash_root(0, &Digest::default()),
+            (Some(lower), None) => {
+                lower.0 < self.element && lower.1.index + 1 == self.len && opens(lower)
+            }
+            (None, Some(upper)) => self.element < upper.0 && upper.1.index == 0 && opens(upper),
+            (Some(lower), Some(upper)) => {
+                lower.0 < self.element
+                    && self.element < upper.0
+                    && lower.1.index + 1 == upper.1.index
+                    && opens(lower)
+                    && opens(upper)
+            }
+        }
+    }
+}
+
+/// A set of elements committed to by a Merkle tree over their sorted field representations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleAccumulator {
+    elements: BTreeSet<Fr>,
+    /// Every level of the tree, leaves first, padded to a power of two.
+    levels: Vec<Vec<Digest>>,
+    commitment: Digest,
+}
+
+impl Default for MerkleAccumulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MerkleAccumulator {
+    pub fn new() -> Self {
+        let mut acc = Self {
+            elements: BTreeSet::new(),
+            levels: Vec::new(),
+            commitment: Digest::default(),
+        };
+        acc.rebuild();
+        acc
+    }
+
+    fn rebuild(&mut self) {
+        let mut leaves: Vec<Digest> = self.elements.iter().map(hash_leaf).collect();
+        if leaves.is_empty() {
+            self.levels = Vec::new();
+            self.commitment = hash_root(0, &Digest::default());
+            return;
+        }
+        leaves.resize(leaves.len().next_power_of_two(), Digest::default());
+        let mut levels = vec![leaves];
+        while let Some(level) = levels.last().filter(|l| l.len() > 1) {
+            let next = level.chunks(2).map(|c| hash_node(&c[0], &c[1])).collect();
+            levels.push(next);
+        }
+        self.commitment = hash_root(self.elements.len() as u64, &levels[levels.len() - 1][0]);
+        self.levels = levels;
+    }
+
+    fn path(&self, index: usize) -> MerklePath {
+        let siblings = self.levels[..self.levels.len() - 1]
+            .iter()
+            .enumerate()
+            .map(|(level, nodes)| nodes[(index >> level) ^ 1])
+            .collect();
+        MerklePath {
+            index: index as u64,
+            siblings,
+        }
+    }
+
+    /// Returns the commitment to the set.
+    pub fn commitment(&self) -> Digest {
+        self.commitment
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    pub fn contains(&self, element: &i64) -> bool {
+        self.elements
+            .contains(&digest_to_prime_field(&element.to_digest()))
+    }
+
+    /// Adds an element. Returns an error if it is already in the set.
+    pub fn add(&mut self, element: &i64) -> Result<()> {
+        self.add_batch(core::slice::from_ref(element))
+    }
+
+    /// Adds several elements and rebuilds the tree once. Nothing is added if any of them is
+    /// already in the set or repeated in the batch.
+    pub fn add_batch(&mut self, elements: &[i64]) -> Result<()> {
+        let mut fresh = BTreeSet::new();
+        for element in elements {
+            let fr = digest_to_prime_field(&element.to_digest());
+            if self.elements.contains(&fr) || !fresh.insert(fr) {
+                bail!("Element already in accumulator");
+            }
+        }
+        self.elements.extend(fresh);
+        self.rebuild();
+        Ok(())
+    }
+
+    /// Deletes an element. Returns an error if it is not in the set.
+    pub fn delete(&mut self, element: &i64) -> Result<()> {
+        if !self
+            .elements
+            .remove(&digest_to_prime_field(&element.to_digest()))
+        {
+            bail!("Element not in accumulator");
+        }
+        self.rebuild();
+        Ok(())
+    }
+
+    pub fn prove_membership(&self, element: &i64) -> Result<MerkleMembershipProof> {
+        let fr = digest_to_prime_field(&element.to_digest());
+        if !self.elements.contains(&fr) {
+            bail!("Element not in accumulator");
+        }
+        let index = self.elements.range(..fr).count();
+        Ok(MerkleMembershipProof {
+            element: ElementHandle(fr),
+            len: self.len() as u64,
+            path: self.path(index),
+        })
+    }
+
+    pub fn prove_non_membership(&self, element: &i64) -> Result<MerkleNonMembershipProof> {
+        let fr = digest_to_prime_field(&element.to_digest());
+        if self.elements.contains(&fr) {
+            bail!("Element is in accumulator");
+        }
+        let index = self.elements.range(..fr).count();
+        let lower = self
+            .elements
+            .range(..fr)
+            .next_back()
+            .map(|e| (ElementHandle(*e), self.path(index - 1)));
+        let upper = self
+            .elements
+            .range(fr..)
+            .next()
+            .map(|e| (ElementHandle(*e), self.path(index)));
+        Ok(MerkleNonMembershipProof {
+            element: ElementHandle(fr),
+            len: self.len() as u64,
+            lower,
+            upper,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_merkle_accumulator() {
+        init_logger();
+        let mut acc = MerkleAccumulator::new();
+        let empty = acc.commitment();
+        assert!(acc.prove_non_membership(&1).unwrap().verify(&empty));
+
+        acc.add_batch(&[1, 2, 3, 4, 5]).unwrap();
+        assert!(acc.add(&3).is_err());
+        let commitment = acc.commitment();
+        for x in 1..=5 {
+            let proof = acc.prove_membership(&x).unwrap();
+            assert!(proof.verify(&commitment));
+            assert!(!proof.verify(&empty));
+        }
+        // Missing elements fall below, between and above the leaves.
+        for x in 6..40 {
+            let proof = acc.prove_non_membership(&x).unwrap();
+            assert!(proof.verify(&commitment));
+        }
+
+        // Openings that are not adjacent, or a forged size, are rejected.
+        let sorted: Vec<Fr> = acc.elements.iter().copied().collect();
+        let proof = (6..40)
+            .map(|x| acc.prove_non_membership(&x).unwrap())
+            .find(|p| p.lower.as_ref().is_some_and(|l| l.1.index > 0))
+            .unwrap();
+        let index = proof.lower.as_ref().unwrap().1.index as usize - 1;
+        let mut wider = proof.clone();
+        wider.lower = Some((ElementHandle(sorted[index]), acc.path(index)));
+        assert!(!wider.verify(&commitment));
+        let mut resized = proof;
+        resized.len += 1;
+        assert!(!resized.verify(&commitment));
+
+        acc.delete(&3).unwrap();
+        assert!(acc
+            .prove_non_membership(&3)
+            .unwrap()
+            .verify(&acc.commitment()));
+        assert!(acc.delete(&3).is_err());
+    }
+}
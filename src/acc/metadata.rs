@@ -0,0 +1,205 @@
+//! Application metadata bound into elements.
+//!
+//! An element can carry [`ElementMetadata`], e.g. an expiry timestamp and permission bits. The
+//! metadata is encoded canonically by [`ElementMetadata::encode`] and hashed together with the
+//! element into the accumulated field element, see [`element_with_metadata`], so changing a
+//! single bit of it gives another element. A [`MetadataMembershipProof`] carries the element and
+//! its metadata in the clear next to the membership proof, and a verifier checks that they hash
+//! to the proven element before enforcing a [`MetadataPolicy`] on them, without asking the
+//! manager.
+
+use super::{
+    dynamic_accumulator::{AddProof, DeleteProof, DynamicAccumulator, MembershipProof},
+    utils::digest_to_prime_field,
+    Fr, G1Affine,
+};
+use crate::digest::{concat_digest, Digestible};
+use anyhow::{bail, ensure, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const ENCODING_VERSION: u8 = 1;
+
+/// Metadata attached to an element.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ElementMetadata {
+    /// The time, in application units, after which the element no longer counts.
+    pub expires_at: Option<u64>,
+    /// Application permission bits.
+    pub permissions: u64,
+    /// Further application attributes.
+    pub attributes: BTreeMap<String, Vec<u8>>,
+}
+
+impl ElementMetadata {
+    /// The canonical encoding hashed into the element: a version byte, the expiry flag and
+    /// value, the permissions, and the attributes in key order, each length prefixed. All
+    /// integers are little endian.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = vec![ENCODING_VERSION];
+        match self.expires_at {
+            Some(expires_at) => {
+                buf.push(1);
+                buf.extend_from_slice(&expires_at.to_le_bytes());
+            }
+            None => buf.push(0),
+        }
+        buf.extend_from_slice(&self.permissions.to_le_bytes());
+        buf.extend_from_slice(&(self.attributes.len() as u64).to_le_bytes());
+        for (key, value) in &self.attributes {
+            for field in [key.as_bytes(), value] {
+                buf.extend_from_slice(&(field.len() as u64).to_le_bytes());
+                buf.extend_from_slice(field);
+            }
+        }
+        buf
+    }
+}
+
+/// The field element accumulated for `element` with `metadata`.
+pub fn element_with_metadata(element: &i64, metadata: &ElementMetadata) -> Fr {
+    digest_to_prime_field(&concat_digest(
+        [
+            b"ESA_RUST_METADATA"[..].to_digest(),
+            element.to_digest(),
+            metadata.encode().to_digest(),
+        ]
+        .into_iter(),
+    ))
+}
+
+/// What a verifier requires of the metadata.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct MetadataPolicy {
+    /// The verifier's current time; elements that expired before it are rejected.
+    pub now: Option<u64>,
+    /// Permission bits that must all be set.
+    pub required_permissions: u64,
+}
+
+impl MetadataPolicy {
+    /// Checks `metadata` against the policy.
+    pub fn check(&self, metadata: &ElementMetadata) -> Result<()> {
+        if let (Some(now), Some(expires_at)) = (self.now, metadata.expires_at) {
+            ensure!(now <= expires_at, "The element expired at {}", expires_at);
+        }
+        let missing = self.required_permissions & !metadata.permissions;
+        ensure!(missing == 0, "Missing permission bits {:#x}", missing);
+        Ok(())
+    }
+}
+
+/// A membership proof for an element together with its metadata.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetadataMembershipProof {
+    pub element: i64,
+    pub metadata: ElementMetadata,
+    pub proof: MembershipProof,
+}
+
+impl MetadataMembershipProof {
+    /// Checks that the element and metadata are the proven element and that the proof is valid
+    /// for the given accumulator value. The metadata is not checked against any policy.
+    pub fn verify(&self, accumulator: G1Affine) -> bool {
+        self.proof.element.0 == element_with_metadata(&self.element, &self.metadata)
+            && self.proof.verify(accumulator)
+    }
+
+    /// [`Self::verify`], then checks the metadata against `policy`.
+    pub fn verify_with_policy(&self, accumulator: G1Affine, policy: &MetadataPolicy) -> Result<()> {
+        if !self.verify(accumulator) {
+            bail!("Invalid membership proof for the element and metadata");
+        }
+        policy.check(&self.metadata)
+    }
+}
+
+impl DynamicAccumulator {
+    /// Adds `element` with `metadata`. The same element with other metadata is another element.
+    pub fn add_with_metadata(
+        &mut self,
+        element: &i64,
+        metadata: &ElementMetadata,
+    ) -> Result<AddProof> {
+        self.add_fr(element_with_metadata(element, metadata))
+    }
+
+    /// Deletes `element` with `metadata`.
+    pub fn delete_with_metadata(
+        &mut self,
+        element: &i64,
+        metadata: &ElementMetadata,
+    ) -> Result<DeleteProof> {
+        self.delete_fr(element_with_metadata(element, metadata))
+    }
+
+    pub fn check_with_metadata(&self, element: &i64, metadata: &ElementMetadata) -> bool {
+        self.elements.has(&element_with_metadata(element, metadata))
+    }
+
+    /// Proves membership of `element` with `metadata`.
+    pub fn prove_membership_with_metadata(
+        &self,
+        element: &i64,
+        metadata: &ElementMetadata,
+    ) -> Result<MetadataMembershipProof> {
+        Ok(MetadataMembershipProof {
+            element: *element,
+            metadata: metadata.clone(),
+            proof: self.prove_membership_fr(element_with_metadata(element, metadata))?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_metadata() {
+        init_logger();
+        let read_write = ElementMetadata {
+            expires_at: Some(1_000),
+            permissions: 0b011,
+            attributes: BTreeMap::from([("role".to_string(), b"admin".to_vec())]),
+        };
+        let mut acc = DynamicAccumulator::new();
+        assert!(acc.add_with_metadata(&7, &read_write).unwrap().verify());
+        assert!(acc.check_with_metadata(&7, &read_write));
+        assert!(
+            !acc.check(&7).unwrap() && !acc.check_with_metadata(&7, &ElementMetadata::default())
+        );
+
+        let proof = acc.prove_membership_with_metadata(&7, &read_write).unwrap();
+        assert!(proof.verify(acc.acc_value));
+        let policy = MetadataPolicy {
+            now: Some(900),
+            required_permissions: 0b001,
+        };
+        proof.verify_with_policy(acc.acc_value, &policy).unwrap();
+        let late = MetadataPolicy {
+            now: Some(1_001),
+            ..policy
+        };
+        assert!(proof.verify_with_policy(acc.acc_value, &late).is_err());
+        let execute = MetadataPolicy {
+            required_permissions: 0b100,
+            ..policy
+        };
+        assert!(proof.verify_with_policy(acc.acc_value, &execute).is_err());
+
+        // Claiming other metadata for the proven element is rejected.
+        let mut forged = proof.clone();
+        forged.metadata.permissions = u64::MAX;
+        assert!(!forged.verify(acc.acc_value));
+        assert!(forged.verify_with_policy(acc.acc_value, &execute).is_err());
+
+        assert!(acc.prove_membership_with_metadata(&8, &read_write).is_err());
+        acc.delete_with_metadata(&7, &read_write).unwrap();
+        assert!(!acc.check_with_metadata(&7, &read_write));
+    }
+}
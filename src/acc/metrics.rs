@@ -0,0 +1,252 @@
+//! Prover and verifier metrics.
+//!
+//! With the `metrics` feature enabled, proof generation and verification record counters and
+//! histograms that can be read with [`snapshot`]. Without the feature the recording functions
+//! compile to nothing.
+//!
+//! Spans for the same operations are emitted through `tracing` in either case.
+
+#[cfg(feature = "metrics")]
+pub use imp::*;
+
+use core::time::Duration;
+
+/// The kinds of operations metrics are recorded for.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Op {
+    ProveMembership,
+    ProveNonMembership,
+    ProveIntersection,
+    VerifyMembership,
+    VerifyNonMembership,
+    BatchAdd,
+}
+
+impl Op {
+    pub const ALL: [Op; 6] = [
+        Op::ProveMembership,
+        Op::ProveNonMembership,
+        Op::ProveIntersection,
+        Op::VerifyMembership,
+        Op::VerifyNonMembership,
+        Op::BatchAdd,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Op::ProveMembership => "prove_membership",
+            Op::ProveNonMembership => "prove_non_membership",
+            Op::ProveIntersection => "prove_intersection",
+            Op::VerifyMembership => "verify_membership",
+            Op::VerifyNonMembership => "verify_non_membership",
+            Op::BatchAdd => "batch_add",
+        }
+    }
+}
+
+/// Runs `f` inside a `tracing` span for `op` and records its latency.
+#[inline]
+pub(crate) fn timed<T>(op: Op, f: impl FnOnce() -> T) -> T {
+    let _span = tracing::debug_span!("acc_op", op = op.name()).entered();
+    let start = std::time::Instant::now();
+    let result = f();
+    record_op(op, start.elapsed());
+    result
+}
+
+/// Records one completed operation and how long it took.
+#[inline]
+pub(crate) fn record_op(op: Op, elapsed: Duration) {
+    #[cfg(feature = "metrics")]
+    imp::METRICS.record_op(op, elapsed);
+    #[cfg(not(feature = "metrics"))]
+    let _ = (op, elapsed);
+}
+
+/// Records pairings computed by a verification.
+#[inline]
+pub(crate) fn record_pairings(n: u64) {
+    #[cfg(feature = "metrics")]
+    imp::METRICS.record_pairings(n);
+    #[cfg(not(feature = "metrics"))]
+    let _ = n;
+}
+
+/// Records the degree of a polynomial built by a prover.
+#[inline]
+pub(crate) fn record_degree(degree: usize) {
+    #[cfg(feature = "metrics")]
+    imp::METRICS.record_degree(degree);
+    #[cfg(not(feature = "metrics"))]
+    let _ = degree;
+}
+
+#[cfg(feature = "metrics")]
+mod imp {
+    use super::Op;
+    use core::time::Duration;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    /// Upper bounds of the latency histogram buckets, in microseconds. The last bucket is
+    /// unbounded.
+    pub const LATENCY_BUCKETS_US: [u64; 8] =
+        [100, 500, 1_000, 5_000, 10_000, 50_000, 100_000, 1_000_000];
+    /// Upper bounds of the polynomial degree histogram buckets. The last bucket is unbounded.
+    pub const DEGREE_BUCKETS: [u64; 6] = [10, 100, 1_000, 10_000, 100_000, 1_000_000];
+
+    struct Histogram {
+        bounds: &'static [u64],
+        /// One count per bound plus one for values above the last bound.
+        counts: Vec<AtomicU64>,
+        sum: AtomicU64,
+    }
+
+    impl Histogram {
+        fn new(bounds: &'static [u64]) -> Self {
+            Self {
+                bounds,
+                counts: (0..=bounds.len()).map(|_| AtomicU64::new(0)).collect(),
+                sum: AtomicU64::new(0),
+            }
+        }
+
+        fn record(&self, value: u64) {
+            let i = self
+                .bounds
+                .iter()
+                .position(|b| value <= *b)
+                .unwrap_or(self.bounds.len());
+            self.counts[i].fetch_add(1, Ordering::Relaxed);
+            self.sum.fetch_add(value, Ordering::Relaxed);
+        }
+
+        fn snapshot(&self) -> HistogramSnapshot {
+            HistogramSnapshot {
+                buckets: self
+                    .counts
+                    .iter()
+                    .map(|c| c.load(Ordering::Relaxed))
+                    .collect(),
+                sum: self.sum.load(Ordering::Relaxed),
+            }
+        }
+    }
+
+    struct OpMetrics {
+        count: AtomicU64,
+        latency_us: Histogram,
+    }
+
+    pub(super) struct Metrics {
+        ops: Vec<OpMetrics>,
+        pairings: AtomicU64,
+        degree: Histogram,
+    }
+
+    lazy_static! {
+        pub(super) static ref METRICS: Metrics = Metrics {
+            ops: Op::ALL
+                .iter()
+                .map(|_| OpMetrics {
+                    count: AtomicU64::new(0),
+                    latency_us: Histogram::new(&LATENCY_BUCKETS_US),
+                })
+                .collect(),
+            pairings: AtomicU64::new(0),
+            degree: Histogram::new(&DEGREE_BUCKETS),
+        };
+    }
+
+    fn op_index(op: Op) -> usize {
+        Op::ALL.iter().position(|o| *o == op).unwrap_or(0)
+    }
+
+    impl Metrics {
+        pub(super) fn record_op(&self, op: Op, elapsed: Duration) {
+            let m = &self.ops[op_index(op)];
+            m.count.fetch_add(1, Ordering::Relaxed);
+            m.latency_us.record(elapsed.as_micros() as u64);
+        }
+
+        pub(super) fn record_pairings(&self, n: u64) {
+            self.pairings.fetch_add(n, Ordering::Relaxed);
+        }
+
+        pub(super) fn record_degree(&self, degree: usize) {
+            self.degree.record(degree as u64);
+        }
+    }
+
+    /// Bucket counts of a histogram (one more than the bucket bounds, for the overflow
+    /// bucket) and the sum of all recorded values.
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    pub struct HistogramSnapshot {
+        pub buckets: Vec<u64>,
+        pub sum: u64,
+    }
+
+    impl HistogramSnapshot {
+        pub fn count(&self) -> u64 {
+            self.buckets.iter().sum()
+        }
+    }
+
+    /// A point-in-time copy of all metrics.
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    pub struct MetricsSnapshot {
+        /// Count and latency (in microseconds) of every operation kind.
+        pub ops: Vec<(Op, u64, HistogramSnapshot)>,
+        pub pairings: u64,
+        pub polynomial_degree: HistogramSnapshot,
+    }
+
+    impl MetricsSnapshot {
+        /// Returns the number of completed operations of the given kind.
+        pub fn op_count(&self, op: Op) -> u64 {
+            self.ops
+                .iter()
+                .find(|(o, _, _)| *o == op)
+                .map_or(0, |(_, count, _)| *count)
+        }
+    }
+
+    /// Returns the current metrics.
+    pub fn snapshot() -> MetricsSnapshot {
+        MetricsSnapshot {
+            ops: Op::ALL
+                .iter()
+                .map(|op| {
+                    let m = &METRICS.ops[op_index(*op)];
+                    (
+                        *op,
+                        m.count.load(Ordering::Relaxed),
+                        m.latency_us.snapshot(),
+                    )
+                })
+                .collect(),
+            pairings: METRICS.pairings.load(Ordering::Relaxed),
+            polynomial_degree: METRICS.degree.snapshot(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use super::*;
+    use crate::acc::dynamic_accumulator::DynamicAccumulator;
+
+    #[test]
+    fn test_snapshot() {
+        let before = snapshot();
+        let mut acc = DynamicAccumulator::new();
+        acc.add_batch(&[1, 2, 3]).unwrap();
+        let proof = acc.prove_non_membership(&4).unwrap();
+        assert!(acc.verify_non_membership(&proof));
+
+        let after = snapshot();
+        assert!(after.op_count(Op::ProveNonMembership) > before.op_count(Op::ProveNonMembership));
+        assert!(after.op_count(Op::BatchAdd) > before.op_count(Op::BatchAdd));
+        assert!(after.pairings >= before.pairings + 3);
+        assert!(after.polynomial_degree.count() > before.polynomial_degree.count());
+    }
+}
@@ -0,0 +1,423 @@
+//! A deny-list layer for [tower](https://docs.rs/tower) services.
+//!
+//! A [`DenyList`] accumulates revoked API keys. [`DenyListLayer`] wraps a service so that every
+//! request must carry an API key header (`x-api-key` by default): requests without one get
+//! `401 Unauthorized`, requests with a denied key get `403 Forbidden`, and the others reach the
+//! inner service. Checking a key is a plain set lookup. With [`DenyListLayer::attach_proof`],
+//! allowed responses also carry a non-membership witness in the [`PROOF_HEADER`] header, so
+//! clients can check that their key was not revoked at the reported epoch. Witnesses cost a
+//! polynomial xgcd and two MSMs each, so the most recent ones are cached, up to a capacity and
+//! only for the current epoch.
+
+use super::{
+    codec::{ElementCodec, KeyPart},
+    commitment::params_id,
+    dynamic_accumulator::{AddProof, DeleteProof, DynamicAccumulator, NonMembershipProof},
+    handle::ElementHandle,
+    Fr, G1Affine, G2Affine,
+};
+use crate::digest::Digestible;
+use anyhow::{anyhow, bail, Context, Result};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context as TaskContext, Poll},
+};
+use http::{header::HeaderName, HeaderValue, Request, Response, StatusCode};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex, RwLock},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// The header the non-membership witness is attached in, as `<epoch>.<hex witness>`.
+pub const PROOF_HEADER: &str = "x-esa-non-membership-proof";
+
+/// The header API keys are read from by default.
+pub const DEFAULT_KEY_HEADER: &str = "x-api-key";
+
+/// The default maximum number of cached non-membership witnesses.
+pub const DEFAULT_PROOF_CACHE_CAPACITY: usize = 1024;
+
+/// Non-membership proofs of one epoch, evicting the oldest once full.
+#[derive(Debug, Default)]
+struct ProofCache {
+    epoch: u64,
+    capacity: usize,
+    proofs: HashMap<Fr, NonMembershipProof>,
+    order: VecDeque<Fr>,
+}
+
+impl ProofCache {
+    fn get(&self, element: &Fr, epoch: u64) -> Option<NonMembershipProof> {
+        if self.epoch != epoch {
+            return None;
+        }
+        self.proofs.get(element).cloned()
+    }
+
+    fn insert(&mut self, element: Fr, proof: NonMembershipProof) {
+        if proof.epoch != self.epoch {
+            self.clear();
+            self.epoch = proof.epoch;
+        }
+        if self.capacity == 0 || self.proofs.contains_key(&element) {
+            return;
+        }
+        if self.proofs.len() == self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.proofs.remove(&oldest);
+            }
+        }
+        self.proofs.insert(element, proof);
+        self.order.push_back(element);
+    }
+
+    fn clear(&mut self) {
+        self.proofs.clear();
+        self.order.clear();
+    }
+}
+
+/// An accumulator of revoked API keys with a bounded cache of non-membership witnesses.
+#[derive(Debug)]
+pub struct DenyList {
+    acc: RwLock<DynamicAccumulator>,
+    codec: ElementCodec,
+    witnesses: Mutex<ProofCache>,
+}
+
+impl Default for DenyList {
+    fn default() -> Self {
+        Self::new(DynamicAccumulator::new())
+    }
+}
+
+impl DenyList {
+    /// Wraps an accumulator whose elements were encoded with [`DenyList::element`].
+    pub fn new(acc: DynamicAccumulator) -> Self {
+        Self::with_cache_capacity(acc, DEFAULT_PROOF_CACHE_CAPACITY)
+    }
+
+    /// Same as [`DenyList::new`], caching at most `capacity` witnesses. A capacity of zero
+    /// disables caching.
+    pub fn with_cache_capacity(acc: DynamicAccumulator, capacity: usize) -> Self {
+        Self {
+            acc: RwLock::new(acc),
+            codec: ElementCodec::new("api_key").component("key"),
+            witnesses: Mutex::new(ProofCache {
+                capacity,
+                ..ProofCache::default()
+            }),
+        }
+    }
+
+    /// Maps an API key to the element accumulated for it.
+    pub fn element(&self, key: &str) -> Fr {
+        self.codec
+            .encode(&[KeyPart::Str(key)])
+            .unwrap_or_else(|_| unreachable!("the schema has one component"))
+    }
+
+    /// Revokes a key.
+    pub fn deny(&self, key: &str) -> Result<AddProof> {
+        let element = self.element(key);
+        self.write()?.add_encoded(element)
+    }
+
+    /// Reinstates a revoked key.
+    pub fn allow(&self, key: &str) -> Result<DeleteProof> {
+        let element = self.element(key);
+        self.write()?.delete_encoded(element)
+    }
+
+    pub fn acc_value(&self) -> Result<G1Affine> {
+        Ok(self.read()?.acc_value)
+    }
+
+    pub fn epoch(&self) -> Result<u64> {
+        Ok(self.read()?.epoch())
+    }
+
+    /// Returns true if the key is denied. Unlike [`DenyList::check`], no proof is built.
+    pub fn is_denied(&self, key: &str) -> Result<bool> {
+        self.read()?.elements.contains(&self.element(key))
+    }
+
+    /// Returns a non-membership proof for an allowed key at the current epoch, or `None` if the
+    /// key is denied.
+    pub fn check(&self, key: &str) -> Result<Option<NonMembershipProof>> {
+        let element = self.element(key);
+        let acc = self.read()?;
+        if acc.elements.contains(&element)? {
+            return Ok(None);
+        }
+        if let Some(proof) = self.lock_witnesses()?.get(&element, acc.epoch()) {
+            return Ok(Some(proof));
+        }
+        // Other keys are served from the cache while the proof is built.
+        let proof = acc.prove_non_membership_encoded(element)?;
+        self.lock_witnesses()?.insert(element, proof.clone());
+        Ok(Some(proof))
+    }
+
+    /// Returns the number of cached witnesses.
+    pub fn cached_proofs(&self) -> Result<usize> {
+        Ok(self.lock_witnesses()?.proofs.len())
+    }
+
+    fn read(&self) -> Result<std::sync::RwLockReadGuard<'_, DynamicAccumulator>> {
+        self.acc
+            .read()
+            .map_err(|_| anyhow!("Deny list lock poisoned"))
+    }
+
+    fn write(&self) -> Result<std::sync::RwLockWriteGuard<'_, DynamicAccumulator>> {
+        self.acc
+            .write()
+            .map_err(|_| anyhow!("Deny list lock poisoned"))
+    }
+
+    fn lock_witnesses(&self) -> Result<std::sync::MutexGuard<'_, ProofCache>> {
+        self.witnesses
+            .lock()
+            .map_err(|_| anyhow!("Witness cache lock poisoned"))
+    }
+}
+
+/// Encodes a non-membership proof for the [`PROOF_HEADER`] header.
+pub fn encode_proof_header(proof: &NonMembershipProof) -> Result<String> {
+    let mut buf = Vec::new();
+    proof.witness.serialize(&mut buf)?;
+    proof.g1_a.serialize(&mut buf)?;
+    Ok(format!("{}.{}", proof.epoch, hex::encode(buf)))
+}
+
+/// Decodes a [`PROOF_HEADER`] value into a proof for the key `element` against `acc_value`.
+pub fn decode_proof_header(
+    value: &str,
+    element: Fr,
+    acc_value: G1Affine,
+) -> Result<NonMembershipProof> {
+    let (epoch, witness) = value.split_once('.').context("Malformed proof header")?;
+    let bytes = hex::decode(witness)?;
+    let mut reader = &bytes[..];
+    let witness = G2Affine::deserialize(&mut reader)?;
+    let g1_a = G1Affine::deserialize(&mut reader)?;
+    if !reader.is_empty() {
+        bail!("Trailing bytes in proof header");
+    }
+    Ok(NonMembershipProof {
+        element: ElementHandle(element),
+        witness,
+        g1_a,
+        epoch: epoch.parse()?,
+        acc_digest: acc_value.to_digest(),
+        params_id: params_id(),
+    })
+}
+
+/// A [`Layer`] that rejects requests whose API key is in a [`DenyList`].
+#[derive(Debug, Clone)]
+pub struct DenyListLayer {
+    list: Arc<DenyList>,
+    key_header: HeaderName,
+    attach_proof: bool,
+}
+
+impl DenyListLayer {
+    pub fn new(list: Arc<DenyList>) -> Self {
+        Self {
+            list,
+            key_header: HeaderName::from_static(DEFAULT_KEY_HEADER),
+            attach_proof: false,
+        }
+    }
+
+    /// Reads API keys from `header` instead of [`DEFAULT_KEY_HEADER`].
+    pub fn key_header(mut self, header: HeaderName) -> Self {
+        self.key_header = header;
+        self
+    }
+
+    /// Attaches the non-membership witness of the key to allowed responses.
+    pub fn attach_proof(mut self, attach: bool) -> Self {
+        self.attach_proof = attach;
+        self
+    }
+}
+
+impl<S> Layer<S> for DenyListLayer {
+    type Service = DenyListService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        DenyListService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// The service produced by [`DenyListLayer`].
+#[derive(Debug, Clone)]
+pub struct DenyListService<S> {
+    inner: S,
+    layer: DenyListLayer,
+}
+
+fn status<B: Default>(code: StatusCode) -> Response<B> {
+    let mut response = Response::new(B::default());
+    *response.status_mut() = code;
+    response
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for DenyListService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+    S::Error: 'static,
+    ResBody: Default + Send + 'static,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut TaskContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let key = req
+            .headers()
+            .get(&self.layer.key_header)
+            .and_then(|v| v.to_str().ok());
+        let list = &self.layer.list;
+        // Proofs are only built for allowed keys and only if they are attached.
+        let checked = key.map(|key| match self.layer.attach_proof {
+            true => list.check(key).map(|proof| proof.map(Some)),
+            false => list.is_denied(key).map(|denied| (!denied).then_some(None)),
+        });
+        let proof = match checked {
+            None => return Box::pin(async { Ok(status(StatusCode::UNAUTHORIZED)) }),
+            Some(Ok(None)) => return Box::pin(async { Ok(status(StatusCode::FORBIDDEN)) }),
+            Some(Ok(Some(proof))) => proof,
+            Some(Err(e)) => {
+                tracing::error!("Deny list check failed: {:#}", e);
+                return Box::pin(async { Ok(status(StatusCode::INTERNAL_SERVER_ERROR)) });
+            }
+        };
+        let header = proof
+            .and_then(|proof| encode_proof_header(&proof).ok())
+            .and_then(|v| HeaderValue::from_str(&v).ok());
+        let response = self.inner.call(req);
+        Box::pin(async move {
+            let mut response = response.await?;
+            if let Some(header) = header {
+                response
+                    .headers_mut()
+                    .insert(HeaderName::from_static(PROOF_HEADER), header);
+            }
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::{convert::Infallible, future::Ready};
+    use std::task::Waker;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    struct Ok200;
+
+    impl Service<Request<()>> for Ok200 {
+        type Response = Response<()>;
+        type Error = Infallible;
+        type Future = Ready<Result<Response<()>, Infallible>>;
+
+        fn poll_ready(&mut self, _: &mut TaskContext<'_>) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _: Request<()>) -> Self::Future {
+            core::future::ready(Ok(Response::new(())))
+        }
+    }
+
+    fn send(service: &mut DenyListService<Ok200>, key: Option<&str>) -> Response<()> {
+        let mut req = Request::builder();
+        if let Some(key) = key {
+            req = req.header(DEFAULT_KEY_HEADER, key);
+        }
+        let mut fut = service.call(req.body(()).unwrap());
+        match fut
+            .as_mut()
+            .poll(&mut TaskContext::from_waker(Waker::noop()))
+        {
+            Poll::Ready(Ok(response)) => response,
+            _ => panic!("the inner service is always ready"),
+        }
+    }
+
+    #[test]
+    fn test_deny_list_layer() {
+        init_logger();
+        let list = Arc::new(DenyList::default());
+        list.deny("revoked").unwrap();
+        let mut service = DenyListLayer::new(list.clone())
+            .attach_proof(true)
+            .layer(Ok200);
+
+        assert_eq!(send(&mut service, None).status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            send(&mut service, Some("revoked")).status(),
+            StatusCode::FORBIDDEN
+        );
+        let response = send(&mut service, Some("valid"));
+        assert_eq!(response.status(), StatusCode::OK);
+        let header = response.headers()[PROOF_HEADER].to_str().unwrap();
+        let acc_value = list.acc_value().unwrap();
+        let proof = decode_proof_header(header, list.element("valid"), acc_value).unwrap();
+        assert!(proof.verify_at(acc_value, list.epoch().unwrap()));
+
+        // Cached witnesses are refreshed once the list changes.
+        list.allow("revoked").unwrap();
+        assert_eq!(send(&mut service, Some("revoked")).status(), StatusCode::OK);
+        assert_eq!(list.cached_proofs().unwrap(), 1);
+        let proof = list.check("valid").unwrap().unwrap();
+        assert!(proof.verify_at(list.acc_value().unwrap(), list.epoch().unwrap()));
+    }
+
+    #[test]
+    fn test_proofs_only_when_attached() {
+        init_logger();
+        let list = Arc::new(DenyList::with_cache_capacity(DynamicAccumulator::new(), 2));
+        list.deny("revoked").unwrap();
+        let mut service = DenyListLayer::new(list.clone()).layer(Ok200);
+        for key in ["a", "b", "c"] {
+            let response = send(&mut service, Some(key));
+            assert_eq!(response.status(), StatusCode::OK);
+            assert!(response.headers().get(PROOF_HEADER).is_none());
+        }
+        assert_eq!(
+            send(&mut service, Some("revoked")).status(),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(list.cached_proofs().unwrap(), 0);
+
+        // The cache keeps the most recent witnesses of the current epoch only.
+        for key in ["a", "b", "c"] {
+            list.check(key).unwrap().unwrap();
+        }
+        assert_eq!(list.cached_proofs().unwrap(), 2);
+        list.deny("d").unwrap();
+        list.check("a").unwrap().unwrap();
+        assert_eq!(list.cached_proofs().unwrap(), 1);
+    }
+}
@@ -1,7 +1,95 @@
+#[cfg(feature = "adversarial")]
+pub mod adversarial;
+pub mod aggregate;
+pub mod arith;
+#[cfg(feature = "ark04")]
+pub mod ark04;
+pub mod audit;
+pub mod auth_log;
+pub mod backend;
+pub mod batch;
+pub mod blinding;
+pub mod block;
+#[cfg(feature = "bench-internals")]
+pub mod bench_internals;
+pub mod bulk;
+pub mod cache;
+pub mod canonical;
+pub mod capacity;
+pub mod capabilities;
+pub mod cardinality;
+pub mod checkpoint;
+pub mod codec;
+pub mod combine;
+pub mod commitment;
+pub mod conformance;
+pub mod consistency;
+pub mod credential;
+pub mod cross_crs;
+pub mod dedup;
+pub mod diff;
+#[cfg(feature = "diagnostics")]
+pub mod diagnostics;
 pub mod digest_set;
 pub mod dynamic_accumulator;
+pub mod engine;
+pub mod escrow;
+pub mod expiry;
+pub mod expr;
+pub mod filter;
+pub mod g2_witness;
+pub mod grace;
+pub mod gt;
+pub mod handle;
+pub mod hardened;
+pub mod hints;
+pub mod index;
+pub mod int;
+pub mod integrity;
+pub mod intersection;
+pub mod interval;
+pub mod lifecycle;
+pub mod merge;
+pub mod merkle;
+pub mod metadata;
+pub mod metrics;
+#[cfg(feature = "tower")]
+pub mod middleware;
+pub mod multisig;
+pub mod multiset_union;
+pub mod namespace;
+pub mod offload;
+pub mod order;
+pub mod page;
+pub mod params;
+pub mod planner;
+pub mod policy;
+pub mod polymath;
+pub mod prepared;
+pub mod protocol;
+pub mod public_prover;
+pub mod receipt;
+pub mod recovery;
+pub mod registry;
+pub mod revocation;
+pub mod rng;
 pub mod serde_impl;
+pub mod signing;
+pub mod static_acc;
+pub mod stats;
+pub mod stream;
+pub mod sum;
+pub mod sync;
+pub mod tombstone;
+pub mod transaction;
+pub mod trapdoor;
+pub mod update;
 pub mod utils;
+pub mod values;
+pub mod verifier;
+pub mod wire;
+pub mod witness_escrow;
+pub mod witness_store;
 
 pub use ark_bls12_381::{
     Bls12_381 as Curve, Fq12, Fr, G1Affine, G1Projective, G2Affine, G2Projective,
@@ -11,13 +99,14 @@ pub type DigestSet = digest_set::DigestSet<Fr>;
 use crate::digest::{Digest, Digestible};
 use crate::set::{MultiSet, SetElement};
 use anyhow::{self, bail, ensure, Context};
-use ark_ec::{msm::VariableBaseMSM, AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
 use ark_ff::{Field, One, PrimeField, ToBytes, Zero};
 use ark_poly::{univariate::DensePolynomial, Polynomial};
 use core::any::Any;
 use core::str::FromStr;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use tracing::{info, trace};
 use utils::{xgcd, FixedBaseCurvePow, FixedBaseScalarPow};
 
 #[cfg(test)]
@@ -25,6 +114,10 @@ const GS_VEC_LEN: usize = 0;
 #[cfg(not(test))]
 const GS_VEC_LEN: usize = 5000;
 
+/// The largest set size (polynomial degree) public parameters are published for.
+/// Degree-bound proofs are only sound for degrees up to this value.
+pub const MAX_DEGREE: usize = 5000;
+
 lazy_static! {
     // 250 bits
     static ref PUB_Q: Fr = Fr::from_str("480721077433357505777975950918924200361380912084288598463024400624539293706").unwrap();
@@ -57,6 +150,8 @@ lazy_static! {
         info!("Done in {}.", timer.elapsed());
         res
     };
+    static ref G1_S: G1Affine = get_g1s(Fr::one());
+    static ref G2_S: G2Affine = get_g2s(Fr::one());
     static ref E_G_G: Fq12 = Curve::pairing(
         G1Affine::prime_subgroup_generator(),
         G2Affine::prime_subgroup_generator()
@@ -73,6 +168,49 @@ fn get_g2s(coeff: Fr) -> G2Affine {
     G2_POWER.apply(&si).into_affine()
 }
 
+/// The published g1^s.
+pub(crate) fn g1_s() -> G1Affine {
+    *G1_S
+}
+
+/// The published g2^s.
+pub(crate) fn g2_s() -> G2Affine {
+    *G2_S
+}
+
+/// g1^(s-x), computed from the published g1^s.
+pub(crate) fn g1_s_minus(x: Fr) -> G1Affine {
+    (G1_S.into_projective() - G1_POWER.apply(&x)).into_affine()
+}
+
+/// g2^(s-x), computed from the published g2^s.
+pub(crate) fn g2_s_minus(x: Fr) -> G2Affine {
+    (G2_S.into_projective() - G2_POWER.apply(&x)).into_affine()
+}
+
+/// Commits to `poly` in G1 with the published powers g1^(s^i). Fails above [`MAX_DEGREE`], for
+/// which no powers are published.
+pub(crate) fn commit_g1(poly: DensePolynomial<Fr>) -> anyhow::Result<G1Affine> {
+    ensure!(
+        poly.degree() <= MAX_DEGREE,
+        "degree {} exceeds the maximum degree {}",
+        poly.degree(),
+        MAX_DEGREE
+    );
+    Ok(Acc1::poly_to_g1(poly))
+}
+
+/// Commits to `poly` in G2 with the published powers g2^(s^i), see [`commit_g1`].
+pub(crate) fn commit_g2(poly: DensePolynomial<Fr>) -> anyhow::Result<G2Affine> {
+    ensure!(
+        poly.degree() <= MAX_DEGREE,
+        "degree {} exceeds the maximum degree {}",
+        poly.degree(),
+        MAX_DEGREE
+    );
+    Ok(Acc1::poly_to_g2(poly))
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum Type {
     ACC1,
@@ -131,18 +269,18 @@ impl Acc1 {
         (0..idxes.len())
             .into_par_iter()
             .map(|i| {
-                G1_S_VEC.get(i).copied().unwrap_or_else(|| {
-                    trace!("access g1 pub key at {}", i);
-                    get_g1s(Fr::from(i as u64))
+                G1_S_VEC.get(idxes[i]).copied().unwrap_or_else(|| {
+                    trace!("access g1 pub key at {}", idxes[i]);
+                    get_g1s(Fr::from(idxes[i] as u64))
                 })
             })
             .collect_into_vec(&mut bases);
         (0..idxes.len())
             .into_par_iter()
-            .map(|i| poly.coeffs[i].into_repr())
+            .map(|i| poly.coeffs[idxes[i]].into_repr())
             .collect_into_vec(&mut scalars);
 
-        VariableBaseMSM::multi_scalar_mul(&bases[..], &scalars[..]).into_affine()
+        offload::msm_g1(&bases, &scalars).into_affine()
     }
 
     fn poly_to_g2(poly: DensePolynomial<Fr>) -> G2Affine {
@@ -159,18 +297,18 @@ impl Acc1 {
         (0..idxes.len())
             .into_par_iter()
             .map(|i| {
-                G2_S_VEC.get(i).copied().unwrap_or_else(|| {
-                    trace!("access g2 pub key at {}", i);
-                    get_g2s(Fr::from(i as u64))
+                G2_S_VEC.get(idxes[i]).copied().unwrap_or_else(|| {
+                    trace!("access g2 pub key at {}", idxes[i]);
+                    get_g2s(Fr::from(idxes[i] as u64))
                 })
             })
             .collect_into_vec(&mut bases);
         (0..idxes.len())
             .into_par_iter()
-            .map(|i| poly.coeffs[i].into_repr())
+            .map(|i| poly.coeffs[idxes[i]].into_repr())
             .collect_into_vec(&mut scalars);
 
-        VariableBaseMSM::multi_scalar_mul(&bases[..], &scalars[..]).into_affine()
+        offload::msm_g2(&bases, &scalars).into_affine()
     }
 }
 
@@ -313,7 +451,7 @@ impl Accumulator for Acc2 {
             .into_par_iter()
             .map(|i| <Fr as PrimeField>::BigInt::from(set[i].1 as u64))
             .collect_into_vec(&mut scalars);
-        VariableBaseMSM::multi_scalar_mul(&bases[..], &scalars[..]).into_affine()
+        offload::msm_g1(&bases, &scalars).into_affine()
     }
     fn cal_acc_g2_sk_d(set: &DigestSet) -> G2Affine {
         let x = set
@@ -336,7 +474,7 @@ impl Accumulator for Acc2 {
             .into_par_iter()
             .map(|i| <Fr as PrimeField>::BigInt::from(set[i].1 as u64))
             .collect_into_vec(&mut scalars);
-        VariableBaseMSM::multi_scalar_mul(&bases[..], &scalars[..]).into_affine()
+        offload::msm_g2(&bases, &scalars).into_affine()
     }
     fn gen_proof(set1: &DigestSet, set2: &DigestSet) -> anyhow::Result<Self::Proof> {
         let produce_size = set1.len() * set2.len();
@@ -365,7 +503,7 @@ impl Accumulator for Acc2 {
             .into_par_iter()
             .map(|i| <Fr as PrimeField>::BigInt::from(product[i].1))
             .collect_into_vec(&mut scalars);
-        let f = VariableBaseMSM::multi_scalar_mul(&bases[..], &scalars[..]).into_affine();
+        let f = offload::msm_g1(&bases, &scalars).into_affine();
         Ok(Acc2Proof { f })
     }
 }
@@ -376,15 +514,33 @@ pub enum Proof {
     ACC2(Box<Acc2Proof>),
 }
 
+/// Bytes of a G1 point written with `ToBytes`: both coordinates and the infinity flag.
+const G1_BYTES: usize = 97;
+
 impl Digestible for G1Affine {
     fn to_digest(&self) -> Digest {
-        let mut buf = Vec::<u8>::new();
-        self.write(&mut buf)
+        // A stack buffer keeps digesting accumulator values, done on every verification,
+        // free of heap allocations.
+        let mut buf = [0u8; G1_BYTES];
+        self.write(&mut buf[..])
             .unwrap_or_else(|_| panic!("failed to serialize {:?}", self));
-        buf.to_digest()
+        buf[..].to_digest()
     }
 }
 
+/// Bytes of a G2 point written with `ToBytes`.
+const G2_BYTES: usize = 193;
+
+/// The digest of a G2 point. `Digestible` cannot be implemented for both affine groups, as
+/// their types differ only in associated types.
+pub(crate) fn g2_digest(point: &G2Affine) -> Digest {
+    let mut buf = [0u8; G2_BYTES];
+    point
+        .write(&mut buf[..])
+        .unwrap_or_else(|_| panic!("failed to serialize {:?}", point));
+    buf[..].to_digest()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
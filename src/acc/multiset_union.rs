@@ -0,0 +1,287 @@
+//! Unions of multisets.
+//!
+//! [`DynamicAccumulator::prove_union`] only supports sets. For multisets the count of an element
+//! in the union is a matter of policy, chosen with [`UnionPolicy`]:
+//!
+//! - [`UnionPolicy::MaxCount`] keeps the larger of the two counts. The characteristic polynomial
+//!   of the union is lcm(P_A, P_B) = P_A * Q_B, with gcd(P_A, P_B) the min-count intersection and
+//!   P_B = Q_B * gcd. The proof is a [`UnionProof`] over that intersection, verified as for sets.
+//! - [`UnionPolicy::SumCount`] adds the counts, so the union polynomial is P_A * P_B. The proof
+//!   commits to P_B in G2 and the verifier checks e(acc_A, g2^P_B(s)) == e(acc_union, g2).
+//!
+//! A [`MultisetUnionProof`] carries the policy it was made for and only verifies for it.
+
+use super::{
+    commit_g1, commit_g2,
+    commitment::params_id,
+    dynamic_accumulator::{ark_serde, DynamicAccumulator, IntersectionProof, UnionProof},
+    polymath,
+    prepared::PreparedPublicParams,
+    trapdoor, Fr, G1Affine, G2Affine, G1_POWER,
+};
+use crate::digest::Digest;
+use anyhow::{anyhow, Result};
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::One;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How counts of the same element in the two multisets combine in their union.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum UnionPolicy {
+    /// max(a, b) occurrences.
+    MaxCount,
+    /// a + b occurrences.
+    SumCount,
+}
+
+impl UnionPolicy {
+    fn count(self, a: usize, b: usize) -> usize {
+        match self {
+            UnionPolicy::MaxCount => a.max(b),
+            UnionPolicy::SumCount => a + b,
+        }
+    }
+}
+
+/// A proof that an accumulator is the union of two multisets under a [`UnionPolicy`].
+// The sum-count variant is a single point, the epochs and the params id, not worth a box of its own.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum MultisetUnionProof {
+    MaxCount(Box<UnionProof>),
+    SumCount {
+        /// g2^P_B(s) for the second multiset.
+        #[serde(with = "ark_serde")]
+        other_g2: G2Affine,
+        /// The epochs of the two accumulator states the proof was generated for.
+        epochs: (u64, u64),
+        /// The identifier of the public parameters the proof was generated under.
+        params_id: Digest,
+    },
+}
+
+impl MultisetUnionProof {
+    /// The policy the proof attests.
+    pub fn policy(&self) -> UnionPolicy {
+        match self {
+            MultisetUnionProof::MaxCount(_) => UnionPolicy::MaxCount,
+            MultisetUnionProof::SumCount { .. } => UnionPolicy::SumCount,
+        }
+    }
+
+    /// The epochs of the two accumulator states the proof was generated for.
+    pub fn epochs(&self) -> (u64, u64) {
+        match self {
+            MultisetUnionProof::MaxCount(proof) => proof.epochs(),
+            MultisetUnionProof::SumCount { epochs, .. } => *epochs,
+        }
+    }
+
+    /// Checks that `union_value` is the union of `acc1_value` and `acc2_value` under `policy`.
+    pub fn verify(
+        &self,
+        acc1_value: G1Affine,
+        acc2_value: G1Affine,
+        union_value: G1Affine,
+        policy: UnionPolicy,
+    ) -> bool {
+        if self.policy() != policy {
+            return false;
+        }
+        match self {
+            MultisetUnionProof::MaxCount(proof) => {
+                DynamicAccumulator::verify_union(acc1_value, acc2_value, union_value, proof)
+            }
+            MultisetUnionProof::SumCount {
+                other_g2,
+                params_id: id,
+                ..
+            } => {
+                if *id != params_id() || !super::canonical::is_canonical_g2(other_g2) {
+                    return false;
+                }
+                let params = PreparedPublicParams::global();
+                let g1 = G1_POWER.apply(&Fr::one()).into_affine();
+                params.pairing_check(g1, (*other_g2).into(), -acc2_value)
+                    && params.pairing_check(acc1_value, (*other_g2).into(), -union_value)
+            }
+        }
+    }
+
+    /// Same as [`Self::verify`] against the accumulator values published at `epochs`.
+    pub fn verify_at(
+        &self,
+        acc1_value: G1Affine,
+        acc2_value: G1Affine,
+        union_value: G1Affine,
+        policy: UnionPolicy,
+        epochs: (u64, u64),
+    ) -> bool {
+        self.epochs() == epochs && self.verify(acc1_value, acc2_value, union_value, policy)
+    }
+}
+
+fn counts(acc: &DynamicAccumulator) -> HashMap<Fr, usize> {
+    let mut counts = HashMap::new();
+    for e in acc.elements_with_multiplicity() {
+        *counts.entry(e).or_insert(0) += 1;
+    }
+    counts
+}
+
+fn count_of(counts: &HashMap<Fr, usize>, e: &Fr) -> usize {
+    counts.get(e).copied().unwrap_or(0)
+}
+
+fn repeated(counts: impl IntoIterator<Item = (Fr, usize)>) -> Vec<Fr> {
+    counts
+        .into_iter()
+        .flat_map(|(e, n)| std::iter::repeat_n(e, n))
+        .collect()
+}
+
+impl DynamicAccumulator {
+    /// Computes the union of two multisets under `policy`, with a proof attesting the policy.
+    pub fn prove_multiset_union(
+        &self,
+        other: &DynamicAccumulator,
+        policy: UnionPolicy,
+    ) -> Result<(DynamicAccumulator, MultisetUnionProof)> {
+        self.ensure_elements()?;
+        other.ensure_elements()?;
+        let (a, b) = (counts(self), counts(other));
+        let mut union_counts = HashMap::new();
+        for e in a.keys().chain(b.keys()) {
+            union_counts
+                .entry(*e)
+                .or_insert_with(|| policy.count(count_of(&a, e), count_of(&b, e)));
+        }
+        let union = repeated(union_counts);
+        let union_value =
+            trapdoor::signer().mul_s_minus(G1Affine::prime_subgroup_generator(), &union)?;
+        let mut union_acc = DynamicAccumulator::new();
+        union_acc.apply_changes(&union, &[], union_value, 0)?;
+
+        let proof = match policy {
+            UnionPolicy::MaxCount => {
+                MultisetUnionProof::MaxCount(Box::new(self.max_count_proof(other, &a, &b)?))
+            }
+            UnionPolicy::SumCount => MultisetUnionProof::SumCount {
+                other_g2: commit_g2(other.characteristic_poly())?,
+                epochs: (self.epoch(), other.epoch()),
+                params_id: params_id(),
+            },
+        };
+        Ok((union_acc, proof))
+    }
+
+    /// Proves the union over the min-count intersection, whose cofactors Q_A and Q_B share no
+    /// roots and are therefore coprime.
+    fn max_count_proof(
+        &self,
+        other: &DynamicAccumulator,
+        a: &HashMap<Fr, usize>,
+        b: &HashMap<Fr, usize>,
+    ) -> Result<UnionProof> {
+        let min = |e: &Fr| count_of(a, e).min(count_of(b, e));
+        let gcd = repeated(a.keys().map(|e| (*e, min(e))));
+        let q_a = repeated(a.iter().map(|(e, n)| (*e, n - min(e))));
+        let q_b = repeated(b.iter().map(|(e, n)| (*e, n - min(e))));
+        let (q_a_poly, q_b_poly) = (polymath::from_roots(&q_a), polymath::from_roots(&q_b));
+        let (x, y) = polymath::bezout_coprime(&q_a_poly, &q_b_poly)
+            .ok_or_else(|| anyhow!("Failed to create union proof, cofactors are not coprime"))?;
+        Ok(UnionProof {
+            intersection_acc_value: commit_g1(polymath::from_roots(&gcd))?,
+            intersection_proof: IntersectionProof {
+                witness_a: commit_g2(q_a_poly)?,
+                witness_b: commit_g2(q_b_poly)?,
+                witness_coprime_a: commit_g1(x)?,
+                witness_coprime_b: commit_g1(y)?,
+                epochs: (self.epoch(), other.epoch()),
+                params_id: params_id(),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::dynamic_accumulator::DuplicatePolicy;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_multiset_union() {
+        init_logger();
+        let mut a =
+            DynamicAccumulator::with_duplicate_policy(DuplicatePolicy::IncrementMultiplicity);
+        for e in [1, 1, 2] {
+            a.add(&e).unwrap();
+        }
+        let mut b =
+            DynamicAccumulator::with_duplicate_policy(DuplicatePolicy::IncrementMultiplicity);
+        for e in [1, 2, 2, 3] {
+            b.add(&e).unwrap();
+        }
+
+        let (max, proof) = a.prove_multiset_union(&b, UnionPolicy::MaxCount).unwrap();
+        assert_eq!(proof.policy(), UnionPolicy::MaxCount);
+        assert_eq!([1, 2, 3].map(|e| max.multiplicity(&e)), [2, 2, 1]);
+        assert!(proof.verify(
+            a.acc_value,
+            b.acc_value,
+            max.acc_value,
+            UnionPolicy::MaxCount
+        ));
+        assert!(!proof.verify(
+            a.acc_value,
+            b.acc_value,
+            max.acc_value,
+            UnionPolicy::SumCount
+        ));
+
+        let (sum, proof) = a.prove_multiset_union(&b, UnionPolicy::SumCount).unwrap();
+        assert_eq!([1, 2, 3].map(|e| sum.multiplicity(&e)), [3, 3, 1]);
+        assert!(proof.verify(
+            a.acc_value,
+            b.acc_value,
+            sum.acc_value,
+            UnionPolicy::SumCount
+        ));
+        assert!(!proof.verify(
+            a.acc_value,
+            b.acc_value,
+            max.acc_value,
+            UnionPolicy::SumCount
+        ));
+        assert!(!proof.verify(
+            b.acc_value,
+            a.acc_value,
+            sum.acc_value,
+            UnionPolicy::SumCount
+        ));
+        let values = (a.acc_value, b.acc_value, sum.acc_value);
+        let policy = UnionPolicy::SumCount;
+        assert!(proof.verify_at(values.0, values.1, values.2, policy, (3, 4)));
+        assert!(!proof.verify_at(values.0, values.1, values.2, policy, (4, 3)));
+
+        // Under either policy, the union of sets is the set union.
+        let mut c = DynamicAccumulator::new();
+        c.add(&1).unwrap();
+        c.add(&4).unwrap();
+        let mut d = DynamicAccumulator::new();
+        d.add(&4).unwrap();
+        let (union, proof) = c.prove_multiset_union(&d, UnionPolicy::MaxCount).unwrap();
+        assert_eq!(union.acc_value, c.prove_union(&d).unwrap().0.acc_value);
+        assert!(proof.verify(
+            c.acc_value,
+            d.acc_value,
+            union.acc_value,
+            UnionPolicy::MaxCount
+        ));
+    }
+}
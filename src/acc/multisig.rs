@@ -0,0 +1,187 @@
+//! Update logs signed jointly by several co-managers.
+//!
+//! Each of n co-managers signs the same [`UpdateLog`] with its own BLS key. The signatures add up
+//! to a single [`Signature`] that verifies against the sum of the co-managers' keys, so a
+//! [`MultiSignedUpdateLog`] costs the same to store and check as a log signed by one manager, and
+//! is only valid if every co-manager signed (n-of-n).
+//!
+//! Adding up keys is only safe if every key is known to belong to someone holding its secret:
+//! otherwise a co-manager could register a key chosen to cancel the others out. A
+//! [`Committee`] therefore only admits keys with a [`PossessionProof`].
+
+use super::{
+    signing::{update_log_digest, Signature, SigningKey, VerifyingKey},
+    update::UpdateLog,
+    G1Projective, G2Projective,
+};
+use crate::digest::{concat_digest, Digest, Digestible};
+use anyhow::{bail, Result};
+use ark_ec::ProjectiveCurve;
+use ark_ff::Zero;
+use ark_serialize::CanonicalSerialize;
+use serde::{Deserialize, Serialize};
+
+/// A signature by a key over itself, showing that its holder knows the secret key.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PossessionProof(pub Signature);
+
+fn possession_digest(vk: &VerifyingKey) -> Digest {
+    let mut bytes = Vec::new();
+    vk.0.serialize(&mut bytes)
+        .expect("serializing into a vector cannot fail");
+    concat_digest([b"PossessionProof"[..].to_digest(), bytes.to_digest()].into_iter())
+}
+
+impl SigningKey {
+    pub fn prove_possession(&self) -> PossessionProof {
+        PossessionProof(self.sign(&possession_digest(&self.verifying_key())))
+    }
+}
+
+impl VerifyingKey {
+    pub fn verify_possession(&self, proof: &PossessionProof) -> bool {
+        self.verify(&possession_digest(self), &proof.0)
+    }
+}
+
+/// The co-managers that must all sign an update log.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Committee {
+    members: Vec<VerifyingKey>,
+    aggregate: VerifyingKey,
+}
+
+impl Committee {
+    /// Admits every key whose proof of possession verifies. Fails on an invalid proof, a
+    /// repeated key, or an empty committee.
+    pub fn new(members: &[(VerifyingKey, PossessionProof)]) -> Result<Self> {
+        if members.is_empty() {
+            bail!("A committee needs at least one member");
+        }
+        let mut keys: Vec<VerifyingKey> = Vec::with_capacity(members.len());
+        let mut aggregate = G2Projective::zero();
+        for (i, (vk, proof)) in members.iter().enumerate() {
+            if !vk.verify_possession(proof) {
+                bail!("Invalid proof of possession for member {}", i);
+            }
+            if keys.contains(vk) {
+                bail!("Member {} is already in the committee", i);
+            }
+            keys.push(*vk);
+            aggregate.add_assign_mixed(&vk.0);
+        }
+        Ok(Self {
+            members: keys,
+            aggregate: VerifyingKey(aggregate.into_affine()),
+        })
+    }
+
+    pub fn members(&self) -> &[VerifyingKey] {
+        &self.members
+    }
+
+    /// The sum of the members' keys, which the aggregate signatures verify against.
+    pub fn aggregate_key(&self) -> VerifyingKey {
+        self.aggregate
+    }
+
+    /// Checks one member's signature on `log`, e.g. to find who signed wrongly.
+    pub fn verify_partial(&self, member: usize, log: &UpdateLog, signature: &Signature) -> bool {
+        self.members
+            .get(member)
+            .is_some_and(|vk| vk.verify(&update_log_digest(log), signature))
+    }
+}
+
+/// An update log with the aggregate signature of a whole committee.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MultiSignedUpdateLog {
+    pub log: UpdateLog,
+    pub signature: Signature,
+}
+
+impl MultiSignedUpdateLog {
+    /// One co-manager's signature on `log`, to be combined with [`Self::aggregate`]. It is the
+    /// same signature as in a [`SignedUpdateLog`](super::signing::SignedUpdateLog).
+    pub fn sign_partial(log: &UpdateLog, key: &SigningKey) -> Signature {
+        key.sign(&update_log_digest(log))
+    }
+
+    /// Adds up the co-managers' signatures, in any order.
+    pub fn aggregate(log: UpdateLog, partials: &[Signature]) -> Self {
+        let signature = partials
+            .iter()
+            .fold(G1Projective::zero(), |mut sum, s| {
+                sum.add_assign_mixed(&s.0);
+                sum
+            })
+            .into_affine();
+        Self {
+            log,
+            signature: Signature(signature),
+        }
+    }
+
+    /// Checks that every member of `committee` signed the log. The proofs inside the log are
+    /// verified separately, e.g. by [`UpdateLog::verify_chain`].
+    pub fn verify(&self, committee: &Committee) -> bool {
+        committee
+            .aggregate
+            .verify(&update_log_digest(&self.log), &self.signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::dynamic_accumulator::DynamicAccumulator;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_multi_signed_update_log() {
+        init_logger();
+        let keys: Vec<SigningKey> = [&b"a"[..], b"b", b"c"]
+            .iter()
+            .map(|seed| SigningKey::from_seed(seed))
+            .collect();
+        let members: Vec<_> = keys
+            .iter()
+            .map(|k| (k.verifying_key(), k.prove_possession()))
+            .collect();
+        let committee = Committee::new(&members).unwrap();
+
+        let mut acc = DynamicAccumulator::new();
+        let mut log = UpdateLog::new(0);
+        log.push(acc.add(&1).unwrap());
+        let partials: Vec<Signature> = keys
+            .iter()
+            .map(|k| MultiSignedUpdateLog::sign_partial(&log, k))
+            .collect();
+        assert!(committee.verify_partial(1, &log, &partials[1]));
+        assert!(!committee.verify_partial(0, &log, &partials[1]));
+
+        let signed = MultiSignedUpdateLog::aggregate(log.clone(), &partials);
+        assert!(signed.verify(&committee));
+        let reversed: Vec<_> = partials.iter().rev().copied().collect();
+        assert_eq!(
+            MultiSignedUpdateLog::aggregate(log.clone(), &reversed),
+            signed
+        );
+
+        // n-of-n: a missing signature fails.
+        assert!(!MultiSignedUpdateLog::aggregate(log.clone(), &partials[..2]).verify(&committee));
+        let mut tampered = signed;
+        tampered.log.start_epoch = 1;
+        assert!(!tampered.verify(&committee));
+
+        // A rogue key without a proof of possession is not admitted.
+        let mut rogue = members.clone();
+        rogue[2].1 = members[1].1;
+        assert!(Committee::new(&rogue).is_err());
+        assert!(Committee::new(&[members[0], members[0]]).is_err());
+        assert!(Committee::new(&[]).is_err());
+    }
+}
@@ -0,0 +1,192 @@
+//! Disjoint element domains in one accumulator.
+//!
+//! The `*_in` and `*_in_ns` methods hash an element together with a namespace name, e.g.
+//! "users", "devices" or "sessions", using the one-component [`ElementCodec`] schema
+//! `(element)` under the namespace as domain. The same value in two namespaces, or in a
+//! namespace and plain [`DynamicAccumulator::add`], therefore maps to different field elements,
+//! and one accumulator holds all of them without collisions. Proofs come wrapped in
+//! [`InNamespace`], which names the namespace so a verifier can check the proof is for the
+//! element it asked about.
+//!
+//! Every namespace also has a marker element, the empty key under the namespace as domain,
+//! accumulated once for each element of the namespace: [`DynamicAccumulator::add_in`] and
+//! [`DynamicAccumulator::delete_in`] change the element and the marker together. The
+//! characteristic polynomial thus has the factor (X - marker) exactly as often as the namespace
+//! has elements, and [`DynamicAccumulator::prove_namespace_empty`] proves the namespace empty
+//! with a non-membership proof for the marker. This covers the elements added through
+//! `add_in`; the marker says nothing about values accumulated by other means.
+
+use super::{
+    codec::{ElementCodec, KeyPart},
+    dynamic_accumulator::{
+        AddProof, DeleteProof, DynamicAccumulator, MembershipProof, NonMembershipProof,
+    },
+    handle::ElementHandle,
+    Fr, G1Affine,
+};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+/// The field element accumulated for `element` in namespace `ns`.
+pub fn element_in(ns: &str, element: &i64) -> Fr {
+    ElementCodec::new(ns)
+        .component("element")
+        .encode(&[KeyPart::Int(*element)])
+        .unwrap_or_else(|_| unreachable!("the schema has one component"))
+}
+
+/// The marker element of namespace `ns`, accumulated once per element of the namespace.
+pub fn marker_of(ns: &str) -> Fr {
+    ElementCodec::new(ns)
+        .encode(&[])
+        .unwrap_or_else(|_| unreachable!("the schema has no components"))
+}
+
+/// A proof about an element of a namespace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InNamespace<P> {
+    pub namespace: String,
+    pub proof: P,
+}
+
+impl InNamespace<MembershipProof> {
+    /// Checks that `element` is in the namespace of the proof.
+    pub fn verify(&self, accumulator: G1Affine, element: &i64) -> bool {
+        self.proof.element == ElementHandle(element_in(&self.namespace, element))
+            && self.proof.verify(accumulator)
+    }
+}
+
+impl InNamespace<NonMembershipProof> {
+    /// Checks that `element` is not in the namespace of the proof.
+    pub fn verify(&self, accumulator: G1Affine, element: &i64) -> bool {
+        self.proof.element == ElementHandle(element_in(&self.namespace, element))
+            && self.proof.verify(accumulator)
+    }
+
+    /// Checks a proof from [`DynamicAccumulator::prove_namespace_empty`].
+    pub fn verify_empty(&self, accumulator: G1Affine) -> bool {
+        self.proof.element == ElementHandle(marker_of(&self.namespace))
+            && self.proof.verify(accumulator)
+    }
+}
+
+impl DynamicAccumulator {
+    /// Adds `element` to namespace `ns`, then one occurrence of the namespace marker. Under
+    /// [`DuplicatePolicy::Ignore`](super::dynamic_accumulator::DuplicatePolicy::Ignore), adding
+    /// a present element returns two no-op proofs.
+    pub fn add_in(&mut self, ns: &str, element: &i64) -> Result<(AddProof, AddProof)> {
+        let proof = self.add_fr(element_in(ns, element))?;
+        if proof.is_noop() {
+            let marker = AddProof {
+                element: ElementHandle(marker_of(ns)),
+                ..proof.clone()
+            };
+            return Ok((proof, marker));
+        }
+        Ok((proof, self.add_occurrence_fr(marker_of(ns))?))
+    }
+
+    /// Deletes `element` from namespace `ns`, then one occurrence of the namespace marker.
+    pub fn delete_in(&mut self, ns: &str, element: &i64) -> Result<(DeleteProof, DeleteProof)> {
+        let proof = self.delete_fr(element_in(ns, element))?;
+        Ok((proof, self.delete_fr(marker_of(ns))?))
+    }
+
+    pub fn contains_in(&self, ns: &str, element: &i64) -> bool {
+        self.elements.has(&element_in(ns, element))
+    }
+
+    pub fn prove_membership_in_ns(
+        &self,
+        ns: &str,
+        element: &i64,
+    ) -> Result<InNamespace<MembershipProof>> {
+        Ok(InNamespace {
+            namespace: ns.to_owned(),
+            proof: self.prove_membership_fr(element_in(ns, element))?,
+        })
+    }
+
+    pub fn prove_non_membership_in_ns(
+        &self,
+        ns: &str,
+        element: &i64,
+    ) -> Result<InNamespace<NonMembershipProof>> {
+        Ok(InNamespace {
+            namespace: ns.to_owned(),
+            proof: self.prove_non_membership_fr(element_in(ns, element))?,
+        })
+    }
+
+    /// Proves that namespace `ns` has no elements.
+    pub fn prove_namespace_empty(&self, ns: &str) -> Result<InNamespace<NonMembershipProof>> {
+        if self.elements.contains(&marker_of(ns))? {
+            bail!("Namespace {} is not empty", ns);
+        }
+        Ok(InNamespace {
+            namespace: ns.to_owned(),
+            proof: self.prove_non_membership_fr(marker_of(ns))?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_namespaces() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        acc.add_in("users", &7).unwrap();
+        acc.add_in("devices", &7).unwrap();
+        acc.add(&7).unwrap();
+        assert!(acc.contains_in("users", &7) && acc.contains_in("devices", &7));
+        assert!(!acc.contains_in("sessions", &7));
+
+        let proof = acc.prove_membership_in_ns("users", &7).unwrap();
+        assert!(proof.verify(acc.acc_value, &7));
+        assert!(!proof.verify(acc.acc_value, &8));
+        let mut relabeled = proof.clone();
+        relabeled.namespace = "devices".to_owned();
+        assert!(!relabeled.verify(acc.acc_value, &7));
+
+        acc.delete_in("users", &7).unwrap();
+        assert!(acc.contains_in("devices", &7));
+        let proof = acc.prove_non_membership_in_ns("users", &7).unwrap();
+        assert!(proof.verify(acc.acc_value, &7));
+        assert!(acc.prove_non_membership_in_ns("devices", &7).is_err());
+    }
+
+    #[test]
+    fn test_namespace_empty() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        let proof = acc.prove_namespace_empty("devices").unwrap();
+        assert!(proof.verify_empty(acc.acc_value));
+
+        acc.add_in("devices", &1).unwrap();
+        let (add, marker) = acc.add_in("devices", &2).unwrap();
+        assert!(add.verify() && marker.verify());
+        acc.add_in("users", &1).unwrap();
+        assert!(acc.prove_namespace_empty("devices").is_err());
+        assert!(!proof.verify_empty(acc.acc_value));
+
+        acc.delete_in("devices", &1).unwrap();
+        assert!(acc.prove_namespace_empty("devices").is_err());
+        acc.delete_in("devices", &2).unwrap();
+        let proof = acc.prove_namespace_empty("devices").unwrap();
+        assert!(proof.verify_empty(acc.acc_value));
+        assert!(acc.prove_namespace_empty("users").is_err());
+
+        // A proof for one namespace does not vouch for another.
+        let mut relabeled = proof;
+        relabeled.namespace = "users".to_owned();
+        assert!(!relabeled.verify_empty(acc.acc_value));
+    }
+}
@@ -0,0 +1,378 @@
+//! Pluggable engines for multi-scalar multiplication and bulk fixed-base exponentiation.
+//!
+//! The MSMs of [`Acc1`](super::Acc1), [`Acc2`](super::Acc2) and
+//! [`WitnessStore`](super::witness_store::WitnessStore), and the fixed-base exponentiations of
+//! [`WitnessStore::refresh_all`], go through the engine
+//! installed with [`set_engine`]. An engine wraps an accelerator such as a GPU MSM kernel; it may
+//! decline any call, e.g. below the size at which offloading pays off or when the device is
+//! busy, by returning `None`, and the work then falls back to the CPU implementations of
+//! arkworks. Without an installed engine everything runs on the CPU.
+//!
+//! With the `gpu` feature, `GpuEngine` runs the MSMs on every OpenCL device found at runtime
+//! with the multiexp kernels of ec-gpu-gen, which the build script generates for BLS12-381.
+//! Install it with `set_engine(Arc::new(GpuEngine::new()?))`; `new` fails on machines without
+//! a working device, and the installed engine leaves MSMs below `GpuEngine::min_len` points,
+//! and any that fail on the device, to the CPU.
+//!
+//! [`WitnessStore::refresh_all`]: super::witness_store::WitnessStore::refresh_all
+
+use super::{Fr, G1Affine, G1Projective, G2Affine, G2Projective, G1_POWER};
+use ark_ec::{msm::VariableBaseMSM, ProjectiveCurve};
+use ark_ff::PrimeField;
+use rayon::prelude::*;
+use std::sync::{Arc, RwLock};
+use tracing::debug;
+#[cfg(feature = "gpu")]
+use {
+    ark_ec::AffineCurve,
+    ec_gpu_gen::{
+        multiexp::MultiexpKernel,
+        rust_gpu_tools::{opencl, Device, GPUError, Program},
+        threadpool::Worker,
+    },
+    std::sync::Mutex,
+};
+
+type BigInt = <Fr as PrimeField>::BigInt;
+
+/// An accelerator for the bulk group operations.
+pub trait MsmEngine: Send + Sync {
+    /// A name for logs.
+    fn name(&self) -> &str;
+
+    /// Returns the sum of `scalars[i] * bases[i]`, or `None` to fall back to the CPU.
+    fn msm_g1(&self, bases: &[G1Affine], scalars: &[BigInt]) -> Option<G1Projective>;
+
+    /// Returns the sum of `scalars[i] * bases[i]`, or `None` to fall back to the CPU.
+    fn msm_g2(&self, bases: &[G2Affine], scalars: &[BigInt]) -> Option<G2Projective>;
+
+    /// Returns `g1^scalars[i]` for every scalar, or `None` to fall back to the CPU.
+    fn fixed_base_g1(&self, _scalars: &[Fr]) -> Option<Vec<G1Affine>> {
+        None
+    }
+}
+
+lazy_static! {
+    static ref ENGINE: RwLock<Option<Arc<dyn MsmEngine>>> = RwLock::new(None);
+}
+
+/// Installs the engine used by all later bulk operations, replacing any previous one.
+pub fn set_engine(engine: Arc<dyn MsmEngine>) {
+    *ENGINE.write().unwrap_or_else(|e| e.into_inner()) = Some(engine);
+}
+
+/// Removes the installed engine, so that everything runs on the CPU again.
+pub fn clear_engine() -> Option<Arc<dyn MsmEngine>> {
+    ENGINE.write().unwrap_or_else(|e| e.into_inner()).take()
+}
+
+/// The installed engine, if any.
+pub fn engine() -> Option<Arc<dyn MsmEngine>> {
+    ENGINE.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+fn offload<T>(op: &str, len: usize, run: impl FnOnce(&dyn MsmEngine) -> Option<T>) -> Option<T> {
+    let engine = engine()?;
+    let result = run(engine.as_ref());
+    if result.is_none() {
+        debug!("{} declined {} of size {}", engine.name(), op, len);
+    }
+    result
+}
+
+/// Computes the sum of `scalars[i] * bases[i]` in G1.
+pub fn msm_g1(bases: &[G1Affine], scalars: &[BigInt]) -> G1Projective {
+    offload("msm_g1", bases.len(), |e| e.msm_g1(bases, scalars))
+        .unwrap_or_else(|| VariableBaseMSM::multi_scalar_mul(bases, scalars))
+}
+
+/// Computes the sum of `scalars[i] * bases[i]` in G2.
+pub fn msm_g2(bases: &[G2Affine], scalars: &[BigInt]) -> G2Projective {
+    offload("msm_g2", bases.len(), |e| e.msm_g2(bases, scalars))
+        .unwrap_or_else(|| VariableBaseMSM::multi_scalar_mul(bases, scalars))
+}
+
+/// Computes `g1^scalar` for every scalar.
+pub fn fixed_base_g1(scalars: &[Fr]) -> Vec<G1Affine> {
+    offload("fixed_base_g1", scalars.len(), |e| e.fixed_base_g1(scalars))
+        .filter(|points| points.len() == scalars.len())
+        .unwrap_or_else(|| {
+            scalars
+                .par_iter()
+                .map(|t| G1_POWER.apply(t).into_affine())
+                .collect()
+        })
+}
+
+/// The ec-gpu-gen multiexp kernels on every OpenCL device, with the `gpu` feature.
+#[cfg(feature = "gpu")]
+pub struct GpuEngine {
+    g1: Mutex<MultiexpKernel<'static, blstrs::G1Affine>>,
+    g2: Mutex<MultiexpKernel<'static, blstrs::G2Affine>>,
+    min_len: usize,
+}
+
+#[cfg(feature = "gpu")]
+impl GpuEngine {
+    /// The default [`min_len`](Self::min_len). Smaller MSMs are faster on the CPU than the
+    /// transfer to the device.
+    pub const DEFAULT_MIN_LEN: usize = 1 << 12;
+
+    /// Compiles the kernels for every OpenCL device. Fails if there is no working device.
+    pub fn new() -> anyhow::Result<Self> {
+        let devices = Device::all();
+        anyhow::ensure!(!devices.is_empty(), "no GPU device found");
+        let programs = || {
+            devices
+                .iter()
+                .map(|device| {
+                    let device = device.opencl_device().ok_or(GPUError::DeviceNotFound)?;
+                    let source = include_str!(env!("_EC_GPU_OPENCL_KERNEL_SOURCE"));
+                    opencl::Program::from_opencl(device, source).map(Program::Opencl)
+                })
+                .collect::<Result<Vec<_>, GPUError>>()
+        };
+        Ok(Self {
+            g1: Mutex::new(MultiexpKernel::create(programs()?, &devices)?),
+            g2: Mutex::new(MultiexpKernel::create(programs()?, &devices)?),
+            min_len: Self::DEFAULT_MIN_LEN,
+        })
+    }
+
+    /// Sends only MSMs of at least `min_len` points to the device.
+    pub fn with_min_len(mut self, min_len: usize) -> Self {
+        self.min_len = min_len;
+        self
+    }
+
+    /// The size below which MSMs are declined.
+    pub fn min_len(&self) -> usize {
+        self.min_len
+    }
+
+    /// Converts the inputs of an MSM for the device, or returns `None` if it is too small.
+    #[allow(clippy::type_complexity)]
+    fn inputs<A: Sync, B: Send>(
+        &self,
+        bases: &[A],
+        scalars: &[BigInt],
+        convert: impl Fn(&A) -> B + Sync + Send,
+    ) -> Option<(Arc<Vec<B>>, Arc<Vec<[u8; 32]>>)> {
+        let len = bases.len().min(scalars.len());
+        if len < self.min_len {
+            return None;
+        }
+        let bases = bases[..len].par_iter().map(convert).collect();
+        let exps = scalars[..len].par_iter().map(blstrs_conv::scalar).collect();
+        Some((Arc::new(bases), Arc::new(exps)))
+    }
+}
+
+/// The result of a kernel, or `None` if the device failed.
+#[cfg(feature = "gpu")]
+fn on_device<T>(result: ec_gpu_gen::EcResult<T>) -> Option<T> {
+    result
+        .map_err(|e| debug!("GPU multiexp failed: {}", e))
+        .ok()
+}
+
+#[cfg(feature = "gpu")]
+impl MsmEngine for GpuEngine {
+    fn name(&self) -> &str {
+        "gpu"
+    }
+
+    fn msm_g1(&self, bases: &[G1Affine], scalars: &[BigInt]) -> Option<G1Projective> {
+        let (bases, exps) = self.inputs(bases, scalars, blstrs_conv::g1)?;
+        let mut kernel = self.g1.try_lock().ok()?;
+        let sum = on_device(kernel.multiexp(&Worker::new(), bases, exps, 0))?;
+        Some(blstrs_conv::from_g1(&sum.into()).into_projective())
+    }
+
+    fn msm_g2(&self, bases: &[G2Affine], scalars: &[BigInt]) -> Option<G2Projective> {
+        let (bases, exps) = self.inputs(bases, scalars, blstrs_conv::g2)?;
+        let mut kernel = self.g2.try_lock().ok()?;
+        let sum = on_device(kernel.multiexp(&Worker::new(), bases, exps, 0))?;
+        Some(blstrs_conv::from_g2(&sum.into()).into_projective())
+    }
+}
+
+/// Points and scalars in the uncompressed big-endian and little-endian encodings blstrs reads.
+#[cfg(feature = "gpu")]
+mod blstrs_conv {
+    use super::{BigInt, G1Affine, G2Affine};
+    use ark_bls12_381::{Fq, Fq2};
+    use ark_ff::{biginteger::BigInteger384, BigInteger, PrimeField, Zero};
+    use std::convert::TryInto;
+
+    const INFINITY: u8 = 0x40;
+
+    fn write(bytes: &mut [u8], coordinates: &[&Fq]) {
+        if coordinates.is_empty() {
+            bytes[0] = INFINITY;
+        }
+        for (chunk, x) in bytes.chunks_mut(48).zip(coordinates) {
+            chunk.copy_from_slice(&x.into_repr().to_bytes_be());
+        }
+    }
+
+    fn read(bytes: &[u8]) -> Fq {
+        let mut limbs = [0u64; 6];
+        for (limb, chunk) in limbs.iter_mut().rev().zip(bytes.chunks(8)) {
+            *limb = u64::from_be_bytes(chunk.try_into().expect("8 bytes"));
+        }
+        Fq::from_repr(BigInteger384(limbs)).expect("a canonical coordinate")
+    }
+
+    pub fn scalar(k: &BigInt) -> [u8; 32] {
+        k.to_bytes_le().try_into().expect("32 bytes")
+    }
+
+    pub fn g1(p: &G1Affine) -> blstrs::G1Affine {
+        let mut bytes = [0u8; 96];
+        if p.infinity {
+            write(&mut bytes, &[]);
+        } else {
+            write(&mut bytes, &[&p.x, &p.y]);
+        }
+        blstrs::G1Affine::from_uncompressed_unchecked(&bytes).expect("a point on the curve")
+    }
+
+    pub fn g2(q: &G2Affine) -> blstrs::G2Affine {
+        let mut bytes = [0u8; 192];
+        if q.infinity {
+            write(&mut bytes, &[]);
+        } else {
+            write(&mut bytes, &[&q.x.c1, &q.x.c0, &q.y.c1, &q.y.c0]);
+        }
+        blstrs::G2Affine::from_uncompressed_unchecked(&bytes).expect("a point on the curve")
+    }
+
+    pub fn from_g1(p: &blstrs::G1Affine) -> G1Affine {
+        let bytes = p.to_uncompressed();
+        if bytes[0] & INFINITY != 0 {
+            return G1Affine::zero();
+        }
+        G1Affine::new(read(&bytes[..48]), read(&bytes[48..]), false)
+    }
+
+    pub fn from_g2(q: &blstrs::G2Affine) -> G2Affine {
+        let bytes = q.to_uncompressed();
+        if bytes[0] & INFINITY != 0 {
+            return G2Affine::zero();
+        }
+        let x = Fq2::new(read(&bytes[48..96]), read(&bytes[..48]));
+        let y = Fq2::new(read(&bytes[144..]), read(&bytes[96..144]));
+        G2Affine::new(x, y, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ec::AffineCurve;
+    #[cfg(feature = "gpu")]
+    use ark_ff::{UniformRand, Zero};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    /// Serves and counts G1 MSMs, and declines the rest. Other tests may run while it is
+    /// installed, so its results must be correct.
+    struct Fake(AtomicUsize);
+
+    impl MsmEngine for Fake {
+        fn name(&self) -> &str {
+            "fake"
+        }
+
+        fn msm_g1(&self, bases: &[G1Affine], scalars: &[BigInt]) -> Option<G1Projective> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Some(VariableBaseMSM::multi_scalar_mul(bases, scalars))
+        }
+
+        fn msm_g2(&self, _: &[G2Affine], _: &[BigInt]) -> Option<G2Projective> {
+            None
+        }
+    }
+
+    #[test]
+    fn test_engine_fallback() {
+        init_logger();
+        let g1 = G1Affine::prime_subgroup_generator();
+        let g2 = G2Affine::prime_subgroup_generator();
+        let scalars = [Fr::from(2u64).into_repr(), Fr::from(3u64).into_repr()];
+        let expected_g1 = g1.mul(Fr::from(5u64).into_repr());
+        let expected_g2 = g2.mul(Fr::from(5u64).into_repr());
+
+        let fake = Arc::new(Fake(AtomicUsize::new(0)));
+        set_engine(fake.clone());
+        assert_eq!(msm_g1(&[g1, g1], &scalars), expected_g1);
+        assert_eq!(msm_g2(&[g2, g2], &scalars), expected_g2);
+        assert_eq!(
+            fixed_base_g1(&[Fr::from(5u64)]),
+            [expected_g1.into_affine()]
+        );
+        assert!(fake.0.load(Ordering::SeqCst) >= 1);
+
+        assert!(clear_engine().is_some());
+        assert!(engine().is_none());
+        assert_eq!(msm_g1(&[g1, g1], &scalars), expected_g1);
+    }
+
+    #[cfg(feature = "gpu")]
+    #[test]
+    fn test_blstrs_conversion() {
+        let mut rng = rand::thread_rng();
+        let k = Fr::rand(&mut rng).into_repr();
+        let p = G1Projective::rand(&mut rng).into_affine();
+        let q = G2Projective::rand(&mut rng).into_affine();
+        let s = blstrs::Scalar::from_bytes_le(&blstrs_conv::scalar(&k)).unwrap();
+
+        for p in [p, G1Affine::zero()] {
+            assert_eq!(blstrs_conv::from_g1(&blstrs_conv::g1(&p)), p);
+            let product = blstrs::G1Affine::from(blstrs_conv::g1(&p) * s);
+            assert_eq!(blstrs_conv::from_g1(&product), p.mul(k).into_affine());
+        }
+        for q in [q, G2Affine::zero()] {
+            assert_eq!(blstrs_conv::from_g2(&blstrs_conv::g2(&q)), q);
+            let product = blstrs::G2Affine::from(blstrs_conv::g2(&q) * s);
+            assert_eq!(blstrs_conv::from_g2(&product), q.mul(k).into_affine());
+        }
+    }
+
+    /// Checks the kernels against the CPU where there is a device, and that `new` fails
+    /// cleanly where there is none.
+    #[cfg(feature = "gpu")]
+    #[test]
+    fn test_gpu_engine() {
+        init_logger();
+        let gpu = match GpuEngine::new() {
+            Ok(gpu) => gpu.with_min_len(2),
+            Err(e) => {
+                debug!("skipping the kernels: {}", e);
+                return;
+            }
+        };
+        let mut rng = rand::thread_rng();
+        let scalars: Vec<_> = (0..64).map(|_| Fr::rand(&mut rng).into_repr()).collect();
+        let g1: Vec<_> = (0..64)
+            .map(|_| G1Projective::rand(&mut rng).into_affine())
+            .collect();
+        let g2: Vec<_> = (0..64)
+            .map(|_| G2Projective::rand(&mut rng).into_affine())
+            .collect();
+
+        assert_eq!(
+            gpu.msm_g1(&g1, &scalars),
+            Some(VariableBaseMSM::multi_scalar_mul(&g1, &scalars))
+        );
+        assert_eq!(
+            gpu.msm_g2(&g2, &scalars),
+            Some(VariableBaseMSM::multi_scalar_mul(&g2, &scalars))
+        );
+        assert_eq!(gpu.msm_g1(&g1[..1], &scalars[..1]), None);
+    }
+}
@@ -0,0 +1,206 @@
+//! Ordering proofs (min / max / k-th smallest) over accumulators of numeric elements.
+//!
+//! The accumulator itself is unordered, so ordering is proven against a bounded domain:
+//! every candidate value between the domain bound and the claimed answer is covered by
+//! either a membership or a non-membership proof. The verifier recomputes the digest of
+//! each candidate, so the prover cannot skip or reorder values.
+
+use super::{
+    dynamic_accumulator::{DynamicAccumulator, QueryResult},
+    handle::ElementHandle,
+    utils::digest_to_prime_field,
+    Fr, G1Affine,
+};
+use crate::digest::Digestible;
+use anyhow::{anyhow, bail, Result};
+use core::ops::RangeInclusive;
+
+/// The direction in which candidates of the domain are scanned.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum OrderDirection {
+    /// Scan upwards from the lower bound of the domain (min / k-th smallest).
+    Ascending,
+    /// Scan downwards from the upper bound of the domain (max / k-th largest).
+    Descending,
+}
+
+/// A proof that `value` is the `rank`-th element of the accumulated set, counted from
+/// `bound` in the given direction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderProof {
+    pub direction: OrderDirection,
+    /// The domain bound the scan starts from (inclusive).
+    pub bound: i64,
+    /// The claimed element.
+    pub value: i64,
+    /// The claimed 1-based rank of `value`.
+    pub rank: usize,
+    /// One proof per candidate in `bound..=value` (or `value..=bound` when descending),
+    /// in scan order.
+    pub steps: Vec<QueryResult>,
+}
+
+impl OrderProof {
+    /// Verifies the ordering proof against the given accumulator value.
+    ///
+    /// Checks that every candidate between `bound` and `value` is covered in order, that
+    /// exactly `rank` of them are members and that the last one (`value`) is a member.
+    pub fn verify(&self, accumulator: G1Affine) -> bool {
+        if self.rank == 0 {
+            return false;
+        }
+        let expected_steps = match self.direction {
+            OrderDirection::Ascending => self.value.checked_sub(self.bound),
+            OrderDirection::Descending => self.bound.checked_sub(self.value),
+        };
+        match expected_steps {
+            Some(n) if n >= 0 && (n as u64).checked_add(1) == Some(self.steps.len() as u64) => {}
+            _ => return false,
+        }
+
+        let mut members = 0usize;
+        for (i, step) in self.steps.iter().enumerate() {
+            let candidate = match self.direction {
+                OrderDirection::Ascending => self.bound + i as i64,
+                OrderDirection::Descending => self.bound - i as i64,
+            };
+            let fr_candidate: Fr = digest_to_prime_field(&candidate.to_digest());
+            let ok = match step {
+                QueryResult::Membership(proof) => {
+                    members += 1;
+                    proof.element == ElementHandle(fr_candidate) && proof.verify(accumulator)
+                }
+                QueryResult::NonMembership(proof) => {
+                    proof.element == ElementHandle(fr_candidate) && proof.verify(accumulator)
+                }
+            };
+            if !ok {
+                return false;
+            }
+        }
+
+        members == self.rank && matches!(self.steps.last(), Some(QueryResult::Membership(_)))
+    }
+}
+
+impl DynamicAccumulator {
+    /// Proves that the returned value is the smallest element within `domain`.
+    pub fn prove_min(&self, domain: RangeInclusive<i64>) -> Result<OrderProof> {
+        self.prove_kth_smallest(domain, 1)
+    }
+
+    /// Proves that the returned value is the largest element within `domain`.
+    pub fn prove_max(&self, domain: RangeInclusive<i64>) -> Result<OrderProof> {
+        self.prove_kth(domain, 1, OrderDirection::Descending)
+    }
+
+    /// Proves that the returned value is the k-th smallest (1-based) element within `domain`.
+    pub fn prove_kth_smallest(&self, domain: RangeInclusive<i64>, k: usize) -> Result<OrderProof> {
+        self.prove_kth(domain, k, OrderDirection::Ascending)
+    }
+
+    fn prove_kth(
+        &self,
+        domain: RangeInclusive<i64>,
+        k: usize,
+        direction: OrderDirection,
+    ) -> Result<OrderProof> {
+        self.ensure_elements()?;
+        if k == 0 {
+            bail!("rank must be at least 1");
+        }
+        if domain.is_empty() {
+            bail!("empty domain");
+        }
+        let (lo, hi) = domain.into_inner();
+        let candidates: Box<dyn Iterator<Item = i64>> = match direction {
+            OrderDirection::Ascending => Box::new(lo..=hi),
+            OrderDirection::Descending => Box::new((lo..=hi).rev()),
+        };
+        let bound = match direction {
+            OrderDirection::Ascending => lo,
+            OrderDirection::Descending => hi,
+        };
+
+        // P(X) only depends on the element set, so build it once for all gap proofs.
+        let p_poly = self.characteristic_poly();
+        let mut steps = Vec::new();
+        let mut members = 0usize;
+        for candidate in candidates {
+            let fr_candidate: Fr = digest_to_prime_field(&candidate.to_digest());
+            if self.elements.contains(&fr_candidate)? {
+                steps.push(QueryResult::Membership(self.prove_membership(&candidate)?));
+                members += 1;
+                if members == k {
+                    return Ok(OrderProof {
+                        direction,
+                        bound,
+                        value: candidate,
+                        rank: k,
+                        steps,
+                    });
+                }
+            } else {
+                steps.push(QueryResult::NonMembership(
+                    self.non_membership_from_poly(&p_poly, fr_candidate)?,
+                ));
+            }
+        }
+
+        Err(anyhow!("fewer than {} elements within the domain", k))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    fn sample_acc() -> DynamicAccumulator {
+        let mut acc = DynamicAccumulator::new();
+        acc.add_batch(&[13, 10, 17, 15]).unwrap();
+        acc
+    }
+
+    #[test]
+    fn test_min_max() {
+        init_logger();
+        let acc = sample_acc();
+
+        let min = acc.prove_min(8..=20).unwrap();
+        assert_eq!(min.value, 10);
+        assert_eq!(min.steps.len(), 3);
+        assert!(min.verify(acc.acc_value));
+
+        let max = acc.prove_max(8..=20).unwrap();
+        assert_eq!(max.value, 17);
+        assert!(max.verify(acc.acc_value));
+
+        assert!(acc.prove_min(18..=20).is_err());
+    }
+
+    #[test]
+    fn test_kth_smallest() {
+        init_logger();
+        let acc = sample_acc();
+
+        let proof = acc.prove_kth_smallest(10..=20, 3).unwrap();
+        assert_eq!(proof.value, 15);
+        assert!(proof.verify(acc.acc_value));
+
+        // Claiming a different rank or dropping a gap proof must fail.
+        let mut wrong_rank = proof.clone();
+        wrong_rank.rank = 2;
+        assert!(!wrong_rank.verify(acc.acc_value));
+
+        let mut skipped = proof.clone();
+        skipped.steps.remove(1);
+        skipped.value -= 1;
+        assert!(!skipped.verify(acc.acc_value));
+
+        assert!(acc.prove_kth_smallest(10..=20, 5).is_err());
+    }
+}
@@ -0,0 +1,97 @@
+//! Ordered iteration and pagination over the accumulated elements.
+//!
+//! Elements are listed in ascending order of their field representation, so pages stay stable
+//! while the set is unchanged. An index that keeps its elements ordered (see
+//! [`BTreeIndex`](super::index::BTreeIndex)) is listed without sorting. Pages of an
+//! accumulator that retains its original values carry those values too.
+
+use super::{dynamic_accumulator::DynamicAccumulator, Fr};
+use serde::{Deserialize, Serialize};
+
+/// One page of the element listing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ElementsPage {
+    #[serde(with = "super::dynamic_accumulator::ark_serde")]
+    pub elements: Vec<Fr>,
+    /// The original values of `elements`, if the accumulator retains them.
+    #[serde(default)]
+    pub values: Option<Vec<i64>>,
+    pub offset: usize,
+    /// The number of distinct elements in the accumulator.
+    pub total: usize,
+    /// The offset of the next page, if there is one.
+    pub next_offset: Option<usize>,
+}
+
+impl DynamicAccumulator {
+    /// Iterates over the distinct accumulated elements in ascending order.
+    pub fn iter_elements(&self) -> Box<dyn Iterator<Item = Fr> + '_> {
+        if self.elements.is_ordered() {
+            self.elements.iter()
+        } else {
+            let mut elements = self.elements_fr();
+            elements.sort_unstable();
+            Box::new(elements.into_iter())
+        }
+    }
+
+    /// Returns up to `limit` elements starting at position `offset` of [`Self::iter_elements`].
+    pub fn elements_page(&self, offset: usize, limit: usize) -> ElementsPage {
+        let total = self.len();
+        let elements: Vec<Fr> = self.iter_elements().skip(offset).take(limit).collect();
+        let end = offset.saturating_add(elements.len());
+        let values = elements
+            .iter()
+            .map(|e| self.value_of(e))
+            .collect::<Option<Vec<i64>>>();
+        ElementsPage {
+            elements,
+            values,
+            offset,
+            total,
+            next_offset: if end < total { Some(end) } else { None },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::index::BTreeIndex;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_elements_page() {
+        init_logger();
+        let mut hashed = DynamicAccumulator::new();
+        let mut ordered = DynamicAccumulator::with_index(BTreeIndex::default()).unwrap();
+        for acc in [&mut hashed, &mut ordered] {
+            acc.add_batch(&(0..7).collect::<Vec<i64>>()).unwrap();
+        }
+        let all: Vec<Fr> = hashed.iter_elements().collect();
+        assert_eq!(all, ordered.iter_elements().collect::<Vec<_>>());
+        assert!(all.windows(2).all(|w| w[0] < w[1]));
+
+        let mut listed = Vec::new();
+        let mut offset = Some(0);
+        while let Some(start) = offset {
+            let page = hashed.elements_page(start, 3);
+            assert_eq!(page.total, 7);
+            listed.extend(page.elements);
+            offset = page.next_offset;
+        }
+        assert_eq!(listed, all);
+        assert!(hashed.elements_page(10, 3).elements.is_empty());
+        assert!(hashed.elements_page(0, 3).values.is_none());
+
+        let mut retained = DynamicAccumulator::new().retain_values().unwrap();
+        retained.add_batch(&[10, 20]).unwrap();
+        let page = retained.elements_page(0, 5);
+        let mut values = page.values.unwrap();
+        values.sort_unstable();
+        assert_eq!(values, vec![10, 20]);
+    }
+}
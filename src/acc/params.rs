@@ -0,0 +1,191 @@
+//! Public parameters as files with integrity hashes.
+//!
+//! The published parameters are the powers g1^(s^i) and g2^(s^i). [`PublicParams::save`] writes
+//! them after a header holding a SHA-256 of the contents and the parameters' identifier, the same
+//! [`params_id`] every proof records. [`PublicParams::load`] rejects a file whose contents do not
+//! match the hash, e.g. after truncation or a flipped bit, or whose identifier is not the one of
+//! its powers. Proofs carry the identifier and their `verify` methods reject them under any other
+//! parameters; [`PublicParams::ensure_active`] checks that loaded parameters are the ones this
+//! build verifies against.
+//!
+//! The layout is the magic `ESAPARM1`, the SHA-256 of everything after it, the identifier, then
+//! the G1 and the G2 powers, each as a little-endian `u64` count followed by compressed points.
+
+use super::{
+    commitment::{params_id, params_id_of},
+    get_g1s, get_g2s,
+    prepared::PreparedPublicParams,
+    Fr, G1Affine, G2Affine,
+};
+use crate::digest::{Digest, DIGEST_LEN};
+use anyhow::{bail, Context, Result};
+use ark_ec::AffineCurve;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use rayon::prelude::*;
+use sha2::{Digest as _, Sha256};
+use std::{fs, path::Path};
+
+const MAGIC: &[u8; 8] = b"ESAPARM1";
+
+/// The powers of the trapdoor in G1 and G2, g^(s^0) to g^(s^degree).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicParams {
+    pub g1_powers: Vec<G1Affine>,
+    pub g2_powers: Vec<G2Affine>,
+}
+
+impl PublicParams {
+    /// The parameters of this build up to `degree`, which is at least 1.
+    pub fn generate(degree: usize) -> Self {
+        let powers = 0..=degree.max(1) as u64;
+        Self {
+            g1_powers: powers
+                .clone()
+                .into_par_iter()
+                .map(|i| get_g1s(Fr::from(i)))
+                .collect(),
+            g2_powers: powers
+                .into_par_iter()
+                .map(|i| get_g2s(Fr::from(i)))
+                .collect(),
+        }
+    }
+
+    /// The largest degree the parameters support.
+    pub fn degree(&self) -> usize {
+        self.g1_powers.len().min(self.g2_powers.len()) - 1
+    }
+
+    /// The identifier proofs record, see [`params_id`].
+    pub fn id(&self) -> Digest {
+        params_id_of(&self.g1_powers[1])
+    }
+
+    /// Fails unless these are the parameters proofs are verified against.
+    pub fn ensure_active(&self) -> Result<()> {
+        if self.id() != params_id() {
+            bail!("The parameters are not the ones this build verifies against");
+        }
+        let params = PreparedPublicParams::global();
+        let g1 = G1Affine::prime_subgroup_generator();
+        if self.g1_powers[0] != g1
+            || self.g2_powers[0] != G2Affine::prime_subgroup_generator()
+            || !params.pairing_check(g1, self.g2_powers[1].into(), -self.g1_powers[1])
+        {
+            bail!("The G2 powers do not match the G1 powers");
+        }
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut body = self.id().0.to_vec();
+        (self.g1_powers.len() as u64).serialize(&mut body)?;
+        for p in &self.g1_powers {
+            p.serialize(&mut body)?;
+        }
+        (self.g2_powers.len() as u64).serialize(&mut body)?;
+        for p in &self.g2_powers {
+            p.serialize(&mut body)?;
+        }
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&Sha256::digest(&body));
+        bytes.extend_from_slice(&body);
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < MAGIC.len() + 32 + DIGEST_LEN || !bytes.starts_with(MAGIC) {
+            bail!("Not a public parameters file");
+        }
+        let (hash, body) = bytes[MAGIC.len()..].split_at(32);
+        if Sha256::digest(body)[..] != hash[..] {
+            bail!("The public parameters do not match their SHA-256");
+        }
+        let (id, mut reader) = body.split_at(DIGEST_LEN);
+        let g1_powers = read_points(&mut reader)?;
+        let g2_powers = read_points(&mut reader)?;
+        if !reader.is_empty() {
+            bail!(
+                "{} trailing bytes after the public parameters",
+                reader.len()
+            );
+        }
+        if g1_powers.len() < 2 || g2_powers.len() < 2 {
+            bail!("The public parameters need at least degree 1");
+        }
+        let params = Self {
+            g1_powers,
+            g2_powers,
+        };
+        if params.id().0[..] != id[..] {
+            bail!("The recorded params-id is not the one of the powers");
+        }
+        Ok(params)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, self.to_bytes()?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::from_bytes(&bytes).with_context(|| format!("Invalid {}", path.display()))
+    }
+}
+
+fn read_points<P: CanonicalDeserialize>(reader: &mut &[u8]) -> Result<Vec<P>> {
+    let len = u64::deserialize(&mut *reader)? as usize;
+    if len > reader.len() {
+        bail!("The point count exceeds the remaining bytes");
+    }
+    (0..len)
+        .map(|_| Ok(P::deserialize(&mut *reader)?))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DynamicAccumulator;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_params_file() {
+        init_logger();
+        let params = PublicParams::generate(4);
+        assert_eq!(params.degree(), 4);
+        assert_eq!(params.id(), params_id());
+        params.ensure_active().unwrap();
+
+        let path = std::env::temp_dir().join(format!("esa_params_{}.bin", std::process::id()));
+        params.save(&path).unwrap();
+        assert_eq!(PublicParams::load(&path).unwrap(), params);
+
+        let mut bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        assert!(PublicParams::from_bytes(&bytes).is_err());
+        assert!(PublicParams::from_bytes(&bytes[..last]).is_err());
+
+        // Parameters with another trapdoor have another identifier, which proofs reject.
+        let mut other = params.clone();
+        other.g1_powers.swap(1, 2);
+        assert!(PublicParams::from_bytes(&other.to_bytes().unwrap())
+            .unwrap()
+            .ensure_active()
+            .is_err());
+        let mut acc = DynamicAccumulator::new();
+        acc.add(&1).unwrap();
+        let mut proof = acc.prove_membership(&1).unwrap();
+        assert_eq!(proof.params_id, params.id());
+        proof.params_id = other.id();
+        assert!(!proof.verify(acc.acc_value));
+    }
+}
@@ -0,0 +1,235 @@
+//! Cost model and planner for set expressions.
+//!
+//! Intersections and unions are commutative and associative, so a chain of them can be proven in
+//! any order, but not at the same cost. Every step of an [`ExprProof`](super::expr::ExprProof)
+//! proves the intersection of its operands, which builds their cofactor polynomials and Bezout
+//! coefficients in time quadratic in the sum of the operand sizes. [`SetExpr::plan`] flattens
+//! each chain and repeatedly combines its two smallest operands, so large intermediate sets are
+//! built as late as possible. Sizes of intermediate results are upper bounds: min(a, b) for an
+//! intersection, a + b for a union and a for a difference. [`SetExpr::explain`] prints the plan
+//! with the [`Cost`] of every step.
+
+use super::{
+    dynamic_accumulator::DynamicAccumulator,
+    expr::{SetExpr, SetOp},
+    stats::{G1_BYTES, G2_BYTES},
+};
+use core::{cmp::Reverse, fmt};
+
+/// Bytes of one step: the result and intersection values and the intersection proof.
+const STEP_BYTES: usize = 4 * G1_BYTES + 2 * G2_BYTES + 2 * 8;
+
+/// Estimated cost of proving and verifying an expression.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Cost {
+    /// Upper bound on the number of elements in the result.
+    pub size: usize,
+    /// Bytes of the proof, as counted by [`super::stats::Proof::size_bytes`].
+    pub proof_bytes: usize,
+    /// Pairings to verify the proof.
+    pub pairings: usize,
+    /// Field multiplications to generate the proof, up to a constant factor.
+    pub prove_work: u64,
+}
+
+impl Cost {
+    fn step(op: SetOp, a: &Cost, b: &Cost) -> Self {
+        let (size, pairings) = match op {
+            SetOp::Inter => (a.size.min(b.size), 7),
+            SetOp::Union => (a.size + b.size, 9),
+            SetOp::Diff => (a.size, 9),
+        };
+        let n = (a.size + b.size) as u64;
+        Cost {
+            size,
+            proof_bytes: a.proof_bytes + b.proof_bytes + STEP_BYTES,
+            pairings: a.pairings + b.pairings + pairings,
+            prove_work: a.prove_work + b.prove_work + n * n,
+        }
+    }
+}
+
+/// The order in which an expression is proven.
+#[derive(Debug, Clone)]
+pub enum Plan<'a> {
+    /// The leaf at this index in expression order.
+    Leaf {
+        index: usize,
+        acc: &'a DynamicAccumulator,
+    },
+    Op {
+        op: SetOp,
+        left: Box<Plan<'a>>,
+        right: Box<Plan<'a>>,
+        /// The cost of this step and the steps below it.
+        cost: Cost,
+    },
+}
+
+impl<'a> Plan<'a> {
+    fn op(op: SetOp, left: Plan<'a>, right: Plan<'a>) -> Self {
+        let cost = Cost::step(op, &left.cost(), &right.cost());
+        Plan::Op {
+            op,
+            left: Box::new(left),
+            right: Box::new(right),
+            cost,
+        }
+    }
+
+    pub fn cost(&self) -> Cost {
+        match self {
+            Plan::Leaf { acc, .. } => Cost {
+                size: acc.elements.len(),
+                ..Cost::default()
+            },
+            Plan::Op { cost, .. } => *cost,
+        }
+    }
+
+    fn fmt_at(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        let indent = 2 * depth;
+        match self {
+            Plan::Leaf { index, acc } => writeln!(
+                f,
+                "{:indent$}leaf #{} size={}",
+                "",
+                index,
+                acc.elements.len()
+            ),
+            Plan::Op {
+                op,
+                left,
+                right,
+                cost,
+            } => {
+                let name = match op {
+                    SetOp::Inter => "intersection",
+                    SetOp::Union => "union",
+                    SetOp::Diff => "difference",
+                };
+                writeln!(
+                    f,
+                    "{:indent$}{} size<={} work={} bytes={} pairings={}",
+                    "", name, cost.size, cost.prove_work, cost.proof_bytes, cost.pairings
+                )?;
+                left.fmt_at(f, depth + 1)?;
+                right.fmt_at(f, depth + 1)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Plan<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_at(f, 0)
+    }
+}
+
+impl<'a> SetExpr<'a> {
+    /// Plans the order of the steps of the proof, see the [module docs](self).
+    pub fn plan(&self) -> Plan<'a> {
+        self.plan_with(&mut 0)
+    }
+
+    /// The estimated cost of proving the planned expression.
+    pub fn cost(&self) -> Cost {
+        self.plan().cost()
+    }
+
+    /// A description of the plan, one step per line.
+    pub fn explain(&self) -> String {
+        self.plan().to_string()
+    }
+
+    fn plan_with(&self, next: &mut usize) -> Plan<'a> {
+        match self {
+            SetExpr::Acc(acc) => {
+                *next += 1;
+                Plan::Leaf {
+                    index: *next - 1,
+                    acc,
+                }
+            }
+            SetExpr::Op(SetOp::Diff, a, b) => {
+                let a = a.plan_with(next);
+                Plan::op(SetOp::Diff, a, b.plan_with(next))
+            }
+            SetExpr::Op(op, ..) => {
+                let mut operands = Vec::new();
+                self.flatten(*op, next, &mut operands);
+                loop {
+                    operands.sort_by_key(|p| Reverse(p.cost().size));
+                    let a = operands.pop().expect("a chain has operands");
+                    match operands.pop() {
+                        Some(b) => operands.push(Plan::op(*op, a, b)),
+                        None => return a,
+                    }
+                }
+            }
+        }
+    }
+
+    fn flatten(&self, op: SetOp, next: &mut usize, operands: &mut Vec<Plan<'a>>) {
+        match self {
+            SetExpr::Op(o, a, b) if *o == op => {
+                a.flatten(op, next, operands);
+                b.flatten(op, next, operands);
+            }
+            _ => operands.push(self.plan_with(next)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    fn acc_of(elements: impl IntoIterator<Item = i64>) -> DynamicAccumulator {
+        let mut acc = DynamicAccumulator::new();
+        for e in elements {
+            acc.add(&e).unwrap();
+        }
+        acc
+    }
+
+    #[test]
+    fn test_plan() {
+        init_logger();
+        let (a, b, c) = (acc_of(0..12), acc_of(4..16), acc_of([5, 6]));
+        let expr = SetExpr::inter(SetExpr::inter(&a, &b), &c);
+
+        // The small set is intersected first, with either large one.
+        let plan = expr.plan();
+        match &plan {
+            Plan::Op { left, right, .. } => {
+                assert!(matches!(**left, Plan::Op { .. }));
+                assert_eq!(left.cost().size, 2);
+                assert!(matches!(**right, Plan::Leaf { .. }));
+            }
+            Plan::Leaf { .. } => panic!("expected a step"),
+        }
+        let naive = Cost::step(
+            SetOp::Inter,
+            &Cost::step(
+                SetOp::Inter,
+                &Plan::Leaf { index: 0, acc: &a }.cost(),
+                &Plan::Leaf { index: 1, acc: &b }.cost(),
+            ),
+            &Plan::Leaf { index: 2, acc: &c }.cost(),
+        );
+        let cost = expr.cost();
+        assert!(cost.prove_work < naive.prove_work);
+        assert_eq!((cost.pairings, cost.proof_bytes), (14, 2 * STEP_BYTES));
+        assert!(expr.explain().contains("leaf #2 size=2"));
+
+        let (result, proof) = expr.prove().unwrap();
+        assert_eq!(result.acc_value, acc_of([5, 6]).acc_value);
+        assert!(proof.verify(&[a.acc_value, b.acc_value, c.acc_value], result.acc_value));
+        assert_eq!(proof.shape(), expr.shape());
+    }
+}
@@ -0,0 +1,339 @@
+//! Threshold ("k-of-n") membership proofs.
+//!
+//! A [`ThresholdMembershipProof`] shows that a public element is a member of at least `k` of
+//! `n` accumulators without revealing which ones. For every accumulator `A_i` the prover
+//! publishes a blinded witness `W_i`; for a real witness `w`, `W_i = w + h*r_i` and
+//!
+//!   T_i = e(W_i, g2^(s-x)) / e(A_i, g2) = E^(r_i), where E = e(h, g2^(s-x)).
+//!
+//! The prover shows knowledge of the discrete logarithm of `T_i` for `k` of the branches with
+//! Schnorr proofs and simulates the other `n - k`. The branch challenges are the evaluations
+//! f(1), ..., f(n) of a polynomial of degree `n - k` with f(0) equal to the Fiat-Shamir
+//! challenge, so at most `n - k` of them can be chosen freely.
+
+use super::{
+    dynamic_accumulator::MembershipProof, g2_s_minus, handle::ElementHandle, signing::hash_to_g1,
+    utils::digest_to_prime_field, Curve, Fq12, Fr, G1Affine, G1Projective, G2Affine,
+};
+use crate::digest::Digestible;
+use anyhow::{bail, ensure, Result};
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{Field, One, PrimeField, ToBytes, UniformRand, Zero};
+
+lazy_static! {
+    static ref GEN_H: G1Affine = hash_to_g1(b"ESA_RUST_THRESHOLD_H");
+}
+
+/// A proof that an element is a member of at least `k` of `n` accumulators.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThresholdMembershipProof {
+    pub element: Fr,
+    /// One blinded witness per accumulator.
+    pub blinded_witnesses: Vec<G1Affine>,
+    /// The Fiat-Shamir challenge, i.e. the constant term of the challenge polynomial.
+    pub challenge: Fr,
+    /// The coefficients of degree 1 to `n - k` of the challenge polynomial.
+    pub challenge_coeffs: Vec<Fr>,
+    /// One Schnorr response per accumulator.
+    pub responses: Vec<Fr>,
+}
+
+/// The values the branch proofs are about: `E` and every `T_i`.
+fn statements(
+    element: Fr,
+    acc_values: &[G1Affine],
+    blinded: &[G1Affine],
+) -> Option<(Fq12, Vec<Fq12>)> {
+    let g2_s_minus_x = g2_s_minus(element);
+    let g2 = G2Affine::prime_subgroup_generator();
+    let base = Curve::pairing(*GEN_H, g2_s_minus_x);
+    let targets = acc_values
+        .iter()
+        .zip(blinded)
+        .map(|(acc, w)| {
+            Curve::pairing(acc.into_projective(), g2)
+                .inverse()
+                .map(|inv| Curve::pairing(*w, g2_s_minus_x) * inv)
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some((base, targets))
+}
+
+fn transcript_challenge(
+    k: usize,
+    element: Fr,
+    acc_values: &[G1Affine],
+    blinded: &[G1Affine],
+    commitments: &[Fq12],
+) -> Fr {
+    let mut buf = Vec::<u8>::new();
+    buf.extend_from_slice(b"ESA_RUST_THRESHOLD_MEMBERSHIP");
+    buf.extend_from_slice(&(k as u64).to_le_bytes());
+    element
+        .write(&mut buf)
+        .and_then(|_| acc_values.iter().try_for_each(|p| p.write(&mut buf)))
+        .and_then(|_| blinded.iter().try_for_each(|p| p.write(&mut buf)))
+        .and_then(|_| commitments.iter().try_for_each(|t| t.write(&mut buf)))
+        .unwrap_or_else(|_| panic!("failed to serialize the threshold proof transcript"));
+    digest_to_prime_field(&buf.to_digest())
+}
+
+/// Evaluates `c + coeffs[0] * x + coeffs[1] * x^2 + ...`.
+fn eval_challenge(c: Fr, coeffs: &[Fr], x: Fr) -> Fr {
+    coeffs.iter().rev().fold(Fr::zero(), |acc, a| (acc + a) * x) + c
+}
+
+/// The coefficients of the polynomial through `points`, lowest degree first.
+fn interpolate(points: &[(Fr, Fr)]) -> Vec<Fr> {
+    let mut coeffs = vec![Fr::zero(); points.len()];
+    for (m, (xm, ym)) in points.iter().enumerate() {
+        // The Lagrange basis polynomial of point m, scaled by ym.
+        let mut basis = vec![Fr::one()];
+        let mut denom = Fr::one();
+        for (l, (xl, _)) in points.iter().enumerate() {
+            if l == m {
+                continue;
+            }
+            let mut next = vec![Fr::zero(); basis.len() + 1];
+            for (i, b) in basis.iter().enumerate() {
+                next[i + 1] += b;
+                next[i] -= &(*b * xl);
+            }
+            basis = next;
+            denom *= *xm - xl;
+        }
+        let scale = *ym
+            * denom
+                .inverse()
+                .expect("interpolation points must be distinct");
+        for (c, b) in coeffs.iter_mut().zip(basis) {
+            *c += b * scale;
+        }
+    }
+    coeffs
+}
+
+impl ThresholdMembershipProof {
+    /// Proves that `element` is a member of at least `k` of the accumulators in `acc_values`.
+    /// `witnesses[i]` is a membership witness for `acc_values[i]` if the prover has one.
+    /// Blinding factors are drawn from the thread-local generator.
+    pub fn prove(
+        element: &i64,
+        acc_values: &[G1Affine],
+        witnesses: &[Option<MembershipProof>],
+        k: usize,
+    ) -> Result<Self> {
+        Self::prove_with_rng(element, acc_values, witnesses, k, &mut rand::thread_rng())
+    }
+
+    /// Same as [`ThresholdMembershipProof::prove`], drawing blinding factors from `rng`.
+    pub fn prove_with_rng<R: rand::Rng + ?Sized>(
+        element: &i64,
+        acc_values: &[G1Affine],
+        witnesses: &[Option<MembershipProof>],
+        k: usize,
+        rng: &mut R,
+    ) -> Result<Self> {
+        let n = acc_values.len();
+        ensure!(
+            witnesses.len() == n,
+            "Expected one witness slot per accumulator"
+        );
+        ensure!(
+            k >= 1 && k <= n,
+            "The threshold must be between 1 and {}",
+            n
+        );
+        let x: Fr = digest_to_prime_field(&element.to_digest());
+
+        // The first k valid witnesses are used, every other branch is simulated.
+        let mut real = vec![false; n];
+        let mut found = 0;
+        for (i, (slot, acc)) in witnesses.iter().zip(acc_values).enumerate() {
+            if found < k
+                && slot
+                    .as_ref()
+                    .is_some_and(|w| w.element == ElementHandle(x) && w.verify(*acc))
+            {
+                real[i] = true;
+                found += 1;
+            }
+        }
+        if found < k {
+            bail!(
+                "Only {} valid membership witnesses for a threshold of {}",
+                found,
+                k
+            );
+        }
+
+        let mut blinding = vec![Fr::zero(); n];
+        let blinded: Vec<G1Affine> = (0..n)
+            .map(|i| {
+                blinding[i] = Fr::rand(rng);
+                let mask = GEN_H.mul(blinding[i].into_repr());
+                match &witnesses[i] {
+                    Some(w) if real[i] => (w.witness.into_projective() + mask).into_affine(),
+                    // A uniformly random point, distributed like a blinded witness.
+                    _ => G1Projective::prime_subgroup_generator()
+                        .mul(Fr::rand(rng).into_repr())
+                        .into_affine(),
+                }
+            })
+            .collect();
+        let (base, targets) = match statements(x, acc_values, &blinded) {
+            Some(s) => s,
+            None => bail!("Failed to compute the branch statements"),
+        };
+
+        let mut nonces = vec![Fr::zero(); n];
+        let mut branch_challenges = vec![Fr::zero(); n];
+        let mut responses = vec![Fr::zero(); n];
+        let mut commitments = Vec::with_capacity(n);
+        for i in 0..n {
+            if real[i] {
+                nonces[i] = Fr::rand(rng);
+                commitments.push(base.pow(nonces[i].into_repr()));
+            } else {
+                branch_challenges[i] = Fr::rand(rng);
+                responses[i] = Fr::rand(rng);
+                let t_c = targets[i].pow(branch_challenges[i].into_repr());
+                let t_c_inv = match t_c.inverse() {
+                    Some(inv) => inv,
+                    None => bail!("Failed to simulate a branch"),
+                };
+                commitments.push(base.pow(responses[i].into_repr()) * t_c_inv);
+            }
+        }
+
+        let c = transcript_challenge(k, x, acc_values, &blinded, &commitments);
+        let mut points = vec![(Fr::zero(), c)];
+        points.extend(
+            (0..n)
+                .filter(|i| !real[*i])
+                .map(|i| (Fr::from((i + 1) as u64), branch_challenges[i])),
+        );
+        let challenge_coeffs = interpolate(&points).split_off(1);
+        for i in (0..n).filter(|i| real[*i]) {
+            let ci = eval_challenge(c, &challenge_coeffs, Fr::from((i + 1) as u64));
+            responses[i] = nonces[i] + ci * blinding[i];
+        }
+
+        Ok(Self {
+            element: x,
+            blinded_witnesses: blinded,
+            challenge: c,
+            challenge_coeffs,
+            responses,
+        })
+    }
+
+    /// Verifies that the element is a member of at least `k` of the accumulators in
+    /// `acc_values`.
+    pub fn verify(&self, acc_values: &[G1Affine], k: usize) -> bool {
+        let n = acc_values.len();
+        if k == 0
+            || k > n
+            || self.blinded_witnesses.len() != n
+            || self.responses.len() != n
+            || self.challenge_coeffs.len() != n - k
+        {
+            return false;
+        }
+        let (base, targets) = match statements(self.element, acc_values, &self.blinded_witnesses) {
+            Some(s) => s,
+            None => return false,
+        };
+        let commitments = match targets
+            .iter()
+            .zip(&self.responses)
+            .enumerate()
+            .map(|(i, (target, z))| {
+                let ci = eval_challenge(
+                    self.challenge,
+                    &self.challenge_coeffs,
+                    Fr::from((i + 1) as u64),
+                );
+                target
+                    .pow(ci.into_repr())
+                    .inverse()
+                    .map(|inv| base.pow(z.into_repr()) * inv)
+            })
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(commitments) => commitments,
+            None => return false,
+        };
+        self.challenge
+            == transcript_challenge(
+                k,
+                self.element,
+                acc_values,
+                &self.blinded_witnesses,
+                &commitments,
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::{dynamic_accumulator::DynamicAccumulator, rng::DeterministicRng};
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let points: Vec<(Fr, Fr)> = [(0u64, 7u64), (2, 3), (5, 11)]
+            .iter()
+            .map(|(x, y)| (Fr::from(*x), Fr::from(*y)))
+            .collect();
+        let coeffs = interpolate(&points);
+        assert_eq!(coeffs.len(), 3);
+        for (x, y) in points {
+            assert_eq!(eval_challenge(coeffs[0], &coeffs[1..], x), y);
+        }
+    }
+
+    #[test]
+    fn test_threshold_membership() {
+        init_logger();
+        let lists: Vec<DynamicAccumulator> = [&[1, 2][..], &[2, 3], &[4], &[2, 5]]
+            .iter()
+            .map(|elements| {
+                let mut acc = DynamicAccumulator::new();
+                acc.add_batch(elements).unwrap();
+                acc
+            })
+            .collect();
+        let values: Vec<G1Affine> = lists.iter().map(|acc| acc.acc_value).collect();
+        let witnesses: Vec<Option<MembershipProof>> = lists
+            .iter()
+            .map(|acc| acc.prove_membership(&2).ok())
+            .collect();
+
+        let proof = ThresholdMembershipProof::prove(&2, &values, &witnesses, 2).unwrap();
+        assert!(proof.verify(&values, 2));
+        assert!(!proof.verify(&values, 3));
+        let mut reordered = values.clone();
+        reordered.swap(1, 2);
+        assert!(!proof.verify(&reordered, 2));
+
+        let all = ThresholdMembershipProof::prove(&2, &values, &witnesses, 3).unwrap();
+        assert!(all.verify(&values, 3));
+        assert!(ThresholdMembershipProof::prove(&2, &values, &witnesses, 4).is_err());
+        assert!(ThresholdMembershipProof::prove(&1, &values, &witnesses, 1).is_err());
+
+        // Proofs from different member sets look alike: every branch has a blinded witness.
+        let mut only_last = vec![None; 4];
+        only_last[3] = witnesses[3].clone();
+        let mut rng = DeterministicRng::from_seed(b"seed");
+        let proof =
+            ThresholdMembershipProof::prove_with_rng(&2, &values, &only_last, 1, &mut rng).unwrap();
+        assert!(proof.verify(&values, 1));
+        assert_eq!(proof.blinded_witnesses.len(), 4);
+        assert_eq!(proof.challenge_coeffs.len(), 3);
+    }
+}
@@ -0,0 +1,202 @@
+//! Polynomial utilities over prime fields.
+//!
+//! These are the building blocks of the set-operation proofs: characteristic polynomials
+//! (products of `X - r`) and Bézout coefficients from the extended Euclidean algorithm. Unlike
+//! [`utils::xgcd`](super::utils::xgcd), [`xgcd`] returns a normalized gcd and is defined for
+//! every input, zero polynomials included.
+//!
+//! [`mul`] multiplies small operands directly and switches to FFT-based multiplication once
+//! both have degree at least [`FFT_THRESHOLD`]; the scalar field of BLS12-381 has 2-adicity 32,
+//! so FFT domains exist for any degree used here.
+
+use super::utils;
+use ark_ff::{PrimeField, Zero};
+use ark_poly::{univariate::DensePolynomial, Polynomial, UVPolynomial};
+
+/// The degree from which [`mul`] multiplies two polynomials with FFTs.
+pub const FFT_THRESHOLD: usize = 64;
+
+/// Multiplies two polynomials, schoolbook for small operands and with FFTs otherwise.
+/// Multiplying by a polynomial of low degree (e.g. `X - r`) is always done directly, in time
+/// linear in the other operand.
+pub fn mul<F: PrimeField>(a: &DensePolynomial<F>, b: &DensePolynomial<F>) -> DensePolynomial<F> {
+    if a.is_zero() || b.is_zero() {
+        return DensePolynomial::zero();
+    }
+    if a.degree().min(b.degree()) < FFT_THRESHOLD {
+        a.naive_mul(b)
+    } else {
+        a * b
+    }
+}
+
+/// Returns `X - root`.
+pub fn linear<F: PrimeField>(root: F) -> DensePolynomial<F> {
+    DensePolynomial::from_coefficients_vec(vec![-root, F::one()])
+}
+
+/// How [`from_roots_with`] expands a product of linear factors.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum RootsStrategy {
+    /// Multiplies the factors in one by one, in quadratic time.
+    Naive,
+    /// Multiplies balanced halves recursively, so that large products use [`mul`]'s FFTs.
+    #[default]
+    SubproductTree,
+}
+
+/// Returns the product of `X - r` over all roots, repeated roots included. The product of no
+/// roots is the constant 1.
+pub fn from_roots<F: PrimeField>(roots: &[F]) -> DensePolynomial<F> {
+    from_roots_with(roots, RootsStrategy::SubproductTree)
+}
+
+/// [`from_roots`] with an explicit expansion strategy. Both strategies return the same
+/// polynomial.
+pub fn from_roots_with<F: PrimeField>(roots: &[F], strategy: RootsStrategy) -> DensePolynomial<F> {
+    match (roots.len(), strategy) {
+        (0, _) => DensePolynomial::from_coefficients_vec(vec![F::one()]),
+        (1, _) => linear(roots[0]),
+        (_, RootsStrategy::Naive) => {
+            let mut coeffs = Vec::with_capacity(roots.len() + 1);
+            coeffs.push(F::one());
+            for root in roots {
+                // Multiply by X - root in place, from the highest coefficient down.
+                coeffs.push(F::zero());
+                for i in (1..coeffs.len()).rev() {
+                    coeffs[i] = coeffs[i - 1] - coeffs[i] * root;
+                }
+                coeffs[0] = -coeffs[0] * root;
+            }
+            DensePolynomial::from_coefficients_vec(coeffs)
+        }
+        (n, RootsStrategy::SubproductTree) => {
+            // Multiplying balanced halves keeps the operands of similar degree.
+            let (left, right) = roots.split_at(n / 2);
+            let (left, right) = rayon::join(|| from_roots(left), || from_roots(right));
+            mul(&left, &right)
+        }
+    }
+}
+
+/// Scales a polynomial so its leading coefficient is one. Returns `None` for the zero
+/// polynomial.
+pub fn monic<F: PrimeField>(poly: &DensePolynomial<F>) -> Option<DensePolynomial<F>> {
+    let poly = DensePolynomial::from_coefficients_slice(&poly.coeffs);
+    let inv = poly.coeffs.last()?.inverse()?;
+    Some(scale(&poly, inv))
+}
+
+fn scale<F: PrimeField>(poly: &DensePolynomial<F>, factor: F) -> DensePolynomial<F> {
+    DensePolynomial::from_coefficients_vec(poly.coeffs.iter().map(|c| *c * factor).collect())
+}
+
+/// Returns `(g, x, y)` with `a*x + b*y = g`, where `g` is the monic gcd of `a` and `b`.
+///
+/// If both inputs are zero, so is every output. If exactly one input is zero, `g` is the other
+/// one made monic.
+pub fn xgcd<F: PrimeField>(
+    a: &DensePolynomial<F>,
+    b: &DensePolynomial<F>,
+) -> (DensePolynomial<F>, DensePolynomial<F>, DensePolynomial<F>) {
+    let zero = DensePolynomial::zero;
+    if a.is_zero() && b.is_zero() {
+        return (zero(), zero(), zero());
+    }
+    // The divisor is never zero inside the loop, so the division cannot fail.
+    let (g, x, y) = utils::xgcd(a, b).unwrap_or_else(|| unreachable!("division by zero"));
+    let lead_inv = g
+        .coeffs
+        .last()
+        .and_then(|c| c.inverse())
+        .unwrap_or_else(|| unreachable!("the gcd of a non-zero input is non-zero"));
+    (
+        scale(&g, lead_inv),
+        scale(&x, lead_inv),
+        scale(&y, lead_inv),
+    )
+}
+
+/// Returns `(x, y)` with `a*x + b*y = 1` if `a` and `b` are coprime, and `None` otherwise
+/// (including when either of them is zero and the other is not a constant).
+pub fn bezout_coprime<F: PrimeField>(
+    a: &DensePolynomial<F>,
+    b: &DensePolynomial<F>,
+) -> Option<(DensePolynomial<F>, DensePolynomial<F>)> {
+    let (g, x, y) = xgcd(a, b);
+    if g.is_zero() || g.coeffs.len() != 1 {
+        return None;
+    }
+    Some((x, y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::Fr;
+    use ark_ff::{One, UniformRand};
+
+    fn roots(values: &[u64]) -> Vec<Fr> {
+        values.iter().map(|v| Fr::from(*v)).collect()
+    }
+
+    #[test]
+    fn test_from_roots() {
+        let p = from_roots(&roots(&[1, 2, 2, 5]));
+        assert_eq!(p.degree(), 4);
+        assert!(p.coeffs.last().unwrap().is_one());
+        for r in roots(&[1, 2, 5]) {
+            assert!(p.evaluate(&r).is_zero());
+        }
+        assert!(!p.evaluate(&Fr::from(3u64)).is_zero());
+        assert_eq!(from_roots::<Fr>(&[]).coeffs, vec![Fr::one()]);
+
+        let many: Vec<Fr> = (0..3 * FFT_THRESHOLD as u64).map(Fr::from).collect();
+        assert_eq!(
+            from_roots_with(&many, RootsStrategy::Naive),
+            from_roots_with(&many, RootsStrategy::SubproductTree)
+        );
+    }
+
+    #[test]
+    fn test_mul_switchover() {
+        let mut rng = rand::thread_rng();
+        for (da, db) in [(3, 5), (FFT_THRESHOLD, FFT_THRESHOLD + 7), (1, 200)] {
+            let a = DensePolynomial::<Fr>::rand(da, &mut rng);
+            let b = DensePolynomial::<Fr>::rand(db, &mut rng);
+            assert_eq!(mul(&a, &b), a.naive_mul(&b));
+            let x = Fr::rand(&mut rng);
+            assert_eq!(mul(&a, &b).evaluate(&x), a.evaluate(&x) * b.evaluate(&x));
+        }
+        assert!(mul(&DensePolynomial::<Fr>::zero(), &linear(Fr::one())).is_zero());
+    }
+
+    #[test]
+    fn test_xgcd_edge_cases() {
+        let a = from_roots(&roots(&[1, 2, 3]));
+        let b = from_roots(&roots(&[2, 3, 4]));
+        let (g, x, y) = xgcd(&a, &b);
+        assert_eq!(g, from_roots(&roots(&[2, 3])));
+        assert_eq!(&(&a * &x) + &(&b * &y), g);
+        assert!(bezout_coprime(&a, &b).is_none());
+
+        // A constant gcd is normalized to one.
+        let c = from_roots(&roots(&[7]));
+        let (x, y) = bezout_coprime(&a, &c).unwrap();
+        assert_eq!(
+            &(&a * &x) + &(&c * &y),
+            DensePolynomial::from_coefficients_vec(vec![Fr::one()])
+        );
+
+        let zero = DensePolynomial::<Fr>::zero();
+        let (g, x, y) = xgcd(&zero, &zero);
+        assert!(g.is_zero() && x.is_zero() && y.is_zero());
+        let (g, x, y) = xgcd(&zero, &scale(&b, Fr::from(3u64)));
+        assert_eq!(g, b);
+        assert_eq!(&(&zero * &x) + &(&scale(&b, Fr::from(3u64)) * &y), b);
+        assert!(bezout_coprime(&zero, &b).is_none());
+        let two = DensePolynomial::from_coefficients_vec(vec![Fr::from(2u64)]);
+        assert!(bezout_coprime(&zero, &two).is_some());
+        assert!(monic(&zero).is_none());
+    }
+}
@@ -0,0 +1,187 @@
+//! Prepared public parameters and allocation-free membership verification.
+//!
+//! Every pairing with a G2 point first prepares it, computing the line coefficients of its
+//! Miller loop. [`PreparedPublicParams`] keeps the generators in prepared form, and the
+//! `verify` methods of the proof types use the shared [`PreparedPublicParams::global`] instance
+//! instead of preparing g2 on every call.
+//!
+//! [`MembershipProof::verify`] still hashes the accumulator value and prepares g2^(s-x) on every
+//! call. A gateway that checks many proofs against the same accumulator value can instead
+//! prepare the value once as a [`PreparedAcc`], and each proof once as a [`PreparedProof`], and
+//! then call [`verify_membership_prepared`]. That check does no hashing and no G2 preparation,
+//! and its only heap allocation is the pair list inside arkworks' Miller loop.
+
+use super::{
+    canonical::CanonicalForm, dynamic_accumulator::MembershipProof, metrics, Curve, Fq12, Fr,
+    G1Affine, G2Affine,
+};
+use crate::digest::{Digest, Digestible};
+use ark_ec::{AffineCurve, PairingEngine};
+use ark_ff::One;
+
+type G1Prepared = <Curve as PairingEngine>::G1Prepared;
+type G2Prepared = <Curve as PairingEngine>::G2Prepared;
+
+lazy_static! {
+    static ref GLOBAL: PreparedPublicParams = PreparedPublicParams::new();
+}
+
+/// The public generators in prepared form, and the values derived from them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreparedPublicParams {
+    g2: G2Prepared,
+    e_g1_g2: Fq12,
+}
+
+impl PreparedPublicParams {
+    pub fn new() -> Self {
+        let g1 = G1Affine::prime_subgroup_generator();
+        let g2 = G2Affine::prime_subgroup_generator();
+        Self {
+            g2: g2.into(),
+            e_g1_g2: Curve::pairing(g1, g2),
+        }
+    }
+
+    /// The instance shared by the `verify` methods, prepared on first use.
+    pub fn global() -> &'static Self {
+        &GLOBAL
+    }
+
+    /// The prepared G2 generator.
+    pub fn g2(&self) -> &G2Prepared {
+        &self.g2
+    }
+
+    /// The precomputed e(g1, g2).
+    pub fn e_g1_g2(&self) -> Fq12 {
+        self.e_g1_g2
+    }
+
+    /// Returns g2^(s-element), prepared.
+    pub fn g2_s_minus(&self, element: Fr) -> G2Prepared {
+        super::g2_s_minus(element).into()
+    }
+
+    /// Checks if e(a, b) * e(c, g2) == 1.
+    pub fn pairing_check(&self, a: G1Affine, b: G2Prepared, c: G1Affine) -> bool {
+        let pairs: [(G1Prepared, G2Prepared); 2] = [(a.into(), b), (c.into(), self.g2.clone())];
+        Curve::product_of_pairings(&pairs).is_one()
+    }
+}
+
+impl Default for PreparedPublicParams {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An accumulator value prepared for repeated verification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreparedAcc {
+    value: G1Affine,
+    digest: Digest,
+    /// The pairing term e(-acc, g2).
+    pair: (G1Prepared, G2Prepared),
+}
+
+impl PreparedAcc {
+    pub fn new(value: G1Affine) -> Self {
+        Self::with_params(value, PreparedPublicParams::global())
+    }
+
+    pub fn with_params(value: G1Affine, params: &PreparedPublicParams) -> Self {
+        Self {
+            value,
+            digest: value.to_digest(),
+            pair: ((-value).into(), params.g2.clone()),
+        }
+    }
+
+    pub fn value(&self) -> G1Affine {
+        self.value
+    }
+
+    pub fn digest(&self) -> Digest {
+        self.digest
+    }
+}
+
+impl From<G1Affine> for PreparedAcc {
+    fn from(value: G1Affine) -> Self {
+        Self::new(value)
+    }
+}
+
+/// A membership proof with its pairing term e(witness, g2^(s-x)) prepared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreparedProof {
+    pub proof: MembershipProof,
+    canonical: bool,
+    pair: (G1Prepared, G2Prepared),
+}
+
+impl PreparedProof {
+    pub fn new(proof: MembershipProof) -> Self {
+        let pair = (
+            proof.witness.into(),
+            PreparedPublicParams::global().g2_s_minus(proof.element.0),
+        );
+        Self {
+            canonical: proof.is_canonical(),
+            proof,
+            pair,
+        }
+    }
+}
+
+impl From<MembershipProof> for PreparedProof {
+    fn from(proof: MembershipProof) -> Self {
+        Self::new(proof)
+    }
+}
+
+/// Same as [`MembershipProof::verify`], on prepared inputs.
+pub fn verify_membership_prepared(acc: &PreparedAcc, proof: &PreparedProof) -> bool {
+    if proof.proof.acc_digest != acc.digest || !proof.canonical {
+        return false;
+    }
+    metrics::record_pairings(2);
+    Curve::product_of_pairings([&proof.pair, &acc.pair]).is_one()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::dynamic_accumulator::DynamicAccumulator;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_verify_membership_prepared() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        acc.add_batch(&[1, 2, 3]).unwrap();
+        let prepared_acc = PreparedAcc::new(acc.acc_value);
+        let proof = PreparedProof::new(acc.prove_membership(&2).unwrap());
+        assert!(verify_membership_prepared(&prepared_acc, &proof));
+        let params = PreparedPublicParams::new();
+        assert_eq!(&params, PreparedPublicParams::global());
+        assert_eq!(
+            PreparedAcc::with_params(acc.acc_value, &params),
+            prepared_acc
+        );
+
+        let stale = PreparedAcc::new(acc.acc_value);
+        acc.add(&4).unwrap();
+        assert!(!verify_membership_prepared(
+            &PreparedAcc::new(acc.acc_value),
+            &proof
+        ));
+        let mut forged = proof.proof.clone();
+        forged.element = acc.prove_membership(&1).unwrap().element;
+        assert!(!verify_membership_prepared(&stale, &forged.into()));
+    }
+}
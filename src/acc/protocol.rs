@@ -0,0 +1,241 @@
+//! Messages between a proof service and its clients.
+//!
+//! A client sends a [`ProofRequest`] naming the elements, the [`ProofKind`] it wants, optionally
+//! the epoch it expects the service to be at, and a nonce. The service answers with
+//! [`DynamicAccumulator::respond`], echoing the nonce, and the client checks the
+//! [`ProofResponse`] with [`ProofResponse::verify`] against the accumulator value it trusts for
+//! that epoch. Both messages implement serde, so any serde format carries them.
+
+use super::{
+    dynamic_accumulator::{DynamicAccumulator, QueryResult},
+    handle::ElementHandle,
+    utils::digest_to_prime_field,
+    Fr, G1Affine,
+};
+use crate::digest::Digestible;
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+/// The version of the messages defined here.
+pub const PROTOCOL_VERSION: u16 = 1;
+
+/// The proof requested for every element.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ProofKind {
+    Membership,
+    NonMembership,
+    /// Whichever of the two holds, as returned by [`DynamicAccumulator::query`].
+    Either,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofRequest {
+    pub version: u16,
+    pub elements: Vec<i64>,
+    pub kind: ProofKind,
+    /// The epoch the proofs must be for, if pinned.
+    pub epoch: Option<u64>,
+    /// Echoed in the response to match it to the request.
+    pub nonce: Vec<u8>,
+}
+
+impl ProofRequest {
+    pub fn new(elements: Vec<i64>, kind: ProofKind) -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            elements,
+            kind,
+            epoch: None,
+            nonce: Vec::new(),
+        }
+    }
+
+    /// Only accepts proofs for `epoch`.
+    pub fn at_epoch(mut self, epoch: u64) -> Self {
+        self.epoch = Some(epoch);
+        self
+    }
+
+    pub fn with_nonce(mut self, nonce: impl Into<Vec<u8>>) -> Self {
+        self.nonce = nonce.into();
+        self
+    }
+}
+
+/// Why a request was refused.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProofError {
+    UnsupportedVersion {
+        version: u16,
+    },
+    /// The request pinned an epoch the service is not at.
+    EpochMismatch {
+        pinned: u64,
+        current: u64,
+    },
+    /// The requested kind of proof does not hold for the element at `index`.
+    WrongKind {
+        index: usize,
+    },
+    /// The proof could not be generated.
+    Failed {
+        reason: String,
+    },
+}
+
+impl fmt::Display for ProofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProofError::UnsupportedVersion { version } => {
+                write!(f, "unsupported protocol version {}", version)
+            }
+            ProofError::EpochMismatch { pinned, current } => write!(
+                f,
+                "request pinned epoch {} but the accumulator is at epoch {}",
+                pinned, current
+            ),
+            ProofError::WrongKind { index } => {
+                write!(f, "the requested proof does not hold for element {}", index)
+            }
+            ProofError::Failed { reason } => write!(f, "proof generation failed: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ProofError {}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofResponse {
+    pub version: u16,
+    pub nonce: Vec<u8>,
+    /// The epoch of the accumulator state the proofs are for.
+    pub epoch: u64,
+    /// One proof per requested element, in order.
+    pub result: Result<Vec<QueryResult>, ProofError>,
+}
+
+impl ProofResponse {
+    /// Checks that this answers `request` with valid proofs of the requested kind against
+    /// `acc_value`, the value the client trusts for `self.epoch`.
+    pub fn verify(&self, request: &ProofRequest, acc_value: G1Affine) -> bool {
+        let proofs = match &self.result {
+            Ok(proofs) => proofs,
+            Err(_) => return false,
+        };
+        if self.version != request.version
+            || self.nonce != request.nonce
+            || request.epoch.is_some_and(|e| e != self.epoch)
+            || proofs.len() != request.elements.len()
+        {
+            return false;
+        }
+        request.elements.iter().zip(proofs).all(|(element, proof)| {
+            let fr_element: Fr = digest_to_prime_field(&element.to_digest());
+            match (proof, request.kind) {
+                (QueryResult::Membership(p), ProofKind::Membership | ProofKind::Either) => {
+                    p.element == ElementHandle(fr_element) && p.verify_at(acc_value, self.epoch)
+                }
+                (QueryResult::NonMembership(p), ProofKind::NonMembership | ProofKind::Either) => {
+                    p.element == ElementHandle(fr_element) && p.verify_at(acc_value, self.epoch)
+                }
+                _ => false,
+            }
+        })
+    }
+}
+
+impl DynamicAccumulator {
+    /// Answers a proof request against the current state.
+    pub fn respond(&self, request: &ProofRequest) -> ProofResponse {
+        ProofResponse {
+            version: PROTOCOL_VERSION,
+            nonce: request.nonce.clone(),
+            epoch: self.epoch(),
+            result: self.prove_request(request),
+        }
+    }
+
+    fn prove_request(&self, request: &ProofRequest) -> Result<Vec<QueryResult>, ProofError> {
+        if request.version != PROTOCOL_VERSION {
+            return Err(ProofError::UnsupportedVersion {
+                version: request.version,
+            });
+        }
+        if let Some(pinned) = request.epoch.filter(|e| *e != self.epoch()) {
+            return Err(ProofError::EpochMismatch {
+                pinned,
+                current: self.epoch(),
+            });
+        }
+        let failed = |e: anyhow::Error| ProofError::Failed {
+            reason: e.to_string(),
+        };
+        request
+            .elements
+            .iter()
+            .enumerate()
+            .map(|(index, element)| {
+                let member = self
+                    .elements
+                    .contains(&digest_to_prime_field(&element.to_digest()))
+                    .map_err(failed)?;
+                match (member, request.kind) {
+                    (true, ProofKind::NonMembership) | (false, ProofKind::Membership) => {
+                        Err(ProofError::WrongKind { index })
+                    }
+                    (true, _) => self
+                        .prove_membership(element)
+                        .map(QueryResult::Membership)
+                        .map_err(failed),
+                    (false, _) => self
+                        .prove_non_membership(element)
+                        .map(QueryResult::NonMembership)
+                        .map_err(failed),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_proof_protocol() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        acc.add(&1).unwrap();
+        acc.add(&2).unwrap();
+
+        let request = ProofRequest::new(vec![1, 3], ProofKind::Either)
+            .at_epoch(2)
+            .with_nonce(&b"n1"[..]);
+        let json = serde_json::to_string(&request).unwrap();
+        let request: ProofRequest = serde_json::from_str(&json).unwrap();
+        let response = acc.respond(&request);
+        let bin = bincode::serialize(&response).unwrap();
+        let response: ProofResponse = bincode::deserialize(&bin).unwrap();
+        assert!(response.verify(&request, acc.acc_value));
+        assert!(!response.verify(&request.clone().with_nonce(&b"n2"[..]), acc.acc_value));
+        assert!(!response.verify(&request, DynamicAccumulator::new().acc_value));
+
+        let wrong = acc.respond(&ProofRequest::new(vec![1, 3], ProofKind::Membership));
+        assert_eq!(wrong.result, Err(ProofError::WrongKind { index: 1 }));
+        let stale = acc.respond(&request.clone().at_epoch(1));
+        assert_eq!(
+            stale.result,
+            Err(ProofError::EpochMismatch {
+                pinned: 1,
+                current: 2
+            })
+        );
+        let mut future = request;
+        future.version = PROTOCOL_VERSION + 1;
+        assert!(acc.respond(&future).result.is_err());
+    }
+}
@@ -0,0 +1,235 @@
+//! Proving without the trapdoor.
+//!
+//! Witnesses are commitments to polynomials derived from the characteristic polynomial
+//! P(X) = product(X - e_i) of the set. They are computed coefficient-wise from the published
+//! powers g1^(s^i) and g2^(s^i) with one MSM each, so anyone who knows the element list can
+//! prove, e.g. an untrusted proof service. The manager's non-membership prover uses the same
+//! code.
+//!
+//! A membership witness is the unique point acc^(1/(s-x)), whether the trapdoor computes it or
+//! the quotient P(X) / (X - x) commits to it, so both paths give byte-identical proofs.
+//! [`ProverBackend`] selects the path for [`DynamicAccumulator::prove_membership_with`], and
+//! proving can move off the secret-holding box without invalidating proofs already handed out.
+
+use super::{
+    commitment::params_id,
+    dynamic_accumulator::{DynamicAccumulator, MembershipProof, NonMembershipProof},
+    handle::ElementHandle,
+    polymath,
+    utils::digest_to_prime_field,
+    Acc1, Fr, G1Affine, G2Affine,
+};
+use crate::digest::Digestible;
+use anyhow::{anyhow, bail, Result};
+use ark_ff::One;
+use ark_poly::{univariate::DensePolynomial, UVPolynomial};
+use std::ops::Neg;
+
+/// Where witnesses are computed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum ProverBackend {
+    /// With the trapdoor s, one exponentiation per witness.
+    #[default]
+    Trapdoor,
+    /// From the element set and the public parameters, one MSM per witness.
+    Public,
+}
+
+/// Commits to A(X) and B(X) with A(X)*(X-x) + B(X)*P(X) = 1, returning (g1^A(s), g2^B(s)).
+/// Returns `None` if x is a root of P(X).
+pub(crate) fn non_membership_witness(
+    p_poly: &DensePolynomial<Fr>,
+    fr_element: Fr,
+) -> Option<(G1Affine, G2Affine)> {
+    let q_poly = DensePolynomial::from_coefficients_vec(vec![fr_element.neg(), Fr::one()]);
+    let (a_poly, b_poly) = polymath::bezout_coprime(&q_poly, p_poly)?;
+    Some((Acc1::poly_to_g1(a_poly), Acc1::poly_to_g2(b_poly)))
+}
+
+/// Proves that `element` is not in the set `elements`, whose accumulator was published at
+/// `epoch`, using only the public parameters.
+pub fn prove_non_membership_public(
+    elements: &[i64],
+    element: &i64,
+    epoch: u64,
+) -> Result<NonMembershipProof> {
+    non_membership_from_roots(
+        &to_roots(elements),
+        digest_to_prime_field(&element.to_digest()),
+        epoch,
+    )
+}
+
+/// Proves that `target` is in the set `elements`, whose accumulator was published at `epoch`,
+/// using only the public parameters. The witness commits to the quotient P(X) / (X - x).
+pub fn prove_membership_public(
+    elements: &[i64],
+    target: &i64,
+    epoch: u64,
+) -> Result<MembershipProof> {
+    membership_from_roots(
+        to_roots(elements),
+        digest_to_prime_field(&target.to_digest()),
+        epoch,
+    )
+}
+
+fn to_roots(elements: &[i64]) -> Vec<Fr> {
+    elements
+        .iter()
+        .map(|e| digest_to_prime_field(&e.to_digest()))
+        .collect()
+}
+
+fn non_membership_from_roots(
+    roots: &[Fr],
+    fr_element: Fr,
+    epoch: u64,
+) -> Result<NonMembershipProof> {
+    if roots.contains(&fr_element) {
+        bail!("Cannot prove non-membership for an element in the set");
+    }
+    let p_poly = polymath::from_roots(roots);
+    let (g1_a, witness) = non_membership_witness(&p_poly, fr_element)
+        .ok_or_else(|| anyhow!("Failed to create non-membership proof"))?;
+    Ok(NonMembershipProof {
+        element: ElementHandle(fr_element),
+        witness,
+        g1_a,
+        epoch,
+        acc_digest: Acc1::poly_to_g1(p_poly).to_digest(),
+        params_id: params_id(),
+    })
+}
+
+fn membership_from_roots(
+    mut roots: Vec<Fr>,
+    fr_element: Fr,
+    epoch: u64,
+) -> Result<MembershipProof> {
+    let position = roots
+        .iter()
+        .position(|e| *e == fr_element)
+        .ok_or_else(|| anyhow!("Cannot prove membership for an element not in the set"))?;
+    roots.swap_remove(position);
+    let quotient = polymath::from_roots(&roots);
+    let p_poly =
+        &quotient * &DensePolynomial::from_coefficients_vec(vec![fr_element.neg(), Fr::one()]);
+    Ok(MembershipProof {
+        witness: Acc1::poly_to_g1(quotient),
+        element: ElementHandle(fr_element),
+        epoch,
+        acc_digest: Acc1::poly_to_g1(p_poly).to_digest(),
+        params_id: params_id(),
+    })
+}
+
+impl DynamicAccumulator {
+    /// [`Self::prove_membership`] along the given path. Both paths give the same proof.
+    pub fn prove_membership_with(
+        &self,
+        backend: ProverBackend,
+        element: &i64,
+    ) -> Result<MembershipProof> {
+        let fr_element = digest_to_prime_field(&element.to_digest());
+        match backend {
+            ProverBackend::Trapdoor => self.prove_membership_fr(fr_element),
+            ProverBackend::Public => {
+                self.ensure_elements()?;
+                membership_from_roots(self.elements_with_multiplicity(), fr_element, self.epoch())
+            }
+        }
+    }
+
+    /// [`Self::prove_non_membership`] along the given path. Non-membership witnesses never
+    /// need the trapdoor, but the public path also recomputes the accumulator value from the
+    /// elements instead of reading the stored one.
+    pub fn prove_non_membership_with(
+        &self,
+        backend: ProverBackend,
+        element: &i64,
+    ) -> Result<NonMembershipProof> {
+        let fr_element = digest_to_prime_field(&element.to_digest());
+        match backend {
+            ProverBackend::Trapdoor => self.prove_non_membership_fr(fr_element),
+            ProverBackend::Public => {
+                self.ensure_elements()?;
+                non_membership_from_roots(
+                    &self.elements_with_multiplicity(),
+                    fr_element,
+                    self.epoch(),
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::{
+        dynamic_accumulator::DuplicatePolicy,
+        wire::{EncodingOptions, WireEncode},
+    };
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_prove_public() {
+        init_logger();
+        let elements = [3, 1, 4, 15, 9];
+        let mut acc = DynamicAccumulator::new();
+        for e in &elements {
+            acc.add(e).unwrap();
+        }
+        let proof = prove_non_membership_public(&elements, &2, acc.epoch()).unwrap();
+        assert!(acc.verify_non_membership(&proof));
+        assert_eq!(proof, acc.prove_non_membership(&2).unwrap());
+        assert!(prove_non_membership_public(&elements, &4, acc.epoch()).is_err());
+
+        let proof = prove_membership_public(&elements, &15, acc.epoch()).unwrap();
+        assert!(acc.verify_membership(&proof));
+        assert_eq!(proof, acc.prove_membership(&15).unwrap());
+        assert!(prove_membership_public(&elements, &2, acc.epoch()).is_err());
+
+        let empty = DynamicAccumulator::new();
+        let proof = prove_non_membership_public(&[], &2, 0).unwrap();
+        assert!(empty.verify_non_membership(&proof));
+    }
+
+    #[test]
+    fn test_backends_byte_identical() {
+        init_logger();
+        let mut acc =
+            DynamicAccumulator::with_duplicate_policy(DuplicatePolicy::IncrementMultiplicity);
+        acc.add_batch(&[3, 1, 4, 15, 9]).unwrap();
+        acc.add(&4).unwrap();
+        acc.delete(&9).unwrap();
+        let options = EncodingOptions::default();
+        for element in [3, 4, 15] {
+            let [fast, slow] = [ProverBackend::Trapdoor, ProverBackend::Public]
+                .map(|b| acc.prove_membership_with(b, &element).unwrap());
+            assert_eq!(
+                fast.encode(&options).unwrap(),
+                slow.encode(&options).unwrap()
+            );
+            assert!(acc.verify_membership(&slow));
+        }
+        for element in [2, 9] {
+            let [fast, slow] = [ProverBackend::Trapdoor, ProverBackend::Public]
+                .map(|b| acc.prove_non_membership_with(b, &element).unwrap());
+            assert_eq!(
+                fast.encode(&options).unwrap(),
+                slow.encode(&options).unwrap()
+            );
+        }
+        assert!(acc
+            .prove_membership_with(ProverBackend::Public, &9)
+            .is_err());
+        assert!(acc
+            .prove_non_membership_with(ProverBackend::Public, &1)
+            .is_err());
+    }
+}
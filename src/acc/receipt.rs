@@ -0,0 +1,126 @@
+//! Signed receipts for issued membership witnesses.
+//!
+//! When the manager hands out a witness it also signs a [`WitnessReceipt`] over the element
+//! digest, the epoch and a hash of the witness. If refresh messages are later withheld, the
+//! holder presents the receipt together with the witness to show that it was a member at that
+//! epoch, and that the manager issued exactly this witness.
+
+use super::{
+    dynamic_accumulator::{DynamicAccumulator, MembershipProof},
+    signing::{Signature, SigningKey, VerifyingKey},
+    update::fr_to_digest,
+};
+use crate::digest::{concat_digest, Digest, Digestible};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// The manager's signed statement that it issued a witness for an element at an epoch.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct WitnessReceipt {
+    pub element_digest: Digest,
+    pub epoch: u64,
+    pub witness_hash: Digest,
+    pub signature: Signature,
+}
+
+/// The digest signed for a receipt.
+pub fn receipt_digest(element_digest: &Digest, epoch: u64, witness_hash: &Digest) -> Digest {
+    concat_digest(
+        [
+            b"WitnessReceipt"[..].to_digest(),
+            *element_digest,
+            epoch.to_digest(),
+            *witness_hash,
+        ]
+        .iter()
+        .copied(),
+    )
+}
+
+impl WitnessReceipt {
+    /// Signs a receipt for `proof`.
+    pub fn new(proof: &MembershipProof, key: &SigningKey) -> Self {
+        let element_digest = fr_to_digest(&proof.element.0);
+        let witness_hash = proof.witness.to_digest();
+        Self {
+            element_digest,
+            epoch: proof.epoch,
+            witness_hash,
+            signature: key.sign(&receipt_digest(&element_digest, proof.epoch, &witness_hash)),
+        }
+    }
+
+    /// Checks the manager's signature.
+    pub fn verify(&self, vk: &VerifyingKey) -> bool {
+        vk.verify(
+            &receipt_digest(&self.element_digest, self.epoch, &self.witness_hash),
+            &self.signature,
+        )
+    }
+
+    /// Returns true if the receipt was issued for `proof`: same element, epoch and witness.
+    pub fn covers(&self, proof: &MembershipProof) -> bool {
+        self.element_digest == fr_to_digest(&proof.element.0)
+            && self.epoch == proof.epoch
+            && self.witness_hash == proof.witness.to_digest()
+    }
+
+    /// Checks the signature and that the receipt was issued for `proof`.
+    pub fn verify_for(&self, proof: &MembershipProof, vk: &VerifyingKey) -> bool {
+        self.covers(proof) && self.verify(vk)
+    }
+}
+
+impl DynamicAccumulator {
+    /// Generates a membership proof together with a signed receipt for it.
+    pub fn issue_witness(
+        &self,
+        element: &i64,
+        key: &SigningKey,
+    ) -> Result<(MembershipProof, WitnessReceipt)> {
+        let proof = self.prove_membership(element)?;
+        let receipt = WitnessReceipt::new(&proof, key);
+        Ok((proof, receipt))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_witness_receipt() {
+        init_logger();
+        let key = SigningKey::from_seed(b"manager");
+        let vk = key.verifying_key();
+        let mut acc = DynamicAccumulator::new();
+        acc.add_batch(&[1, 2, 3]).unwrap();
+
+        let (proof, receipt) = acc.issue_witness(&2, &key).unwrap();
+        assert!(proof.verify(acc.acc_value));
+        assert!(receipt.verify_for(&proof, &vk));
+        assert!(!receipt.verify_for(&proof, &SigningKey::from_seed(b"other").verifying_key()));
+        assert!(acc.issue_witness(&4, &key).is_err());
+
+        // The receipt outlives the state it was issued for, but only covers its own witness.
+        acc.add(&4).unwrap();
+        let fresh = acc.prove_membership(&2).unwrap();
+        assert!(!proof.verify(acc.acc_value));
+        assert!(receipt.verify(&vk) && !receipt.covers(&fresh));
+        assert!(!receipt.covers(&acc.prove_membership(&1).unwrap()));
+
+        let mut forged = receipt.clone();
+        forged.epoch += 1;
+        assert!(!forged.verify(&vk));
+
+        let json = serde_json::to_string(&receipt).unwrap();
+        assert_eq!(
+            serde_json::from_str::<WitnessReceipt>(&json).unwrap(),
+            receipt
+        );
+    }
+}
@@ -0,0 +1,249 @@
+//! Many independent accumulators keyed by namespace.
+//!
+//! A [`Registry`] holds one [`DynamicAccumulator`] per namespace, e.g. per tenant. All of them
+//! share the public parameters of the crate, but each has its own set and epoch. Operations on
+//! several namespaces can be applied as one batch, and [`Registry::snapshot`] publishes every
+//! namespace's `(epoch, acc_value)` in one signable object.
+
+use super::{
+    dynamic_accumulator::{ark_serde, DynamicAccumulator},
+    signing::{Signature, SigningKey, VerifyingKey},
+    update::Update,
+    G1Affine,
+};
+use crate::digest::{concat_digest, Digest, Digestible};
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// An operation on one namespace of a [`Registry`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryOp {
+    Add { namespace: String, element: i64 },
+    Delete { namespace: String, element: i64 },
+}
+
+impl RegistryOp {
+    pub fn namespace(&self) -> &str {
+        match self {
+            RegistryOp::Add { namespace, .. } | RegistryOp::Delete { namespace, .. } => namespace,
+        }
+    }
+}
+
+/// Accumulators keyed by namespace.
+#[derive(Debug, Clone, Default)]
+pub struct Registry {
+    accumulators: BTreeMap<String, DynamicAccumulator>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty accumulator for `namespace`. Returns an error if it already exists.
+    pub fn create(&mut self, namespace: &str) -> Result<&mut DynamicAccumulator> {
+        if self.accumulators.contains_key(namespace) {
+            bail!("Namespace {} already exists", namespace);
+        }
+        Ok(self.accumulators.entry(namespace.to_owned()).or_default())
+    }
+
+    /// Removes a namespace and returns its accumulator.
+    pub fn remove(&mut self, namespace: &str) -> Option<DynamicAccumulator> {
+        self.accumulators.remove(namespace)
+    }
+
+    pub fn get(&self, namespace: &str) -> Option<&DynamicAccumulator> {
+        self.accumulators.get(namespace)
+    }
+
+    pub fn get_mut(&mut self, namespace: &str) -> Option<&mut DynamicAccumulator> {
+        self.accumulators.get_mut(namespace)
+    }
+
+    /// Returns the epoch of a namespace.
+    pub fn epoch(&self, namespace: &str) -> Option<u64> {
+        self.get(namespace).map(|acc| acc.epoch())
+    }
+
+    /// Returns the namespaces in ascending order.
+    pub fn namespaces(&self) -> impl Iterator<Item = &str> {
+        self.accumulators.keys().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.accumulators.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accumulators.is_empty()
+    }
+
+    /// Applies operations across namespaces in order and returns their proofs. If any of them
+    /// fails, every namespace is left as it was before the batch.
+    pub fn apply_batch(&mut self, ops: &[RegistryOp]) -> Result<Vec<(String, Update)>> {
+        let mut originals: HashMap<&str, DynamicAccumulator> = HashMap::new();
+        for op in ops {
+            let namespace = op.namespace();
+            if !originals.contains_key(namespace) {
+                let acc = self
+                    .get(namespace)
+                    .ok_or_else(|| anyhow!("Unknown namespace {}", namespace))?;
+                originals.insert(namespace, acc.clone());
+            }
+        }
+
+        let mut updates = Vec::with_capacity(ops.len());
+        for (i, op) in ops.iter().enumerate() {
+            let acc = self
+                .accumulators
+                .get_mut(op.namespace())
+                .expect("namespace checked above");
+            let update = match op {
+                RegistryOp::Add { element, .. } => acc.add(element).map(Update::from),
+                RegistryOp::Delete { element, .. } => acc.delete(element).map(Update::from),
+            };
+            match update.with_context(|| format!("Operation {} failed", i)) {
+                Ok(update) => updates.push((op.namespace().to_owned(), update)),
+                Err(e) => {
+                    for (namespace, acc) in originals {
+                        self.accumulators.insert(namespace.to_owned(), acc);
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        Ok(updates)
+    }
+
+    /// Returns the state of every namespace.
+    pub fn snapshot(&self) -> RegistrySnapshot {
+        RegistrySnapshot {
+            namespaces: self
+                .accumulators
+                .iter()
+                .map(|(namespace, acc)| NamespaceState {
+                    namespace: namespace.clone(),
+                    epoch: acc.epoch(),
+                    acc_value: acc.acc_value,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The published state of one namespace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamespaceState {
+    pub namespace: String,
+    pub epoch: u64,
+    #[serde(with = "ark_serde")]
+    pub acc_value: G1Affine,
+}
+
+/// The states of all namespaces of a registry, sorted by namespace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegistrySnapshot {
+    pub namespaces: Vec<NamespaceState>,
+}
+
+impl RegistrySnapshot {
+    pub fn get(&self, namespace: &str) -> Option<&NamespaceState> {
+        self.namespaces
+            .binary_search_by(|state| state.namespace.as_str().cmp(namespace))
+            .ok()
+            .map(|i| &self.namespaces[i])
+    }
+
+    /// The digest of the snapshot, which commits to every namespace and its state.
+    pub fn digest(&self) -> Digest {
+        concat_digest(core::iter::once(b"RegistrySnapshot"[..].to_digest()).chain(
+            self.namespaces.iter().map(|state| {
+                concat_digest(
+                    [
+                        state.namespace.to_digest(),
+                        state.epoch.to_digest(),
+                        state.acc_value.to_digest(),
+                    ]
+                    .iter()
+                    .copied(),
+                )
+            }),
+        ))
+    }
+
+    pub fn sign(&self, key: &SigningKey) -> Signature {
+        key.sign(&self.digest())
+    }
+
+    pub fn verify(&self, vk: &VerifyingKey, signature: &Signature) -> bool {
+        vk.verify(&self.digest(), signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    fn add(namespace: &str, element: i64) -> RegistryOp {
+        RegistryOp::Add {
+            namespace: namespace.to_owned(),
+            element,
+        }
+    }
+
+    #[test]
+    fn test_registry() {
+        init_logger();
+        let mut registry = Registry::new();
+        registry.create("tenant-b").unwrap();
+        registry.create("tenant-a").unwrap().add(&1).unwrap();
+        assert!(registry.create("tenant-a").is_err());
+        assert_eq!(
+            registry.namespaces().collect::<Vec<_>>(),
+            ["tenant-a", "tenant-b"]
+        );
+
+        let updates = registry
+            .apply_batch(&[add("tenant-a", 2), add("tenant-b", 1), add("tenant-b", 2)])
+            .unwrap();
+        assert_eq!(updates.len(), 3);
+        assert!(updates.iter().all(|(_, u)| u.verify()));
+        assert_eq!(registry.epoch("tenant-a"), Some(2));
+        assert_eq!(registry.epoch("tenant-b"), Some(2));
+        assert_eq!(
+            registry.get("tenant-a").unwrap().acc_value,
+            registry.get("tenant-b").unwrap().acc_value
+        );
+
+        // A failing operation rolls back the whole batch.
+        let before = registry.snapshot();
+        let delete = RegistryOp::Delete {
+            namespace: "tenant-a".to_owned(),
+            element: 3,
+        };
+        assert!(registry.apply_batch(&[add("tenant-b", 3), delete]).is_err());
+        assert!(registry.apply_batch(&[add("tenant-c", 1)]).is_err());
+        assert_eq!(registry.snapshot(), before);
+
+        let key = SigningKey::from_seed(b"manager");
+        let signature = before.sign(&key);
+        assert!(before.verify(&key.verifying_key(), &signature));
+        assert_eq!(before.get("tenant-b").unwrap().epoch, 2);
+        assert!(before.get("tenant-c").is_none());
+        let json = serde_json::to_string(&before).unwrap();
+        let decoded: RegistrySnapshot = serde_json::from_str(&json).unwrap();
+        assert!(decoded.verify(&key.verifying_key(), &signature));
+
+        registry.get_mut("tenant-a").unwrap().add(&3).unwrap();
+        assert!(!registry.snapshot().verify(&key.verifying_key(), &signature));
+        assert!(registry.remove("tenant-b").is_some());
+        assert_eq!(registry.len(), 1);
+    }
+}
@@ -0,0 +1,123 @@
+//! Revocation with a pair of accumulators.
+//!
+//! A [`RevocationRegistry`] holds an "issued" accumulator of every element ever issued and a
+//! "revoked" accumulator of the elements that were withdrawn. An element is valid if it is a
+//! member of the former and not a member of the latter, which [`ValidityProof`] shows in one
+//! object.
+
+use super::{
+    dynamic_accumulator::{AddProof, DynamicAccumulator, MembershipProof, NonMembershipProof},
+    handle::ElementHandle,
+    utils::digest_to_prime_field,
+    G1Affine,
+};
+use crate::digest::Digestible;
+use anyhow::{bail, Result};
+
+/// A proof that an element is issued and not revoked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidityProof {
+    pub issued: MembershipProof,
+    pub not_revoked: NonMembershipProof,
+}
+
+impl ValidityProof {
+    /// The element the proof is about.
+    pub fn element(&self) -> ElementHandle {
+        self.issued.element
+    }
+
+    /// Verifies the proof against the values of the issued and revoked accumulators.
+    pub fn verify(&self, issued_value: G1Affine, revoked_value: G1Affine) -> bool {
+        self.issued.element == self.not_revoked.element
+            && self.issued.verify(issued_value)
+            && self.not_revoked.verify(revoked_value)
+    }
+}
+
+/// A registry of issued and revoked elements.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RevocationRegistry {
+    pub issued: DynamicAccumulator,
+    pub revoked: DynamicAccumulator,
+}
+
+impl RevocationRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Issues a new element. Returns an error if it was issued before.
+    pub fn issue(&mut self, element: &i64) -> Result<AddProof> {
+        self.issued.add(element)
+    }
+
+    /// Revokes an issued element. Returns an error if it was never issued or is already
+    /// revoked.
+    pub fn revoke(&mut self, element: &i64) -> Result<AddProof> {
+        let fr_element = digest_to_prime_field(&element.to_digest());
+        if !self.issued.elements.contains(&fr_element)? {
+            bail!("Element was never issued");
+        }
+        self.revoked.add_fr(fr_element)
+    }
+
+    /// Returns whether the element is issued and not revoked.
+    pub fn is_valid(&self, element: &i64) -> bool {
+        let fr_element = digest_to_prime_field(&element.to_digest());
+        // A failed lookup in the revoked set counts as revoked.
+        self.issued.elements.has(&fr_element)
+            && matches!(self.revoked.elements.contains(&fr_element), Ok(false))
+    }
+
+    /// Proves that the element is issued and not revoked.
+    pub fn prove_valid(&self, element: &i64) -> Result<ValidityProof> {
+        Ok(ValidityProof {
+            issued: self.issued.prove_membership(element)?,
+            not_revoked: self.revoked.prove_non_membership(element)?,
+        })
+    }
+
+    /// Verifies a validity proof against the current state of the registry.
+    pub fn verify_valid(&self, proof: &ValidityProof) -> bool {
+        self.issued.verify_membership(&proof.issued)
+            && self.revoked.verify_non_membership(&proof.not_revoked)
+            && proof.issued.element == proof.not_revoked.element
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_validity_proof() {
+        init_logger();
+        let mut registry = RevocationRegistry::new();
+        registry.issue(&1).unwrap();
+        registry.issue(&2).unwrap();
+        registry.revoke(&2).unwrap();
+        assert!(registry.revoke(&3).is_err());
+        assert!(registry.is_valid(&1));
+        assert!(!registry.is_valid(&2));
+
+        let proof = registry.prove_valid(&1).unwrap();
+        assert!(registry.verify_valid(&proof));
+        assert!(proof.verify(registry.issued.acc_value, registry.revoked.acc_value));
+        assert!(registry.prove_valid(&2).is_err());
+        assert!(registry.prove_valid(&3).is_err());
+
+        // Proofs about different elements cannot be combined.
+        let mut mixed = proof.clone();
+        mixed.not_revoked = registry.revoked.prove_non_membership(&3).unwrap();
+        assert!(!mixed.verify(registry.issued.acc_value, registry.revoked.acc_value));
+
+        // Revocation makes older proofs stale.
+        registry.revoke(&1).unwrap();
+        assert!(!registry.verify_valid(&proof));
+    }
+}
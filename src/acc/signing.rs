@@ -0,0 +1,231 @@
+//! BLS signatures over BLS12-381 for authenticating published accumulator states.
+//!
+//! The manager signs `(epoch, acc_value)` pairs and update logs with a [`SigningKey`], and
+//! clients check them with the matching [`VerifyingKey`] before trusting a value. Signatures
+//! live in G1 and public keys in G2, so verification is e(sig, g2) == e(H(m), pk).
+
+use super::{
+    dynamic_accumulator::DynamicAccumulator, serde_impl, update::UpdateLog, Curve, Fr, G1Affine,
+    G2Affine,
+};
+use crate::digest::{concat_digest, Digest, Digestible};
+use ark_bls12_381::Fq;
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{PrimeField, Zero};
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+const HASH_TO_G1_DST: &[u8] = b"ESA_RUST_BLS_SIG_BLS12381G1_TAI";
+const KEYGEN_DST: &[u8] = b"ESA_RUST_BLS_KEYGEN";
+
+fn wide_hash(parts: &[&[u8]]) -> [u8; 64] {
+    let mut state = blake2b_simd::Params::new().hash_length(64).to_state();
+    for part in parts {
+        state.update(part);
+    }
+    let mut out = [0u8; 64];
+    out.copy_from_slice(state.finalize().as_bytes());
+    out
+}
+
+/// Hashes a message to a point in the prime-order subgroup of G1 (try-and-increment).
+pub fn hash_to_g1(msg: &[u8]) -> G1Affine {
+    let mut counter: u32 = 0;
+    loop {
+        let bytes = wide_hash(&[HASH_TO_G1_DST, msg, &counter.to_le_bytes()]);
+        let x = Fq::from_be_bytes_mod_order(&bytes);
+        let greatest = bytes[0] & 1 == 1;
+        if let Some(point) = G1Affine::get_point_from_x(x, greatest) {
+            let point = point.scale_by_cofactor();
+            if !point.is_zero() {
+                return point.into_affine();
+            }
+        }
+        counter += 1;
+    }
+}
+
+/// A BLS secret key.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SigningKey(Fr);
+
+impl SigningKey {
+    /// Derives a signing key from secret seed material.
+    pub fn from_seed(seed: &[u8]) -> Self {
+        let mut counter: u32 = 0;
+        loop {
+            let bytes = wide_hash(&[KEYGEN_DST, seed, &counter.to_le_bytes()]);
+            let sk = Fr::from_be_bytes_mod_order(&bytes);
+            if !sk.is_zero() {
+                return Self(sk);
+            }
+            counter += 1;
+        }
+    }
+
+    pub fn verifying_key(&self) -> VerifyingKey {
+        VerifyingKey(
+            G2Affine::prime_subgroup_generator()
+                .mul(self.0.into_repr())
+                .into_affine(),
+        )
+    }
+
+    /// Signs a message digest.
+    pub fn sign(&self, msg: &Digest) -> Signature {
+        Signature(hash_to_g1(&msg.0).mul(self.0.into_repr()).into_affine())
+    }
+}
+
+impl fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SigningKey(..)")
+    }
+}
+
+/// A BLS public key.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct VerifyingKey(#[serde(with = "serde_impl")] pub G2Affine);
+
+impl VerifyingKey {
+    /// Verifies a signature on a message digest.
+    /// It checks if e(signature, g2) == e(H(msg), pk).
+    pub fn verify(&self, msg: &Digest, signature: &Signature) -> bool {
+        if signature.0.is_zero() || self.0.is_zero() {
+            return false;
+        }
+        let lhs = Curve::pairing(signature.0, G2Affine::prime_subgroup_generator());
+        let rhs = Curve::pairing(hash_to_g1(&msg.0), self.0);
+        lhs == rhs
+    }
+}
+
+/// A BLS signature.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Signature(#[serde(with = "serde_impl")] pub G1Affine);
+
+/// An accumulator value published by the manager, authenticated by a signature over
+/// `(epoch, acc_value)`.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SignedState {
+    pub epoch: u64,
+    #[serde(with = "serde_impl")]
+    pub acc_value: G1Affine,
+    pub signature: Signature,
+}
+
+/// The digest signed for a published `(epoch, acc_value)` pair.
+pub fn state_digest(epoch: u64, acc_value: &G1Affine) -> Digest {
+    concat_digest(
+        [
+            b"SignedState"[..].to_digest(),
+            epoch.to_digest(),
+            acc_value.to_digest(),
+        ]
+        .iter()
+        .copied(),
+    )
+}
+
+impl SignedState {
+    pub fn new(epoch: u64, acc_value: G1Affine, key: &SigningKey) -> Self {
+        Self {
+            epoch,
+            acc_value,
+            signature: key.sign(&state_digest(epoch, &acc_value)),
+        }
+    }
+
+    pub fn verify(&self, vk: &VerifyingKey) -> bool {
+        vk.verify(&state_digest(self.epoch, &self.acc_value), &self.signature)
+    }
+}
+
+/// An update log authenticated by the manager's signature.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SignedUpdateLog {
+    pub log: UpdateLog,
+    pub signature: Signature,
+}
+
+pub(crate) fn update_log_digest(log: &UpdateLog) -> Digest {
+    concat_digest(
+        [b"UpdateLog"[..].to_digest(), log.to_digest()]
+            .iter()
+            .copied(),
+    )
+}
+
+impl SignedUpdateLog {
+    pub fn new(log: UpdateLog, key: &SigningKey) -> Self {
+        let signature = key.sign(&update_log_digest(&log));
+        Self { log, signature }
+    }
+
+    /// Checks the manager's signature. The proofs inside the log are verified separately,
+    /// e.g. by [`UpdateLog::verify_chain`].
+    pub fn verify(&self, vk: &VerifyingKey) -> bool {
+        vk.verify(&update_log_digest(&self.log), &self.signature)
+    }
+}
+
+impl DynamicAccumulator {
+    /// Signs the current `(epoch, acc_value)` pair for publication.
+    pub fn sign_state(&self, key: &SigningKey) -> SignedState {
+        SignedState::new(self.epoch(), self.acc_value, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_hash_to_g1() {
+        let p = hash_to_g1(b"hello");
+        assert!(p.is_on_curve());
+        assert!(p.is_in_correct_subgroup_assuming_on_curve());
+        assert_eq!(p, hash_to_g1(b"hello"));
+        assert_ne!(p, hash_to_g1(b"world"));
+    }
+
+    #[test]
+    fn test_signed_state() {
+        init_logger();
+        let key = SigningKey::from_seed(b"manager");
+        let vk = key.verifying_key();
+        let mut acc = DynamicAccumulator::new();
+        acc.add(&1).unwrap();
+
+        let state = acc.sign_state(&key);
+        assert_eq!(state.epoch, 1);
+        assert!(state.verify(&vk));
+
+        let mut forged = state.clone();
+        forged.epoch = 2;
+        assert!(!forged.verify(&vk));
+        assert!(!state.verify(&SigningKey::from_seed(b"other").verifying_key()));
+
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(serde_json::from_str::<SignedState>(&json).unwrap(), state);
+    }
+
+    #[test]
+    fn test_signed_update_log() {
+        init_logger();
+        let key = SigningKey::from_seed(b"manager");
+        let mut acc = DynamicAccumulator::new();
+        let mut log = UpdateLog::new(0);
+        log.push(acc.add(&1).unwrap());
+        let signed = SignedUpdateLog::new(log, &key);
+        assert!(signed.verify(&key.verifying_key()));
+
+        let mut tampered = signed.clone();
+        tampered.log.start_epoch = 1;
+        assert!(!tampered.verify(&key.verifying_key()));
+    }
+}
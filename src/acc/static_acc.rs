@@ -0,0 +1,106 @@
+//! Set operations between dynamic accumulators and static [`Acc1`] commitments.
+//!
+//! [`Acc1::cal_acc_g1`] commits to a [`MultiSet`] with the same element hashing and public
+//! parameters as [`DynamicAccumulator`], so a static commitment built in batch equals the value of
+//! a dynamic accumulator holding the same elements. [`DynamicAccumulator::from_static`] rebuilds
+//! that state from the multiset, checking it against the commitment, and the
+//! `*_with_static` provers use it as the other operand. Their proofs verify with
+//! [`DynamicAccumulator::verify_intersection`] and [`DynamicAccumulator::verify_union`], with the
+//! static commitment in place of the second accumulator value.
+
+use super::{
+    digest_set::DigestSet,
+    dynamic_accumulator::{DuplicatePolicy, DynamicAccumulator, IntersectionProof, UnionProof},
+    Acc1, Accumulator, Fr, G1Affine,
+};
+use crate::set::{MultiSet, SetElement};
+use anyhow::{bail, Result};
+
+impl DynamicAccumulator {
+    /// The accumulator holding the elements of `set`, at epoch 0. Fails if `commitment` is not
+    /// the static commitment to `set`. Elements with a count above one make it a multiset.
+    pub fn from_static<T: SetElement>(set: &MultiSet<T>, commitment: G1Affine) -> Result<Self> {
+        if Acc1::cal_acc_g1_sk(set) != commitment {
+            bail!("The commitment is not the static accumulator of the set");
+        }
+        let digests: DigestSet<Fr> = DigestSet::new(set);
+        let elements: Vec<Fr> = digests
+            .inner
+            .iter()
+            .flat_map(|(e, count)| std::iter::repeat_n(*e, *count as usize))
+            .collect();
+        let mut acc = if elements.len() > digests.inner.len() {
+            DynamicAccumulator::with_duplicate_policy(DuplicatePolicy::IncrementMultiplicity)
+        } else {
+            DynamicAccumulator::new()
+        };
+        acc.apply_changes(&elements, &[], commitment, 0)?;
+        Ok(acc)
+    }
+
+    /// Proves the intersection with the set behind a static commitment.
+    pub fn prove_intersection_with_static<T: SetElement>(
+        &self,
+        set: &MultiSet<T>,
+        commitment: G1Affine,
+    ) -> Result<(DynamicAccumulator, IntersectionProof)> {
+        self.prove_intersection(&Self::from_static(set, commitment)?)
+    }
+
+    /// Proves the union with the set behind a static commitment.
+    pub fn prove_union_with_static<T: SetElement>(
+        &self,
+        set: &MultiSet<T>,
+        commitment: G1Affine,
+    ) -> Result<(DynamicAccumulator, UnionProof)> {
+        self.prove_union(&Self::from_static(set, commitment)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_static_interop() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        for e in [1, 2, 3] {
+            acc.add(&e).unwrap();
+        }
+        let set = MultiSet::from_vec(vec![2i64, 3, 4]);
+        let commitment = Acc1::cal_acc_g1(&set);
+
+        let (intersection, proof) = acc
+            .prove_intersection_with_static(&set, commitment)
+            .unwrap();
+        assert!(intersection.contains(&2) && intersection.contains(&3));
+        assert!(!intersection.contains(&1) && !intersection.contains(&4));
+        assert!(DynamicAccumulator::verify_intersection(
+            acc.acc_value,
+            commitment,
+            intersection.acc_value,
+            &proof
+        ));
+
+        let (union, proof) = acc.prove_union_with_static(&set, commitment).unwrap();
+        assert!(DynamicAccumulator::verify_union(
+            acc.acc_value,
+            commitment,
+            union.acc_value,
+            &proof
+        ));
+
+        let other = Acc1::cal_acc_g1(&MultiSet::from_vec(vec![5i64]));
+        assert!(acc.prove_intersection_with_static(&set, other).is_err());
+
+        let multiset = MultiSet::from_tuple_vec(vec![(7i64, 2)]);
+        let restored =
+            DynamicAccumulator::from_static(&multiset, Acc1::cal_acc_g1(&multiset)).unwrap();
+        assert_eq!(restored.multiplicity(&7), 2);
+    }
+}
@@ -0,0 +1,339 @@
+//! Size and verification cost of proofs.
+//!
+//! [`Proof::size_bytes`] counts the compressed encoding of every point and field element in a
+//! proof plus its fixed-width integers and digests, without any container framing, so it is a
+//! lower bound on any wire format. [`Proof::pairing_cost`] is the number of pairings (Miller
+//! loops) the verifier computes, the same count the `metrics` feature records. A
+//! [`ProofReport`] collects both across proofs of any type, e.g. for bandwidth budgets or to
+//! track proof sizes in benches.
+
+use super::{
+    aggregate::AggregateMembershipProof,
+    arith::ArithProof,
+    consistency::ConsistencyProof,
+    diff::DiffProof,
+    dynamic_accumulator::{
+        AddProof, BatchAddProof, DeleteProof, IntersectionProof, MembershipProof,
+        NonMembershipProof, UnionProof,
+    },
+    intersection::GcdIntersectionProof,
+    policy::ThresholdMembershipProof,
+};
+use crate::digest::DIGEST_LEN;
+use core::fmt;
+
+/// Bytes of a compressed G1 point.
+pub const G1_BYTES: usize = 48;
+/// Bytes of a compressed G2 point.
+pub const G2_BYTES: usize = 96;
+/// Bytes of a scalar field element.
+pub const FR_BYTES: usize = 32;
+const U64_BYTES: usize = 8;
+
+/// Size and verification cost of a proof.
+pub trait Proof {
+    /// A short name of the proof type.
+    fn kind(&self) -> &'static str;
+
+    /// The number of bytes of the proof's elements, see the [module docs](self).
+    fn size_bytes(&self) -> usize;
+
+    /// The number of pairings needed to verify the proof.
+    fn pairing_cost(&self) -> usize;
+
+    fn stats(&self) -> ProofStats {
+        ProofStats {
+            kind: self.kind(),
+            size_bytes: self.size_bytes(),
+            pairing_cost: self.pairing_cost(),
+        }
+    }
+}
+
+/// The statistics of one proof.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ProofStats {
+    pub kind: &'static str,
+    pub size_bytes: usize,
+    pub pairing_cost: usize,
+}
+
+/// Statistics of a collection of proofs.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct ProofReport {
+    pub entries: Vec<ProofStats>,
+}
+
+impl ProofReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, proof: &dyn Proof) -> &mut Self {
+        self.entries.push(proof.stats());
+        self
+    }
+
+    pub fn total_bytes(&self) -> usize {
+        self.entries.iter().map(|s| s.size_bytes).sum()
+    }
+
+    pub fn total_pairings(&self) -> usize {
+        self.entries.iter().map(|s| s.pairing_cost).sum()
+    }
+}
+
+impl fmt::Display for ProofReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<24} {:>10} {:>10}", "proof", "bytes", "pairings")?;
+        for s in &self.entries {
+            writeln!(
+                f,
+                "{:<24} {:>10} {:>10}",
+                s.kind, s.size_bytes, s.pairing_cost
+            )?;
+        }
+        write!(
+            f,
+            "{:<24} {:>10} {:>10}",
+            "total",
+            self.total_bytes(),
+            self.total_pairings()
+        )
+    }
+}
+
+impl Proof for AddProof {
+    fn kind(&self) -> &'static str {
+        "add"
+    }
+
+    fn size_bytes(&self) -> usize {
+        2 * G1_BYTES + FR_BYTES + U64_BYTES + DIGEST_LEN + self.op_id.map_or(0, |_| U64_BYTES)
+    }
+
+    fn pairing_cost(&self) -> usize {
+        2
+    }
+}
+
+impl Proof for BatchAddProof {
+    fn kind(&self) -> &'static str {
+        "batch_add"
+    }
+
+    fn size_bytes(&self) -> usize {
+        2 * G1_BYTES + self.elements.len() * FR_BYTES + U64_BYTES
+    }
+
+    fn pairing_cost(&self) -> usize {
+        2
+    }
+}
+
+impl Proof for DeleteProof {
+    fn kind(&self) -> &'static str {
+        "delete"
+    }
+
+    fn size_bytes(&self) -> usize {
+        2 * G1_BYTES + FR_BYTES + U64_BYTES + DIGEST_LEN + self.op_id.map_or(0, |_| U64_BYTES)
+    }
+
+    fn pairing_cost(&self) -> usize {
+        2
+    }
+}
+
+impl Proof for MembershipProof {
+    fn kind(&self) -> &'static str {
+        "membership"
+    }
+
+    fn size_bytes(&self) -> usize {
+        G1_BYTES + FR_BYTES + U64_BYTES + 2 * DIGEST_LEN
+    }
+
+    fn pairing_cost(&self) -> usize {
+        2
+    }
+}
+
+impl Proof for NonMembershipProof {
+    fn kind(&self) -> &'static str {
+        "non_membership"
+    }
+
+    fn size_bytes(&self) -> usize {
+        G2_BYTES + G1_BYTES + FR_BYTES + U64_BYTES + 2 * DIGEST_LEN
+    }
+
+    fn pairing_cost(&self) -> usize {
+        3
+    }
+}
+
+impl Proof for IntersectionProof {
+    fn kind(&self) -> &'static str {
+        "intersection"
+    }
+
+    fn size_bytes(&self) -> usize {
+        2 * G2_BYTES + 2 * G1_BYTES + 2 * U64_BYTES
+    }
+
+    fn pairing_cost(&self) -> usize {
+        7
+    }
+}
+
+impl Proof for UnionProof {
+    fn kind(&self) -> &'static str {
+        "union"
+    }
+
+    fn size_bytes(&self) -> usize {
+        G1_BYTES + self.intersection_proof.size_bytes()
+    }
+
+    fn pairing_cost(&self) -> usize {
+        self.intersection_proof.pairing_cost()
+    }
+}
+
+impl Proof for GcdIntersectionProof {
+    fn kind(&self) -> &'static str {
+        "gcd_intersection"
+    }
+
+    fn size_bytes(&self) -> usize {
+        3 * G1_BYTES + 3 * FR_BYTES + 2 * U64_BYTES
+    }
+
+    fn pairing_cost(&self) -> usize {
+        3
+    }
+}
+
+impl Proof for AggregateMembershipProof {
+    fn kind(&self) -> &'static str {
+        "aggregate_membership"
+    }
+
+    fn size_bytes(&self) -> usize {
+        self.proofs.iter().map(Proof::size_bytes).sum()
+    }
+
+    fn pairing_cost(&self) -> usize {
+        self.proofs.len() + 1
+    }
+}
+
+impl Proof for ThresholdMembershipProof {
+    fn kind(&self) -> &'static str {
+        "threshold_membership"
+    }
+
+    fn size_bytes(&self) -> usize {
+        self.blinded_witnesses.len() * G1_BYTES
+            + (2 + self.challenge_coeffs.len() + self.responses.len()) * FR_BYTES
+    }
+
+    fn pairing_cost(&self) -> usize {
+        2 * self.blinded_witnesses.len() + 1
+    }
+}
+
+impl Proof for DiffProof {
+    fn kind(&self) -> &'static str {
+        "diff"
+    }
+
+    fn size_bytes(&self) -> usize {
+        2 * (G1_BYTES + U64_BYTES) + (self.added.len() + self.removed.len()) * FR_BYTES
+    }
+
+    fn pairing_cost(&self) -> usize {
+        2
+    }
+}
+
+impl Proof for ConsistencyProof {
+    fn kind(&self) -> &'static str {
+        "consistency"
+    }
+
+    fn size_bytes(&self) -> usize {
+        2 * U64_BYTES + DIGEST_LEN + 2 * G2_BYTES
+    }
+
+    fn pairing_cost(&self) -> usize {
+        2
+    }
+}
+
+impl Proof for ArithProof {
+    fn kind(&self) -> &'static str {
+        "arith"
+    }
+
+    fn size_bytes(&self) -> usize {
+        2 * G1_BYTES + G2_BYTES
+    }
+
+    fn pairing_cost(&self) -> usize {
+        2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::{dynamic_accumulator::DynamicAccumulator, G1Affine, G2Affine};
+    use ark_ec::AffineCurve;
+    use ark_serialize::CanonicalSerialize;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_proof_stats() {
+        init_logger();
+        assert_eq!(
+            G1Affine::prime_subgroup_generator().serialized_size(),
+            G1_BYTES
+        );
+        assert_eq!(
+            G2Affine::prime_subgroup_generator().serialized_size(),
+            G2_BYTES
+        );
+
+        let mut a = DynamicAccumulator::new();
+        let add = a.add_batch(&[1, 2, 3]).unwrap();
+        let mut b = DynamicAccumulator::new();
+        b.add_batch(&[2, 3, 4]).unwrap();
+        let membership = a.prove_membership(&1).unwrap();
+        let (_, intersection) = a.prove_intersection(&b).unwrap();
+        let (_, gcd_intersection) = a.prove_intersection_gcd(&b).unwrap();
+        assert!(gcd_intersection.size_bytes() < intersection.size_bytes());
+        assert!(gcd_intersection.pairing_cost() < intersection.pairing_cost());
+
+        let mut report = ProofReport::new();
+        report
+            .add(&add)
+            .add(&membership)
+            .add(&a.prove_non_membership(&9).unwrap());
+        assert_eq!(report.entries[1], membership.stats());
+        assert_eq!(report.total_pairings(), 2 + 2 + 3);
+        assert_eq!(
+            report.total_bytes(),
+            (2 * G1_BYTES + 3 * FR_BYTES + 8)
+                + (G1_BYTES + FR_BYTES + 8 + 2 * DIGEST_LEN)
+                + (G2_BYTES + G1_BYTES + FR_BYTES + 8 + 2 * DIGEST_LEN)
+        );
+        assert!(report
+            .to_string()
+            .ends_with(&format!("{:>10} {:>10}", report.total_bytes(), 7)));
+    }
+}
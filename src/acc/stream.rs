@@ -0,0 +1,281 @@
+//! Streaming verification of proof archives.
+//!
+//! An archive is a sequence of records, each a little-endian `u64` length followed by that many
+//! bytes: a kind tag and the [`WireEncode`] encoding of a [`StreamItem`]. [`StreamWriter`] writes
+//! archives and [`verify_stream`] checks one record at a time, reusing a single buffer, so
+//! archives of any size are audited in constant memory.
+//!
+//! A record that does not decode is reported and skipped, since its length is known. A
+//! truncated record, an oversized length or an I/O error ends the stream with an error.
+
+use super::{
+    dynamic_accumulator::{AddProof, DeleteProof, MembershipProof, NonMembershipProof},
+    wire::{EncodingOptions, WireEncode},
+    G1Affine,
+};
+use anyhow::{bail, Context, Result};
+use std::io::{ErrorKind, Read, Write};
+
+/// The largest record [`verify_stream`] accepts, to bound the memory a corrupt length can claim.
+pub const MAX_RECORD_LEN: usize = 1 << 20;
+
+/// A proof together with the public values it is checked against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamItem {
+    Membership {
+        proof: MembershipProof,
+        accumulator: G1Affine,
+    },
+    NonMembership {
+        proof: NonMembershipProof,
+        accumulator: G1Affine,
+    },
+    Add(AddProof),
+    Delete(DeleteProof),
+}
+
+impl StreamItem {
+    pub fn verify(&self) -> bool {
+        match self {
+            StreamItem::Membership { proof, accumulator } => proof.verify(*accumulator),
+            StreamItem::NonMembership { proof, accumulator } => proof.verify(*accumulator),
+            StreamItem::Add(proof) => proof.verify(),
+            StreamItem::Delete(proof) => proof.verify(),
+        }
+    }
+}
+
+impl WireEncode for StreamItem {
+    /// A kind tag (0 to 3, in declaration order), the accumulator value if any, then the proof.
+    fn encode_into(&self, options: &EncodingOptions, buf: &mut Vec<u8>) -> Result<()> {
+        match self {
+            StreamItem::Membership { proof, accumulator } => {
+                buf.push(0);
+                options.write(accumulator, buf)?;
+                proof.encode_into(options, buf)
+            }
+            StreamItem::NonMembership { proof, accumulator } => {
+                buf.push(1);
+                options.write(accumulator, buf)?;
+                proof.encode_into(options, buf)
+            }
+            StreamItem::Add(proof) => {
+                buf.push(2);
+                proof.encode_into(options, buf)
+            }
+            StreamItem::Delete(proof) => {
+                buf.push(3);
+                proof.encode_into(options, buf)
+            }
+        }
+    }
+
+    fn decode_from(reader: &mut &[u8], options: &EncodingOptions) -> Result<Self> {
+        let mut kind = [0u8; 1];
+        reader.read_exact(&mut kind)?;
+        Ok(match kind[0] {
+            0 => StreamItem::Membership {
+                accumulator: options.read(reader)?,
+                proof: MembershipProof::decode_from(reader, options)?,
+            },
+            1 => StreamItem::NonMembership {
+                accumulator: options.read(reader)?,
+                proof: NonMembershipProof::decode_from(reader, options)?,
+            },
+            2 => StreamItem::Add(AddProof::decode_from(reader, options)?),
+            3 => StreamItem::Delete(DeleteProof::decode_from(reader, options)?),
+            k => bail!("Unknown item kind {}", k),
+        })
+    }
+}
+
+/// Appends records to an archive.
+pub struct StreamWriter<W: Write> {
+    writer: W,
+    options: EncodingOptions,
+    buf: Vec<u8>,
+}
+
+impl<W: Write> StreamWriter<W> {
+    pub fn new(writer: W, options: EncodingOptions) -> Self {
+        Self {
+            writer,
+            options,
+            buf: Vec::new(),
+        }
+    }
+
+    pub fn write(&mut self, item: &StreamItem) -> Result<()> {
+        self.buf.clear();
+        item.encode_into(&self.options, &mut self.buf)?;
+        self.writer
+            .write_all(&(self.buf.len() as u64).to_le_bytes())?;
+        self.writer.write_all(&self.buf)?;
+        Ok(())
+    }
+
+    /// Flushes and returns the underlying writer.
+    pub fn finish(mut self) -> Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// What a record turned out to be.
+#[derive(Debug)]
+pub enum Outcome {
+    Valid,
+    Invalid,
+    /// The record could not be decoded.
+    Malformed(anyhow::Error),
+}
+
+/// The result for one record.
+#[derive(Debug)]
+pub struct ItemResult {
+    /// The position of the record in the stream, from 0.
+    pub index: u64,
+    /// The byte offset of the record's length prefix.
+    pub offset: u64,
+    pub outcome: Outcome,
+}
+
+/// Counts of the outcomes in a stream.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct StreamSummary {
+    pub valid: u64,
+    pub invalid: u64,
+    pub malformed: u64,
+}
+
+impl StreamSummary {
+    pub fn total(&self) -> u64 {
+        self.valid + self.invalid + self.malformed
+    }
+
+    /// Returns true if every record decoded and verified.
+    pub fn all_valid(&self) -> bool {
+        self.invalid == 0 && self.malformed == 0
+    }
+}
+
+/// Reads a record length, or `None` at the end of the stream.
+fn read_len(reader: &mut impl Read) -> Result<Option<u64>> {
+    let mut bytes = [0u8; 8];
+    let mut filled = 0;
+    while filled < bytes.len() {
+        match reader.read(&mut bytes[filled..]) {
+            Ok(0) if filled == 0 => return Ok(None),
+            Ok(0) => bail!("Truncated record length"),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(Some(u64::from_le_bytes(bytes)))
+}
+
+/// Verifies every record of an archive, calling `on_item` with each result in order, and
+/// returns the counts.
+pub fn verify_stream(
+    mut reader: impl Read,
+    options: &EncodingOptions,
+    mut on_item: impl FnMut(ItemResult),
+) -> Result<StreamSummary> {
+    let mut summary = StreamSummary::default();
+    let mut buf = Vec::new();
+    let mut offset = 0u64;
+    while let Some(len) = read_len(&mut reader)? {
+        let index = summary.total();
+        if len > MAX_RECORD_LEN as u64 {
+            bail!("Record {} claims {} bytes", index, len);
+        }
+        buf.resize(len as usize, 0);
+        reader
+            .read_exact(&mut buf)
+            .with_context(|| format!("Truncated record {}", index))?;
+        let outcome = match StreamItem::decode(&buf, options) {
+            Ok(item) if item.verify() => {
+                summary.valid += 1;
+                Outcome::Valid
+            }
+            Ok(_) => {
+                summary.invalid += 1;
+                Outcome::Invalid
+            }
+            Err(e) => {
+                summary.malformed += 1;
+                Outcome::Malformed(e)
+            }
+        };
+        on_item(ItemResult {
+            index,
+            offset,
+            outcome,
+        });
+        offset += 8 + len;
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::dynamic_accumulator::DynamicAccumulator;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_verify_stream() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        let add = acc.add(&1).unwrap();
+        acc.add(&2).unwrap();
+        let items = [
+            StreamItem::Add(add.clone()),
+            StreamItem::Membership {
+                proof: acc.prove_membership(&1).unwrap(),
+                accumulator: acc.acc_value,
+            },
+            StreamItem::NonMembership {
+                proof: acc.prove_non_membership(&3).unwrap(),
+                accumulator: acc.acc_value,
+            },
+            StreamItem::Membership {
+                proof: acc.prove_membership(&2).unwrap(),
+                accumulator: add.new_acc_value,
+            },
+        ];
+        let options = EncodingOptions::default();
+        let mut writer = StreamWriter::new(Vec::new(), options);
+        for item in &items {
+            writer.write(item).unwrap();
+        }
+        let mut archive = writer.finish().unwrap();
+        // A record with an unknown kind.
+        archive.extend_from_slice(&1u64.to_le_bytes());
+        archive.push(9);
+
+        let mut results = Vec::new();
+        let summary = verify_stream(&archive[..], &options, |r| results.push(r)).unwrap();
+        assert_eq!(
+            summary,
+            StreamSummary {
+                valid: 3,
+                invalid: 1,
+                malformed: 1
+            }
+        );
+        assert!(matches!(results[3].outcome, Outcome::Invalid));
+        assert!(matches!(results[4].outcome, Outcome::Malformed(_)));
+        assert_eq!(results[4].offset as usize, archive.len() - 9);
+
+        assert!(verify_stream(&archive[..archive.len() - 1], &options, |_| {}).is_err());
+        assert_eq!(
+            verify_stream(&[][..], &options, |_| {}).unwrap(),
+            StreamSummary::default()
+        );
+    }
+}
@@ -0,0 +1,256 @@
+//! SUM aggregate proofs over accumulated (key, value) pairs.
+//!
+//! Every pair is accumulated as the digest of `(key, value)`, and a homomorphic commitment
+//! g1^(sum of values) is maintained in lockstep with inserts and removals. Update proofs
+//! carry both the accumulator proof and the commitment transition, so a verifier that
+//! follows the updates knows the commitment belongs to the accumulated set.
+
+use super::{
+    dynamic_accumulator::{AddProof, DeleteProof, DynamicAccumulator, MembershipProof},
+    handle::ElementHandle,
+    utils::digest_to_prime_field,
+    Fr, G1Affine, G1Projective,
+};
+use crate::digest::{concat_digest, Digestible};
+use anyhow::{anyhow, bail, Result};
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{PrimeField, Zero};
+use std::collections::HashMap;
+
+/// Maps a (key, value) pair to the field element that is accumulated.
+pub fn key_value_to_fr(key: i64, value: u64) -> Fr {
+    digest_to_prime_field(&concat_digest(
+        [key.to_digest(), value.to_digest()].iter().copied(),
+    ))
+}
+
+fn g1_pow_u64(value: u64) -> G1Projective {
+    G1Projective::prime_subgroup_generator().mul(Fr::from(value).into_repr())
+}
+
+/// The accumulator part of a [`SumUpdateProof`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SumOp {
+    Insert(AddProof),
+    Remove(DeleteProof),
+}
+
+/// A proof that an insert or removal updated both the accumulator and the sum commitment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SumUpdateProof {
+    pub key: i64,
+    pub value: u64,
+    pub old_sum_commitment: G1Affine,
+    pub new_sum_commitment: G1Affine,
+    pub op: SumOp,
+}
+
+impl SumUpdateProof {
+    /// Verifies that the accumulator proof is valid for the accumulated `(key, value)` pair and
+    /// that the sum commitment moved by exactly `value` in the matching direction.
+    pub fn verify(&self) -> bool {
+        let fr_element = key_value_to_fr(self.key, self.value);
+        let delta = g1_pow_u64(self.value);
+        let (acc_ok, expected) = match &self.op {
+            SumOp::Insert(proof) => (
+                proof.element == ElementHandle(fr_element) && proof.verify(),
+                self.old_sum_commitment.into_projective() + delta,
+            ),
+            SumOp::Remove(proof) => (
+                proof.element == ElementHandle(fr_element) && proof.verify(),
+                self.old_sum_commitment.into_projective() - delta,
+            ),
+        };
+        acc_ok && expected.into_affine() == self.new_sum_commitment
+    }
+}
+
+/// A proof that the values accumulated under `acc_value` sum up to `total`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SumProof {
+    pub acc_value: G1Affine,
+    pub sum_commitment: G1Affine,
+    pub total: u64,
+}
+
+impl SumProof {
+    /// Verifies the claimed total against a trusted (accumulator, sum commitment) pair.
+    pub fn verify(&self, acc_value: G1Affine, sum_commitment: G1Affine) -> bool {
+        self.acc_value == acc_value
+            && self.sum_commitment == sum_commitment
+            && g1_pow_u64(self.total).into_affine() == sum_commitment
+    }
+}
+
+/// An accumulator of (key, value) pairs with a homomorphic commitment to the sum of values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SumAccumulator {
+    /// The accumulated pairs, only mutated by [`Self::insert`] and [`Self::remove`] so that
+    /// they stay in lockstep with `sum_commitment`.
+    acc: DynamicAccumulator,
+    /// g1^(sum of values), updated in lockstep with `acc`.
+    sum_commitment: G1Affine,
+    entries: HashMap<i64, u64>,
+    total: u64,
+}
+
+impl SumAccumulator {
+    /// Creates an empty accumulator whose sum commitment is g1^0.
+    pub fn new() -> Self {
+        Self {
+            acc: DynamicAccumulator::new(),
+            sum_commitment: G1Projective::zero().into_affine(),
+            entries: HashMap::new(),
+            total: 0,
+        }
+    }
+
+    /// Inserts a new (key, value) pair. Returns an error if the key already exists or the
+    /// total would overflow.
+    pub fn insert(&mut self, key: i64, value: u64) -> Result<SumUpdateProof> {
+        if self.entries.contains_key(&key) {
+            bail!("Key already in accumulator");
+        }
+        let total = self
+            .total
+            .checked_add(value)
+            .ok_or_else(|| anyhow!("Sum overflow"))?;
+        let add_proof = self.acc.add_fr(key_value_to_fr(key, value))?;
+
+        let old_sum_commitment = self.sum_commitment;
+        self.sum_commitment =
+            (old_sum_commitment.into_projective() + g1_pow_u64(value)).into_affine();
+        self.entries.insert(key, value);
+        self.total = total;
+
+        Ok(SumUpdateProof {
+            key,
+            value,
+            old_sum_commitment,
+            new_sum_commitment: self.sum_commitment,
+            op: SumOp::Insert(add_proof),
+        })
+    }
+
+    /// Removes the pair stored under `key`. Returns an error if the key is not present.
+    pub fn remove(&mut self, key: &i64) -> Result<SumUpdateProof> {
+        let value = *self
+            .entries
+            .get(key)
+            .ok_or_else(|| anyhow!("Key not in accumulator"))?;
+        let delete_proof = self.acc.delete_fr(key_value_to_fr(*key, value))?;
+
+        let old_sum_commitment = self.sum_commitment;
+        self.sum_commitment =
+            (old_sum_commitment.into_projective() - g1_pow_u64(value)).into_affine();
+        self.entries.remove(key);
+        self.total -= value;
+
+        Ok(SumUpdateProof {
+            key: *key,
+            value,
+            old_sum_commitment,
+            new_sum_commitment: self.sum_commitment,
+            op: SumOp::Remove(delete_proof),
+        })
+    }
+
+    /// Returns the current accumulator value over the (key, value) pairs.
+    pub fn acc_value(&self) -> G1Affine {
+        self.acc.acc_value
+    }
+
+    /// Returns the current sum commitment g1^(sum of values).
+    pub fn sum_commitment(&self) -> G1Affine {
+        self.sum_commitment
+    }
+
+    /// Returns the current epoch, i.e. the number of inserts and removals applied so far.
+    pub fn epoch(&self) -> u64 {
+        self.acc.epoch()
+    }
+
+    /// Returns the value stored under `key`, if any.
+    pub fn get(&self, key: &i64) -> Option<u64> {
+        self.entries.get(key).copied()
+    }
+
+    /// Returns the current sum of all values.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Proves that the current values sum up to [`Self::total`].
+    pub fn prove_sum(&self) -> SumProof {
+        SumProof {
+            acc_value: self.acc.acc_value,
+            sum_commitment: self.sum_commitment,
+            total: self.total,
+        }
+    }
+
+    /// Proves that `key` is accumulated together with its current value.
+    pub fn prove_entry(&self, key: &i64) -> Result<(u64, MembershipProof)> {
+        let value = self
+            .get(key)
+            .ok_or_else(|| anyhow!("Key not in accumulator"))?;
+        let proof = self.acc.prove_membership_fr(key_value_to_fr(*key, value))?;
+        Ok((value, proof))
+    }
+}
+
+impl Default for SumAccumulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_sum_proof() {
+        init_logger();
+        let mut acc = SumAccumulator::new();
+        let p1 = acc.insert(1, 100).unwrap();
+        let p2 = acc.insert(2, 250).unwrap();
+        assert!(p1.verify());
+        assert!(p2.verify());
+        assert!(acc.insert(1, 5).is_err());
+        assert_eq!(acc.epoch(), 2);
+        assert_eq!(p2.new_sum_commitment, acc.sum_commitment());
+
+        let proof = acc.prove_sum();
+        assert_eq!(proof.total, 350);
+        assert!(proof.verify(acc.acc_value(), acc.sum_commitment()));
+
+        let p3 = acc.remove(&1).unwrap();
+        assert!(p3.verify());
+        assert_eq!(acc.total(), 250);
+        assert_eq!(acc.epoch(), 3);
+        assert!(acc
+            .prove_sum()
+            .verify(acc.acc_value(), acc.sum_commitment()));
+
+        // A stale total does not match the new commitment.
+        assert!(!proof.verify(acc.acc_value(), acc.sum_commitment()));
+    }
+
+    #[test]
+    fn test_tampered_update() {
+        init_logger();
+        let mut acc = SumAccumulator::new();
+        let mut proof = acc.insert(7, 42).unwrap();
+        proof.value = 43;
+        assert!(!proof.verify());
+
+        let (value, membership) = acc.prove_entry(&7).unwrap();
+        assert_eq!(value, 42);
+        assert!(membership.verify(acc.acc_value()));
+    }
+}
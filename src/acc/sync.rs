@@ -0,0 +1,297 @@
+//! Light-client synchronization with a remote accumulator manager.
+//!
+//! A [`LightClient`] starts from a trusted [`SignedState`] and follows the manager through
+//! [`SyncMessage`]s, each a signed update log plus the signed state it ends in. It never holds
+//! the element set or the trapdoor: updates are checked with their proofs, tracked membership
+//! witnesses are fast-forwarded with the public update rules, and every signed state seen is
+//! remembered so that rollbacks and forks (two signed values for one epoch) are detected.
+
+use super::{
+    dynamic_accumulator::MembershipProof,
+    signing::{SignedState, SignedUpdateLog, VerifyingKey},
+    update::Update,
+    utils::digest_to_prime_field,
+    Fr, G1Affine,
+};
+use crate::digest::Digestible;
+use core::fmt;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// A message published by the manager: the updates since the client's epoch and the signed
+/// state they lead to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncMessage {
+    pub log: SignedUpdateLog,
+    pub state: SignedState,
+}
+
+/// Errors raised while synchronizing a light client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncError {
+    /// A signature did not verify under the manager's key.
+    InvalidSignature,
+    /// The message is for an epoch older than the client's current epoch.
+    Rollback { current: u64, received: u64 },
+    /// Two different signed values were seen for the same epoch.
+    Fork { epoch: u64 },
+    /// The update log does not start at the client's current epoch.
+    Gap { expected: u64, received: u64 },
+    /// The update log does not lead to the signed state, or contains an invalid proof.
+    InvalidUpdate(String),
+    /// A tracked witness is not valid for the current state.
+    InvalidWitness,
+}
+
+impl fmt::Display for SyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyncError::InvalidSignature => write!(f, "invalid manager signature"),
+            SyncError::Rollback { current, received } => write!(
+                f,
+                "rollback detected: received epoch {} while at epoch {}",
+                received, current
+            ),
+            SyncError::Fork { epoch } => {
+                write!(
+                    f,
+                    "fork detected: conflicting signed values at epoch {}",
+                    epoch
+                )
+            }
+            SyncError::Gap { expected, received } => write!(
+                f,
+                "update log starts at epoch {}, expected {}",
+                received, expected
+            ),
+            SyncError::InvalidUpdate(reason) => write!(f, "invalid update: {}", reason),
+            SyncError::InvalidWitness => write!(f, "witness is not valid for the current state"),
+        }
+    }
+}
+
+impl std::error::Error for SyncError {}
+
+/// A client that tracks a remote accumulator's value from a trusted checkpoint.
+#[derive(Debug, Clone)]
+pub struct LightClient {
+    vk: VerifyingKey,
+    latest: SignedState,
+    /// Signed values seen so far, by epoch.
+    history: BTreeMap<u64, G1Affine>,
+    witnesses: HashMap<Fr, MembershipProof>,
+}
+
+impl LightClient {
+    /// Creates a client trusting `checkpoint`, which must be signed by `vk`.
+    pub fn new(trusted_checkpoint: SignedState, vk: VerifyingKey) -> Result<Self, SyncError> {
+        if !trusted_checkpoint.verify(&vk) {
+            return Err(SyncError::InvalidSignature);
+        }
+        let mut history = BTreeMap::new();
+        history.insert(trusted_checkpoint.epoch, trusted_checkpoint.acc_value);
+        Ok(Self {
+            vk,
+            latest: trusted_checkpoint,
+            history,
+            witnesses: HashMap::new(),
+        })
+    }
+
+    /// The accumulator value of the latest verified state.
+    pub fn current_value(&self) -> G1Affine {
+        self.latest.acc_value
+    }
+
+    /// The epoch of the latest verified state.
+    pub fn current_epoch(&self) -> u64 {
+        self.latest.epoch
+    }
+
+    /// The latest verified signed state, e.g. to hand to other clients as a checkpoint.
+    pub fn latest_state(&self) -> &SignedState {
+        &self.latest
+    }
+
+    /// Records a signed state seen out of band (e.g. gossiped by a peer) and reports a fork if
+    /// it conflicts with a value already seen for the same epoch.
+    pub fn observe(&mut self, state: &SignedState) -> Result<(), SyncError> {
+        if !state.verify(&self.vk) {
+            return Err(SyncError::InvalidSignature);
+        }
+        match self.history.get(&state.epoch) {
+            Some(value) if *value != state.acc_value => Err(SyncError::Fork { epoch: state.epoch }),
+            _ => {
+                self.history.insert(state.epoch, state.acc_value);
+                Ok(())
+            }
+        }
+    }
+
+    /// Verifies a sync message and advances the client to the signed state it carries,
+    /// fast-forwarding every tracked witness. Witnesses of deleted elements are dropped.
+    /// On error the client is left unchanged.
+    pub fn apply_update(&mut self, msg: &SyncMessage) -> Result<(), SyncError> {
+        if !msg.state.verify(&self.vk) || !msg.log.verify(&self.vk) {
+            return Err(SyncError::InvalidSignature);
+        }
+        if let Some(value) = self.history.get(&msg.state.epoch) {
+            if *value != msg.state.acc_value {
+                return Err(SyncError::Fork {
+                    epoch: msg.state.epoch,
+                });
+            }
+        }
+        if msg.state.epoch < self.current_epoch() {
+            return Err(SyncError::Rollback {
+                current: self.current_epoch(),
+                received: msg.state.epoch,
+            });
+        }
+
+        let log = &msg.log.log;
+        if log.start_epoch != self.current_epoch() {
+            return Err(SyncError::Gap {
+                expected: self.current_epoch(),
+                received: log.start_epoch,
+            });
+        }
+        let end_value = log
+            .verify_chain(self.current_value())
+            .map_err(|e| SyncError::InvalidUpdate(e.to_string()))?;
+        if end_value != msg.state.acc_value || log.end_epoch() != msg.state.epoch {
+            return Err(SyncError::InvalidUpdate(
+                "update log does not lead to the signed state".to_owned(),
+            ));
+        }
+
+        let mut witnesses = self.witnesses.clone();
+        for update in &log.updates {
+            witnesses.retain(|_, proof| match update {
+                Update::Add(add) => proof.update_on_add(add).is_ok(),
+                Update::Delete(delete) => proof.update_on_delete(delete).is_ok(),
+            });
+        }
+
+        self.witnesses = witnesses;
+        self.history.insert(msg.state.epoch, msg.state.acc_value);
+        self.latest = msg.state.clone();
+        Ok(())
+    }
+
+    /// Starts tracking a membership witness. It must be valid for the current state.
+    pub fn track_witness(&mut self, proof: MembershipProof) -> Result<(), SyncError> {
+        if !proof.verify_at(self.current_value(), self.current_epoch()) {
+            return Err(SyncError::InvalidWitness);
+        }
+        self.witnesses.insert(proof.element.0, proof);
+        Ok(())
+    }
+
+    /// Returns the fast-forwarded witness of `element`, if it is tracked and still a member.
+    pub fn witness(&self, element: &i64) -> Option<&MembershipProof> {
+        let fr_element: Fr = digest_to_prime_field(&element.to_digest());
+        self.witnesses.get(&fr_element)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::{
+        dynamic_accumulator::DynamicAccumulator, signing::SigningKey, update::UpdateLog,
+    };
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    fn sync_message(
+        acc: &mut DynamicAccumulator,
+        key: &SigningKey,
+        ops: impl FnOnce(&mut DynamicAccumulator, &mut UpdateLog),
+    ) -> SyncMessage {
+        let mut log = UpdateLog::new(acc.epoch());
+        ops(acc, &mut log);
+        SyncMessage {
+            log: SignedUpdateLog::new(log, key),
+            state: acc.sign_state(key),
+        }
+    }
+
+    #[test]
+    fn test_light_client_follows_manager() {
+        init_logger();
+        let key = SigningKey::from_seed(b"manager");
+        let mut manager = DynamicAccumulator::new();
+        manager.add_batch(&[1, 2]).unwrap();
+
+        let mut client = LightClient::new(manager.sign_state(&key), key.verifying_key()).unwrap();
+        client
+            .track_witness(manager.prove_membership(&1).unwrap())
+            .unwrap();
+        client
+            .track_witness(manager.prove_membership(&2).unwrap())
+            .unwrap();
+
+        let msg = sync_message(&mut manager, &key, |acc, log| {
+            log.push(acc.add(&3).unwrap());
+            log.push(acc.delete(&2).unwrap());
+        });
+        client.apply_update(&msg).unwrap();
+        assert_eq!(client.current_value(), manager.acc_value);
+        assert_eq!(client.current_epoch(), manager.epoch());
+
+        let witness = client.witness(&1).unwrap();
+        assert!(manager.verify_membership(witness));
+        assert!(client.witness(&2).is_none());
+
+        // Replaying the message is rejected since the log no longer starts at the client's epoch.
+        assert_eq!(
+            client.apply_update(&msg),
+            Err(SyncError::Gap {
+                expected: 4,
+                received: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_fork_and_rollback_detection() {
+        init_logger();
+        let key = SigningKey::from_seed(b"manager");
+        let mut manager = DynamicAccumulator::new();
+        let checkpoint = manager.sign_state(&key);
+        let mut client = LightClient::new(checkpoint.clone(), key.verifying_key()).unwrap();
+
+        let mut other = manager.clone();
+        let msg = sync_message(&mut manager, &key, |acc, log| {
+            log.push(acc.add(&1).unwrap())
+        });
+        let fork = sync_message(&mut other, &key, |acc, log| log.push(acc.add(&2).unwrap()));
+
+        client.apply_update(&msg).unwrap();
+        assert_eq!(
+            client.observe(&fork.state),
+            Err(SyncError::Fork { epoch: 1 })
+        );
+
+        let stale = SyncMessage {
+            log: SignedUpdateLog::new(UpdateLog::new(0), &key),
+            state: checkpoint,
+        };
+        assert_eq!(
+            client.apply_update(&stale),
+            Err(SyncError::Rollback {
+                current: 1,
+                received: 0
+            })
+        );
+
+        let forged = SigningKey::from_seed(b"attacker");
+        let bad = sync_message(&mut manager, &forged, |acc, log| {
+            log.push(acc.add(&3).unwrap())
+        });
+        assert_eq!(client.apply_update(&bad), Err(SyncError::InvalidSignature));
+    }
+}
@@ -0,0 +1,324 @@
+//! Reversible deletion with tombstones.
+//!
+//! A [`TombstoneAccumulator`] wraps a [`DynamicAccumulator`] and remembers, for every deleted
+//! element, the add proof that introduced it and the delete proof that removed it. An element
+//! can later be restored with [`TombstoneAccumulator::undelete`], whose proof links the restore
+//! back to the original add. The accumulator value of every epoch is kept as well, so membership
+//! at a past epoch can still be proven after the element has been deleted, and a
+//! [`DeletionProof`] shows that an element was removed at an epoch and is still absent.
+
+use super::{
+    commitment::params_id,
+    dynamic_accumulator::{
+        AddProof, DeleteProof, DynamicAccumulator, MembershipProof, NonMembershipProof,
+    },
+    handle::ElementHandle,
+    trapdoor,
+    utils::digest_to_prime_field,
+    Fr, G1Affine,
+};
+use crate::digest::Digestible;
+use anyhow::{anyhow, bail, Result};
+use std::collections::HashMap;
+
+/// The evidence kept for a deleted element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tombstone {
+    /// The proof of the add that introduced the element.
+    pub add_proof: AddProof,
+    /// The proof of the delete that removed it.
+    pub delete_proof: DeleteProof,
+}
+
+impl Tombstone {
+    /// Checks that both proofs are valid, are for the same element and are in order.
+    pub fn verify(&self) -> bool {
+        self.add_proof.element == self.delete_proof.element
+            && self.add_proof.epoch < self.delete_proof.epoch
+            && self.add_proof.verify()
+            && self.delete_proof.verify()
+    }
+}
+
+/// A proof that a previously deleted element was restored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UndeleteProof {
+    pub tombstone: Tombstone,
+    /// The proof of the add that restored the element.
+    pub restore_proof: AddProof,
+}
+
+impl UndeleteProof {
+    /// Verifies that the restore re-adds the element of a valid tombstone after its deletion.
+    pub fn verify(&self) -> bool {
+        self.tombstone.verify()
+            && self.restore_proof.element == self.tombstone.delete_proof.element
+            && self.restore_proof.epoch > self.tombstone.delete_proof.epoch
+            && self.restore_proof.verify()
+    }
+}
+
+/// A proof that an element was deleted at an epoch and has not been added back since.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeletionProof {
+    /// The proof of the delete that removed the element.
+    pub delete_proof: DeleteProof,
+    /// A proof that the element is not in the current accumulator.
+    pub non_membership: NonMembershipProof,
+}
+
+impl DeletionProof {
+    /// The epoch the element was deleted at.
+    pub fn deleted_at(&self) -> u64 {
+        self.delete_proof.epoch
+    }
+
+    /// Verifies the proof against the published accumulator value of the deletion epoch and the
+    /// current one.
+    pub fn verify(&self, deleted_value: G1Affine, current_value: G1Affine) -> bool {
+        self.delete_proof.new_acc_value == deleted_value
+            && self.non_membership.element == self.delete_proof.element
+            && self.non_membership.epoch >= self.delete_proof.epoch
+            && self.delete_proof.verify()
+            && self.non_membership.verify(current_value)
+    }
+}
+
+/// A dynamic accumulator whose deletions can be reverted with evidence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TombstoneAccumulator {
+    acc: DynamicAccumulator,
+    /// `history[i]` is the accumulator value at epoch i.
+    history: Vec<G1Affine>,
+    /// The add proof of every current member.
+    adds: HashMap<Fr, AddProof>,
+    tombstones: HashMap<Fr, Tombstone>,
+    /// Epoch ranges `[added, deleted)` during which each element was a member.
+    lifetimes: HashMap<Fr, Vec<(u64, Option<u64>)>>,
+}
+
+impl TombstoneAccumulator {
+    pub fn new() -> Self {
+        let acc = DynamicAccumulator::new();
+        let history = vec![acc.acc_value];
+        Self {
+            acc,
+            history,
+            adds: HashMap::new(),
+            tombstones: HashMap::new(),
+            lifetimes: HashMap::new(),
+        }
+    }
+
+    /// The wrapped accumulator, e.g. to prove current (non-)membership.
+    pub fn acc(&self) -> &DynamicAccumulator {
+        &self.acc
+    }
+
+    /// The accumulator value at the given epoch.
+    pub fn value_at(&self, epoch: u64) -> Option<G1Affine> {
+        self.history.get(epoch as usize).copied()
+    }
+
+    /// Adds an element. Any tombstone left by an earlier deletion is discarded.
+    pub fn add(&mut self, element: &i64) -> Result<AddProof> {
+        let fr_element = digest_to_prime_field(&element.to_digest());
+        let proof = self.acc.add_fr(fr_element)?;
+        self.tombstones.remove(&fr_element);
+        self.record_add(&proof);
+        Ok(proof)
+    }
+
+    /// Deletes an element and records a tombstone for it.
+    pub fn delete(&mut self, element: &i64) -> Result<DeleteProof> {
+        let fr_element = digest_to_prime_field(&element.to_digest());
+        let add_proof = self
+            .adds
+            .get(&fr_element)
+            .cloned()
+            .ok_or_else(|| anyhow!("Element not in accumulator"))?;
+        let delete_proof = self.acc.delete_fr(fr_element)?;
+        self.history.push(self.acc.acc_value);
+        self.adds.remove(&fr_element);
+        if let Some((_, end)) = self
+            .lifetimes
+            .get_mut(&fr_element)
+            .and_then(|ranges| ranges.last_mut())
+        {
+            *end = Some(delete_proof.epoch);
+        }
+        self.tombstones.insert(
+            fr_element,
+            Tombstone {
+                add_proof,
+                delete_proof: delete_proof.clone(),
+            },
+        );
+        Ok(delete_proof)
+    }
+
+    /// Returns the tombstone of a deleted element, if any.
+    pub fn tombstone(&self, element: &i64) -> Option<&Tombstone> {
+        self.tombstones
+            .get(&digest_to_prime_field(&element.to_digest()))
+    }
+
+    /// Restores a deleted element. The proof links the restore to the original add proof.
+    /// Returns an error if the element has no tombstone.
+    pub fn undelete(&mut self, element: &i64) -> Result<UndeleteProof> {
+        let fr_element = digest_to_prime_field(&element.to_digest());
+        let tombstone = self
+            .tombstones
+            .get(&fr_element)
+            .cloned()
+            .ok_or_else(|| anyhow!("Element has no tombstone"))?;
+        let restore_proof = self.acc.add_fr(fr_element)?;
+        self.tombstones.remove(&fr_element);
+        self.record_add(&restore_proof);
+        Ok(UndeleteProof {
+            tombstone,
+            restore_proof,
+        })
+    }
+
+    /// Proves that `element` was deleted at `epoch` and is still absent. Returns an error if its
+    /// latest deletion happened at another epoch or it has been added back.
+    pub fn prove_deleted(&self, element: &i64, epoch: u64) -> Result<DeletionProof> {
+        let tombstone = self
+            .tombstone(element)
+            .ok_or_else(|| anyhow!("Element is not deleted"))?;
+        if tombstone.delete_proof.epoch != epoch {
+            bail!(
+                "Element was deleted at epoch {}, not {}",
+                tombstone.delete_proof.epoch,
+                epoch
+            );
+        }
+        Ok(DeletionProof {
+            delete_proof: tombstone.delete_proof.clone(),
+            non_membership: self.acc.prove_non_membership(element)?,
+        })
+    }
+
+    /// Proves that `element` was a member of the accumulator at `epoch`, even if it has been
+    /// deleted since. The proof verifies against [`Self::value_at`] for that epoch.
+    pub fn prove_was_member_at(&self, element: &i64, epoch: u64) -> Result<MembershipProof> {
+        let fr_element = digest_to_prime_field(&element.to_digest());
+        let head = self
+            .value_at(epoch)
+            .ok_or_else(|| anyhow!("Epoch {} is in the future", epoch))?;
+        let was_member = self.lifetimes.get(&fr_element).is_some_and(|ranges| {
+            ranges
+                .iter()
+                .any(|(start, end)| *start <= epoch && end.is_none_or(|end| epoch < end))
+        });
+        if !was_member {
+            bail!("Element was not a member at epoch {}", epoch);
+        }
+        // witness = head^((s-element)^-1)
+        let witness = trapdoor::signer().div_s_minus(head, fr_element)?;
+        Ok(MembershipProof {
+            witness,
+            element: ElementHandle(fr_element),
+            epoch,
+            acc_digest: head.to_digest(),
+            params_id: params_id(),
+        })
+    }
+
+    fn record_add(&mut self, proof: &AddProof) {
+        self.history.push(self.acc.acc_value);
+        self.adds.insert(proof.element.0, proof.clone());
+        self.lifetimes
+            .entry(proof.element.0)
+            .or_default()
+            .push((proof.epoch, None));
+    }
+}
+
+impl Default for TombstoneAccumulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_undelete() {
+        init_logger();
+        let mut acc = TombstoneAccumulator::new();
+        let add_proof = acc.add(&1).unwrap();
+        acc.add(&2).unwrap();
+        acc.delete(&1).unwrap();
+        assert!(acc.acc().prove_non_membership(&1).is_ok());
+        assert_eq!(acc.tombstone(&1).unwrap().add_proof, add_proof);
+
+        let proof = acc.undelete(&1).unwrap();
+        assert!(proof.verify());
+        assert_eq!(proof.tombstone.add_proof, add_proof);
+        assert!(acc.tombstone(&1).is_none());
+        assert!(acc.acc().prove_membership(&1).is_ok());
+        assert!(acc.undelete(&1).is_err());
+        assert!(acc.undelete(&2).is_err());
+
+        // A restore that precedes the deletion is rejected.
+        let mut reordered = proof.clone();
+        reordered.restore_proof = add_proof;
+        assert!(!reordered.verify());
+    }
+
+    #[test]
+    fn test_prove_was_member_at() {
+        init_logger();
+        let mut acc = TombstoneAccumulator::new();
+        acc.add(&1).unwrap(); // epoch 1
+        acc.add(&2).unwrap(); // epoch 2
+        acc.delete(&1).unwrap(); // epoch 3
+        acc.undelete(&1).unwrap(); // epoch 4
+
+        for epoch in [1, 2, 4].iter() {
+            let proof = acc.prove_was_member_at(&1, *epoch).unwrap();
+            assert!(proof.verify_at(acc.value_at(*epoch).unwrap(), *epoch));
+        }
+        assert!(acc.prove_was_member_at(&1, 0).is_err());
+        assert!(acc.prove_was_member_at(&1, 3).is_err());
+        assert!(acc.prove_was_member_at(&1, 5).is_err());
+
+        let proof = acc.prove_was_member_at(&2, 3).unwrap();
+        assert!(!proof.verify_at(acc.value_at(2).unwrap(), 2));
+    }
+
+    #[test]
+    fn test_prove_deleted() {
+        init_logger();
+        let mut acc = TombstoneAccumulator::new();
+        acc.add(&1).unwrap(); // epoch 1
+        acc.add(&2).unwrap(); // epoch 2
+        acc.delete(&1).unwrap(); // epoch 3
+        acc.add(&3).unwrap(); // epoch 4
+
+        let proof = acc.prove_deleted(&1, 3).unwrap();
+        assert_eq!(proof.deleted_at(), 3);
+        let deleted_value = acc.value_at(3).unwrap();
+        assert!(proof.verify(deleted_value, acc.acc().acc_value));
+        assert!(!proof.verify(acc.value_at(2).unwrap(), acc.acc().acc_value));
+        assert!(acc.prove_deleted(&1, 2).is_err());
+        assert!(acc.prove_deleted(&2, 3).is_err());
+
+        // The non-membership proof must be for the deleted element.
+        let mut mixed = proof.clone();
+        mixed.non_membership = acc.acc().prove_non_membership(&5).unwrap();
+        assert!(!mixed.verify(deleted_value, acc.acc().acc_value));
+
+        acc.undelete(&1).unwrap();
+        assert!(acc.prove_deleted(&1, 3).is_err());
+        assert!(!proof.verify(deleted_value, acc.acc().acc_value));
+    }
+}
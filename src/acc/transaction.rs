@@ -0,0 +1,175 @@
+//! Two-phase commit of accumulator mutations.
+//!
+//! To keep the published accumulator value in step with rows of an external database, the
+//! accumulator takes part in the database transaction as a participant:
+//!
+//! 1. [`DynamicAccumulator::prepare`] applies the mutations to a fork and freezes the
+//!    accumulator, so nothing else can change it. The [`Prepared`] result holds the new value,
+//!    epoch and update proofs, to be written in the database transaction.
+//! 2. Once the transaction is decided, [`DynamicAccumulator::commit`] installs the prepared
+//!    state or [`DynamicAccumulator::abort`] drops it; both reopen the accumulator.
+//!
+//! A prepare that fails, e.g. because a deleted element is not accumulated, leaves the
+//! accumulator unchanged and mutable. Every prepare gets a unique transaction ID, which the
+//! accumulator keeps while frozen. Commit and abort only accept the [`Prepared`] of that
+//! transaction, so a prepared state cannot be installed twice, into another accumulator, or
+//! after the accumulator was reopened behind the transaction's back.
+
+use super::{
+    dynamic_accumulator::DynamicAccumulator, lifecycle::LifecycleError, update::Update, G1Affine,
+};
+use anyhow::{bail, Result};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static NEXT_TRANSACTION: AtomicU64 = AtomicU64::new(0);
+
+/// A mutation requested in a transaction.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Mutation {
+    Add(i64),
+    Delete(i64),
+}
+
+/// Mutations applied to a fork of the accumulator, waiting to be committed or aborted.
+#[derive(Debug)]
+#[must_use = "a prepared transaction keeps the accumulator frozen until committed or aborted"]
+pub struct Prepared {
+    id: u64,
+    base_value: G1Affine,
+    base_epoch: u64,
+    staged: DynamicAccumulator,
+    updates: Vec<Update>,
+}
+
+impl Prepared {
+    /// The transaction ID.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// The accumulator value after the commit.
+    pub fn new_value(&self) -> G1Affine {
+        self.staged.acc_value
+    }
+
+    /// The epoch after the commit.
+    pub fn new_epoch(&self) -> u64 {
+        self.staged.epoch()
+    }
+
+    /// The proofs of the mutations, in order.
+    pub fn updates(&self) -> &[Update] {
+        &self.updates
+    }
+}
+
+impl DynamicAccumulator {
+    /// Applies `mutations` to a fork and freezes the accumulator until [`Self::commit`] or
+    /// [`Self::abort`]. Fails without any effect if a mutation fails.
+    pub fn prepare(&mut self, mutations: &[Mutation]) -> Result<Prepared> {
+        self.ensure_mutable()?;
+        let mut staged = self.fork();
+        let updates = mutations
+            .iter()
+            .map(|m| match m {
+                Mutation::Add(element) => staged.add(element).map(Update::from),
+                Mutation::Delete(element) => staged.delete(element).map(Update::from),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.freeze()?;
+        let id = NEXT_TRANSACTION.fetch_add(1, Ordering::Relaxed);
+        self.transaction = Some(id);
+        Ok(Prepared {
+            id,
+            base_value: self.acc_value,
+            base_epoch: self.epoch(),
+            staged,
+            updates,
+        })
+    }
+
+    /// Installs the prepared state and reopens the accumulator. Returns the update proofs.
+    pub fn commit(&mut self, prepared: Prepared) -> Result<Vec<Update>> {
+        self.ensure_prepared(&prepared)?;
+        *self = prepared.staged;
+        self.transaction = None;
+        Ok(prepared.updates)
+    }
+
+    /// Drops the prepared state and reopens the accumulator.
+    pub fn abort(&mut self, prepared: Prepared) -> Result<()> {
+        self.ensure_prepared(&prepared)?;
+        Ok(self.reopen()?)
+    }
+
+    fn ensure_prepared(&self, prepared: &Prepared) -> Result<()> {
+        if !self.is_frozen() {
+            bail!(LifecycleError::NotFrozen);
+        }
+        if self.transaction != Some(prepared.id) {
+            bail!(
+                "The accumulator is not frozen by transaction {}",
+                prepared.id
+            );
+        }
+        if self.acc_value != prepared.base_value || self.epoch() != prepared.base_epoch {
+            bail!("The accumulator changed since the transaction was prepared");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_two_phase_commit() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        acc.add(&1).unwrap();
+        let before = acc.clone();
+
+        let prepared = acc
+            .prepare(&[Mutation::Add(2), Mutation::Delete(1)])
+            .unwrap();
+        assert!(acc.add(&3).is_err());
+        assert!(acc.prepare(&[Mutation::Add(3)]).is_err());
+        let (value, epoch) = (prepared.new_value(), prepared.new_epoch());
+        let updates = acc.commit(prepared).unwrap();
+        assert_eq!(updates.len(), 2);
+        assert_eq!((acc.acc_value, acc.epoch()), (value, epoch));
+        assert!(acc.contains(&2) && !acc.contains(&1));
+        assert!(!acc.is_frozen());
+
+        let mut aborted = before.clone();
+        let prepared = aborted.prepare(&[Mutation::Add(2)]).unwrap();
+        aborted.abort(prepared).unwrap();
+        assert_eq!(aborted, before);
+        aborted.add(&3).unwrap();
+
+        // A prepared state only commits into the accumulator and freeze it was prepared for.
+        let mut first = before.clone();
+        let mut second = before.clone();
+        let prepared = first.prepare(&[Mutation::Add(2)]).unwrap();
+        let other = second.prepare(&[Mutation::Add(2)]).unwrap();
+        assert_ne!(prepared.id(), other.id());
+        assert!(second.commit(prepared).is_err());
+        first.reopen().unwrap();
+        first.freeze().unwrap();
+        assert!(first.abort(other).is_err());
+        assert!(first.is_frozen());
+
+        // A failing prepare has no effect.
+        let mut failed = before.clone();
+        assert!(failed
+            .prepare(&[Mutation::Add(2), Mutation::Delete(9)])
+            .is_err());
+        assert_eq!(failed, before);
+        assert!(!failed.is_frozen());
+    }
+}
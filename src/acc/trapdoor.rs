@@ -0,0 +1,272 @@
+//! Operations with the trapdoor behind a pluggable signer.
+//!
+//! Updating the accumulator and computing membership witnesses raise a point to (s-x) or to its
+//! inverse, which needs the trapdoor `s`. [`TrapdoorSigner`] is a scalar-multiplication oracle
+//! for exactly these operations, so the trapdoor can stay in an HSM or a KMS that never exports
+//! it: the signer receives a point and the elements, never returns `s`, and may refuse a call.
+//! [`DynamicAccumulator`](super::DynamicAccumulator) adds, deletes and proves membership through
+//! the signer installed with [`set_signer`]; without one, the software signer [`Local`] holds the
+//! trapdoor in process memory.
+//!
+//! Nothing else touches the trapdoor. Verifiers compute g^(s-x) from the published g^s, and
+//! proofs whose witnesses are polynomials known to the prover, e.g. the quotients and Bézout
+//! coefficients of the set operations, commit to them with the published powers g^(s^i), which
+//! only exist up to [`MAX_DEGREE`](super::MAX_DEGREE). Raising a point to an arbitrary
+//! polynomial goes through [`TrapdoorSigner::mul_poly`] and [`TrapdoorSigner::div_poly`], which
+//! a signer may not support.
+
+use super::{offload, Fr, G1Affine, PRI_S};
+use anyhow::{anyhow, bail, Result};
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{Field, One, PrimeField, Zero};
+use ark_poly::{univariate::DensePolynomial, Polynomial};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// A holder of the trapdoor that raises points to polynomials in it.
+pub trait TrapdoorSigner: Send + Sync {
+    /// A name for logs.
+    fn name(&self) -> &str;
+
+    /// Returns `point^(product of (s-x))` over `elements`.
+    fn mul_s_minus(&self, point: G1Affine, elements: &[Fr]) -> Result<G1Affine>;
+
+    /// Returns `point^(1/(s-element))`.
+    fn div_s_minus(&self, point: G1Affine, element: Fr) -> Result<G1Affine>;
+
+    /// Returns the membership witness of every x in `members` in the accumulator of `elements`:
+    /// g1 raised to the product of (s-e) over `elements` with one occurrence of x left out.
+    /// Every member must occur in `elements`. The default divides the accumulator value by
+    /// (s-x) once per member.
+    fn membership_witnesses(&self, elements: &[Fr], members: &[Fr]) -> Result<Vec<G1Affine>> {
+        let acc_value = self.mul_s_minus(G1Affine::prime_subgroup_generator(), elements)?;
+        members
+            .iter()
+            .map(|x| self.div_s_minus(acc_value, *x))
+            .collect()
+    }
+
+    /// Returns `point^f(s)`. The default refuses.
+    fn mul_poly(&self, _point: G1Affine, _poly: &DensePolynomial<Fr>) -> Result<G1Affine> {
+        bail!(
+            "The {} signer does not support polynomial exponents",
+            self.name()
+        )
+    }
+
+    /// Returns `point^(1/f(s))`. The default refuses.
+    fn div_poly(&self, _point: G1Affine, _poly: &DensePolynomial<Fr>) -> Result<G1Affine> {
+        bail!(
+            "The {} signer does not support polynomial exponents",
+            self.name()
+        )
+    }
+}
+
+/// The software signer with the trapdoor in process memory.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Local;
+
+impl TrapdoorSigner for Local {
+    fn name(&self) -> &str {
+        "local"
+    }
+
+    fn mul_s_minus(&self, point: G1Affine, elements: &[Fr]) -> Result<G1Affine> {
+        let product = elements.iter().fold(Fr::one(), |acc, e| acc * (*PRI_S - e));
+        Ok(point.mul(product.into_repr()).into_affine())
+    }
+
+    fn div_s_minus(&self, point: G1Affine, element: Fr) -> Result<G1Affine> {
+        let inverse = (*PRI_S - element)
+            .inverse()
+            .ok_or_else(|| anyhow!("Failed to compute inverse"))?;
+        Ok(point.mul(inverse.into_repr()).into_affine())
+    }
+
+    /// Computes every exponent in O(n log n) field operations and then does one fixed-base
+    /// exponentiation per witness, all in parallel.
+    fn membership_witnesses(&self, elements: &[Fr], members: &[Fr]) -> Result<Vec<G1Affine>> {
+        // The product of (s - x) over the elements without one occurrence of each member.
+        let mut rest: HashMap<Fr, usize> = HashMap::new();
+        for e in elements {
+            *rest.entry(*e).or_insert(0) += 1;
+        }
+        for x in members {
+            match rest.get_mut(x) {
+                Some(count) if *count > 0 => *count -= 1,
+                _ => bail!("A member does not occur in the elements"),
+            }
+        }
+        let rest_product = rest.iter().fold(Fr::one(), |acc, (e, count)| {
+            (0..*count).fold(acc, |acc, _| acc * (*PRI_S - e))
+        });
+
+        let leaves: Vec<Fr> = members.iter().map(|x| *PRI_S - x).collect();
+        let mut exponents = vec![Fr::zero(); leaves.len()];
+        root_factor(rest_product, &leaves, &mut exponents);
+        Ok(offload::fixed_base_g1(&exponents))
+    }
+
+    fn mul_poly(&self, point: G1Affine, poly: &DensePolynomial<Fr>) -> Result<G1Affine> {
+        Ok(point.mul(poly.evaluate(&PRI_S).into_repr()).into_affine())
+    }
+
+    fn div_poly(&self, point: G1Affine, poly: &DensePolynomial<Fr>) -> Result<G1Affine> {
+        let inverse = poly
+            .evaluate(&PRI_S)
+            .inverse()
+            .ok_or_else(|| anyhow!("The polynomial has no inverse at s"))?;
+        Ok(point.mul(inverse.into_repr()).into_affine())
+    }
+}
+
+/// Sets `out[i]` to `base` times the product of all leaves but `leaves[i]`, without divisions.
+/// Each half is passed the product of the other half, so every level of the recursion costs
+/// O(n) multiplications; the halves run in parallel.
+fn root_factor(base: Fr, leaves: &[Fr], out: &mut [Fr]) {
+    match leaves.len() {
+        0 => {}
+        1 => out[0] = base,
+        n => {
+            let (left, right) = leaves.split_at(n / 2);
+            let (out_left, out_right) = out.split_at_mut(n / 2);
+            let (left_product, right_product) = rayon::join(
+                || left.iter().product::<Fr>(),
+                || right.iter().product::<Fr>(),
+            );
+            rayon::join(
+                || root_factor(base * right_product, left, out_left),
+                || root_factor(base * left_product, right, out_right),
+            );
+        }
+    }
+}
+
+lazy_static! {
+    static ref SIGNER: RwLock<Arc<dyn TrapdoorSigner>> = RwLock::new(Arc::new(Local));
+}
+
+/// Installs the signer used by all later operations with the trapdoor, replacing the previous
+/// one, which is returned.
+pub fn set_signer(signer: Arc<dyn TrapdoorSigner>) -> Arc<dyn TrapdoorSigner> {
+    std::mem::replace(
+        &mut *SIGNER.write().unwrap_or_else(|e| e.into_inner()),
+        signer,
+    )
+}
+
+/// Reinstalls the software signer.
+pub fn reset_signer() -> Arc<dyn TrapdoorSigner> {
+    set_signer(Arc::new(Local))
+}
+
+/// The installed signer.
+pub fn signer() -> Arc<dyn TrapdoorSigner> {
+    SIGNER.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DynamicAccumulator;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    /// Forwards to [`Local`] and counts the calls, as a remote oracle would be metered. Other
+    /// tests may run while it is installed, so its results must be correct.
+    struct Counting(AtomicUsize);
+
+    impl TrapdoorSigner for Counting {
+        fn name(&self) -> &str {
+            "counting"
+        }
+
+        fn mul_s_minus(&self, point: G1Affine, elements: &[Fr]) -> Result<G1Affine> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Local.mul_s_minus(point, elements)
+        }
+
+        fn div_s_minus(&self, point: G1Affine, element: Fr) -> Result<G1Affine> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Local.div_s_minus(point, element)
+        }
+
+        fn mul_poly(&self, point: G1Affine, poly: &DensePolynomial<Fr>) -> Result<G1Affine> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Local.mul_poly(point, poly)
+        }
+
+        fn div_poly(&self, point: G1Affine, poly: &DensePolynomial<Fr>) -> Result<G1Affine> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Local.div_poly(point, poly)
+        }
+    }
+
+    /// A signer with only the required operations, as a minimal remote oracle would offer.
+    struct Linear;
+
+    impl TrapdoorSigner for Linear {
+        fn name(&self) -> &str {
+            "linear"
+        }
+
+        fn mul_s_minus(&self, point: G1Affine, elements: &[Fr]) -> Result<G1Affine> {
+            Local.mul_s_minus(point, elements)
+        }
+
+        fn div_s_minus(&self, point: G1Affine, element: Fr) -> Result<G1Affine> {
+            Local.div_s_minus(point, element)
+        }
+    }
+
+    #[test]
+    fn test_signer() {
+        init_logger();
+        let counting = Arc::new(Counting(AtomicUsize::new(0)));
+        assert_eq!(set_signer(counting.clone()).name(), "local");
+        assert_eq!(signer().name(), "counting");
+
+        let mut acc = DynamicAccumulator::new();
+        assert!(acc.add(&1).unwrap().verify());
+        acc.add_batch(&[2, 3]).unwrap();
+        assert!(acc.delete(&3).unwrap().verify());
+        acc.set_witness_cache_capacity(0);
+        assert!(acc.verify_membership(&acc.prove_membership(&2).unwrap()));
+        assert!(counting.0.load(Ordering::SeqCst) >= 4);
+
+        assert_eq!(reset_signer().name(), "counting");
+        assert_eq!(signer().name(), "local");
+    }
+
+    #[test]
+    fn test_default_operations() {
+        init_logger();
+        let elements: Vec<Fr> = [1u64, 2, 2, 3].into_iter().map(Fr::from).collect();
+        let members = [Fr::from(2u64), Fr::from(3u64)];
+        let witnesses = Local.membership_witnesses(&elements, &members).unwrap();
+        assert_eq!(
+            Linear.membership_witnesses(&elements, &members).unwrap(),
+            witnesses
+        );
+        assert!(Local
+            .membership_witnesses(&elements, &[Fr::from(4u64)])
+            .is_err());
+
+        let g1 = G1Affine::prime_subgroup_generator();
+        let poly = DensePolynomial {
+            coeffs: vec![Fr::from(5u64), Fr::one()],
+        };
+        assert!(Local.mul_poly(g1, &poly).is_ok());
+        assert!(Linear.mul_poly(g1, &poly).is_err());
+        assert!(Linear.div_poly(g1, &poly).is_err());
+
+        let leaves: Vec<Fr> = (1..=5u64).map(Fr::from).collect();
+        let mut out = vec![Fr::zero(); 5];
+        root_factor(Fr::from(2u64), &leaves, &mut out);
+        assert_eq!(out[2], Fr::from(2u64 * 120 / 3));
+    }
+}
@@ -0,0 +1,237 @@
+//! Update logs: ordered sequences of add/delete proofs that move an accumulator from one
+//! published state to another.
+
+use super::{
+    dedup::OpId,
+    dynamic_accumulator::{AddProof, DeleteProof, DynamicAccumulator},
+    handle::ElementHandle,
+    Fr, G1Affine,
+};
+use crate::digest::{concat_digest, Digest, Digestible};
+use anyhow::{bail, Result};
+use ark_ff::ToBytes;
+use serde::{Deserialize, Serialize};
+
+/// A single accumulator mutation together with its proof.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Update {
+    Add(AddProof),
+    Delete(DeleteProof),
+}
+
+impl Update {
+    pub fn old_acc_value(&self) -> G1Affine {
+        match self {
+            Update::Add(proof) => proof.old_acc_value,
+            Update::Delete(proof) => proof.old_acc_value,
+        }
+    }
+
+    pub fn new_acc_value(&self) -> G1Affine {
+        match self {
+            Update::Add(proof) => proof.new_acc_value,
+            Update::Delete(proof) => proof.new_acc_value,
+        }
+    }
+
+    pub fn element(&self) -> ElementHandle {
+        match self {
+            Update::Add(proof) => proof.element,
+            Update::Delete(proof) => proof.element,
+        }
+    }
+
+    /// The epoch of the accumulator after the update.
+    pub fn epoch(&self) -> u64 {
+        match self {
+            Update::Add(proof) => proof.epoch,
+            Update::Delete(proof) => proof.epoch,
+        }
+    }
+
+    pub fn verify(&self) -> bool {
+        match self {
+            Update::Add(proof) => proof.verify(),
+            Update::Delete(proof) => proof.verify(),
+        }
+    }
+
+    /// The caller's operation ID, if the update was submitted with one.
+    pub fn op_id(&self) -> Option<OpId> {
+        match self {
+            Update::Add(proof) => proof.op_id,
+            Update::Delete(proof) => proof.op_id,
+        }
+    }
+}
+
+impl From<AddProof> for Update {
+    fn from(proof: AddProof) -> Self {
+        Update::Add(proof)
+    }
+}
+
+impl From<DeleteProof> for Update {
+    fn from(proof: DeleteProof) -> Self {
+        Update::Delete(proof)
+    }
+}
+
+pub(crate) fn fr_to_digest(fr: &Fr) -> Digest {
+    let mut buf = Vec::<u8>::new();
+    fr.write(&mut buf)
+        .unwrap_or_else(|_| panic!("failed to serialize {:?}", fr));
+    buf.to_digest()
+}
+
+impl Digestible for Update {
+    fn to_digest(&self) -> Digest {
+        let tag: u8 = match self {
+            Update::Add(_) => 0,
+            Update::Delete(_) => 1,
+        };
+        concat_digest(
+            [
+                [tag].to_digest(),
+                self.old_acc_value().to_digest(),
+                self.new_acc_value().to_digest(),
+                fr_to_digest(&self.element().0),
+                self.epoch().to_digest(),
+            ]
+            .iter()
+            .copied()
+            .chain(self.op_id().map(|op_id| op_id.to_digest())),
+        )
+    }
+}
+
+/// An ordered list of updates published by the manager, starting at `start_epoch`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpdateLog {
+    pub start_epoch: u64,
+    pub updates: Vec<Update>,
+}
+
+impl UpdateLog {
+    /// Creates an empty log starting at the given epoch.
+    pub fn new(start_epoch: u64) -> Self {
+        Self {
+            start_epoch,
+            updates: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, update: impl Into<Update>) {
+        self.updates.push(update.into());
+    }
+
+    /// The epoch reached after applying every update in the log.
+    pub fn end_epoch(&self) -> u64 {
+        self.start_epoch + self.updates.len() as u64
+    }
+
+    /// Verifies that the updates form an unbroken chain starting at `(start_value,
+    /// start_epoch)` and returns the final accumulator value.
+    pub fn verify_chain(&self, start_value: G1Affine) -> Result<G1Affine> {
+        let mut value = start_value;
+        for (i, update) in self.updates.iter().enumerate() {
+            let epoch = self.start_epoch + i as u64 + 1;
+            if update.old_acc_value() != value {
+                bail!(
+                    "update {} does not start at the previous accumulator value",
+                    i
+                );
+            }
+            if update.epoch() != epoch {
+                bail!(
+                    "update {} is for epoch {}, expected {}",
+                    i,
+                    update.epoch(),
+                    epoch
+                );
+            }
+            if !update.verify() {
+                bail!("update {} has an invalid proof", i);
+            }
+            value = update.new_acc_value();
+        }
+        Ok(value)
+    }
+}
+
+impl Digestible for UpdateLog {
+    fn to_digest(&self) -> Digest {
+        concat_digest(
+            core::iter::once(self.start_epoch.to_digest())
+                .chain(self.updates.iter().map(|u| u.to_digest())),
+        )
+    }
+}
+
+impl DynamicAccumulator {
+    /// Verifies and applies a single update produced by a (possibly untrusted) manager.
+    pub fn apply_update(&mut self, update: &Update) -> Result<()> {
+        match update {
+            Update::Add(proof) => self.apply_add_proof(proof),
+            Update::Delete(proof) => self.apply_delete_proof(proof),
+        }
+    }
+
+    /// Verifies and applies every update of `log` in order. The log must start at the local
+    /// epoch. On error the accumulator is left at the last successfully applied update.
+    pub fn apply_update_log(&mut self, log: &UpdateLog) -> Result<()> {
+        if log.start_epoch != self.epoch() {
+            bail!(
+                "update log starts at epoch {}, local epoch is {}",
+                log.start_epoch,
+                self.epoch()
+            );
+        }
+        for update in &log.updates {
+            self.apply_update(update)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_update_log_chain() {
+        init_logger();
+        let mut manager = DynamicAccumulator::new();
+        let start = manager.acc_value;
+        let mut log = UpdateLog::new(manager.epoch());
+        log.push(manager.add(&1).unwrap());
+        log.push(manager.add(&2).unwrap());
+        log.push(manager.delete(&1).unwrap());
+        assert_eq!(log.end_epoch(), 3);
+        assert_eq!(log.verify_chain(start).unwrap(), manager.acc_value);
+
+        let mut mirror = DynamicAccumulator::new();
+        mirror.apply_update_log(&log).unwrap();
+        assert_eq!(mirror, manager);
+
+        let mut reordered = log.clone();
+        reordered.updates.swap(0, 1);
+        assert!(reordered.verify_chain(start).is_err());
+        assert_ne!(reordered.to_digest(), log.to_digest());
+    }
+
+    #[test]
+    fn test_serde() {
+        let mut manager = DynamicAccumulator::new();
+        let mut log = UpdateLog::new(0);
+        log.push(manager.add(&1).unwrap());
+        let json = serde_json::to_string(&log).unwrap();
+        let bin = bincode::serialize(&log).unwrap();
+        assert_eq!(serde_json::from_str::<UpdateLog>(&json).unwrap(), log);
+        assert_eq!(bincode::deserialize::<UpdateLog>(&bin[..]).unwrap(), log);
+    }
+}
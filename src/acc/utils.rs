@@ -1,12 +1,16 @@
 use crate::digest::Digest;
 use ark_ec::ProjectiveCurve;
-use ark_ff::{BigInteger, FpParameters, PrimeField, Zero};
+use ark_ff::{BigInteger, FpParameters, FromBytes, PrimeField, Zero};
 use ark_poly::{
     univariate::{DenseOrSparsePolynomial, DensePolynomial},
     UVPolynomial,
 };
 use std::iter;
 
+/// Maps a digest to a field element: the digest is read as a big-endian integer, reduced modulo
+/// the field order and truncated to its low 192 bits, i.e. the first three 64-bit limbs (the
+/// fourth limb is cleared as well, despite the 248-bit bound noted below). Implementations in
+/// other languages can reproduce it with the canonical encoding of [`fr_to_bytes`].
 pub fn try_digest_to_prime_field<F: PrimeField>(input: &Digest) -> Option<F> {
     let mut num = F::from_be_bytes_mod_order(&input.0).into_repr();
     // ensure the result is at most in 248 bits. so PUB_Q - Fr and Fr + PUB_Q - Fr never overflow.
@@ -23,6 +27,52 @@ pub fn digest_to_prime_field<F: PrimeField>(input: &Digest) -> F {
     try_digest_to_prime_field(input).expect("failed to convert digest to prime field")
 }
 
+/// The byte order of a canonical field element encoding.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Endianness {
+    Big,
+    Little,
+}
+
+/// The length of the canonical encoding of an element of `F`, e.g. 32 bytes for `Fr`.
+pub fn fr_byte_len<F: PrimeField>() -> usize {
+    (<F as PrimeField>::Params::MODULUS_BITS as usize).div_ceil(8)
+}
+
+/// Encodes a field element as its integer value in [0, modulus), in exactly
+/// [`fr_byte_len`] bytes of the given byte order.
+pub fn fr_to_bytes<F: PrimeField>(value: &F, endianness: Endianness) -> Vec<u8> {
+    let repr = value.into_repr();
+    let len = fr_byte_len::<F>();
+    match endianness {
+        Endianness::Big => {
+            let bytes = repr.to_bytes_be();
+            bytes[bytes.len() - len..].to_vec()
+        }
+        Endianness::Little => {
+            let mut bytes = repr.to_bytes_le();
+            bytes.truncate(len);
+            bytes
+        }
+    }
+}
+
+/// Decodes the encoding of [`fr_to_bytes`]. Returns `None` unless `bytes` has exactly
+/// [`fr_byte_len`] bytes and encodes an integer below the modulus, so every field element has
+/// a single accepted encoding.
+pub fn fr_from_bytes<F: PrimeField>(bytes: &[u8], endianness: Endianness) -> Option<F> {
+    if bytes.len() != fr_byte_len::<F>() {
+        return None;
+    }
+    let mut le = bytes.to_vec();
+    if endianness == Endianness::Big {
+        le.reverse();
+    }
+    le.resize(F::BigInt::NUM_LIMBS * 8, 0);
+    let repr = F::BigInt::read(&le[..]).ok()?;
+    F::from_repr(repr)
+}
+
 /// Return (g, x, y) s.t. a*x + b*y = g = gcd(a, b)
 pub fn xgcd<'a, F: PrimeField>(
     a: impl Into<DenseOrSparsePolynomial<'a, F>>,
@@ -189,6 +239,32 @@ mod tests {
         assert_eq!(gcd, g);
     }
 
+    #[test]
+    fn test_fr_bytes() {
+        let mut rng = rand::thread_rng();
+        let value: Fr = rng.gen();
+        for endianness in [Endianness::Big, Endianness::Little] {
+            let bytes = fr_to_bytes(&value, endianness);
+            assert_eq!(bytes.len(), 32);
+            assert_eq!(fr_from_bytes::<Fr>(&bytes, endianness), Some(value));
+            assert_eq!(fr_from_bytes::<Fr>(&bytes[1..], endianness), None);
+        }
+        let mut one = vec![0u8; 32];
+        one[31] = 1;
+        assert_eq!(fr_from_bytes(&one, Endianness::Big), Some(Fr::from(1u32)));
+        let modulus = <Fr as PrimeField>::Params::MODULUS.to_bytes_be();
+        assert_eq!(fr_from_bytes::<Fr>(&modulus, Endianness::Big), None);
+
+        // digest_to_prime_field keeps the low 192 bits of the big-endian digest mod r.
+        let digest = Digest([0xff; 32]);
+        let mut expected = fr_to_bytes(&Fr::from_be_bytes_mod_order(&digest.0), Endianness::Big);
+        expected[..8].fill(0);
+        assert_eq!(
+            fr_to_bytes(&digest_to_prime_field::<Fr>(&digest), Endianness::Big),
+            expected
+        );
+    }
+
     #[test]
     fn test_pow_g1() {
         let g1p = FixedBaseCurvePow::build(&G1Projective::prime_subgroup_generator());
@@ -0,0 +1,118 @@
+//! Optional retention of the original element values.
+//!
+//! Accumulators only keep the field elements the values hash to. With retention enabled, an
+//! accumulator also remembers the original value of every element added by value (`add`,
+//! `add_batch`, bulk imports and their variants), so the set can be exported and used for
+//! value-based set operations without a parallel plaintext list. Elements that arrive in
+//! another form, e.g. through `apply_add_proof` or `add_encoded`, have no retained value.
+
+use super::{
+    dynamic_accumulator::{DynamicAccumulator, UnionProof},
+    utils::digest_to_prime_field,
+    Fr,
+};
+use crate::digest::Digestible;
+use anyhow::{anyhow, bail, Result};
+use std::collections::HashMap;
+
+impl DynamicAccumulator {
+    /// Enables retention of original values. Returns an error if the accumulator is not empty,
+    /// as the values of its elements would be unknown.
+    pub fn retain_values(mut self) -> Result<Self> {
+        if !self.is_empty() {
+            bail!("Value retention must be enabled on an empty accumulator");
+        }
+        self.values = Some(HashMap::new());
+        Ok(self)
+    }
+
+    /// Returns true if original values are retained.
+    pub fn retains_values(&self) -> bool {
+        self.values.is_some()
+    }
+
+    pub(crate) fn retain_value(&mut self, fr_element: Fr, value: i64) {
+        if let Some(values) = self.values.as_mut() {
+            values.insert(fr_element, value);
+        }
+    }
+
+    /// Returns true if the value is accumulated.
+    pub fn contains(&self, element: &i64) -> bool {
+        self.elements
+            .has(&digest_to_prime_field(&element.to_digest()))
+    }
+
+    /// Returns the retained original value of an accumulated element.
+    pub fn value_of(&self, fr_element: &Fr) -> Option<i64> {
+        self.values.as_ref()?.get(fr_element).copied()
+    }
+
+    /// Returns the retained values of all accumulated elements in ascending order.
+    /// Returns an error if values are not retained or an element has no retained value.
+    pub fn export_values(&self) -> Result<Vec<i64>> {
+        let values = self
+            .values
+            .as_ref()
+            .ok_or_else(|| anyhow!("Original values are not retained"))?;
+        if values.len() != self.len() {
+            bail!("Some accumulated elements have no retained value");
+        }
+        let mut exported: Vec<i64> = values.values().copied().collect();
+        exported.sort_unstable();
+        Ok(exported)
+    }
+
+    /// Same as [`DynamicAccumulator::prove_union_with_values`], taking the values of both
+    /// sets from the accumulators.
+    pub fn prove_union_with_retained_values(
+        &self,
+        other: &DynamicAccumulator,
+    ) -> Result<(Vec<i64>, Vec<i64>, DynamicAccumulator, UnionProof)> {
+        self.prove_union_with_values(other, &self.export_values()?, &other.export_values()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_retained_values() {
+        init_logger();
+        let mut a = DynamicAccumulator::new().retain_values().unwrap();
+        let mut b = DynamicAccumulator::new().retain_values().unwrap();
+        a.add_batch(&[3, 1, 2]).unwrap();
+        a.delete(&2).unwrap();
+        b.add(&3).unwrap();
+        b.add_with_op_id(&4, 7).unwrap();
+        assert!(a.contains(&1) && !a.contains(&2));
+        assert_eq!(a.export_values().unwrap(), vec![1, 3]);
+        assert_eq!(
+            a.value_of(&digest_to_prime_field(&3i64.to_digest())),
+            Some(3)
+        );
+
+        let (union, intersection, union_acc, proof) =
+            a.prove_union_with_retained_values(&b).unwrap();
+        assert_eq!(union, vec![1, 3, 4]);
+        assert_eq!(intersection, vec![3]);
+        assert!(DynamicAccumulator::verify_union_with_values(
+            a.acc_value,
+            b.acc_value,
+            &union,
+            &intersection,
+            &proof
+        ));
+        assert_eq!(union_acc.len(), 3);
+
+        let mut plain = DynamicAccumulator::new();
+        plain.add(&1).unwrap();
+        assert!(plain.export_values().is_err());
+        assert!(plain.retain_values().is_err());
+    }
+}
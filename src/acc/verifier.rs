@@ -0,0 +1,138 @@
+//! A reusable verification context.
+//!
+//! The `verify` methods on the proof types derive their constants on every call: the pairing
+//! e(g1, g2) of the non-membership check, and the line coefficients of g2 inside each pairing.
+//! [`VerifierCtx`] holds them as [`PreparedPublicParams`]. Each check then runs as a single
+//! product of pairings with one final exponentiation.
+
+use super::{
+    commitment::params_id,
+    dynamic_accumulator::{AddProof, DeleteProof, MembershipProof, NonMembershipProof},
+    prepared::PreparedPublicParams,
+    Curve, Fq12, G1Affine,
+};
+use crate::digest::Digestible;
+use ark_ec::PairingEngine;
+use core::ops::Neg;
+
+type G1Prepared = <Curve as PairingEngine>::G1Prepared;
+type G2Prepared = <Curve as PairingEngine>::G2Prepared;
+
+/// Precomputed public values shared by all verifications.
+#[derive(Debug, Clone)]
+pub struct VerifierCtx {
+    params: PreparedPublicParams,
+}
+
+impl VerifierCtx {
+    pub fn new() -> Self {
+        Self::with_params(PreparedPublicParams::global().clone())
+    }
+
+    pub fn with_params(params: PreparedPublicParams) -> Self {
+        Self { params }
+    }
+
+    pub fn params(&self) -> &PreparedPublicParams {
+        &self.params
+    }
+
+    /// The precomputed e(g1, g2).
+    pub fn e_g1_g2(&self) -> Fq12 {
+        self.params.e_g1_g2()
+    }
+
+    /// Same as [`MembershipProof::verify`].
+    pub fn verify_membership(&self, proof: &MembershipProof, accumulator: G1Affine) -> bool {
+        proof.acc_digest == accumulator.to_digest()
+            && proof.params_id == params_id()
+            && self.params.pairing_check(
+                proof.witness,
+                self.params.g2_s_minus(proof.element.0),
+                accumulator.neg(),
+            )
+    }
+
+    /// Same as [`NonMembershipProof::verify`].
+    pub fn verify_non_membership(&self, proof: &NonMembershipProof, accumulator: G1Affine) -> bool {
+        if proof.acc_digest != accumulator.to_digest() || proof.params_id != params_id() {
+            return false;
+        }
+        let pairs: [(G1Prepared, G2Prepared); 2] = [
+            (accumulator.into(), proof.witness.into()),
+            (proof.g1_a.into(), self.params.g2_s_minus(proof.element.0)),
+        ];
+        Curve::product_of_pairings(&pairs) == self.params.e_g1_g2()
+    }
+
+    /// Same as [`AddProof::verify`].
+    pub fn verify_add(&self, proof: &AddProof) -> bool {
+        proof.params_id == params_id()
+            && self.params.pairing_check(
+                proof.old_acc_value,
+                self.params.g2_s_minus(proof.element.0),
+                proof.new_acc_value.neg(),
+            )
+    }
+
+    /// Same as [`DeleteProof::verify`].
+    pub fn verify_delete(&self, proof: &DeleteProof) -> bool {
+        proof.params_id == params_id()
+            && self.params.pairing_check(
+                proof.new_acc_value,
+                self.params.g2_s_minus(proof.element.0),
+                proof.old_acc_value.neg(),
+            )
+    }
+}
+
+impl Default for VerifierCtx {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::dynamic_accumulator::DynamicAccumulator;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_verifier_ctx() {
+        init_logger();
+        let ctx = VerifierCtx::new();
+        let mut acc = DynamicAccumulator::new();
+        let add = acc.add(&1).unwrap();
+        acc.add(&2).unwrap();
+        assert!(ctx.verify_add(&add));
+
+        let membership = acc.prove_membership(&1).unwrap();
+        let non_membership = acc.prove_non_membership(&3).unwrap();
+        assert!(ctx.verify_membership(&membership, acc.acc_value));
+        assert!(ctx.verify_non_membership(&non_membership, acc.acc_value));
+
+        let old_value = acc.acc_value;
+        let delete = acc.delete(&2).unwrap();
+        assert!(ctx.verify_delete(&delete));
+        assert!(!ctx.verify_add(&AddProof {
+            old_acc_value: delete.old_acc_value,
+            new_acc_value: delete.new_acc_value,
+            element: delete.element,
+            epoch: delete.epoch,
+            op_id: None,
+            params_id: delete.params_id,
+        }));
+        assert!(!ctx.verify_membership(&membership, acc.acc_value));
+
+        let mut forged = membership.clone();
+        forged.element = non_membership.element;
+        assert!(!ctx.verify_membership(&forged, old_value));
+        let mut forged = non_membership;
+        forged.element = membership.element;
+        assert!(!ctx.verify_non_membership(&forged, old_value));
+    }
+}
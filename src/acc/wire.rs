@@ -0,0 +1,375 @@
+//! Binary encoding of proofs with a choice of point encoding.
+//!
+//! [`EncodingOptions`] selects compressed points (the default, and what the serde
+//! implementations use) or uncompressed points, which are twice as large but decode without a
+//! square root. Decoding uncompressed points can also skip the curve and subgroup checks when
+//! the bytes come from a trusted source. For hot verifiers, a [`PreparedNonMembershipProof`]
+//! additionally carries the precomputed Miller loop coefficients of its G2 witness.
+//!
+//! Fields are written in declaration order; integers are little-endian.
+
+use super::{
+    dynamic_accumulator::{AddProof, DeleteProof, MembershipProof, NonMembershipProof},
+    handle::ElementHandle,
+    prepared::PreparedPublicParams,
+    Curve, Fr, G1Affine,
+};
+use crate::digest::{Digest, Digestible};
+use anyhow::{bail, Result};
+use ark_bls12_381::Fq2;
+use ark_ec::PairingEngine;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read};
+
+type G1Prepared = <Curve as PairingEngine>::G1Prepared;
+type G2Prepared = <Curve as PairingEngine>::G2Prepared;
+
+/// Bytes of an encoded Fq2 element.
+const FQ2_BYTES: usize = 96;
+
+/// How points are written and read.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct EncodingOptions {
+    /// Write points compressed (x coordinate and flags only).
+    pub compressed: bool,
+    /// Check that decoded points are on the curve and in the prime-order subgroup. Compressed
+    /// points are always checked.
+    pub validate: bool,
+}
+
+impl Default for EncodingOptions {
+    fn default() -> Self {
+        Self::compressed()
+    }
+}
+
+impl EncodingOptions {
+    pub fn compressed() -> Self {
+        Self {
+            compressed: true,
+            validate: true,
+        }
+    }
+
+    pub fn uncompressed() -> Self {
+        Self {
+            compressed: false,
+            validate: true,
+        }
+    }
+
+    /// Uncompressed points, decoded without any checks. Only for bytes from a trusted source.
+    pub fn uncompressed_unchecked() -> Self {
+        Self {
+            compressed: false,
+            validate: false,
+        }
+    }
+
+    pub(crate) fn write<T: CanonicalSerialize>(&self, value: &T, buf: &mut Vec<u8>) -> Result<()> {
+        if self.compressed {
+            value.serialize(buf)?;
+        } else {
+            value.serialize_uncompressed(buf)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn read<T: CanonicalDeserialize>(&self, reader: &mut &[u8]) -> Result<T> {
+        Ok(match (self.compressed, self.validate) {
+            (true, _) => T::deserialize(reader)?,
+            (false, true) => T::deserialize_uncompressed(reader)?,
+            (false, false) => T::deserialize_unchecked(reader)?,
+        })
+    }
+}
+
+/// Types with a binary encoding parameterized by [`EncodingOptions`].
+pub trait WireEncode: Sized {
+    fn encode_into(&self, options: &EncodingOptions, buf: &mut Vec<u8>) -> Result<()>;
+
+    fn decode_from(reader: &mut &[u8], options: &EncodingOptions) -> Result<Self>;
+
+    fn encode(&self, options: &EncodingOptions) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        self.encode_into(options, &mut buf)?;
+        Ok(buf)
+    }
+
+    /// Decodes a value that must span all of `bytes`.
+    fn decode(bytes: &[u8], options: &EncodingOptions) -> Result<Self> {
+        let mut reader = bytes;
+        let value = Self::decode_from(&mut reader, options)?;
+        if !reader.is_empty() {
+            bail!("{} trailing bytes after the encoded value", reader.len());
+        }
+        Ok(value)
+    }
+}
+
+pub(crate) fn write_u64(value: u64, buf: &mut Vec<u8>) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+pub(crate) fn read_u64(reader: &mut &[u8]) -> Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn write_op_id(op_id: Option<u64>, buf: &mut Vec<u8>) {
+    match op_id {
+        None => buf.push(0),
+        Some(id) => {
+            buf.push(1);
+            write_u64(id, buf);
+        }
+    }
+}
+
+fn read_op_id(reader: &mut &[u8]) -> Result<Option<u64>> {
+    let mut flag = [0u8; 1];
+    reader.read_exact(&mut flag)?;
+    match flag[0] {
+        0 => Ok(None),
+        1 => Ok(Some(read_u64(reader)?)),
+        f => bail!("Invalid operation ID flag {}", f),
+    }
+}
+
+pub(crate) fn read_digest(reader: &mut &[u8]) -> Result<Digest> {
+    let mut digest = Digest::default();
+    reader.read_exact(&mut digest.0)?;
+    Ok(digest)
+}
+
+impl WireEncode for MembershipProof {
+    fn encode_into(&self, options: &EncodingOptions, buf: &mut Vec<u8>) -> Result<()> {
+        options.write(&self.witness, buf)?;
+        self.element.0.serialize(&mut *buf)?;
+        write_u64(self.epoch, buf);
+        buf.extend_from_slice(&self.acc_digest.0);
+        buf.extend_from_slice(&self.params_id.0);
+        Ok(())
+    }
+
+    fn decode_from(reader: &mut &[u8], options: &EncodingOptions) -> Result<Self> {
+        Ok(Self {
+            witness: options.read(reader)?,
+            element: ElementHandle(Fr::deserialize(&mut *reader)?),
+            epoch: read_u64(reader)?,
+            acc_digest: read_digest(reader)?,
+            params_id: read_digest(reader)?,
+        })
+    }
+}
+
+impl WireEncode for NonMembershipProof {
+    fn encode_into(&self, options: &EncodingOptions, buf: &mut Vec<u8>) -> Result<()> {
+        self.element.0.serialize(&mut *buf)?;
+        options.write(&self.witness, buf)?;
+        options.write(&self.g1_a, buf)?;
+        write_u64(self.epoch, buf);
+        buf.extend_from_slice(&self.acc_digest.0);
+        buf.extend_from_slice(&self.params_id.0);
+        Ok(())
+    }
+
+    fn decode_from(reader: &mut &[u8], options: &EncodingOptions) -> Result<Self> {
+        Ok(Self {
+            element: ElementHandle(Fr::deserialize(&mut *reader)?),
+            witness: options.read(reader)?,
+            g1_a: options.read(reader)?,
+            epoch: read_u64(reader)?,
+            acc_digest: read_digest(reader)?,
+            params_id: read_digest(reader)?,
+        })
+    }
+}
+
+impl WireEncode for AddProof {
+    fn encode_into(&self, options: &EncodingOptions, buf: &mut Vec<u8>) -> Result<()> {
+        options.write(&self.old_acc_value, buf)?;
+        options.write(&self.new_acc_value, buf)?;
+        self.element.0.serialize(&mut *buf)?;
+        write_u64(self.epoch, buf);
+        write_op_id(self.op_id, buf);
+        buf.extend_from_slice(&self.params_id.0);
+        Ok(())
+    }
+
+    fn decode_from(reader: &mut &[u8], options: &EncodingOptions) -> Result<Self> {
+        Ok(Self {
+            old_acc_value: options.read(reader)?,
+            new_acc_value: options.read(reader)?,
+            element: ElementHandle(Fr::deserialize(&mut *reader)?),
+            epoch: read_u64(reader)?,
+            op_id: read_op_id(reader)?,
+            params_id: read_digest(reader)?,
+        })
+    }
+}
+
+impl WireEncode for DeleteProof {
+    fn encode_into(&self, options: &EncodingOptions, buf: &mut Vec<u8>) -> Result<()> {
+        options.write(&self.old_acc_value, buf)?;
+        options.write(&self.new_acc_value, buf)?;
+        self.element.0.serialize(&mut *buf)?;
+        write_u64(self.epoch, buf);
+        write_op_id(self.op_id, buf);
+        buf.extend_from_slice(&self.params_id.0);
+        Ok(())
+    }
+
+    fn decode_from(reader: &mut &[u8], options: &EncodingOptions) -> Result<Self> {
+        Ok(Self {
+            old_acc_value: options.read(reader)?,
+            new_acc_value: options.read(reader)?,
+            element: ElementHandle(Fr::deserialize(&mut *reader)?),
+            epoch: read_u64(reader)?,
+            op_id: read_op_id(reader)?,
+            params_id: read_digest(reader)?,
+        })
+    }
+}
+
+/// A non-membership proof with its witness prepared for pairing.
+///
+/// Preparing a G2 point is a large part of a pairing; a verifier that checks the same proof
+/// repeatedly, or receives it from a prover that already prepared it, skips that step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreparedNonMembershipProof {
+    pub proof: NonMembershipProof,
+    pub witness: G2Prepared,
+}
+
+impl From<NonMembershipProof> for PreparedNonMembershipProof {
+    fn from(proof: NonMembershipProof) -> Self {
+        let witness = proof.witness.into();
+        Self { proof, witness }
+    }
+}
+
+impl PreparedNonMembershipProof {
+    /// Same as [`NonMembershipProof::verify`], using the prepared witness.
+    pub fn verify(&self, accumulator: G1Affine) -> bool {
+        if self.proof.acc_digest != accumulator.to_digest() {
+            return false;
+        }
+        let params = PreparedPublicParams::global();
+        let pairs: [(G1Prepared, G2Prepared); 2] = [
+            (accumulator.into(), self.witness.clone()),
+            (
+                self.proof.g1_a.into(),
+                params.g2_s_minus(self.proof.element.0),
+            ),
+        ];
+        Curve::product_of_pairings(&pairs) == params.e_g1_g2()
+    }
+
+    pub fn verify_at(&self, accumulator: G1Affine, epoch: u64) -> bool {
+        self.proof.epoch == epoch && self.verify(accumulator)
+    }
+}
+
+impl WireEncode for PreparedNonMembershipProof {
+    /// The proof, then the infinity flag and line coefficients of the prepared witness. The
+    /// coefficients are field elements and are written the same way whatever the options.
+    fn encode_into(&self, options: &EncodingOptions, buf: &mut Vec<u8>) -> Result<()> {
+        self.proof.encode_into(options, buf)?;
+        buf.push(self.witness.infinity as u8);
+        write_u64(self.witness.ell_coeffs.len() as u64, buf);
+        for (a, b, c) in &self.witness.ell_coeffs {
+            a.serialize(&mut *buf)?;
+            b.serialize(&mut *buf)?;
+            c.serialize(&mut *buf)?;
+        }
+        Ok(())
+    }
+
+    /// Unless the options skip validation, the coefficients are recomputed from the witness and
+    /// must match, which costs as much as preparing the witness.
+    fn decode_from(reader: &mut &[u8], options: &EncodingOptions) -> Result<Self> {
+        let proof = NonMembershipProof::decode_from(reader, options)?;
+        let mut infinity = [0u8; 1];
+        reader.read_exact(&mut infinity)?;
+        let len = read_u64(reader)? as usize;
+        if len > reader.len() / (3 * FQ2_BYTES) {
+            bail!("Too many line coefficients");
+        }
+        let mut ell_coeffs = Vec::with_capacity(len);
+        for _ in 0..len {
+            ell_coeffs.push((
+                Fq2::deserialize(&mut *reader)?,
+                Fq2::deserialize(&mut *reader)?,
+                Fq2::deserialize(&mut *reader)?,
+            ));
+        }
+        let witness = G2Prepared {
+            ell_coeffs,
+            infinity: infinity[0] != 0,
+        };
+        if options.validate && witness != G2Prepared::from(proof.witness) {
+            bail!("Prepared witness does not match the proof");
+        }
+        Ok(Self { proof, witness })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acc::{dynamic_accumulator::DynamicAccumulator, G2Affine};
+    use ark_ec::AffineCurve;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_encoding_options() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        let add = acc.add(&1).unwrap();
+        acc.add_batch(&[2, 3]).unwrap();
+        let membership = acc.prove_membership(&2).unwrap();
+        let non_membership = acc.prove_non_membership(&4).unwrap();
+
+        for options in [
+            EncodingOptions::compressed(),
+            EncodingOptions::uncompressed(),
+            EncodingOptions::uncompressed_unchecked(),
+        ] {
+            let bytes = membership.encode(&options).unwrap();
+            assert_eq!(
+                MembershipProof::decode(&bytes, &options).unwrap(),
+                membership
+            );
+            let bytes = non_membership.encode(&options).unwrap();
+            assert_eq!(
+                NonMembershipProof::decode(&bytes, &options).unwrap(),
+                non_membership
+            );
+            let bytes = add.encode(&options).unwrap();
+            assert_eq!(AddProof::decode(&bytes, &options).unwrap(), add);
+            assert!(AddProof::decode(&bytes[1..], &options).is_err());
+        }
+        let compressed = membership.encode(&EncodingOptions::compressed()).unwrap();
+        let uncompressed = membership.encode(&EncodingOptions::uncompressed()).unwrap();
+        assert_eq!(uncompressed.len(), compressed.len() + 48);
+        assert!(MembershipProof::decode(&uncompressed, &EncodingOptions::compressed()).is_err());
+
+        let prepared = PreparedNonMembershipProof::from(non_membership);
+        assert!(prepared.verify_at(acc.acc_value, acc.epoch()));
+        let bytes = prepared.encode(&EncodingOptions::uncompressed()).unwrap();
+        let decoded =
+            PreparedNonMembershipProof::decode(&bytes, &EncodingOptions::uncompressed()).unwrap();
+        assert_eq!(decoded, prepared);
+
+        // A witness that does not match its coefficients is rejected.
+        let mut mismatched = prepared.clone();
+        mismatched.witness = G2Affine::prime_subgroup_generator().into();
+        let bytes = mismatched.encode(&EncodingOptions::default()).unwrap();
+        assert!(PreparedNonMembershipProof::decode(&bytes, &EncodingOptions::default()).is_err());
+        assert!(!mismatched.verify(acc.acc_value));
+    }
+}
@@ -0,0 +1,220 @@
+//! Escrow of membership witnesses encrypted to their holders.
+//!
+//! A holder who loses its local state would otherwise have to ask the manager to reissue its
+//! witness. A manager running a [`WitnessEscrow`] instead keeps every issued witness ElGamal
+//! encrypted to the holder's [`EncryptionKey`], `(g1^r, w * pk^r)`, and
+//! [`WitnessEscrow::recover_witness`] hands the ciphertext back. Only the holder can open it, and
+//! the opened proof is checked like any other, so recovery needs no trust in the escrow.
+//!
+//! The escrow keeps the ciphertexts current without decrypting them. The witness update rules
+//! of [`MembershipProof::update_on_add`] and [`MembershipProof::update_on_delete`] combine the
+//! witness with public values and powers, so applying them to both halves of a ciphertext
+//! yields an encryption of the updated witness:
+//!
+//! - on adding y, `(c1^(x-y), old_acc * c2^(x-y))`,
+//! - on deleting y, `(c1^(1/(x-y)), (c2 / new_acc)^(1/(x-y)))`.
+
+use super::{
+    commitment::params_id,
+    dynamic_accumulator::{AddProof, DeleteProof, MembershipProof},
+    escrow::{Ciphertext, DecryptionKey, EncryptionKey},
+    handle::ElementHandle,
+    update::Update,
+    utils::digest_to_prime_field,
+    Fr, G1Affine,
+};
+use crate::digest::{Digest, Digestible};
+use anyhow::{anyhow, bail, Result};
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{Field, PrimeField, UniformRand};
+use std::collections::HashMap;
+
+/// A membership witness encrypted to its holder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EscrowedWitness {
+    pub ciphertext: Ciphertext,
+    pub element: ElementHandle,
+    pub epoch: u64,
+    pub acc_digest: Digest,
+    pub params_id: Digest,
+}
+
+impl EscrowedWitness {
+    /// Decrypts the witness. Verify the returned proof before relying on it.
+    pub fn open(&self, key: &DecryptionKey) -> MembershipProof {
+        MembershipProof {
+            witness: key.decrypt(&self.ciphertext),
+            element: self.element,
+            epoch: self.epoch,
+            acc_digest: self.acc_digest,
+            params_id: self.params_id,
+        }
+    }
+
+    fn update_on_add(&mut self, proof: &AddProof) -> Result<()> {
+        if self.acc_digest != proof.old_acc_value.to_digest() {
+            bail!("Add proof does not start at the escrowed witness's accumulator value");
+        }
+        let x_minus_y = (self.element.0 - proof.element.0).into_repr();
+        let Ciphertext { c1, c2 } = self.ciphertext;
+        self.ciphertext = Ciphertext {
+            c1: c1.mul(x_minus_y).into_affine(),
+            c2: (proof.old_acc_value.into_projective() + c2.mul(x_minus_y)).into_affine(),
+        };
+        self.epoch = proof.epoch;
+        self.acc_digest = proof.new_acc_value.to_digest();
+        Ok(())
+    }
+
+    fn update_on_delete(&mut self, proof: &DeleteProof) -> Result<()> {
+        if self.acc_digest != proof.old_acc_value.to_digest() {
+            bail!("Delete proof does not start at the escrowed witness's accumulator value");
+        }
+        let x_minus_y_inv = (self.element.0 - proof.element.0)
+            .inverse()
+            .ok_or_else(|| anyhow!("The witnessed element was deleted"))?
+            .into_repr();
+        let Ciphertext { c1, c2 } = self.ciphertext;
+        self.ciphertext = Ciphertext {
+            c1: c1.mul(x_minus_y_inv).into_affine(),
+            c2: (c2.into_projective() - proof.new_acc_value.into_projective())
+                .mul(x_minus_y_inv)
+                .into_affine(),
+        };
+        self.epoch = proof.epoch;
+        self.acc_digest = proof.new_acc_value.to_digest();
+        Ok(())
+    }
+}
+
+/// The manager's store of encrypted witnesses, by element and holder key.
+#[derive(Debug, Clone, Default)]
+pub struct WitnessEscrow {
+    entries: HashMap<(Fr, Digest), EscrowedWitness>,
+}
+
+impl WitnessEscrow {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Escrows an issued witness for `holder`, replacing an earlier one for the same element
+    /// and key. Randomness is drawn from the thread-local generator.
+    pub fn escrow(&mut self, proof: &MembershipProof, holder: &EncryptionKey) {
+        self.escrow_with_rng(proof, holder, &mut rand::thread_rng())
+    }
+
+    /// Same as [`WitnessEscrow::escrow`], drawing randomness from `rng`.
+    pub fn escrow_with_rng<R: rand::Rng + ?Sized>(
+        &mut self,
+        proof: &MembershipProof,
+        holder: &EncryptionKey,
+        rng: &mut R,
+    ) {
+        let r = Fr::rand(rng).into_repr();
+        let ciphertext = Ciphertext {
+            c1: G1Affine::prime_subgroup_generator().mul(r).into_affine(),
+            c2: (proof.witness.into_projective() + holder.0.mul(r)).into_affine(),
+        };
+        let entry = EscrowedWitness {
+            ciphertext,
+            element: proof.element,
+            epoch: proof.epoch,
+            acc_digest: proof.acc_digest,
+            params_id: proof.params_id,
+        };
+        self.entries
+            .insert((proof.element.0, holder.0.to_digest()), entry);
+    }
+
+    /// Moves every escrowed witness over `update`. Witnesses of a deleted element are dropped.
+    /// On error, e.g. an update that does not follow the escrowed state, nothing changes.
+    pub fn apply_update(&mut self, update: &Update) -> Result<()> {
+        let mut entries = self.entries.clone();
+        entries.retain(|_, entry| match update {
+            Update::Delete(proof) => proof.element != entry.element,
+            Update::Add(_) => true,
+        });
+        for entry in entries.values_mut() {
+            match update {
+                Update::Add(proof) => entry.update_on_add(proof)?,
+                Update::Delete(proof) => entry.update_on_delete(proof)?,
+            }
+        }
+        self.entries = entries;
+        Ok(())
+    }
+
+    /// Returns the current encrypted witness of `element` for `holder`.
+    pub fn recover_witness(
+        &self,
+        element: &i64,
+        holder: &EncryptionKey,
+    ) -> Result<EscrowedWitness> {
+        let x: Fr = digest_to_prime_field(&element.to_digest());
+        let entry = self
+            .entries
+            .get(&(x, holder.0.to_digest()))
+            .ok_or_else(|| anyhow!("No witness escrowed for the element and holder"))?;
+        if entry.params_id != params_id() {
+            bail!("The escrowed witness is under other parameters");
+        }
+        Ok(entry.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DynamicAccumulator;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_witness_escrow() {
+        init_logger();
+        let holder = DecryptionKey::from_seed(b"holder");
+        let pk = holder.encryption_key();
+        let mut acc = DynamicAccumulator::new();
+        let mut escrow = WitnessEscrow::new();
+        acc.add(&1).unwrap();
+        acc.add(&2).unwrap();
+        escrow.escrow(&acc.prove_membership(&1).unwrap(), &pk);
+        escrow.escrow(&acc.prove_membership(&2).unwrap(), &pk);
+        assert_eq!(escrow.len(), 2);
+
+        escrow.apply_update(&acc.add(&3).unwrap().into()).unwrap();
+        escrow
+            .apply_update(&acc.delete(&2).unwrap().into())
+            .unwrap();
+        escrow.apply_update(&acc.add(&4).unwrap().into()).unwrap();
+        assert_eq!(escrow.len(), 1);
+        assert!(escrow.recover_witness(&2, &pk).is_err());
+
+        // The holder opens a witness for the current state; other keys open garbage.
+        let escrowed = escrow.recover_witness(&1, &pk).unwrap();
+        assert_ne!(
+            escrowed.ciphertext.c2,
+            acc.prove_membership(&1).unwrap().witness
+        );
+        assert!(acc.verify_membership(&escrowed.open(&holder)));
+        let other = DecryptionKey::from_seed(b"other");
+        assert!(!acc.verify_membership(&escrowed.open(&other)));
+        assert!(escrow.recover_witness(&1, &other.encryption_key()).is_err());
+
+        // An update that does not follow the escrowed state is rejected as a whole.
+        let mut fork = DynamicAccumulator::new();
+        assert!(escrow.apply_update(&fork.add(&5).unwrap().into()).is_err());
+        assert!(acc.verify_membership(&escrow.recover_witness(&1, &pk).unwrap().open(&holder)));
+    }
+}
@@ -0,0 +1,426 @@
+//! Server-side storage of membership witnesses for many holders.
+//!
+//! A [`WitnessStore`] keeps one witness per element, current as of the latest accumulator value
+//! it was refreshed to. [`WitnessStore::refresh`] moves every witness over a batch of updates
+//! with the public update rules, so the store does not need the trapdoor. Each update maps a
+//! witness `w` to `a*w + b*B`:
+//!
+//! - an add of x from `old`: a = y - x, b = 1, B = old;
+//! - a delete of x to `new`: a = 1/(y - x), b = -1/(y - x), B = new.
+//!
+//! Over a batch this unrolls to one multi-scalar multiplication per witness, computed in
+//! parallel. Entries are evicted according to an [`EvictionPolicy`].
+//!
+//! The manager, which holds the trapdoor, can instead recompute every witness from the
+//! accumulated elements with [`WitnessStore::refresh_all`], whose cost does not depend on the
+//! number of updates.
+
+use super::{
+    dynamic_accumulator::{DynamicAccumulator, MembershipProof},
+    offload, trapdoor,
+    update::Update,
+    utils::digest_to_prime_field,
+    Fr, G1Affine,
+};
+use crate::digest::Digestible;
+use anyhow::{bail, Result};
+use ark_ec::ProjectiveCurve;
+use ark_ff::{fields::batch_inversion, One, PrimeField, Zero};
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+/// Which entries a full store gives up, and which ones [`WitnessStore::gc`] collects.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum EvictionPolicy {
+    /// Evicts the least recently read or inserted entry.
+    LeastRecentlyUsed,
+    /// Evicts the entry inserted first.
+    FirstInFirstOut,
+    /// Like `LeastRecentlyUsed`, and `gc` also drops entries not read for this many epochs.
+    MaxIdleEpochs(u64),
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    witness: MembershipProof,
+    inserted: u64,
+    last_used: u64,
+    last_used_epoch: u64,
+}
+
+/// The outcome of a refresh.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct RefreshReport {
+    /// The number of witnesses moved to the new accumulator value.
+    pub refreshed: usize,
+    /// The number of entries dropped because their element was deleted.
+    pub revoked: usize,
+}
+
+/// Membership witnesses of many elements, kept current with the accumulator.
+#[derive(Debug, Clone)]
+pub struct WitnessStore {
+    acc_value: G1Affine,
+    epoch: u64,
+    entries: HashMap<Fr, Entry>,
+    capacity: usize,
+    policy: EvictionPolicy,
+    /// A logical clock ordering insertions and reads.
+    clock: u64,
+}
+
+impl WitnessStore {
+    /// Creates an empty store for the current state of `acc`, holding at most `capacity`
+    /// witnesses.
+    pub fn new(acc: &DynamicAccumulator, capacity: usize, policy: EvictionPolicy) -> Self {
+        Self {
+            acc_value: acc.acc_value,
+            epoch: acc.epoch(),
+            entries: HashMap::new(),
+            capacity,
+            policy,
+            clock: 0,
+        }
+    }
+
+    /// The accumulator value all stored witnesses are valid for.
+    pub fn acc_value(&self) -> G1Affine {
+        self.acc_value
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    /// Stores a witness, evicting another entry if the store is full.
+    /// Returns an error if the witness is not valid for the store's accumulator value.
+    pub fn insert(&mut self, witness: MembershipProof) -> Result<()> {
+        if !witness.verify_at(self.acc_value, self.epoch) {
+            bail!("The witness is not valid for the store's accumulator state");
+        }
+        if self.capacity == 0 {
+            return Ok(());
+        }
+        if !self.entries.contains_key(&witness.element.0) && self.entries.len() >= self.capacity {
+            self.evict_one();
+        }
+        let now = self.tick();
+        self.entries.insert(
+            witness.element.0,
+            Entry {
+                witness,
+                inserted: now,
+                last_used: now,
+                last_used_epoch: self.epoch,
+            },
+        );
+        Ok(())
+    }
+
+    /// Returns the current witness of `element`, if stored.
+    pub fn get_witness(&mut self, element: &i64) -> Option<MembershipProof> {
+        let fr_element: Fr = digest_to_prime_field(&element.to_digest());
+        let now = self.tick();
+        let epoch = self.epoch;
+        self.entries.get_mut(&fr_element).map(|entry| {
+            entry.last_used = now;
+            entry.last_used_epoch = epoch;
+            entry.witness.clone()
+        })
+    }
+
+    /// Removes the witness of `element`. Returns true if it was stored.
+    pub fn remove(&mut self, element: &i64) -> bool {
+        let fr_element: Fr = digest_to_prime_field(&element.to_digest());
+        self.entries.remove(&fr_element).is_some()
+    }
+
+    fn evict_one(&mut self) {
+        let victim = match self.policy {
+            EvictionPolicy::FirstInFirstOut => self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.inserted)
+                .map(|(element, _)| *element),
+            EvictionPolicy::LeastRecentlyUsed | EvictionPolicy::MaxIdleEpochs(_) => self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(element, _)| *element),
+        };
+        if let Some(element) = victim {
+            self.entries.remove(&element);
+        }
+    }
+
+    /// Drops entries the policy considers expired. Returns the number of dropped entries.
+    pub fn gc(&mut self) -> usize {
+        let before = self.entries.len();
+        if let EvictionPolicy::MaxIdleEpochs(max_idle) = self.policy {
+            let epoch = self.epoch;
+            self.entries
+                .retain(|_, entry| epoch.saturating_sub(entry.last_used_epoch) <= max_idle);
+        }
+        while self.entries.len() > self.capacity {
+            self.evict_one();
+        }
+        before - self.entries.len()
+    }
+
+    /// Moves every witness over `updates`, which must start at the store's accumulator value.
+    /// Entries whose element is deleted by the batch are dropped. The store is left unchanged
+    /// if the batch does not chain or an update proof is invalid.
+    pub fn refresh(&mut self, updates: &[Update]) -> Result<RefreshReport> {
+        let mut acc_value = self.acc_value;
+        for update in updates {
+            if update.old_acc_value() != acc_value {
+                bail!("The updates do not chain from the store's accumulator value");
+            }
+            if !update.verify() {
+                bail!("Invalid update proof");
+            }
+            acc_value = update.new_acc_value();
+        }
+        let last = match updates.last() {
+            Some(last) => last,
+            None => return Ok(RefreshReport::default()),
+        };
+
+        // The bases are the same for every witness: its old value first, then one per update.
+        let bases: Vec<G1Affine> = updates
+            .iter()
+            .map(|update| match update {
+                Update::Add(proof) => proof.old_acc_value,
+                Update::Delete(proof) => proof.new_acc_value,
+            })
+            .collect();
+        let acc_digest = acc_value.to_digest();
+        let epoch = last.epoch();
+
+        let before = self.entries.len();
+        let refreshed: HashMap<Fr, Entry> = self
+            .entries
+            .par_iter()
+            .filter_map(|(element, entry)| {
+                let witness = refresh_witness(entry.witness.witness, *element, updates, &bases)?;
+                let mut entry = entry.clone();
+                entry.witness.witness = witness;
+                entry.witness.epoch = epoch;
+                entry.witness.acc_digest = acc_digest;
+                Some((*element, entry))
+            })
+            .collect();
+        self.entries = refreshed;
+        self.acc_value = acc_value;
+        self.epoch = epoch;
+        Ok(RefreshReport {
+            refreshed: self.entries.len(),
+            revoked: before - self.entries.len(),
+        })
+    }
+}
+
+impl WitnessStore {
+    /// Recomputes every witness for the current state of `acc` with one call to the installed
+    /// [`TrapdoorSigner`](super::trapdoor::TrapdoorSigner), which the software signer answers
+    /// in O(n log n) field operations and one fixed-base exponentiation per witness. Entries
+    /// whose element is no longer accumulated are dropped. Needs the trapdoor, so only the
+    /// manager can use it.
+    pub fn refresh_all(&mut self, acc: &DynamicAccumulator) -> Result<RefreshReport> {
+        let before = self.entries.len();
+        let mut failed = None;
+        self.entries
+            .retain(|element, _| match acc.elements.contains(element) {
+                Ok(found) => found,
+                Err(e) => {
+                    failed.get_or_insert(e);
+                    true
+                }
+            });
+        if let Some(e) = failed {
+            return Err(e);
+        }
+        let elements: Vec<Fr> = self.entries.keys().copied().collect();
+        let witnesses = trapdoor::signer()
+            .membership_witnesses(&acc.elements_with_multiplicity(), &elements)?;
+
+        let acc_digest = acc.acc_value.to_digest();
+        for (element, witness) in elements.iter().zip(witnesses) {
+            if let Some(entry) = self.entries.get_mut(element) {
+                entry.witness.witness = witness;
+                entry.witness.epoch = acc.epoch();
+                entry.witness.acc_digest = acc_digest;
+            }
+        }
+        self.acc_value = acc.acc_value;
+        self.epoch = acc.epoch();
+        Ok(RefreshReport {
+            refreshed: self.entries.len(),
+            revoked: before - self.entries.len(),
+        })
+    }
+}
+
+/// Applies the update rules of `updates` to the witness of `y` with a single multi-scalar
+/// multiplication. Returns `None` if `y` is deleted by one of the updates.
+fn refresh_witness(
+    witness: G1Affine,
+    y: Fr,
+    updates: &[Update],
+    bases: &[G1Affine],
+) -> Option<G1Affine> {
+    // 1/(y - x) of every delete, inverted together.
+    let mut inverses: Vec<Fr> = updates
+        .iter()
+        .filter_map(|update| match update {
+            Update::Delete(proof) => Some(y - proof.element.0),
+            Update::Add(_) => None,
+        })
+        .collect();
+    if inverses.iter().any(|d| d.is_zero()) {
+        return None;
+    }
+    batch_inversion(&mut inverses);
+    let mut inverses = inverses.into_iter();
+
+    let steps: Vec<(Fr, Fr)> = updates
+        .iter()
+        .map(|update| match update {
+            Update::Add(proof) => (y - proof.element.0, Fr::one()),
+            Update::Delete(_) => {
+                let inv = inverses.next().unwrap_or_else(Fr::zero);
+                (inv, -inv)
+            }
+        })
+        .collect();
+
+    // w_m = (a_1 ... a_m) * w_0 + sum_j b_j * (a_{j+1} ... a_m) * B_j
+    let mut scalars = vec![Fr::zero(); steps.len() + 1];
+    let mut suffix = Fr::one();
+    for (j, (a, b)) in steps.iter().enumerate().rev() {
+        scalars[j + 1] = *b * suffix;
+        suffix *= a;
+    }
+    scalars[0] = suffix;
+
+    let mut all_bases = Vec::with_capacity(bases.len() + 1);
+    all_bases.push(witness);
+    all_bases.extend_from_slice(bases);
+    let scalars: Vec<_> = scalars.iter().map(|s| s.into_repr()).collect();
+    Some(offload::msm_g1(&all_bases, &scalars).into_affine())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_logger() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    #[test]
+    fn test_refresh() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        acc.add_batch(&[1, 2, 3, 4]).unwrap();
+        let mut store = WitnessStore::new(&acc, 10, EvictionPolicy::LeastRecentlyUsed);
+        for e in 1..=4 {
+            store.insert(acc.prove_membership(&e).unwrap()).unwrap();
+        }
+
+        let updates: Vec<Update> = vec![
+            acc.add(&5).unwrap().into(),
+            acc.delete(&2).unwrap().into(),
+            acc.add(&6).unwrap().into(),
+            acc.delete(&5).unwrap().into(),
+        ];
+        let report = store.refresh(&updates).unwrap();
+        assert_eq!(
+            report,
+            RefreshReport {
+                refreshed: 3,
+                revoked: 1
+            }
+        );
+        assert_eq!(store.acc_value(), acc.acc_value);
+        for e in [1, 3, 4] {
+            let witness = store.get_witness(&e).unwrap();
+            assert!(acc.verify_membership(&witness));
+            assert_eq!(witness, acc.prove_membership(&e).unwrap());
+        }
+        assert!(store.get_witness(&2).is_none());
+
+        // A batch that does not start at the store's value is rejected.
+        let stale = acc.add(&7).unwrap();
+        let next = acc.add(&8).unwrap();
+        assert!(store.refresh(&[next.into()]).is_err());
+        store.refresh(&[stale.into()]).unwrap();
+        assert!(store.insert(acc.prove_membership(&1).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_refresh_all() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        acc.add_batch(&(0..20).collect::<Vec<i64>>()).unwrap();
+        let mut store = WitnessStore::new(&acc, 100, EvictionPolicy::LeastRecentlyUsed);
+        for e in 0..10 {
+            store.insert(acc.prove_membership(&e).unwrap()).unwrap();
+        }
+        let mut incremental = store.clone();
+
+        let updates: Vec<Update> = vec![
+            acc.delete(&3).unwrap().into(),
+            acc.add(&20).unwrap().into(),
+            acc.delete(&15).unwrap().into(),
+        ];
+        let report = store.refresh_all(&acc).unwrap();
+        assert_eq!(report, incremental.refresh(&updates).unwrap());
+        assert_eq!(report.revoked, 1);
+        for e in (0..10).filter(|e| *e != 3) {
+            let witness = store.get_witness(&e).unwrap();
+            assert!(acc.verify_membership(&witness));
+            assert_eq!(Some(witness), incremental.get_witness(&e));
+        }
+    }
+
+    #[test]
+    fn test_eviction() {
+        init_logger();
+        let mut acc = DynamicAccumulator::new();
+        acc.add_batch(&[1, 2, 3]).unwrap();
+
+        let mut lru = WitnessStore::new(&acc, 2, EvictionPolicy::LeastRecentlyUsed);
+        let mut fifo = WitnessStore::new(&acc, 2, EvictionPolicy::FirstInFirstOut);
+        for store in [&mut lru, &mut fifo] {
+            store.insert(acc.prove_membership(&1).unwrap()).unwrap();
+            store.insert(acc.prove_membership(&2).unwrap()).unwrap();
+            store.get_witness(&1);
+            store.insert(acc.prove_membership(&3).unwrap()).unwrap();
+        }
+        assert!(lru.get_witness(&1).is_some() && lru.get_witness(&2).is_none());
+        assert!(fifo.get_witness(&1).is_none() && fifo.get_witness(&2).is_some());
+
+        let mut idle = WitnessStore::new(&acc, 10, EvictionPolicy::MaxIdleEpochs(1));
+        idle.insert(acc.prove_membership(&1).unwrap()).unwrap();
+        idle.insert(acc.prove_membership(&2).unwrap()).unwrap();
+        idle.refresh(&[acc.add(&4).unwrap().into()]).unwrap();
+        idle.get_witness(&2);
+        idle.refresh(&[acc.add(&5).unwrap().into()]).unwrap();
+        assert_eq!(idle.gc(), 1);
+        assert!(idle.get_witness(&2).is_some());
+    }
+}
@@ -1,7 +1,15 @@
+#![cfg_attr(feature = "safe", forbid(unsafe_code))]
+
+#[cfg(all(feature = "safe", feature = "asm"))]
+compile_error!(
+    "the `safe` feature excludes `asm`, build with `--no-default-features --features safe`"
+);
+
+#[cfg(all(feature = "safe", feature = "blst"))]
+compile_error!("the `safe` feature excludes `blst`, which links the blst C library");
+
 #[macro_use]
 extern crate lazy_static;
-#[macro_use]
-extern crate log;
 
 pub mod digest;
 pub use digest::*;